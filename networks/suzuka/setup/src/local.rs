@@ -1,5 +1,6 @@
 use crate::SuzukaFullNodeSetupOperations;
 use dot_movement::DotMovement;
+use m1_da_light_node_util::ir_blob::encode_public_key;
 
 // use tracing::debug;
 
@@ -31,6 +32,15 @@ impl Local {
 		// Update the config with the new m1_da_light_node_config
 		config.m1_da_light_node = new_m1_da_light_node_config;
 
+		// the blocks of the local light node are executed, unless other sequencers are configured
+		if config.execution_extension.allowed_sequencers.is_empty() {
+			let signing_key = config.m1_da_light_node.m1_da_light_node_config.try_signing_key()?;
+			config
+				.execution_extension
+				.allowed_sequencers
+				.push(hex::encode(encode_public_key(signing_key.verifying_key())));
+		}
+
 		tracing::info!("Running mcr_settlement_setup");
 		let mcr_settlement_config: mcr_settlement_config::Config = config.mcr.clone();
 		let (mcr_config, join_handle) =
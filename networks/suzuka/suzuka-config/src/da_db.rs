@@ -5,12 +5,34 @@ use serde::{Deserialize, Serialize};
 pub struct Config {
 	#[serde(default = "default_da_db_path")]
 	pub da_db_path: String,
+
+	/// The number of heights below the finalized height, or below the latest executed height without settlement,
+	/// for which the ids of executed blocks are kept. If 0, the executed blocks are never pruned.
+	#[serde(default = "default_executed_block_retention_heights")]
+	pub executed_block_retention_heights: u64,
+
+	/// The interval between the prunings of the executed blocks.
+	#[serde(default = "default_prune_interval_seconds")]
+	pub prune_interval_seconds: u64,
 }
 
 impl Default for Config {
 	fn default() -> Self {
-		Self { da_db_path: default_da_db_path() }
+		Self {
+			da_db_path: default_da_db_path(),
+			executed_block_retention_heights: default_executed_block_retention_heights(),
+			prune_interval_seconds: default_prune_interval_seconds(),
+		}
 	}
 }
 
 env_default!(default_da_db_path, "SUZUKA_DA_DB_PATH", String, "suzuka-da-db".to_string());
+
+env_default!(
+	default_executed_block_retention_heights,
+	"SUZUKA_DA_DB_EXECUTED_BLOCK_RETENTION_HEIGHTS",
+	u64,
+	100_000
+);
+
+env_default!(default_prune_interval_seconds, "SUZUKA_DA_DB_PRUNE_INTERVAL_SECONDS", u64, 60);
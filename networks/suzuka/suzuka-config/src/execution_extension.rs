@@ -1,33 +1,69 @@
 use godfig::env_default;
+use k256::ecdsa::VerifyingKey;
+use m1_da_light_node_util::ir_blob::decode_hex_public_key;
 use serde::{Deserialize, Serialize};
 
 /// The execution extension configuration.
 /// This covers Suzuka configurations that do not configure the Maptos executor, but do configure the way it is used.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
-	/// The number of times to retry a block if it fails to execute.
-	#[serde(default = "default_block_retry_count")]
-	pub block_retry_count: u64,
+	/// Whether only the blocks signed by an allowed sequencer are executed.
+	/// If not, every block is executed, including the unsigned blocks written before blocks were signed.
+	#[serde(default = "default_sequencer_verification_enabled")]
+	pub sequencer_verification_enabled: bool,
 
-	/// The amount by which to increment the block timestamp if it fails to execute. (This is the most common reason for a block to fail to execute.)
-	#[serde(default = "default_block_retry_increment_microseconds")]
-	pub block_retry_increment_microseconds: u64,
+	/// The hex-encoded public keys of the sequencers whose blocks are executed.
+	/// Required if the sequencers are verified.
+	#[serde(default = "default_allowed_sequencers")]
+	pub allowed_sequencers: Vec<String>,
+
+	/// The number of blocks from the DA which are decoded ahead of the block being executed.
+	#[serde(default = "default_block_pipeline_depth")]
+	pub block_pipeline_depth: usize,
 }
 
 impl Default for Config {
 	fn default() -> Self {
 		Self {
-			block_retry_count: default_block_retry_count(),
-			block_retry_increment_microseconds: default_block_retry_increment_microseconds(),
+			sequencer_verification_enabled: default_sequencer_verification_enabled(),
+			allowed_sequencers: default_allowed_sequencers(),
+			block_pipeline_depth: default_block_pipeline_depth(),
 		}
 	}
 }
 
-env_default!(default_block_retry_count, "SUZUKA_BLOCK_RETRY_COUNT", u64, 10);
+impl Config {
+	/// Decodes the public keys of the allowed sequencers, or gives none if the sequencers are not verified.
+	pub fn try_allowed_sequencers(&self) -> Result<Option<Vec<VerifyingKey>>, anyhow::Error> {
+		if !self.sequencer_verification_enabled {
+			return Ok(None);
+		}
+		if self.allowed_sequencers.is_empty() {
+			anyhow::bail!(
+				"No allowed sequencers are configured, set allowed_sequencers or SUZUKA_ALLOWED_SEQUENCERS, \
+				 or disable sequencer verification"
+			);
+		}
+		self.allowed_sequencers
+			.iter()
+			.map(|sequencer| decode_hex_public_key(sequencer))
+			.collect::<Result<_, _>>()
+			.map(Some)
+	}
+}
 
 env_default!(
-	default_block_retry_increment_microseconds,
-	"SUZUKA_BLOCK_RETRY_INCREMENT_MICROSECONDS",
-	u64,
-	5000
+	default_sequencer_verification_enabled,
+	"SUZUKA_SEQUENCER_VERIFICATION_ENABLED",
+	bool,
+	true
 );
+
+env_default!(default_block_pipeline_depth, "SUZUKA_BLOCK_PIPELINE_DEPTH", usize, 8);
+
+pub fn default_allowed_sequencers() -> Vec<String> {
+	match std::env::var("SUZUKA_ALLOWED_SEQUENCERS") {
+		Ok(val) => val.split(',').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect(),
+		Err(_) => vec![],
+	}
+}
@@ -1,5 +1,8 @@
 pub mod da_db;
 pub mod execution_extension;
+pub mod movement_rest;
+pub mod settlement;
+pub mod transaction_ingress;
 
 use serde::{Deserialize, Serialize};
 
@@ -23,8 +26,17 @@ pub struct Config {
 	#[serde(default)]
 	pub da_db: da_db::Config,
 
+	#[serde(default)]
+	pub settlement: settlement::Config,
+
 	#[serde(default)]
 	pub execution_extension: execution_extension::Config,
+
+	#[serde(default)]
+	pub movement_rest: movement_rest::Config,
+
+	#[serde(default)]
+	pub transaction_ingress: transaction_ingress::Config,
 }
 
 impl Default for Config {
@@ -34,7 +46,10 @@ impl Default for Config {
 			m1_da_light_node: M1DaLightNodeConfig::default(),
 			mcr: McrConfig::default(),
 			da_db: da_db::Config::default(),
+			settlement: settlement::Config::default(),
 			execution_extension: execution_extension::Config::default(),
+			movement_rest: movement_rest::Config::default(),
+			transaction_ingress: transaction_ingress::Config::default(),
 		}
 	}
 }
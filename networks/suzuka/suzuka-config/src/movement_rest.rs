@@ -0,0 +1,29 @@
+use godfig::env_default;
+use movement_rest::MovementRest;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+	/// Whether the movement REST service, which serves the status of submitted transactions, is run.
+	#[serde(default = "default_movement_rest_enabled")]
+	pub enabled: bool,
+
+	/// The URL the movement REST service binds to.
+	#[serde(default = "default_movement_rest_url")]
+	pub url: String,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self { enabled: default_movement_rest_enabled(), url: default_movement_rest_url() }
+	}
+}
+
+env_default!(default_movement_rest_enabled, "SUZUKA_MOVEMENT_REST_ENABLED", bool, false);
+
+env_default!(
+	default_movement_rest_url,
+	MovementRest::MOVEMENT_REST_ENV_VAR,
+	String,
+	MovementRest::DEFAULT_URL.to_string()
+);
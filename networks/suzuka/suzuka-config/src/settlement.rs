@@ -0,0 +1,22 @@
+use godfig::env_default;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+	/// The DB of the settlement manager, which keeps the commitments which are not settled yet.
+	#[serde(default = "default_settlement_db_path")]
+	pub settlement_db_path: String,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self { settlement_db_path: default_settlement_db_path() }
+	}
+}
+
+env_default!(
+	default_settlement_db_path,
+	"SUZUKA_SETTLEMENT_DB_PATH",
+	String,
+	"suzuka-settlement-db".to_string()
+);
@@ -0,0 +1,46 @@
+use godfig::env_default;
+use movement_envelope::WriteFormat;
+use serde::{Deserialize, Serialize};
+
+/// The configuration of the ingress of the transactions accepted by the node, which writes them to the DA light node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+	/// The number of times to retry writing a batch of transactions to the DA light node before the transactions are marked as failed.
+	#[serde(default = "default_batch_write_retry_count")]
+	pub batch_write_retry_count: u64,
+
+	/// The delay before the first retry of a batch write, doubling with each further retry.
+	#[serde(default = "default_batch_write_retry_backoff_milliseconds")]
+	pub batch_write_retry_backoff_milliseconds: u64,
+
+	/// The format transactions are written in, `envelope` or `legacy` while DA light nodes which predate envelopes remain.
+	#[serde(default = "default_transaction_write_format")]
+	pub transaction_write_format: WriteFormat,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			batch_write_retry_count: default_batch_write_retry_count(),
+			batch_write_retry_backoff_milliseconds: default_batch_write_retry_backoff_milliseconds(
+			),
+			transaction_write_format: default_transaction_write_format(),
+		}
+	}
+}
+
+env_default!(default_batch_write_retry_count, "SUZUKA_BATCH_WRITE_RETRY_COUNT", u64, 10);
+
+env_default!(
+	default_batch_write_retry_backoff_milliseconds,
+	"SUZUKA_BATCH_WRITE_RETRY_BACKOFF_MILLISECONDS",
+	u64,
+	500
+);
+
+env_default!(
+	default_transaction_write_format,
+	"SUZUKA_TRANSACTION_WRITE_FORMAT",
+	WriteFormat,
+	WriteFormat::Envelope
+);
@@ -0,0 +1,22 @@
+use suzuka_full_node::compaction;
+
+use godfig::{backend::config_file::ConfigFile, Godfig};
+use suzuka_config::Config;
+
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> Result<ExitCode, anyhow::Error> {
+	let _guard = movement_tracing::init_tracing_subscriber(Default::default());
+
+	// get the config file
+	let dot_movement = dot_movement::DotMovement::try_from_env()?;
+	let config_file = dot_movement.try_get_or_create_config_file().await?;
+	let godfig: Godfig<Config, ConfigFile> = Godfig::new(ConfigFile::new(config_file), vec![]);
+	let config = godfig.try_wait_for_ready().await?;
+
+	// the databases are locked by a running node, so this fails unless the node is stopped
+	compaction::compact(&config).await?;
+
+	Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,47 @@
+use suzuka_full_node::snapshot;
+
+use anyhow::Context;
+use godfig::{backend::config_file::ConfigFile, Godfig};
+use suzuka_config::Config;
+
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: suzuka-full-node-snapshot export <snapshot-dir> [block-height]\n       suzuka-full-node-snapshot import <snapshot-dir>";
+
+#[tokio::main]
+async fn main() -> Result<ExitCode, anyhow::Error> {
+	let _guard = movement_tracing::init_tracing_subscriber(Default::default());
+
+	let args = env::args().skip(1).collect::<Vec<_>>();
+	let (command, snapshot_path, block_height) = match args.as_slice() {
+		[command, snapshot_path] => (command.as_str(), PathBuf::from(snapshot_path), None),
+		[command, snapshot_path, block_height] if command == "export" => {
+			let block_height = block_height.parse::<u64>().context("Invalid block height")?;
+			(command.as_str(), PathBuf::from(snapshot_path), Some(block_height))
+		}
+		_ => {
+			eprintln!("{}", USAGE);
+			return Ok(ExitCode::FAILURE);
+		}
+	};
+
+	// get the config file
+	let dot_movement = dot_movement::DotMovement::try_from_env()?;
+	let config_file = dot_movement.try_get_or_create_config_file().await?;
+	let godfig: Godfig<Config, ConfigFile> = Godfig::new(ConfigFile::new(config_file), vec![]);
+	let config = godfig.try_wait_for_ready().await?;
+
+	let manifest = match command {
+		"export" => snapshot::export(&config, &snapshot_path, block_height).await?,
+		"import" => snapshot::import(&config, &snapshot_path).await?,
+		_ => {
+			eprintln!("{}", USAGE);
+			return Ok(ExitCode::FAILURE);
+		}
+	};
+	println!("{}", serde_json::to_string_pretty(&manifest)?);
+
+	Ok(ExitCode::SUCCESS)
+}
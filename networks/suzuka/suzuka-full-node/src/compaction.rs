@@ -0,0 +1,77 @@
+//! Offline compaction of the databases of a stopped full node.
+//!
+//! Pruning deletes entries in the background, but RocksDB only reclaims their space as it compacts its files.
+//! Compacting the databases explicitly reclaims the space at once.
+
+use crate::da_db::DaDB;
+use suzuka_config::Config;
+
+use anyhow::Context;
+use rocksdb::{Options, DB};
+use tracing::info;
+
+use std::path::{Path, PathBuf};
+
+/// Prunes the executed blocks of the DA DB, then compacts the DA DB and the Aptos DB.
+pub async fn compact(config: &Config) -> Result<(), anyhow::Error> {
+	let da_db = DaDB::open(&config.da_db.da_db_path).context("Failed to open the DA DB")?;
+	if config.da_db.executed_block_retention_heights > 0 {
+		let pruned = da_db
+			.prune_executed_blocks(config.da_db.executed_block_retention_heights)
+			.await?;
+		info!("Pruned {} executed blocks", pruned);
+	}
+	da_db.compact().await?;
+	info!("Compacted the DA DB at {}", config.da_db.da_db_path);
+	drop(da_db);
+
+	let maptos_db_path = config
+		.execution_config
+		.maptos_config
+		.chain
+		.maptos_db_path
+		.clone()
+		.context("No maptos db path provided")?;
+	let compacted = tokio::task::spawn_blocking(move || {
+		let mut db_paths = Vec::new();
+		find_rocksdb_paths(&maptos_db_path, &mut db_paths)?;
+		for db_path in &db_paths {
+			compact_rocksdb(db_path)?;
+		}
+		Ok::<_, anyhow::Error>(db_paths)
+	})
+	.await??;
+	for db_path in compacted {
+		info!("Compacted the Aptos DB at {}", db_path.display());
+	}
+
+	Ok(())
+}
+
+/// The Aptos DB is split into several RocksDB instances, each in a directory with a `CURRENT` file.
+fn find_rocksdb_paths(path: &Path, db_paths: &mut Vec<PathBuf>) -> Result<(), anyhow::Error> {
+	if path.join("CURRENT").is_file() {
+		db_paths.push(path.to_path_buf());
+		return Ok(());
+	}
+	for entry in std::fs::read_dir(path)? {
+		let entry = entry?;
+		if entry.file_type()?.is_dir() {
+			find_rocksdb_paths(&entry.path(), db_paths)?;
+		}
+	}
+	Ok(())
+}
+
+fn compact_rocksdb(path: &Path) -> Result<(), anyhow::Error> {
+	let options = Options::default();
+	let column_families = DB::list_cf(&options, path)
+		.map_err(|e| anyhow::anyhow!("Failed to list column families: {:?}", e))?;
+	let db = DB::open_cf(&options, path, &column_families)
+		.map_err(|e| anyhow::anyhow!("Failed to open {}: {:?}", path.display(), e))?;
+	for name in &column_families {
+		let cf = db.cf_handle(name).ok_or(anyhow::anyhow!("No {} column family", name))?;
+		db.compact_range_cf(&cf, None::<&[u8]>, None::<&[u8]>);
+	}
+	Ok(())
+}
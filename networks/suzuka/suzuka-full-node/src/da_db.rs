@@ -1,14 +1,41 @@
-use rocksdb::{ColumnFamilyDescriptor, Options, DB};
+use async_trait::async_trait;
+use movement_transaction_status::{TransactionStatus, TransactionStatusStore};
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use serde::{Deserialize, Serialize};
 
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 mod column_families {
 	pub const EXECUTED_BLOCKS: &str = "executed_blocks";
+	pub const EXECUTED_BLOCK_HEIGHTS: &str = "executed_block_heights";
 	pub const SYNCED_HEIGHT: &str = "synced_height";
+	pub const PENDING_TRANSACTION_WRITES: &str = "pending_transaction_writes";
+	pub const FAILED_TRANSACTION_WRITES: &str = "failed_transaction_writes";
 }
 use column_families::*;
 
+/// A block from the DA executed by the full node.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExecutedBlock {
+	/// The id of the blob the block was read from.
+	pub id: String,
+	/// The DA height the block was read at.
+	pub da_height: u64,
+}
+
+/// A transaction accepted by the full node, queued to be written to the DA light node.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PendingTransactionWrite {
+	/// The committed hash of the transaction.
+	pub hash: String,
+	/// The blob data written to the DA light node.
+	pub data: Vec<u8>,
+}
+
 /// Simple data store for locally recorded DA events.
 ///
 /// An async access API is provided to avoid blocking async tasks.
@@ -16,6 +43,12 @@ use column_families::*;
 #[derive(Clone, Debug)]
 pub struct DaDB {
 	inner: Arc<DB>,
+	// the index assigned to the next queued transaction write
+	next_write_index: Arc<AtomicU64>,
+	// the number of queued transaction writes which have not been acknowledged
+	pending_write_count: Arc<AtomicU64>,
+	// the number of transaction writes which failed since the DB was opened
+	failed_write_count: Arc<AtomicU64>,
 }
 
 impl DaDB {
@@ -26,26 +59,437 @@ impl DaDB {
 
 		let synced_height = ColumnFamilyDescriptor::new(SYNCED_HEIGHT, Options::default());
 		let executed_blocks = ColumnFamilyDescriptor::new(EXECUTED_BLOCKS, Options::default());
+		let executed_block_heights =
+			ColumnFamilyDescriptor::new(EXECUTED_BLOCK_HEIGHTS, Options::default());
+		let pending_transaction_writes =
+			ColumnFamilyDescriptor::new(PENDING_TRANSACTION_WRITES, Options::default());
+		let failed_transaction_writes =
+			ColumnFamilyDescriptor::new(FAILED_TRANSACTION_WRITES, Options::default());
+
+		let db = DB::open_cf_descriptors(
+			&options,
+			path,
+			vec![
+				synced_height,
+				executed_blocks,
+				executed_block_heights,
+				pending_transaction_writes,
+				failed_transaction_writes,
+			],
+		)
+		.map_err(|e| anyhow::anyhow!("Failed to open DA DB: {:?}", e))?;
+
+		// the queue is keyed by big-endian indices, so the last key is the highest index
+		let (next_write_index, pending_write_count) = {
+			let cf = db
+				.cf_handle(PENDING_TRANSACTION_WRITES)
+				.ok_or(anyhow::anyhow!("No pending_transaction_writes column family"))?;
+			let mut last_index = None;
+			let mut count = 0;
+			for item in db.iterator_cf(&cf, IteratorMode::Start) {
+				let (key, _) = item?;
+				last_index = Some(Self::decode_write_index(&key)?);
+				count += 1;
+			}
+			(last_index.map_or(0, |index| index + 1), count)
+		};
+
+		Ok(Self {
+			inner: Arc::new(db),
+			next_write_index: Arc::new(AtomicU64::new(next_write_index)),
+			pending_write_count: Arc::new(AtomicU64::new(pending_write_count)),
+			failed_write_count: Arc::new(AtomicU64::new(0)),
+		})
+	}
 
-		let db = DB::open_cf_descriptors(&options, path, vec![synced_height, executed_blocks])
-			.map_err(|e| anyhow::anyhow!("Failed to open DA DB: {:?}", e))?;
-		Ok(Self { inner: Arc::new(db) })
+	fn decode_write_index(key: &[u8]) -> Result<u64, anyhow::Error> {
+		Ok(u64::from_be_bytes(
+			key.try_into().map_err(|_| anyhow::anyhow!("Invalid transaction write index"))?,
+		))
 	}
 
-	pub async fn add_executed_block(&self, id: String) -> Result<(), anyhow::Error> {
+	/// Records a block read from the DA at `da_height` as executed at `block_height`.
+	pub async fn add_executed_block(
+		&self,
+		id: String,
+		block_height: u64,
+		da_height: u64,
+	) -> Result<(), anyhow::Error> {
 		let da_db = self.inner.clone();
 		tokio::task::spawn_blocking(move || {
-			let cf = da_db
+			let blocks_cf = da_db
 				.cf_handle(EXECUTED_BLOCKS)
 				.ok_or(anyhow::anyhow!("No executed_blocks column family"))?;
+			let heights_cf = da_db
+				.cf_handle(EXECUTED_BLOCK_HEIGHTS)
+				.ok_or(anyhow::anyhow!("No executed_block_heights column family"))?;
+			let mut batch = WriteBatch::default();
+			batch.put_cf(&blocks_cf, id.clone(), id.clone());
+			batch.put_cf(
+				&heights_cf,
+				block_height.to_be_bytes(),
+				bcs::to_bytes(&ExecutedBlock { id, da_height })?,
+			);
 			da_db
-				.put_cf(&cf, id.clone(), id)
+				.write(batch)
 				.map_err(|e| anyhow::anyhow!("Failed to add executed block: {:?}", e))
 		})
 		.await??;
 		Ok(())
 	}
 
+	/// Gets the block from the DA executed at the given block height.
+	pub async fn get_executed_block(
+		&self,
+		block_height: u64,
+	) -> Result<Option<ExecutedBlock>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(EXECUTED_BLOCK_HEIGHTS)
+				.ok_or(anyhow::anyhow!("No executed_block_heights column family"))?;
+			let executed_block = da_db
+				.get_cf(&cf, block_height.to_be_bytes())
+				.map_err(|e| anyhow::anyhow!("Failed to get executed block: {:?}", e))?;
+			executed_block
+				.map(|executed_block| bcs::from_bytes(&executed_block))
+				.transpose()
+				.map_err(|e| anyhow::anyhow!("Failed to deserialize executed block: {:?}", e))
+		})
+		.await?
+	}
+
+	/// Forgets the blocks executed after the given block height, so that they are executed again,
+	/// and rewinds the synced height to the DA height of the block executed at the given height.
+	///
+	/// The state which only concerns the node the DB belongs to is cleared, so that the DB can bootstrap
+	/// another node: the queued and failed transaction writes and any pending rollback. The finalized height
+	/// is lowered to the given block height if it is above it.
+	pub async fn truncate_executed_blocks(
+		&self,
+		block_height: u64,
+	) -> Result<ExecutedBlock, anyhow::Error> {
+		let da_db = self.inner.clone();
+		let retained = tokio::task::spawn_blocking(move || {
+			let blocks_cf = da_db
+				.cf_handle(EXECUTED_BLOCKS)
+				.ok_or(anyhow::anyhow!("No executed_blocks column family"))?;
+			let heights_cf = da_db
+				.cf_handle(EXECUTED_BLOCK_HEIGHTS)
+				.ok_or(anyhow::anyhow!("No executed_block_heights column family"))?;
+			let synced_height_cf = da_db
+				.cf_handle(SYNCED_HEIGHT)
+				.ok_or(anyhow::anyhow!("No synced_height column family"))?;
+			let pending_writes_cf = da_db
+				.cf_handle(PENDING_TRANSACTION_WRITES)
+				.ok_or(anyhow::anyhow!("No pending_transaction_writes column family"))?;
+			let failed_writes_cf = da_db
+				.cf_handle(FAILED_TRANSACTION_WRITES)
+				.ok_or(anyhow::anyhow!("No failed_transaction_writes column family"))?;
+
+			let retained: ExecutedBlock =
+				match da_db.get_cf(&heights_cf, block_height.to_be_bytes())? {
+					Some(executed_block) => bcs::from_bytes(&executed_block)?,
+					None => anyhow::bail!(
+						"No executed block is recorded at height {}: the heights of the blocks executed \
+						 before they were recorded are unknown, and the blocks may have been pruned",
+						block_height
+					),
+				};
+
+			let mut batch = WriteBatch::default();
+			let start = (block_height + 1).to_be_bytes();
+			for item in
+				da_db.iterator_cf(&heights_cf, IteratorMode::From(&start, Direction::Forward))
+			{
+				let (key, value) = item?;
+				let executed_block: ExecutedBlock = bcs::from_bytes(&value)?;
+				batch.delete_cf(&blocks_cf, executed_block.id);
+				batch.delete_cf(&heights_cf, key);
+			}
+			// the blocks at the DA height of the retained block are read again, as in execution
+			let synced_height = serde_json::to_string(&retained.da_height.saturating_sub(1))?;
+			batch.put_cf(&synced_height_cf, "synced_height", synced_height);
+
+			for cf in [&pending_writes_cf, &failed_writes_cf] {
+				for item in da_db.iterator_cf(cf, IteratorMode::Start) {
+					let (key, _) = item?;
+					batch.delete_cf(cf, key);
+				}
+			}
+			batch.delete_cf(&synced_height_cf, "pending_rollback_height");
+			let finalized_height = da_db
+				.get_cf(&synced_height_cf, "finalized_height")?
+				.map(|height| serde_json::from_slice::<u64>(&height))
+				.transpose()?;
+			if finalized_height.is_some_and(|height| height > block_height) {
+				batch.put_cf(
+					&synced_height_cf,
+					"finalized_height",
+					serde_json::to_string(&block_height)?,
+				);
+			}
+			da_db
+				.write(batch)
+				.map_err(|e| anyhow::anyhow!("Failed to truncate executed blocks: {:?}", e))?;
+			Ok::<_, anyhow::Error>(retained)
+		})
+		.await??;
+		self.pending_write_count.store(0, Ordering::SeqCst);
+		Ok(retained)
+	}
+
+	/// Fails if blocks executed after the given block height were executed before their heights were recorded.
+	///
+	/// Those blocks are older than any block with a recorded height, so the blocks above the height are all recorded
+	/// if the first recorded height follows it. Otherwise, any id without a recorded height may be above it,
+	/// and it could neither be forgotten nor read again from a known DA height.
+	fn check_heights_recorded_above(da_db: &DB, block_height: u64) -> Result<(), anyhow::Error> {
+		let blocks_cf = da_db
+			.cf_handle(EXECUTED_BLOCKS)
+			.ok_or(anyhow::anyhow!("No executed_blocks column family"))?;
+		let heights_cf = da_db
+			.cf_handle(EXECUTED_BLOCK_HEIGHTS)
+			.ok_or(anyhow::anyhow!("No executed_block_heights column family"))?;
+		let synced_height_cf = da_db
+			.cf_handle(SYNCED_HEIGHT)
+			.ok_or(anyhow::anyhow!("No synced_height column family"))?;
+
+		if let Some(item) = da_db.iterator_cf(&heights_cf, IteratorMode::Start).next() {
+			let (key, _) = item?;
+			let first_recorded_height = u64::from_be_bytes(
+				key.as_ref()
+					.try_into()
+					.map_err(|_| anyhow::anyhow!("Invalid executed block height"))?,
+			);
+			if first_recorded_height <= block_height.saturating_add(1) {
+				return Ok(());
+			}
+		}
+		if da_db.get_cf(&synced_height_cf, "legacy_blocks_pruned")?.is_some() {
+			return Ok(());
+		}
+
+		let mut recorded_ids = HashSet::new();
+		for item in da_db.iterator_cf(&heights_cf, IteratorMode::Start) {
+			let (_, value) = item?;
+			let executed_block: ExecutedBlock = bcs::from_bytes(&value)?;
+			recorded_ids.insert(executed_block.id);
+		}
+		for item in da_db.iterator_cf(&blocks_cf, IteratorMode::Start) {
+			let (key, _) = item?;
+			if !recorded_ids.contains(String::from_utf8_lossy(&key).as_ref()) {
+				anyhow::bail!(
+					"Cannot roll back to block height {}: blocks above it may have been executed \
+					 before their heights were recorded",
+					block_height
+				);
+			}
+		}
+		Ok(())
+	}
+
+	/// Checks that the blocks executed after the given block height can be rolled back,
+	/// so that a rollback fails before anything is reverted.
+	pub async fn check_rollback(&self, block_height: u64) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			Self::check_heights_recorded_above(&da_db, block_height)
+		})
+		.await?
+	}
+
+	/// Forgets the blocks executed after the given block height, so that they are executed again once read from the DA,
+	/// and rewinds the synced height to the DA height of the earliest of them. Any pending rollback is completed.
+	///
+	/// Fails without forgetting any block if blocks above the height were executed before their heights were recorded.
+	pub async fn rollback_executed_blocks(&self, block_height: u64) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			Self::check_heights_recorded_above(&da_db, block_height)?;
+			let blocks_cf = da_db
+				.cf_handle(EXECUTED_BLOCKS)
+				.ok_or(anyhow::anyhow!("No executed_blocks column family"))?;
+			let heights_cf = da_db
+				.cf_handle(EXECUTED_BLOCK_HEIGHTS)
+				.ok_or(anyhow::anyhow!("No executed_block_heights column family"))?;
+			let synced_height_cf = da_db
+				.cf_handle(SYNCED_HEIGHT)
+				.ok_or(anyhow::anyhow!("No synced_height column family"))?;
+
+			let mut batch = WriteBatch::default();
+			let mut earliest_da_height = None;
+			let start = block_height.saturating_add(1).to_be_bytes();
+			for item in
+				da_db.iterator_cf(&heights_cf, IteratorMode::From(&start, Direction::Forward))
+			{
+				let (key, value) = item?;
+				let executed_block: ExecutedBlock = bcs::from_bytes(&value)?;
+				earliest_da_height =
+					Some(earliest_da_height.map_or(executed_block.da_height, |da_height: u64| {
+						da_height.min(executed_block.da_height)
+					}));
+				batch.delete_cf(&blocks_cf, executed_block.id);
+				batch.delete_cf(&heights_cf, key);
+			}
+			// the DA height is read again from its start, skipping the blocks which are still executed
+			if let Some(da_height) = earliest_da_height {
+				let synced_height = serde_json::to_string(&da_height.saturating_sub(1))?;
+				batch.put_cf(&synced_height_cf, "synced_height", synced_height);
+			}
+			batch.delete_cf(&synced_height_cf, "pending_rollback_height");
+			da_db
+				.write(batch)
+				.map_err(|e| anyhow::anyhow!("Failed to roll back executed blocks: {:?}", e))
+		})
+		.await?
+	}
+
+	/// Records that the node is rolling back to the given block height, so that the rollback is completed after a crash.
+	pub async fn set_pending_rollback(&self, block_height: u64) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(SYNCED_HEIGHT)
+				.ok_or(anyhow::anyhow!("No synced_height column family"))?;
+			let height = serde_json::to_string(&block_height)
+				.map_err(|e| anyhow::anyhow!("Failed to serialize rollback height: {:?}", e))?;
+			da_db
+				.put_cf(&cf, "pending_rollback_height", height)
+				.map_err(|e| anyhow::anyhow!("Failed to set pending rollback: {:?}", e))
+		})
+		.await??;
+		Ok(())
+	}
+
+	/// Gets the block height of a rollback which was not completed, if any.
+	pub async fn get_pending_rollback(&self) -> Result<Option<u64>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(SYNCED_HEIGHT)
+				.ok_or(anyhow::anyhow!("No synced_height column family"))?;
+			let height = da_db
+				.get_cf(&cf, "pending_rollback_height")
+				.map_err(|e| anyhow::anyhow!("Failed to get pending rollback: {:?}", e))?;
+			height
+				.map(|height| serde_json::from_slice(&height))
+				.transpose()
+				.map_err(|e| anyhow::anyhow!("Failed to deserialize rollback height: {:?}", e))
+		})
+		.await?
+	}
+
+	/// Forgets the ids of the blocks executed more than `retained_heights` below the finalized height,
+	/// or below the latest executed height if no block was finalized, returning the number of blocks pruned.
+	///
+	/// The blocks executed before their heights were recorded are older than any block with a recorded height,
+	/// so they are forgotten once, along with the first block with a recorded height which is pruned.
+	///
+	/// The pruned blocks can no longer be truncated to, nor recognized as executed if read again from the DA.
+	pub async fn prune_executed_blocks(&self, retained_heights: u64) -> Result<u64, anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let blocks_cf = da_db
+				.cf_handle(EXECUTED_BLOCKS)
+				.ok_or(anyhow::anyhow!("No executed_blocks column family"))?;
+			let heights_cf = da_db
+				.cf_handle(EXECUTED_BLOCK_HEIGHTS)
+				.ok_or(anyhow::anyhow!("No executed_block_heights column family"))?;
+			let synced_height_cf = da_db
+				.cf_handle(SYNCED_HEIGHT)
+				.ok_or(anyhow::anyhow!("No synced_height column family"))?;
+
+			let finalized_height = da_db
+				.get_cf(&synced_height_cf, "finalized_height")?
+				.map(|height| serde_json::from_slice::<u64>(&height))
+				.transpose()?;
+			let reference_height = match finalized_height {
+				Some(height) => height,
+				// the heights are keyed big-endian, so the last key is the latest executed height
+				None => match da_db.iterator_cf(&heights_cf, IteratorMode::End).next() {
+					Some(item) => {
+						let (key, _) = item?;
+						u64::from_be_bytes(
+							key.as_ref()
+								.try_into()
+								.map_err(|_| anyhow::anyhow!("Invalid executed block height"))?,
+						)
+					}
+					None => return Ok(0),
+				},
+			};
+			let prune_below = reference_height.saturating_sub(retained_heights);
+
+			// the ids of the blocks with a recorded height are only needed to find the others
+			let legacy_pruned = da_db.get_cf(&synced_height_cf, "legacy_blocks_pruned")?.is_some();
+			let mut batch = WriteBatch::default();
+			let mut pruned = 0;
+			let mut recorded_ids = HashSet::new();
+			for item in da_db.iterator_cf(&heights_cf, IteratorMode::Start) {
+				let (key, value) = item?;
+				let below = key.as_ref() < prune_below.to_be_bytes().as_slice();
+				if !below && legacy_pruned {
+					break;
+				}
+				let executed_block: ExecutedBlock = bcs::from_bytes(&value)?;
+				if below {
+					batch.delete_cf(&blocks_cf, &executed_block.id);
+					batch.delete_cf(&heights_cf, key);
+					pruned += 1;
+				}
+				recorded_ids.insert(executed_block.id);
+			}
+			if pruned > 0 && !legacy_pruned {
+				for item in da_db.iterator_cf(&blocks_cf, IteratorMode::Start) {
+					let (key, _) = item?;
+					if !recorded_ids.contains(String::from_utf8_lossy(&key).as_ref()) {
+						batch.delete_cf(&blocks_cf, key);
+						pruned += 1;
+					}
+				}
+				batch.put_cf(&synced_height_cf, "legacy_blocks_pruned", "true");
+			}
+			da_db
+				.write(batch)
+				.map_err(|e| anyhow::anyhow!("Failed to prune executed blocks: {:?}", e))?;
+			Ok(pruned)
+		})
+		.await?
+	}
+
+	/// Creates a consistent copy of the DB at the given path, with a RocksDB checkpoint.
+	pub async fn create_checkpoint(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		let path = path.as_ref().to_path_buf();
+		tokio::task::spawn_blocking(move || {
+			Checkpoint::new(&da_db)
+				.and_then(|checkpoint| checkpoint.create_checkpoint(&path))
+				.map_err(|e| anyhow::anyhow!("Failed to create DA DB checkpoint: {:?}", e))
+		})
+		.await?
+	}
+
+	/// Compacts the DB, reclaiming the space of the pruned and deleted entries.
+	pub async fn compact(&self) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			for name in [
+				SYNCED_HEIGHT,
+				EXECUTED_BLOCKS,
+				EXECUTED_BLOCK_HEIGHTS,
+				PENDING_TRANSACTION_WRITES,
+				FAILED_TRANSACTION_WRITES,
+			] {
+				let cf =
+					da_db.cf_handle(name).ok_or(anyhow::anyhow!("No {} column family", name))?;
+				da_db.compact_range_cf(&cf, None::<&[u8]>, None::<&[u8]>);
+			}
+			Ok(())
+		})
+		.await?
+	}
+
 	pub async fn has_executed_block(&self, id: String) -> Result<bool, anyhow::Error> {
 		let da_db = self.inner.clone();
 		let id = tokio::task::spawn_blocking(move || {
@@ -97,4 +541,402 @@ impl DaDB {
 		.await??;
 		Ok(height)
 	}
+
+	/// Records the height of the latest block finalized by settlement.
+	pub async fn set_finalized_height(&self, height: u64) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(SYNCED_HEIGHT)
+				.ok_or(anyhow::anyhow!("No synced_height column family"))?;
+			let height = serde_json::to_string(&height)
+				.map_err(|e| anyhow::anyhow!("Failed to serialize finalized height: {:?}", e))?;
+			da_db
+				.put_cf(&cf, "finalized_height", height)
+				.map_err(|e| anyhow::anyhow!("Failed to set finalized height: {:?}", e))
+		})
+		.await??;
+		Ok(())
+	}
+
+	/// Gets the height of the latest block finalized by settlement, if any block was finalized.
+	pub async fn get_finalized_height(&self) -> Result<Option<u64>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(SYNCED_HEIGHT)
+				.ok_or(anyhow::anyhow!("No synced_height column family"))?;
+			let height = da_db
+				.get_cf(&cf, "finalized_height")
+				.map_err(|e| anyhow::anyhow!("Failed to get finalized height: {:?}", e))?;
+			height
+				.map(|height| serde_json::from_slice(&height))
+				.transpose()
+				.map_err(|e| anyhow::anyhow!("Failed to deserialize finalized height: {:?}", e))
+		})
+		.await?
+	}
+
+	/// Persists transaction writes before they are sent to the DA light node, returning their indices in the queue.
+	pub async fn enqueue_transaction_writes(
+		&self,
+		writes: Vec<PendingTransactionWrite>,
+	) -> Result<Vec<u64>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		let first_index = self.next_write_index.fetch_add(writes.len() as u64, Ordering::SeqCst);
+		let count = writes.len() as u64;
+		let indices = tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(PENDING_TRANSACTION_WRITES)
+				.ok_or(anyhow::anyhow!("No pending_transaction_writes column family"))?;
+			let mut batch = WriteBatch::default();
+			let mut indices = Vec::with_capacity(writes.len());
+			for (offset, write) in writes.iter().enumerate() {
+				let index = first_index + offset as u64;
+				batch.put_cf(&cf, index.to_be_bytes(), bcs::to_bytes(write)?);
+				indices.push(index);
+			}
+			da_db
+				.write(batch)
+				.map_err(|e| anyhow::anyhow!("Failed to enqueue transaction writes: {:?}", e))?;
+			Ok::<Vec<u64>, anyhow::Error>(indices)
+		})
+		.await??;
+		self.pending_write_count.fetch_add(count, Ordering::SeqCst);
+		Ok(indices)
+	}
+
+	/// Gets the queued transaction writes which have not been acknowledged, in the order they were queued.
+	pub async fn get_pending_transaction_writes(
+		&self,
+	) -> Result<Vec<(u64, PendingTransactionWrite)>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(PENDING_TRANSACTION_WRITES)
+				.ok_or(anyhow::anyhow!("No pending_transaction_writes column family"))?;
+			da_db
+				.iterator_cf(&cf, IteratorMode::Start)
+				.map(|item| {
+					let (key, value) = item?;
+					Ok::<_, anyhow::Error>((
+						Self::decode_write_index(&key)?,
+						bcs::from_bytes(&value)?,
+					))
+				})
+				.collect()
+		})
+		.await?
+	}
+
+	/// Removes transaction writes the DA light node acknowledged from the queue.
+	pub async fn acknowledge_transaction_writes(
+		&self,
+		indices: Vec<u64>,
+	) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		let count = indices.len() as u64;
+		tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(PENDING_TRANSACTION_WRITES)
+				.ok_or(anyhow::anyhow!("No pending_transaction_writes column family"))?;
+			let mut batch = WriteBatch::default();
+			for index in indices {
+				batch.delete_cf(&cf, index.to_be_bytes());
+			}
+			da_db
+				.write(batch)
+				.map_err(|e| anyhow::anyhow!("Failed to acknowledge transaction writes: {:?}", e))
+		})
+		.await??;
+		self.pending_write_count.fetch_sub(count, Ordering::SeqCst);
+		Ok(())
+	}
+
+	/// Removes transaction writes which could not be written from the queue and records why, by transaction hash.
+	pub async fn fail_transaction_writes(
+		&self,
+		writes: Vec<(u64, PendingTransactionWrite)>,
+		reason: String,
+	) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		let count = writes.len() as u64;
+		tokio::task::spawn_blocking(move || {
+			let pending_cf = da_db
+				.cf_handle(PENDING_TRANSACTION_WRITES)
+				.ok_or(anyhow::anyhow!("No pending_transaction_writes column family"))?;
+			let failed_cf = da_db
+				.cf_handle(FAILED_TRANSACTION_WRITES)
+				.ok_or(anyhow::anyhow!("No failed_transaction_writes column family"))?;
+			let mut batch = WriteBatch::default();
+			for (index, write) in writes {
+				batch.delete_cf(&pending_cf, index.to_be_bytes());
+				batch.put_cf(&failed_cf, write.hash, reason.as_bytes());
+			}
+			da_db
+				.write(batch)
+				.map_err(|e| anyhow::anyhow!("Failed to fail transaction writes: {:?}", e))
+		})
+		.await??;
+		self.pending_write_count.fetch_sub(count, Ordering::SeqCst);
+		self.failed_write_count.fetch_add(count, Ordering::SeqCst);
+		Ok(())
+	}
+
+	/// Whether any transaction write which could not be written to the DA is recorded.
+	pub async fn has_failed_transaction_writes(&self) -> Result<bool, anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(FAILED_TRANSACTION_WRITES)
+				.ok_or(anyhow::anyhow!("No failed_transaction_writes column family"))?;
+			match da_db.iterator_cf(&cf, IteratorMode::Start).next() {
+				Some(item) => {
+					item?;
+					Ok(true)
+				}
+				None => Ok(false),
+			}
+		})
+		.await?
+	}
+
+	/// Gets the reason the transaction with the given hash could not be written to the DA, if it failed.
+	pub async fn get_transaction_write_failure(
+		&self,
+		hash: String,
+	) -> Result<Option<String>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		let reason = tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(FAILED_TRANSACTION_WRITES)
+				.ok_or(anyhow::anyhow!("No failed_transaction_writes column family"))?;
+			da_db
+				.get_cf(&cf, hash)
+				.map_err(|e| anyhow::anyhow!("Failed to get transaction write failure: {:?}", e))
+		})
+		.await??;
+		Ok(reason.map(|reason| String::from_utf8_lossy(&reason).to_string()))
+	}
+
+	/// The number of queued transaction writes which have not been acknowledged.
+	pub fn pending_transaction_write_count(&self) -> u64 {
+		self.pending_write_count.load(Ordering::SeqCst)
+	}
+
+	/// The number of transaction writes which failed since the DB was opened.
+	pub fn failed_transaction_write_count(&self) -> u64 {
+		self.failed_write_count.load(Ordering::SeqCst)
+	}
+}
+
+/// Of the transactions which are no longer indexed, only those which could not be written to the DA are recorded.
+#[async_trait]
+impl TransactionStatusStore for DaDB {
+	async fn get_status(&self, hash: &str) -> Result<Option<TransactionStatus>, anyhow::Error> {
+		let reason = self.get_transaction_write_failure(hash.to_string()).await?;
+		Ok(reason.map(|reason| TransactionStatus::Failed { reason }))
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	fn write(hash: &str) -> PendingTransactionWrite {
+		PendingTransactionWrite { hash: hash.to_string(), data: hash.as_bytes().to_vec() }
+	}
+
+	#[tokio::test]
+	async fn test_transaction_write_queue_survives_reopen() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		{
+			let da_db = DaDB::open(temp_dir.path())?;
+			let indices = da_db.enqueue_transaction_writes(vec![write("a"), write("b")]).await?;
+			assert_eq!(indices, vec![0, 1]);
+			da_db.enqueue_transaction_writes(vec![write("c")]).await?;
+			da_db.acknowledge_transaction_writes(vec![0]).await?;
+			assert_eq!(da_db.pending_transaction_write_count(), 2);
+		}
+
+		let da_db = DaDB::open(temp_dir.path())?;
+		assert_eq!(da_db.pending_transaction_write_count(), 2);
+		assert_eq!(
+			da_db.get_pending_transaction_writes().await?,
+			vec![(1, write("b")), (2, write("c"))]
+		);
+		// indices are not reused after a restart
+		assert_eq!(da_db.enqueue_transaction_writes(vec![write("d")]).await?, vec![3]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_truncate_executed_blocks() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let da_db = DaDB::open(temp_dir.path())?;
+		da_db.add_executed_block("a".to_string(), 1, 2).await?;
+		da_db.add_executed_block("b".to_string(), 2, 2).await?;
+		da_db.add_executed_block("c".to_string(), 3, 5).await?;
+		da_db.set_synced_height(4).await?;
+		da_db.set_finalized_height(3).await?;
+		da_db.set_pending_rollback(2).await?;
+		let indices = da_db.enqueue_transaction_writes(vec![write("x"), write("y")]).await?;
+		da_db
+			.fail_transaction_writes(vec![(indices[0], write("x"))], "unavailable".to_string())
+			.await?;
+
+		let retained = da_db.truncate_executed_blocks(2).await?;
+		assert_eq!(retained, ExecutedBlock { id: "b".to_string(), da_height: 2 });
+		assert!(da_db.has_executed_block("b".to_string()).await?);
+		assert!(!da_db.has_executed_block("c".to_string()).await?);
+		assert_eq!(da_db.get_executed_block(3).await?, None);
+		assert_eq!(da_db.get_synced_height().await?, 1);
+		// the state of the node the DB was copied from is not carried over
+		assert_eq!(da_db.get_finalized_height().await?, Some(2));
+		assert_eq!(da_db.get_pending_rollback().await?, None);
+		assert!(da_db.get_pending_transaction_writes().await?.is_empty());
+		assert!(!da_db.has_failed_transaction_writes().await?);
+		assert_eq!(da_db.pending_transaction_write_count(), 0);
+
+		assert!(da_db.truncate_executed_blocks(3).await.is_err());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_rollback_executed_blocks() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let da_db = DaDB::open(temp_dir.path())?;
+		da_db.add_executed_block("a".to_string(), 1, 2).await?;
+		da_db.add_executed_block("b".to_string(), 2, 3).await?;
+		da_db.add_executed_block("c".to_string(), 3, 3).await?;
+		da_db.add_executed_block("d".to_string(), 4, 6).await?;
+		da_db.set_synced_height(5).await?;
+		da_db.set_pending_rollback(2).await?;
+		assert_eq!(da_db.get_pending_rollback().await?, Some(2));
+
+		da_db.rollback_executed_blocks(2).await?;
+		assert!(da_db.has_executed_block("b".to_string()).await?);
+		assert!(!da_db.has_executed_block("c".to_string()).await?);
+		assert!(!da_db.has_executed_block("d".to_string()).await?);
+		// the DA height of the first reverted block is read again
+		assert_eq!(da_db.get_synced_height().await?, 2);
+		assert_eq!(da_db.get_pending_rollback().await?, None);
+
+		// rolling back again leaves the synced height untouched
+		da_db.rollback_executed_blocks(2).await?;
+		assert_eq!(da_db.get_synced_height().await?, 2);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_rollback_blocks_executed_before_heights_were_recorded(
+	) -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let da_db = DaDB::open(temp_dir.path())?;
+		{
+			let cf = da_db.inner.cf_handle(EXECUTED_BLOCKS).expect("executed_blocks column family");
+			da_db.inner.put_cf(&cf, "legacy", "legacy")?;
+		}
+		da_db.add_executed_block("a".to_string(), 3, 2).await?;
+		da_db.add_executed_block("b".to_string(), 4, 3).await?;
+		da_db.set_synced_height(5).await?;
+
+		// the blocks above height 2 all have a recorded height
+		da_db.check_rollback(2).await?;
+		da_db.rollback_executed_blocks(3).await?;
+		assert!(da_db.has_executed_block("a".to_string()).await?);
+		assert!(!da_db.has_executed_block("b".to_string()).await?);
+		assert_eq!(da_db.get_synced_height().await?, 2);
+
+		// the legacy block may be above height 1, so nothing is rolled back
+		assert!(da_db.check_rollback(1).await.is_err());
+		assert!(da_db.rollback_executed_blocks(1).await.is_err());
+		assert!(da_db.has_executed_block("legacy".to_string()).await?);
+		assert!(da_db.has_executed_block("a".to_string()).await?);
+		assert_eq!(da_db.get_synced_height().await?, 2);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_prune_executed_blocks() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let da_db = DaDB::open(temp_dir.path())?;
+		for height in 1..=5 {
+			da_db.add_executed_block(height.to_string(), height, height).await?;
+		}
+
+		// without a finalized height, the blocks are retained below the latest executed height
+		assert_eq!(da_db.prune_executed_blocks(3).await?, 1);
+		assert!(!da_db.has_executed_block("1".to_string()).await?);
+		assert!(da_db.has_executed_block("2".to_string()).await?);
+
+		da_db.set_finalized_height(4).await?;
+		assert_eq!(da_db.prune_executed_blocks(1).await?, 1);
+		assert_eq!(da_db.get_executed_block(2).await?, None);
+		assert!(da_db.get_executed_block(3).await?.is_some());
+		assert_eq!(da_db.prune_executed_blocks(1).await?, 0);
+
+		da_db.compact().await?;
+		assert!(da_db.has_executed_block("5".to_string()).await?);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_prune_blocks_executed_before_heights_were_recorded() -> Result<(), anyhow::Error>
+	{
+		let temp_dir = tempfile::tempdir()?;
+		let da_db = DaDB::open(temp_dir.path())?;
+		{
+			let cf = da_db.inner.cf_handle(EXECUTED_BLOCKS).expect("executed_blocks column family");
+			da_db.inner.put_cf(&cf, "legacy", "legacy")?;
+		}
+		for height in 1..=3 {
+			da_db.add_executed_block(height.to_string(), height, height).await?;
+		}
+
+		// the legacy block is kept as long as no block with a recorded height is pruned
+		assert_eq!(da_db.prune_executed_blocks(3).await?, 0);
+		assert!(da_db.has_executed_block("legacy".to_string()).await?);
+
+		assert_eq!(da_db.prune_executed_blocks(1).await?, 2);
+		assert!(!da_db.has_executed_block("legacy".to_string()).await?);
+		assert!(!da_db.has_executed_block("1".to_string()).await?);
+		assert!(da_db.has_executed_block("2".to_string()).await?);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_failed_transaction_writes_are_recorded() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let da_db = DaDB::open(temp_dir.path())?;
+		let indices = da_db.enqueue_transaction_writes(vec![write("a")]).await?;
+
+		da_db
+			.fail_transaction_writes(vec![(indices[0], write("a"))], "unavailable".to_string())
+			.await?;
+		assert_eq!(da_db.pending_transaction_write_count(), 0);
+		assert_eq!(da_db.failed_transaction_write_count(), 1);
+		assert!(da_db.get_pending_transaction_writes().await?.is_empty());
+		assert_eq!(
+			da_db.get_transaction_write_failure("a".to_string()).await?,
+			Some("unavailable".to_string())
+		);
+		assert_eq!(da_db.get_transaction_write_failure("b".to_string()).await?, None);
+
+		// the failures are the statuses of the transactions which are no longer indexed
+		assert_eq!(
+			da_db.get_status("a").await?,
+			Some(TransactionStatus::Failed { reason: "unavailable".to_string() })
+		);
+		assert_eq!(da_db.get_status("b").await?, None);
+
+		Ok(())
+	}
 }
@@ -1,6 +1,8 @@
+pub mod compaction;
 mod da_db;
 pub mod manager;
 pub mod partial;
+pub mod snapshot;
 mod tasks;
 
 #[cfg(test)]
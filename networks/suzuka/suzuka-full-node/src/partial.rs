@@ -2,13 +2,16 @@ use crate::{da_db::DaDB, tasks};
 use m1_da_light_node_client::LightNodeServiceClient;
 use maptos_dof_execution::MakeOptFinServices;
 use maptos_dof_execution::{v1::Executor, DynOptFinExecutor};
+use mcr_settlement_client::simulator::McrSimulator;
 use mcr_settlement_client::McrSettlementClient;
 use mcr_settlement_manager::CommitmentEventStream;
+use mcr_settlement_manager::ManagerStore;
 use mcr_settlement_manager::McrSettlementManager;
 use movement_rest::MovementRest;
 use suzuka_config::Config;
 
 use anyhow::Context;
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::try_join;
 use tracing::debug;
@@ -37,37 +40,57 @@ where
 		let services = context.services();
 		let mut movement_rest = self.movement_rest;
 		movement_rest.set_context(services.opt_api_context());
-		let exec_settle_task = tasks::execute_settle::Task::new(
+		let transaction_status = self.executor.transaction_status_index().clone();
+		// the transactions which are no longer indexed are looked up in the DA DB
+		transaction_status.set_store(Arc::new(self.da_db.clone()));
+		movement_rest.set_transaction_status_index(transaction_status.clone());
+		let movement_rest_enabled = self.config.movement_rest.enabled;
+		let exec_settle_task = tasks::execute_settle::Task::try_new(
 			self.executor,
 			self.settlement_manager,
-			self.da_db,
+			self.da_db.clone(),
 			self.light_node_client.clone(),
 			self.commitment_events,
 			self.config.execution_extension.clone(),
-		);
+		)?;
+		let prune_task = tasks::prune::Task::new(self.da_db.clone(), self.config.da_db.clone());
 		let transaction_ingress_task = tasks::transaction_ingress::Task::new(
 			transaction_receiver,
 			self.light_node_client,
 			// FIXME: why are the struct member names so tautological?
 			self.config.m1_da_light_node.m1_da_light_node_config,
+			self.da_db,
+			self.config.transaction_ingress,
+			transaction_status,
 		);
 
 		let (
 			execution_and_settlement_result,
 			transaction_ingress_result,
+			prune_result,
 			background_task_result,
 			services_result,
+			movement_rest_result,
 		) = try_join!(
 			tokio::spawn(async move { exec_settle_task.run().await }),
 			tokio::spawn(async move { transaction_ingress_task.run().await }),
+			tokio::spawn(async move { prune_task.run().await }),
 			tokio::spawn(exec_background),
 			tokio::spawn(services.run()),
-			// tokio::spawn(async move { movement_rest.run_service().await }),
+			tokio::spawn(async move {
+				if movement_rest_enabled {
+					movement_rest.run_service().await
+				} else {
+					Ok(())
+				}
+			}),
 		)?;
 		execution_and_settlement_result
 			.and(transaction_ingress_result)
+			.and(prune_result)
 			.and(background_task_result)
 			.and(services_result)
+			.and(movement_rest_result)
 	}
 }
 
@@ -100,18 +123,30 @@ impl SuzukaPartialNode<Executor> {
 		let executor = Executor::try_from_config(&config.execution_config.maptos_config)
 			.context("Failed to create the inner executor")?;
 
-		debug!("Creating the settlement client");
-		let settlement_client = McrSettlementClient::build_with_config(&config.mcr)
-			.await
-			.context("Failed to build MCR settlement client with config")?;
-		let (settlement_manager, commitment_events) =
-			McrSettlementManager::new(settlement_client, &config.mcr);
-		let commitment_events =
-			if config.mcr.should_settle() { Some(commitment_events) } else { None };
+		let settlement_store = ManagerStore::open(&config.settlement.settlement_db_path)
+			.context("Failed to create or get settlement manager DB")?;
+		let (settlement_manager, commitment_events) = if config.mcr.simulator.is_some() {
+			debug!("Creating the simulated settlement contract");
+			let (_, settlement_client) = McrSimulator::build_with_config(&config.mcr)
+				.await
+				.context("Failed to build MCR simulator with config")?;
+			McrSettlementManager::with_store(settlement_client, &config.mcr, settlement_store)
+		} else {
+			debug!("Creating the settlement client");
+			let settlement_client = McrSettlementClient::build_with_config(&config.mcr)
+				.await
+				.context("Failed to build MCR settlement client with config")?;
+			McrSettlementManager::with_store(settlement_client, &config.mcr, settlement_store)
+		};
+		// the simulated contract settles without L1
+		let commitment_events = if config.mcr.should_settle() || config.mcr.simulator.is_some() {
+			Some(commitment_events)
+		} else {
+			None
+		};
 
 		debug!("Creating the movement rest service");
-		let movement_rest =
-			MovementRest::try_from_env().context("Failed to create MovementRest")?;
+		let movement_rest = MovementRest::new(config.movement_rest.url.clone());
 
 		debug!("Creating the DA DB");
 		let da_db =
@@ -0,0 +1,289 @@
+//! Snapshots of the state of a full node, to bootstrap a new node without replaying the DA from genesis.
+//!
+//! A snapshot is a copy of the execution DB and of the DA DB of a node, rewound to a finalized block height.
+//! Once a snapshot is imported, the node verifies it against the commitment settled for that height
+//! and resumes executing blocks from the DA height of the last block in the snapshot.
+
+use crate::da_db::DaDB;
+use maptos_dof_execution::{create_db_checkpoint, v1::Executor, DynOptFinExecutor};
+use mcr_settlement_client::{McrSettlementClient, McrSettlementClientOperations};
+use movement_types::block::Commitment;
+use suzuka_config::Config;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILE: &str = "manifest.json";
+const MAPTOS_DB_DIR: &str = "maptos-db";
+const DA_DB_DIR: &str = "da-db";
+
+/// Describes the state held by a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+	/// The height of the last block executed in the snapshot.
+	pub block_height: u64,
+	/// The DA height the last block executed in the snapshot was read at.
+	pub da_height: u64,
+	/// The commitment to the state after the last block executed in the snapshot.
+	pub commitment: Commitment,
+}
+
+fn maptos_db_path(config: &Config) -> Result<PathBuf, anyhow::Error> {
+	config
+		.execution_config
+		.maptos_config
+		.chain
+		.maptos_db_path
+		.clone()
+		.context("No maptos db path provided")
+}
+
+fn open_executor(config: &Config, maptos_db_path: PathBuf) -> Result<Executor, anyhow::Error> {
+	let mut maptos_config = config.execution_config.maptos_config.clone();
+	maptos_config.chain.maptos_db_path = Some(maptos_db_path);
+	Executor::try_from_config(&maptos_config).context("Failed to open the snapshot executor")
+}
+
+fn copy_dir_all(from: &Path, to: &Path) -> Result<(), anyhow::Error> {
+	std::fs::create_dir_all(to)?;
+	for entry in std::fs::read_dir(from)? {
+		let entry = entry?;
+		let target = to.join(entry.file_name());
+		if entry.file_type()?.is_dir() {
+			copy_dir_all(&entry.path(), &target)?;
+		} else {
+			std::fs::copy(entry.path(), target)?;
+		}
+	}
+	Ok(())
+}
+
+/// Exports a snapshot of the state of a stopped node at the given finalized block height,
+/// or at the latest finalized block height if none is given.
+///
+/// The DBs of the node are copied with RocksDB checkpoints while they are held open, so that the export
+/// fails on their locks rather than copying them while the node writes to them.
+pub async fn export(
+	config: &Config,
+	snapshot_path: &Path,
+	block_height: Option<u64>,
+) -> Result<SnapshotManifest, anyhow::Error> {
+	if snapshot_path.exists() {
+		anyhow::bail!("Snapshot path {} already exists", snapshot_path.display());
+	}
+	let maptos_db_path = maptos_db_path(config)?;
+	let da_db_path = PathBuf::from(&config.da_db.da_db_path);
+
+	let da_db = DaDB::open(&da_db_path)
+		.context("Failed to open the DA DB, the node must be stopped to export a snapshot")?;
+	let finalized_height = da_db
+		.get_finalized_height()
+		.await?
+		.context("No block has been finalized, a snapshot cannot be verified")?;
+	let block_height = block_height.unwrap_or(finalized_height);
+	if block_height > finalized_height {
+		anyhow::bail!(
+			"Block height {} is not finalized, the finalized height is {}",
+			block_height,
+			finalized_height
+		);
+	}
+	// the DA height to resume from is only known for the blocks whose heights were recorded
+	if da_db.get_executed_block(block_height).await?.is_none() {
+		anyhow::bail!(
+			"No executed block is recorded at height {}, a snapshot can only be exported at a height \
+			 recorded by the DA DB",
+			block_height
+		);
+	}
+
+	// the snapshot is rewound on a copy, so that the node keeps its own state
+	let snapshot_maptos_db_path = snapshot_path.join(MAPTOS_DB_DIR);
+	let snapshot_da_db_path = snapshot_path.join(DA_DB_DIR);
+	tokio::fs::create_dir_all(snapshot_path).await?;
+	{
+		let (maptos_db_path, to_maptos_db_path) =
+			(maptos_db_path.clone(), snapshot_maptos_db_path.clone());
+		tokio::task::spawn_blocking(move || {
+			create_db_checkpoint(&maptos_db_path, &to_maptos_db_path)
+		})
+		.await?
+		.context(
+			"Failed to copy the execution DB, the node must be stopped to export a snapshot",
+		)?;
+	}
+	da_db
+		.create_checkpoint(&snapshot_da_db_path)
+		.await
+		.context("Failed to copy the DA DB")?;
+	drop(da_db);
+
+	let executor = open_executor(config, snapshot_maptos_db_path)?;
+	let head_height = executor.get_block_head_height()?;
+	if head_height < block_height {
+		anyhow::bail!(
+			"Block height {} has not been executed, the head is at {}",
+			block_height,
+			head_height
+		);
+	}
+	if head_height > block_height {
+		executor.revert_block_head_to(block_height).await?;
+	}
+	let commitment = executor.get_block_head_state_commitment()?;
+	drop(executor);
+
+	let da_db = DaDB::open(&snapshot_da_db_path).context("Failed to open the snapshot DA DB")?;
+	let executed_block = da_db.truncate_executed_blocks(block_height).await?;
+	drop(da_db);
+
+	let manifest =
+		SnapshotManifest { block_height, da_height: executed_block.da_height, commitment };
+	tokio::fs::write(snapshot_path.join(MANIFEST_FILE), serde_json::to_vec_pretty(&manifest)?)
+		.await?;
+	info!(
+		"Exported snapshot at block height {} and DA height {}",
+		manifest.block_height, manifest.da_height
+	);
+
+	Ok(manifest)
+}
+
+/// Imports a snapshot into a node without state, after verifying it against the settled commitment.
+pub async fn import(
+	config: &Config,
+	snapshot_path: &Path,
+) -> Result<SnapshotManifest, anyhow::Error> {
+	let manifest: SnapshotManifest = {
+		let manifest = tokio::fs::read(snapshot_path.join(MANIFEST_FILE))
+			.await
+			.context("Failed to read the snapshot manifest")?;
+		serde_json::from_slice(&manifest)?
+	};
+	let maptos_db_path = maptos_db_path(config)?;
+	let da_db_path = PathBuf::from(&config.da_db.da_db_path);
+	for path in [&maptos_db_path, &da_db_path] {
+		if path.exists() {
+			anyhow::bail!(
+				"{} already exists, a snapshot can only be imported by a new node",
+				path.display()
+			);
+		}
+	}
+
+	{
+		let (maptos_db_path, da_db_path) = (maptos_db_path.clone(), da_db_path.clone());
+		let snapshot_path = snapshot_path.to_path_buf();
+		tokio::task::spawn_blocking(move || {
+			copy_dir_all(&snapshot_path.join(MAPTOS_DB_DIR), &maptos_db_path)?;
+			copy_dir_all(&snapshot_path.join(DA_DB_DIR), &da_db_path)
+		})
+		.await?
+		.context("Failed to copy the snapshot")?;
+	}
+
+	if let Err(e) = verify(config, &manifest, maptos_db_path.clone(), &da_db_path).await {
+		// leave the node without state rather than with an unverified one
+		for path in [&maptos_db_path, &da_db_path] {
+			if let Err(cleanup_error) = std::fs::remove_dir_all(path) {
+				warn!("Failed to remove {}: {}", path.display(), cleanup_error);
+			}
+		}
+		return Err(e.context("Failed to verify the snapshot"));
+	}
+	info!(
+		"Imported snapshot at block height {}, resuming from DA height {}",
+		manifest.block_height, manifest.da_height
+	);
+
+	Ok(manifest)
+}
+
+async fn verify(
+	config: &Config,
+	manifest: &SnapshotManifest,
+	maptos_db_path: PathBuf,
+	da_db_path: &Path,
+) -> Result<(), anyhow::Error> {
+	let executor = open_executor(config, maptos_db_path)?;
+	let head_height = executor.get_block_head_height()?;
+	if head_height != manifest.block_height {
+		anyhow::bail!(
+			"Snapshot head is at block height {}, the manifest is at {}",
+			head_height,
+			manifest.block_height
+		);
+	}
+	let commitment = executor.get_block_head_state_commitment()?;
+	if commitment != manifest.commitment {
+		anyhow::bail!("Snapshot state commitment does not match the manifest");
+	}
+	drop(executor);
+
+	// the node resumes reading the DA from the height of the last block in the snapshot
+	let da_db = DaDB::open(da_db_path).context("Failed to open the snapshot DA DB")?;
+	match da_db.get_executed_block(manifest.block_height).await? {
+		Some(executed_block) if executed_block.da_height == manifest.da_height => {}
+		Some(executed_block) => anyhow::bail!(
+			"Snapshot block at height {} was read at DA height {}, the manifest is at {}",
+			manifest.block_height,
+			executed_block.da_height,
+			manifest.da_height
+		),
+		None => anyhow::bail!(
+			"Snapshot DA DB has no executed block at height {}",
+			manifest.block_height
+		),
+	}
+	if da_db.get_executed_block(manifest.block_height + 1).await?.is_some() {
+		anyhow::bail!("Snapshot DA DB has executed blocks above height {}", manifest.block_height);
+	}
+	let synced_height = da_db.get_synced_height().await?;
+	if synced_height != manifest.da_height.saturating_sub(1) {
+		anyhow::bail!(
+			"Snapshot DA DB is synced to DA height {}, the manifest is at DA height {}",
+			synced_height,
+			manifest.da_height
+		);
+	}
+	// the state of the node the snapshot was exported from must not be carried over
+	if !da_db.get_pending_transaction_writes().await?.is_empty() {
+		anyhow::bail!("Snapshot DA DB has queued transaction writes");
+	}
+	if da_db.has_failed_transaction_writes().await? {
+		anyhow::bail!("Snapshot DA DB has failed transaction writes");
+	}
+	if let Some(block_height) = da_db.get_pending_rollback().await? {
+		anyhow::bail!("Snapshot DA DB has a pending rollback to block height {}", block_height);
+	}
+	if let Some(finalized_height) = da_db.get_finalized_height().await? {
+		if finalized_height > manifest.block_height {
+			anyhow::bail!(
+				"Snapshot DA DB is finalized at block height {}, above the manifest at {}",
+				finalized_height,
+				manifest.block_height
+			);
+		}
+	}
+	drop(da_db);
+
+	let settlement_client = McrSettlementClient::build_with_config(&config.mcr)
+		.await
+		.context("Failed to build MCR settlement client with config")?;
+	let settled = settlement_client
+		.get_commitment_at_height(manifest.block_height)
+		.await?
+		.context("No commitment has been settled at the snapshot height")?;
+	// the block ids are not part of the state, so only the state commitments are compared
+	if settled.height() != manifest.block_height || settled.commitment() != commitment {
+		anyhow::bail!(
+			"Snapshot state commitment does not match the commitment settled at height {}",
+			manifest.block_height
+		);
+	}
+
+	Ok(())
+}
@@ -3,23 +3,243 @@
 use crate::da_db::DaDB;
 
 use m1_da_light_node_client::{
-	blob_response, LightNodeServiceClient, StreamReadFromHeightRequest,
+	blob_response, LightNodeServiceClient, ResumeToken, StreamReadFromHeightRequest,
 	StreamReadFromHeightResponse,
 };
 use maptos_dof_execution::{
 	DynOptFinExecutor, ExecutableBlock, ExecutableTransactions, HashValue,
-	SignatureVerifiedTransaction, SignedTransaction, Transaction,
+	SignatureVerifiedTransaction, SignedTransaction, Transaction, TransactionStatus,
 };
 use mcr_settlement_manager::{CommitmentEventStream, McrSettlementManagerOperations};
-use movement_types::block::{Block, BlockCommitment, BlockCommitmentEvent};
+use movement_envelope::LegacyFormat;
+use movement_types::{
+	block::{Block, BlockCommitment, BlockCommitmentEvent},
+	legacy,
+};
 
 use anyhow::Context;
 use futures::{future::Either, stream};
+use k256::ecdsa::VerifyingKey;
+use once_cell::sync::Lazy;
+use prometheus::{register_int_counter, IntCounter};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 use suzuka_config::execution_extension;
 use tokio::select;
 use tokio_stream::{Stream, StreamExt};
 use tracing::{debug, error, info, info_span, warn, Instrument};
 
+/// The delay before resuming the stream of blocks from the DA after the light node failed to read a height,
+/// doubled after each attempt to resume which fails, up to the maximum.
+const DA_STREAM_RESUME_DELAY: Duration = Duration::from_secs(1);
+const DA_STREAM_MAX_RESUME_DELAY: Duration = Duration::from_secs(60);
+
+/// The number of transactions from the DA rejected for an invalid signature.
+static REJECTED_TRANSACTIONS: Lazy<IntCounter> = Lazy::new(|| {
+	register_int_counter!(
+		"suzuka_da_rejected_transactions_total",
+		"Number of transactions from the DA rejected for an invalid signature"
+	)
+	.expect("failed to register the rejected transactions metric")
+});
+
+/// The number of transactions from the DA skipped because they could not be decoded.
+static UNDECODABLE_TRANSACTIONS: Lazy<IntCounter> = Lazy::new(|| {
+	register_int_counter!(
+		"suzuka_da_undecodable_transactions_total",
+		"Number of transactions from the DA skipped because they could not be decoded"
+	)
+	.expect("failed to register the undecodable transactions metric")
+});
+
+/// The reason a block from the DA failed to execute.
+#[derive(Debug, thiserror::Error)]
+pub enum BlockExecutionError {
+	#[error("invalid block timestamp: {0}")]
+	Timestamp(String),
+	#[error("invalid block metadata: {0}")]
+	Metadata(anyhow::Error),
+	#[error("invalid transaction in block: {0}")]
+	Transaction(anyhow::Error),
+	#[error("block failed to execute in the VM: {0}")]
+	Vm(anyhow::Error),
+	#[error("failed to read the executed state: {0}")]
+	Db(anyhow::Error),
+}
+
+/// Derives the timestamp at which a block is executed from the timestamp proposed for it.
+///
+/// The timestamp depends only on the block and on the executed chain, so that all nodes execute the block at the same time.
+/// Block timestamps must strictly increase, so the proposed timestamp is raised above that of the last executed block.
+pub fn derive_block_timestamp(
+	proposed_timestamp: u64,
+	last_executed_timestamp: u64,
+) -> Result<u64, BlockExecutionError> {
+	let next_timestamp = last_executed_timestamp.checked_add(1).ok_or_else(|| {
+		BlockExecutionError::Timestamp(format!(
+			"no timestamp follows the last executed timestamp {}",
+			last_executed_timestamp
+		))
+	})?;
+	Ok(proposed_timestamp.max(next_timestamp))
+}
+
+/// The stream of blocks from the DA, decoded ahead of their execution.
+type PreprocessedBlockStream =
+	Pin<Box<dyn Stream<Item = Result<anyhow::Result<PreprocessedBlock>, tonic::Status>> + Send>>;
+
+/// A block read from the DA and decoded ahead of its execution.
+struct PreprocessedBlock {
+	// The position to resume the DA stream from after this block
+	resume_token: Option<ResumeToken>,
+	blob_id: String,
+	da_height: u64,
+	da_timestamp: u64,
+	// The decoded block, or `None` if it cannot be decoded or is not signed by an allowed sequencer
+	decoded: Option<DecodedBlock>,
+}
+
+/// A block with its transactions decoded, deduplicated and their signatures verified.
+struct DecodedBlock {
+	block: Block,
+	transactions: Vec<(HashValue, SignatureVerifiedTransaction)>,
+	// The number of transactions of the block which could not be decoded and were skipped
+	undecodable_count: u64,
+}
+
+/// Decodes a block read from the DA, verifies its sequencer, decodes its transactions and verifies their signatures.
+///
+/// None of this depends on the execution of the preceding blocks, so it runs ahead of their execution.
+async fn preprocess_block(
+	response: StreamReadFromHeightResponse,
+	allowed_sequencers: Arc<Option<Vec<VerifyingKey>>>,
+) -> anyhow::Result<PreprocessedBlock> {
+	let blob = match response
+		.blob
+		.ok_or(anyhow::anyhow!("No blob in response"))?
+		.blob_type
+		.ok_or(anyhow::anyhow!("No blob type in response"))?
+	{
+		blob_response::BlobType::SequencedBlobBlock(blob) => blob,
+		_ => {
+			anyhow::bail!("Invalid blob type in response")
+		}
+	};
+
+	let block_bytes = blob.data;
+	let decoded = tokio::task::spawn_blocking(move || {
+		decode_block(&block_bytes, allowed_sequencers.as_deref())
+	})
+	.await?;
+	// anyone can write to the DA, so a malformed block must not halt the execution of the chain
+	let decoded = decoded.unwrap_or_else(|e| {
+		warn!(blob_id = %blob.blob_id, "Failed to decode block: {:?}. It will be skipped", e);
+		None
+	});
+
+	Ok(PreprocessedBlock {
+		resume_token: response.resume_token,
+		blob_id: blob.blob_id,
+		da_height: blob.height,
+		da_timestamp: blob.timestamp,
+		decoded,
+	})
+}
+
+/// Preprocesses the blocks streamed from the DA, up to the pipeline depth of blocks concurrently.
+/// The blocks are yielded in the order of the DA.
+fn preprocess_blocks<St>(
+	blocks_from_da: St,
+	allowed_sequencers: Arc<Option<Vec<VerifyingKey>>>,
+	block_pipeline_depth: usize,
+) -> PreprocessedBlockStream
+where
+	St: Stream<Item = Result<StreamReadFromHeightResponse, tonic::Status>> + Send + 'static,
+{
+	let preprocessed_blocks = futures::StreamExt::map(blocks_from_da, move |response| {
+		let allowed_sequencers = allowed_sequencers.clone();
+		async move {
+			let response = response?;
+			Ok::<_, tonic::Status>(preprocess_block(response, allowed_sequencers).await)
+		}
+	});
+	Box::pin(futures::StreamExt::buffered(preprocessed_blocks, block_pipeline_depth.max(1)))
+}
+
+/// Decodes a transaction of a block.
+fn decode_transaction(data: &[u8]) -> Result<SignedTransaction, BlockExecutionError> {
+	movement_envelope::from_bytes_or_legacy(data, LegacyFormat::Json)
+		.map_err(BlockExecutionError::Transaction)
+}
+
+/// Decodes a block and its transactions. If the sequencers are verified, the blocks which are not signed
+/// by an allowed sequencer are skipped. The transactions which cannot be decoded are skipped too.
+fn decode_block(
+	block_bytes: &[u8],
+	allowed_sequencers: Option<&[VerifyingKey]>,
+) -> anyhow::Result<Option<DecodedBlock>> {
+	// blocks from sequencers which predate envelopes are zstd compressed BCS, in the unsigned legacy layout
+	let block = if movement_envelope::is_enveloped(block_bytes) {
+		movement_envelope::from_bytes::<Block>(block_bytes)?
+	} else {
+		movement_envelope::from_bytes_or_legacy::<legacy::Block>(
+			block_bytes,
+			LegacyFormat::ZstdBcs,
+		)?
+		.into()
+	};
+
+	// only execute blocks which are signed by an allowed sequencer
+	if let Some(allowed_sequencers) = allowed_sequencers {
+		if let Err(e) = block.verify_sequencer(allowed_sequencers) {
+			warn!("Block failed verification: {:?}. It will be skipped", e);
+			return Ok(None);
+		}
+	}
+
+	// a transaction may be written to the DA more than once, e.g., when a batch write is retried
+	let mut hashes = HashSet::new();
+	let mut transactions = Vec::with_capacity(block.transactions().len());
+	let mut undecodable_count = 0;
+	for transaction in block.transactions() {
+		// anyone can write to the DA, so a malformed transaction must not halt the execution of the chain
+		let signed_transaction = match decode_transaction(transaction.data()) {
+			Ok(signed_transaction) => signed_transaction,
+			Err(e) => {
+				warn!(block_id = %block.id(), "{}. The transaction will be skipped", e);
+				undecodable_count += 1;
+				continue;
+			}
+		};
+		if hashes.insert(signed_transaction.committed_hash()) {
+			transactions.push(signed_transaction);
+		}
+	}
+
+	// anyone can write to the DA, so the signature of every transaction is verified
+	// transactions with an invalid signature are marked as such and discarded by the executor
+	let transactions = transactions
+		.into_par_iter()
+		.map(|signed_transaction| {
+			let hash = signed_transaction.committed_hash();
+			let transaction = match signed_transaction.verify_signature() {
+				Ok(()) => SignatureVerifiedTransaction::Valid(Transaction::UserTransaction(
+					signed_transaction,
+				)),
+				Err(_) => SignatureVerifiedTransaction::Invalid(Transaction::UserTransaction(
+					signed_transaction,
+				)),
+			};
+			(hash, transaction)
+		})
+		.collect();
+
+	Ok(Some(DecodedBlock { block, transactions, undecodable_count }))
+}
+
 pub struct Task<E, S> {
 	executor: E,
 	settlement_manager: S,
@@ -28,30 +248,39 @@ pub struct Task<E, S> {
 	// Stream receiving commitment events, conditionally enabled
 	commitment_events:
 		Either<CommitmentEventStream, stream::Pending<<CommitmentEventStream as Stream>::Item>>,
-	execution_extension: execution_extension::Config,
+	// The sequencers whose blocks are executed, any if the sequencers are not verified
+	allowed_sequencers: Arc<Option<Vec<VerifyingKey>>>,
+	// The number of blocks decoded ahead of the block being executed
+	block_pipeline_depth: usize,
 }
 
 impl<E, S> Task<E, S> {
-	pub(crate) fn new(
+	pub(crate) fn try_new(
 		executor: E,
 		settlement_manager: S,
 		da_db: DaDB,
 		da_light_node_client: LightNodeServiceClient<tonic::transport::Channel>,
 		commitment_events: Option<CommitmentEventStream>,
 		execution_extension: execution_extension::Config,
-	) -> Self {
+	) -> anyhow::Result<Self> {
+		let allowed_sequencers = Arc::new(
+			execution_extension
+				.try_allowed_sequencers()
+				.context("invalid allowed sequencers")?,
+		);
 		let commitment_events = match commitment_events {
 			Some(stream) => Either::Left(stream),
 			None => Either::Right(stream::pending()),
 		};
-		Task {
+		Ok(Task {
 			executor,
 			settlement_manager,
 			da_db,
 			da_light_node_client,
 			commitment_events,
-			execution_extension,
-		}
+			allowed_sequencers,
+			block_pipeline_depth: execution_extension.block_pipeline_depth,
+		})
 	}
 
 	fn settlement_enabled(&self) -> bool {
@@ -70,23 +299,61 @@ where
 		// (b) requires modifications to Aptos Core.
 		self.executor.rollover_genesis_block().await?;
 
-		let mut blocks_from_da = self
-			.da_light_node_client
-			.stream_read_from_height(StreamReadFromHeightRequest {
-				height: self.da_db.get_synced_height().await?,
-			})
-			.await?
-			.into_inner();
+		// complete a rollback interrupted by a restart
+		if let Some(block_height) = self.da_db.get_pending_rollback().await? {
+			self.rollback_to(block_height).await?;
+		}
+
+		let mut resume_token =
+			ResumeToken { height: self.da_db.get_synced_height().await?, blob_index: 0 };
+		let mut blocks_from_da = self.stream_blocks_from_da(resume_token.clone()).await?;
 
 		loop {
 			select! {
-				Some(res) = blocks_from_da.next() => {
-					let response = res.context("failed to get next block from DA")?;
-					self.process_block_from_da(response).await?;
+				res = blocks_from_da.next() => {
+					match res {
+						Some(Ok(Ok(preprocessed))) => {
+							let next_token = preprocessed.resume_token.clone();
+							self.process_block_from_da(preprocessed).await?;
+							if let Some(next_token) = next_token {
+								resume_token = next_token;
+							}
+						}
+						Some(Ok(Err(e))) => {
+							// the response is not a block, so there is nothing to execute
+							warn!("Invalid response from the DA: {:?}. It will be skipped", e);
+						}
+						// the light node failed to read a height, so resume from the last block rather than skipping it
+						Some(Err(status)) if status.code() == tonic::Code::Unavailable => {
+							warn!(
+								"DA stream failed: {}. Resuming from height {} blob {}",
+								status.message(),
+								resume_token.height,
+								resume_token.blob_index
+							);
+							blocks_from_da = self.resume_blocks_from_da(resume_token.clone()).await;
+						}
+						Some(Err(status)) => {
+							return Err(status).context("failed to get next block from DA");
+						}
+						// the stream never ends while the light node is up, so it is resumed as if it failed
+						None => {
+							warn!(
+								"DA stream ended. Resuming from height {} blob {}",
+								resume_token.height,
+								resume_token.blob_index
+							);
+							blocks_from_da = self.resume_blocks_from_da(resume_token.clone()).await;
+						}
+					}
 				}
 				Some(res) = self.commitment_events.next() => {
 					let event = res.context("failed to get commitment event")?;
-					self.process_commitment_event(event).await?;
+					if let Some(rollback_token) = self.process_commitment_event(event).await? {
+						// the blocks read ahead of the rollback are dropped and read again
+						resume_token = rollback_token;
+						blocks_from_da = self.stream_blocks_from_da(resume_token.clone()).await?;
+					}
 				}
 				else => break,
 			}
@@ -94,24 +361,53 @@ where
 		Ok(())
 	}
 
+	/// Streams the blocks from the DA, preprocessing up to the pipeline depth of blocks concurrently
+	/// while the current block executes. The blocks are yielded in the order of the DA.
+	async fn stream_blocks_from_da(
+		&mut self,
+		resume_token: ResumeToken,
+	) -> anyhow::Result<PreprocessedBlockStream> {
+		let blocks_from_da = self
+			.da_light_node_client
+			.stream_read_from_height(StreamReadFromHeightRequest {
+				height: resume_token.height,
+				resume_token: Some(resume_token),
+			})
+			.await?
+			.into_inner();
+
+		Ok(preprocess_blocks(
+			blocks_from_da,
+			self.allowed_sequencers.clone(),
+			self.block_pipeline_depth,
+		))
+	}
+
+	/// Streams the blocks from the DA again after the light node failed,
+	/// retrying with backoff until the light node is available again.
+	async fn resume_blocks_from_da(
+		&mut self,
+		resume_token: ResumeToken,
+	) -> PreprocessedBlockStream {
+		let mut delay = DA_STREAM_RESUME_DELAY;
+		loop {
+			tokio::time::sleep(delay).await;
+			match self.stream_blocks_from_da(resume_token.clone()).await {
+				Ok(blocks_from_da) => return blocks_from_da,
+				Err(e) => {
+					delay = (delay * 2).min(DA_STREAM_MAX_RESUME_DELAY);
+					warn!("Failed to resume the DA stream: {:?}. Retrying in {:?}", e, delay);
+				}
+			}
+		}
+	}
+
 	async fn process_block_from_da(
 		&mut self,
-		response: StreamReadFromHeightResponse,
+		preprocessed: PreprocessedBlock,
 	) -> anyhow::Result<()> {
-		// get the block
-		let (block_bytes, block_timestamp, block_id, da_height) = match response
-			.blob
-			.ok_or(anyhow::anyhow!("No blob in response"))?
-			.blob_type
-			.ok_or(anyhow::anyhow!("No blob type in response"))?
-		{
-			blob_response::BlobType::SequencedBlobBlock(blob) => {
-				(blob.data, blob.timestamp, blob.blob_id, blob.height)
-			}
-			_ => {
-				anyhow::bail!("Invalid blob type in response")
-			}
-		};
+		let PreprocessedBlock { blob_id: block_id, da_height, da_timestamp, decoded, .. } =
+			preprocessed;
 
 		// check if the block has already been executed
 		if self.da_db.has_executed_block(block_id.clone()).await? {
@@ -124,19 +420,26 @@ where
 			anyhow::bail!("Invalid DA height: {:?}", da_height);
 		}
 
-		// decompress the block bytes
-		let block = tokio::task::spawn_blocking(move || {
-			let decompressed_block_bytes = zstd::decode_all(&block_bytes[..])?;
-			let block: Block = bcs::from_bytes(&decompressed_block_bytes)?;
-			Ok::<Block, anyhow::Error>(block)
-		})
-		.await??;
+		// blocks which failed to decode or failed verification were skipped when they were decoded
+		let Some(decoded) = decoded else {
+			return Ok(());
+		};
+
+		// the sequencer assigns a monotonic timestamp, so that all nodes execute the block at the same time
+		// blocks from sequencers which do not assign timestamps fall back to the DA timestamp
+		let block_timestamp = match decoded.block.metadata().timestamp() {
+			0 => da_timestamp,
+			timestamp => timestamp,
+		};
 
 		// get the transactions
-		let transactions_count = block.transactions().len();
+		let transactions_count = decoded.block.transactions().len();
 		let span = info_span!(target: "movement_timing", "execute_block", id = %block_id);
-		let commitment =
-			self.execute_block_with_retries(block, block_timestamp).instrument(span).await?;
+		let commitment = self
+			.execute_block(decoded, block_timestamp, da_height)
+			.instrument(span)
+			.await
+			.with_context(|| format!("failed to execute block {}", block_id))?;
 
 		// decrement the number of transactions in flight on the executor
 		self.executor.decrement_transactions_in_flight(transactions_count as u64);
@@ -146,7 +449,9 @@ where
 		self.da_db.set_synced_height(da_height - 1).await?;
 
 		// set the block as executed
-		self.da_db.add_executed_block(block_id.to_string()).await?;
+		self.da_db
+			.add_executed_block(block_id.to_string(), commitment.height(), da_height)
+			.await?;
 
 		// todo: this needs defaults
 		if self.settlement_enabled() {
@@ -163,67 +468,167 @@ where
 
 		Ok(())
 	}
-}
 
-impl<E, S> Task<E, S>
-where
-	E: DynOptFinExecutor,
-{
-	/// Retries executing a block several times.
-	/// This can be valid behavior if the block timestamps are too tightly clustered for the full node execution.
-	/// However, this has to be deterministic, otherwise nodes will not be able to agree on the block commitment.
-	async fn execute_block_with_retries(
+	/// Processes a commitment event from settlement, returning the position to resume the DA stream from
+	/// if the blocks above a rejected commitment were rolled back.
+	async fn process_commitment_event(
 		&mut self,
-		block: Block,
-		mut block_timestamp: u64,
-	) -> anyhow::Result<BlockCommitment> {
-		for _ in 0..self.execution_extension.block_retry_count {
-			// we have to clone here because the block is supposed to be consumed by the executor
-			match self.execute_block(block.clone(), block_timestamp).await {
-				Ok(commitment) => return Ok(commitment),
-				Err(e) => {
-					warn!("Failed to execute block: {:?}. Retrying", e);
-					block_timestamp += self.execution_extension.block_retry_increment_microseconds; // increase the timestamp by 5 ms (5000 microseconds)
+		event: BlockCommitmentEvent,
+	) -> anyhow::Result<Option<ResumeToken>> {
+		match event {
+			BlockCommitmentEvent::Accepted(commitment) => {
+				debug!("Commitment accepted: {:?}", commitment);
+				self.executor
+					.set_finalized_block_height(commitment.height())
+					.context("failed to set finalized block height")?;
+				// the finalized height is persisted, so that snapshots can be taken at it
+				self.da_db
+					.set_finalized_height(commitment.height())
+					.await
+					.context("failed to record finalized block height")?;
+				Ok(None)
+			}
+			BlockCommitmentEvent::Rejected { height, reason } => {
+				debug!("Commitment rejected: {:?} {:?}", height, reason);
+				let current_head_height = self.executor.get_block_head_height()?;
+				if height > current_head_height {
+					// Nothing to revert
+					Ok(None)
+				} else {
+					// there is no block below the genesis block to roll back to
+					let block_height = height.checked_sub(1).ok_or_else(|| {
+						anyhow::anyhow!("Commitment rejected at height 0 cannot be rolled back")
+					})?;
+					let resume_token = self.rollback_to(block_height).await?;
+					Ok(Some(resume_token))
 				}
 			}
 		}
+	}
+
+	/// Rolls the executor, the executed blocks and the pending commitments back to the given block height,
+	/// returning the position to resume the DA stream from to execute the following blocks again.
+	///
+	/// The rollback is recorded before it starts, so that it is completed after a restart if it is interrupted.
+	async fn rollback_to(&mut self, block_height: u64) -> anyhow::Result<ResumeToken> {
+		// nothing is reverted if the DA DB could not forget the blocks above the height
+		self.da_db.check_rollback(block_height).await?;
+		self.da_db.set_pending_rollback(block_height).await?;
+
+		// reverting the executor also reverts the status of the transactions and checks the finalized height
+		if self.executor.get_block_head_height()? > block_height {
+			self.executor
+				.revert_block_head_to(block_height)
+				.await
+				.with_context(|| format!("failed to revert to block height {}", block_height))?;
+		}
+		if self.settlement_enabled() {
+			self.settlement_manager
+				.rollback_block_commitments(block_height)
+				.await
+				.context("failed to roll back block commitments")?;
+		}
+		self.da_db.rollback_executed_blocks(block_height).await?;
 
-		anyhow::bail!("Failed to execute block after 5 retries")
+		let synced_height = self.da_db.get_synced_height().await?;
+		warn!(
+			"Rolled back to block height {}, executing again from DA height {}",
+			block_height, synced_height
+		);
+		Ok(ResumeToken { height: synced_height, blob_index: 0 })
 	}
+}
 
+impl<E, S> Task<E, S>
+where
+	E: DynOptFinExecutor,
+{
+	/// Executes a block once, at a timestamp derived from the proposed timestamp.
+	///
+	/// Execution is deterministic, so a block which fails to execute would fail again on every node.
 	async fn execute_block(
 		&mut self,
-		block: Block,
-		block_timestamp: u64,
-	) -> anyhow::Result<BlockCommitment> {
+		decoded: DecodedBlock,
+		proposed_timestamp: u64,
+		da_height: u64,
+	) -> Result<BlockCommitment, BlockExecutionError> {
+		let DecodedBlock { block, transactions, undecodable_count } = decoded;
 		let block_id = block.id();
-		let block_hash = HashValue::from_slice(block.id())?;
+		let block_hash = HashValue::from_slice(block.id())
+			.map_err(|e| BlockExecutionError::Metadata(e.into()))?;
+
+		let last_executed_timestamp =
+			self.executor.get_block_head_timestamp().map_err(BlockExecutionError::Db)?;
+		let block_timestamp = derive_block_timestamp(proposed_timestamp, last_executed_timestamp)?;
+		if block_timestamp != proposed_timestamp {
+			debug!(
+				proposed_timestamp,
+				block_timestamp, "Raised the block timestamp above the last executed block"
+			);
+		}
 
 		// get the transactions
 		let mut block_transactions = Vec::new();
-		let block_metadata = self.executor.build_block_metadata(
-			HashValue::sha3_256_of(block_id.as_bytes().as_slice()),
-			block_timestamp,
-		)?;
+		let block_metadata = self
+			.executor
+			.build_block_metadata(
+				HashValue::sha3_256_of(block_id.as_bytes().as_slice()),
+				block_timestamp,
+			)
+			.map_err(BlockExecutionError::Metadata)?;
 		let block_metadata_transaction =
 			SignatureVerifiedTransaction::Valid(Transaction::BlockMetadata(block_metadata));
 		block_transactions.push(block_metadata_transaction);
 
-		for transaction in block.transactions() {
-			let signed_transaction: SignedTransaction = serde_json::from_slice(transaction.data())?;
+		let mut rejected_count = 0;
+		for (hash, transaction) in transactions {
+			if !transaction.is_valid() {
+				rejected_count += 1;
+				self.executor.transaction_status_index().set(
+					&hash.to_string(),
+					TransactionStatus::Failed { reason: "invalid signature".to_string() },
+				);
+				block_transactions.push(transaction);
+				continue;
+			}
 
 			// check if the transaction has already been executed to prevent replays
+			// this depends on the execution of the preceding blocks, so it cannot be done ahead
 			if self
 				.executor
-				.has_executed_transaction_opt(signed_transaction.committed_hash())?
+				.has_executed_transaction_opt(hash)
+				.map_err(BlockExecutionError::Db)?
 			{
 				continue;
 			}
 
-			let signature_verified_transaction = SignatureVerifiedTransaction::Valid(
-				Transaction::UserTransaction(signed_transaction),
+			self.executor.transaction_status_index().set(
+				&hash.to_string(),
+				TransactionStatus::Sequenced { block_id: block_id.to_string(), da_height },
+			);
+			block_transactions.push(transaction);
+		}
+
+		if rejected_count > 0 {
+			REJECTED_TRANSACTIONS.inc_by(rejected_count);
+			warn!(
+				target: "movement_timing",
+				block_id = %block_id,
+				rejected_count,
+				total_rejected_count = REJECTED_TRANSACTIONS.get(),
+				"rejected_transaction_signatures"
+			);
+		}
+
+		if undecodable_count > 0 {
+			UNDECODABLE_TRANSACTIONS.inc_by(undecodable_count);
+			warn!(
+				target: "movement_timing",
+				block_id = %block_id,
+				undecodable_count,
+				total_undecodable_count = UNDECODABLE_TRANSACTIONS.get(),
+				"skipped_undecodable_transactions"
 			);
-			block_transactions.push(signature_verified_transaction);
 		}
 
 		// form the executable transactions vec
@@ -232,37 +637,381 @@ where
 		// form the executable block and execute it
 		let executable_block = ExecutableBlock::new(block_hash, block);
 		let block_id = executable_block.block_id;
-		let commitment = self.executor.execute_block_opt(executable_block).await?;
+		let commitment = self
+			.executor
+			.execute_block_opt(executable_block)
+			.await
+			.map_err(BlockExecutionError::Vm)?;
 
 		info!("Executed block: {}", block_id);
 
 		Ok(commitment)
 	}
+}
 
-	async fn process_commitment_event(
-		&mut self,
-		event: BlockCommitmentEvent,
-	) -> anyhow::Result<()> {
-		match event {
-			BlockCommitmentEvent::Accepted(commitment) => {
-				debug!("Commitment accepted: {:?}", commitment);
-				self.executor
-					.set_finalized_block_height(commitment.height())
-					.context("failed to set finalized block height")
-			}
-			BlockCommitmentEvent::Rejected { height, reason } => {
-				debug!("Commitment rejected: {:?} {:?}", height, reason);
-				let current_head_height = self.executor.get_block_head_height()?;
-				if height > current_head_height {
-					// Nothing to revert
-					Ok(())
-				} else {
-					self.executor
-						.revert_block_head_to(height - 1)
-						.await
-						.context(format!("failed to revert to block height {}", height - 1))
-				}
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use alloy_primitives::{Address, U256};
+	use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, SigningKey, Uniform};
+	use aptos_types::{
+		account_address::AccountAddress,
+		chain_id::ChainId,
+		transaction::{RawTransaction, Script, TransactionPayload},
+	};
+	use m1_da_light_node_client::{Blob, BlobResponse};
+	use maptos_dof_execution::v1::Executor;
+	use mcr_settlement_client::simulator::McrSimulator;
+	use mcr_settlement_client::McrSettlementClientOperations;
+	use mcr_settlement_manager::McrSettlementManager;
+	use movement_envelope::Codec;
+	use movement_types::block::{BlockCommitmentRejectionReason, Commitment};
+	use movement_types::{block, transaction};
+	use std::collections::BTreeSet;
+	use std::time::{SystemTime, UNIX_EPOCH};
+
+	fn create_raw_transaction(sender: AccountAddress, gas_unit_price: u64) -> RawTransaction {
+		let transaction_payload = TransactionPayload::Script(Script::new(vec![0], vec![], vec![]));
+		RawTransaction::new(sender, 0, transaction_payload, 0, gas_unit_price, 0, ChainId::test())
+	}
+
+	fn create_signed_transaction() -> Result<SignedTransaction, anyhow::Error> {
+		let private_key = Ed25519PrivateKey::generate_for_testing();
+		let raw_transaction = create_raw_transaction(AccountAddress::random(), 0);
+		let signature = private_key.sign(&raw_transaction)?;
+		Ok(SignedTransaction::new(raw_transaction, private_key.public_key(), signature))
+	}
+
+	/// Encodes a block signed by the given sequencer, with the given transactions.
+	fn create_block_bytes(
+		signing_key: &k256::ecdsa::SigningKey,
+		transactions: Vec<transaction::Transaction>,
+	) -> Result<Vec<u8>, anyhow::Error> {
+		let mut block = Block::new(
+			block::BlockMetadata::default(),
+			block::Id::test(),
+			transactions.into_iter().collect(),
+		);
+		block.sign(signing_key);
+		movement_envelope::to_bytes(&block, Codec::None)
+	}
+
+	/// A response of the light node with a block at the given DA height.
+	fn block_response(
+		block_bytes: Vec<u8>,
+		da_height: u64,
+	) -> Result<StreamReadFromHeightResponse, tonic::Status> {
+		let blob = Blob {
+			blob_id: format!("blob-{}", da_height),
+			data: block_bytes,
+			height: da_height,
+			signatures: vec![],
+			timestamp: 0,
+		};
+		Ok(StreamReadFromHeightResponse {
+			blob: Some(BlobResponse {
+				blob_type: Some(blob_response::BlobType::SequencedBlobBlock(blob)),
+			}),
+			resume_token: Some(ResumeToken { height: da_height, blob_index: 1 }),
+		})
+	}
+
+	#[test]
+	fn test_derive_block_timestamp() -> Result<(), anyhow::Error> {
+		// the proposed timestamp is kept when it follows the last executed block
+		assert_eq!(derive_block_timestamp(1_000, 999)?, 1_000);
+		// otherwise it is raised just above the last executed block
+		assert_eq!(derive_block_timestamp(1_000, 1_000)?, 1_001);
+		assert_eq!(derive_block_timestamp(0, 5_000)?, 5_001);
+		assert!(matches!(
+			derive_block_timestamp(0, u64::MAX),
+			Err(BlockExecutionError::Timestamp(_))
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_undecodable_transaction_is_skipped() -> Result<(), anyhow::Error> {
+		let signing_key = k256::ecdsa::SigningKey::from_slice(&[1; 32])?;
+		let mut block = Block::test();
+		block.sign(&signing_key);
+		let block_bytes = movement_envelope::to_bytes(&block, movement_envelope::Codec::None)?;
+
+		let decoded = decode_block(&block_bytes, Some(&[*signing_key.verifying_key()]))?
+			.expect("block should not be skipped");
+		assert!(decoded.transactions.is_empty());
+		assert_eq!(decoded.undecodable_count, 1);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_legacy_block_requires_disabled_verification() -> Result<(), anyhow::Error> {
+		let signing_key = k256::ecdsa::SigningKey::from_slice(&[1; 32])?;
+		let legacy_block = legacy::Block::from(Block::test());
+		let block_bytes = zstd::encode_all(bcs::to_bytes(&legacy_block)?.as_slice(), 0)?;
+
+		assert!(decode_block(&block_bytes, Some(&[*signing_key.verifying_key()]))?.is_none());
+		let decoded = decode_block(&block_bytes, None)?.expect("block should not be skipped");
+		assert_eq!(decoded.block.id(), legacy_block.id());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_tampered_signature_fails() -> Result<(), anyhow::Error> {
+		let private_key = Ed25519PrivateKey::generate_for_testing();
+		let public_key = private_key.public_key();
+		let sender = AccountAddress::random();
+		let raw_transaction = create_raw_transaction(sender, 0);
+		let signature = private_key.sign(&raw_transaction)?;
+		let valid_transaction =
+			SignedTransaction::new(raw_transaction, public_key.clone(), signature.clone());
+		// the signature does not cover the raised gas unit price
+		let tampered_transaction =
+			SignedTransaction::new(create_raw_transaction(sender, 1), public_key, signature);
+
+		let signing_key = k256::ecdsa::SigningKey::from_slice(&[1; 32])?;
+		let transactions = [&valid_transaction, &tampered_transaction]
+			.into_iter()
+			.map(|signed_transaction| {
+				Ok(transaction::Transaction::new(
+					movement_envelope::to_bytes(signed_transaction, Codec::None)?,
+					0,
+				))
+			})
+			.collect::<Result<BTreeSet<_>, anyhow::Error>>()?;
+		let mut block =
+			Block::new(block::BlockMetadata::default(), block::Id::test(), transactions);
+		block.sign(&signing_key);
+		let block_bytes = movement_envelope::to_bytes(&block, Codec::None)?;
+
+		let decoded = decode_block(&block_bytes, Some(&[*signing_key.verifying_key()]))?
+			.expect("block should not be skipped");
+		let tampered_hash = tampered_transaction.committed_hash();
+		for (hash, transaction) in &decoded.transactions {
+			if *hash == tampered_hash {
+				assert!(matches!(transaction, SignatureVerifiedTransaction::Invalid(_)));
+			} else {
+				assert_eq!(*hash, valid_transaction.committed_hash());
+				assert!(transaction.is_valid());
 			}
 		}
+
+		let temp_dir = tempfile::tempdir()?;
+		let config = suzuka_config::Config::default();
+		let executor = Executor::try_from_config(&config.execution_config.maptos_config)?;
+		let da_light_node_client = LightNodeServiceClient::new(
+			tonic::transport::Channel::from_static("http://127.0.0.1:30730").connect_lazy(),
+		);
+		let mut task = Task::try_new(
+			executor,
+			(),
+			DaDB::open(temp_dir.path())?,
+			da_light_node_client,
+			None,
+			execution_extension::Config {
+				sequencer_verification_enabled: false,
+				..Default::default()
+			},
+		)?;
+
+		let rejected_before = REJECTED_TRANSACTIONS.get();
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_micros() as u64;
+		task.execute_block(decoded, timestamp, 2).await?;
+
+		assert!(REJECTED_TRANSACTIONS.get() > rejected_before);
+		assert!(matches!(
+			task.executor.transaction_status_index().get(&tampered_hash.to_string()),
+			Some(TransactionStatus::Failed { .. })
+		));
+		assert!(!matches!(
+			task.executor
+				.transaction_status_index()
+				.get(&valid_transaction.committed_hash().to_string()),
+			Some(TransactionStatus::Failed { .. })
+		));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_preprocessed_blocks_keep_da_order() -> Result<(), anyhow::Error> {
+		let signing_key = k256::ecdsa::SigningKey::from_slice(&[1; 32])?;
+		// the earlier blocks take longer to decode, so they would complete last without ordering
+		let responses = (0..16u64)
+			.map(|i| {
+				let transactions = (0..(16 - i) * 32)
+					.map(|sequence_number| transaction::Transaction::new(vec![1], sequence_number))
+					.collect();
+				Ok(block_response(create_block_bytes(&signing_key, transactions)?, i + 2))
+			})
+			.collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+		let allowed_sequencers = Arc::new(Some(vec![*signing_key.verifying_key()]));
+		let preprocessed = preprocess_blocks(stream::iter(responses), allowed_sequencers, 4)
+			.collect::<Vec<_>>()
+			.await;
+
+		let mut da_heights = Vec::new();
+		for preprocessed in preprocessed {
+			let preprocessed = preprocessed??;
+			assert!(preprocessed.decoded.is_some());
+			da_heights.push(preprocessed.da_height);
+		}
+		assert_eq!(da_heights, (2..18).collect::<Vec<_>>());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_preprocessing_skips_unverified_and_malformed_blocks() -> Result<(), anyhow::Error>
+	{
+		let signing_key = k256::ecdsa::SigningKey::from_slice(&[1; 32])?;
+		let other_signing_key = k256::ecdsa::SigningKey::from_slice(&[2; 32])?;
+		let responses = vec![
+			block_response(create_block_bytes(&signing_key, vec![])?, 2),
+			block_response(create_block_bytes(&other_signing_key, vec![])?, 3),
+			block_response(vec![0xff; 16], 4),
+			Ok(StreamReadFromHeightResponse { blob: None, resume_token: None }),
+			block_response(create_block_bytes(&signing_key, vec![])?, 5),
+		];
+
+		let allowed_sequencers = Arc::new(Some(vec![*signing_key.verifying_key()]));
+		let mut preprocessed = preprocess_blocks(stream::iter(responses), allowed_sequencers, 2);
+
+		let block = preprocessed.next().await.expect("block from the allowed sequencer")??;
+		assert!(block.decoded.is_some());
+		let block = preprocessed.next().await.expect("block from another sequencer")??;
+		assert_eq!(block.da_height, 3);
+		assert!(block.decoded.is_none());
+		let block = preprocessed.next().await.expect("malformed block")??;
+		assert_eq!(block.da_height, 4);
+		assert!(block.decoded.is_none());
+		assert!(preprocessed.next().await.expect("response without a blob")?.is_err());
+		let block = preprocessed.next().await.expect("block after the skipped blocks")??;
+		assert_eq!(block.da_height, 5);
+		assert!(block.decoded.is_some());
+		assert!(preprocessed.next().await.is_none());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_preprocessing_deduplicates_transactions() -> Result<(), anyhow::Error> {
+		let signing_key = k256::ecdsa::SigningKey::from_slice(&[1; 32])?;
+		let signed_transaction = create_signed_transaction()?;
+		let data = movement_envelope::to_bytes(&signed_transaction, Codec::None)?;
+		// the same transaction written twice, e.g., by a retried batch write
+		let transactions = vec![
+			transaction::Transaction::new(data.clone(), 0),
+			transaction::Transaction::new(data, 1),
+			transaction::Transaction::new(
+				movement_envelope::to_bytes(&create_signed_transaction()?, Codec::None)?,
+				0,
+			),
+		];
+		let responses = vec![block_response(create_block_bytes(&signing_key, transactions)?, 2)];
+
+		let allowed_sequencers = Arc::new(Some(vec![*signing_key.verifying_key()]));
+		let mut preprocessed = preprocess_blocks(stream::iter(responses), allowed_sequencers, 1);
+		let block = preprocessed.next().await.expect("block")??;
+		let decoded = block.decoded.expect("block should not be skipped");
+
+		assert_eq!(decoded.transactions.len(), 2);
+		assert_eq!(
+			decoded
+				.transactions
+				.iter()
+				.filter(|(hash, _)| *hash == signed_transaction.committed_hash())
+				.count(),
+			1
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_rejected_commitment_rolls_back_block() -> Result<(), anyhow::Error> {
+		let mut config = suzuka_config::Config::default();
+		config.mcr.transactions.batch_timeout = 10;
+
+		// the other attester has the supermajority of the stake
+		let simulator = McrSimulator::new(16, U256::ZERO);
+		let attester = simulator.add_attester(Address::with_last_byte(1), U256::from(1)).await;
+		let dissenter = simulator.add_attester(Address::with_last_byte(2), U256::from(3)).await;
+		let (settlement_manager, commitment_events) =
+			McrSettlementManager::new(attester.clone(), &config.mcr);
+
+		let temp_dir = tempfile::tempdir()?;
+		let executor = Executor::try_from_config(&config.execution_config.maptos_config)?;
+		let da_light_node_client = LightNodeServiceClient::new(
+			tonic::transport::Channel::from_static("http://127.0.0.1:30730").connect_lazy(),
+		);
+		let mut task = Task::try_new(
+			executor,
+			settlement_manager,
+			DaDB::open(temp_dir.path())?,
+			da_light_node_client,
+			Some(commitment_events),
+			execution_extension::Config {
+				sequencer_verification_enabled: false,
+				..Default::default()
+			},
+		)?;
+
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_micros() as u64;
+		let preprocessed = PreprocessedBlock {
+			resume_token: None,
+			blob_id: "blob-2".to_string(),
+			da_height: 2,
+			da_timestamp: timestamp,
+			decoded: Some(DecodedBlock {
+				block: Block::test(),
+				transactions: vec![],
+				undecodable_count: 0,
+			}),
+		};
+		task.process_block_from_da(preprocessed).await?;
+		let height = task.executor.get_block_head_height()?;
+		assert!(task.da_db.has_executed_block("blob-2".to_string()).await?);
+
+		// the commitment stream is polled while the dissenter settles on other commitments
+		let dissent = async {
+			while simulator
+				.get_attester_commitment_at_height(height, attester.address())
+				.await
+				.is_none()
+			{
+				tokio::time::sleep(Duration::from_millis(10)).await;
+			}
+			let commitments = (1..=height)
+				.map(|height| {
+					BlockCommitment::new(height, Default::default(), Commitment::new([0xff; 32]))
+				})
+				.collect();
+			dissenter.post_block_commitment_batch(commitments).await
+		};
+		let (event, dissented) = tokio::join!(
+			tokio::time::timeout(Duration::from_secs(10), task.commitment_events.next()),
+			dissent
+		);
+		dissented?;
+		let event = event?.expect("commitment event stream has ended")?;
+		assert_eq!(
+			event,
+			BlockCommitmentEvent::Rejected {
+				height,
+				reason: BlockCommitmentRejectionReason::InvalidCommitment,
+			}
+		);
+
+		assert!(task.process_commitment_event(event).await?.is_some());
+		assert_eq!(task.executor.get_block_head_height()?, height - 1);
+		assert!(!task.da_db.has_executed_block("blob-2".to_string()).await?);
+
+		Ok(())
 	}
 }
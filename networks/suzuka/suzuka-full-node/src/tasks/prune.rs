@@ -0,0 +1,37 @@
+//! Task to prune the executed blocks recorded in the DA DB
+
+use crate::da_db::DaDB;
+use suzuka_config::da_db;
+
+use tracing::{debug, info};
+
+use std::time::Duration;
+
+pub struct Task {
+	da_db: DaDB,
+	config: da_db::Config,
+}
+
+impl Task {
+	pub(crate) fn new(da_db: DaDB, config: da_db::Config) -> Self {
+		Task { da_db, config }
+	}
+
+	pub async fn run(self) -> anyhow::Result<()> {
+		if self.config.executed_block_retention_heights == 0 {
+			info!("Pruning of executed blocks is disabled");
+			return Ok(());
+		}
+
+		let mut interval =
+			tokio::time::interval(Duration::from_secs(self.config.prune_interval_seconds.max(1)));
+		loop {
+			interval.tick().await;
+			let pruned = self
+				.da_db
+				.prune_executed_blocks(self.config.executed_block_retention_heights)
+				.await?;
+			debug!("Pruned {} executed blocks", pruned);
+		}
+	}
+}
@@ -1,34 +1,71 @@
 //! Task to process incoming transactions and write to DA
 
+use crate::da_db::{DaDB, PendingTransactionWrite};
+
 use m1_da_light_node_client::{BatchWriteRequest, BlobWrite, LightNodeServiceClient};
 use m1_da_light_node_util::config::Config as LightNodeConfig;
-use maptos_dof_execution::SignedTransaction;
+use maptos_dof_execution::{AcceptedTransaction, TransactionStatus, TransactionStatusIndex};
+use movement_envelope::{Codec, LegacyFormat, WriteFormat};
+use movement_types::legacy;
+use suzuka_config::transaction_ingress;
 
 use tokio::sync::mpsc;
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 
+use std::collections::HashMap;
+use std::future::Future;
 use std::ops::ControlFlow;
 use std::sync::atomic::AtomicU64;
 use std::time::{Duration, Instant};
 
 const LOGGING_UID: AtomicU64 = AtomicU64::new(0);
 
+/// The maximum number of queued transactions resent in a single batch after a restart.
+const MAX_RESEND_BATCH_SIZE: usize = 1024;
+
+/// The maximum number of times the backoff between batch write retries doubles.
+const MAX_BACKOFF_DOUBLINGS: u32 = 6;
+
 pub struct Task {
-	transaction_receiver: mpsc::Receiver<SignedTransaction>,
+	transaction_receiver: mpsc::Receiver<AcceptedTransaction>,
 	da_light_node_client: LightNodeServiceClient<tonic::transport::Channel>,
 	da_light_node_config: LightNodeConfig,
+	// Queue of transactions which have not been acknowledged by the DA light node
+	da_db: DaDB,
+	config: transaction_ingress::Config,
+	transaction_status: TransactionStatusIndex,
 }
 
 impl Task {
 	pub(crate) fn new(
-		transaction_receiver: mpsc::Receiver<SignedTransaction>,
+		transaction_receiver: mpsc::Receiver<AcceptedTransaction>,
 		da_light_node_client: LightNodeServiceClient<tonic::transport::Channel>,
 		da_light_node_config: LightNodeConfig,
+		da_db: DaDB,
+		config: transaction_ingress::Config,
+		transaction_status: TransactionStatusIndex,
 	) -> Self {
-		Task { transaction_receiver, da_light_node_client, da_light_node_config }
+		Task {
+			transaction_receiver,
+			da_light_node_client,
+			da_light_node_config,
+			da_db,
+			config,
+			transaction_status,
+		}
 	}
 
 	pub async fn run(mut self) -> anyhow::Result<()> {
+		// resend the transactions which were accepted but not acknowledged before a restart,
+		// before any new transactions, so that they are written in the order they were accepted
+		let pending_writes = self.da_db.get_pending_transaction_writes().await?;
+		if !pending_writes.is_empty() {
+			info!("Resending {} unacknowledged transactions to DA", pending_writes.len());
+			for batch in pending_writes.chunks(MAX_RESEND_BATCH_SIZE) {
+				self.batch_write(batch.to_vec()).await;
+			}
+		}
+
 		while let ControlFlow::Continue(()) = self.spawn_write_next_transaction_batch().await? {}
 		Ok(())
 	}
@@ -63,7 +100,8 @@ impl Task {
 			.await
 			{
 				Ok(transaction) => match transaction {
-					Some(transaction) => {
+					Some(accepted) => {
+						let transaction = accepted.transaction();
 						info!(
 							target : "movement_timing",
 							batch_id = %batch_id,
@@ -72,13 +110,39 @@ impl Task {
 							sequence_number = transaction.sequence_number(),
 							"received transaction",
 						);
-						let serialized_aptos_transaction = serde_json::to_vec(&transaction)?;
+						// transactions are small, so compressing them is not worth it
+						let write_format = self.config.transaction_write_format;
+						let serialized_aptos_transaction =
+							write_format.to_bytes(&transaction, Codec::None, LegacyFormat::Json)?;
+						// the gas unit price is the priority, so that the mempool can order by fee
+						// the maximum gas amount bounds the gas the transaction can use in a block
 						let movement_transaction = movement_types::transaction::Transaction::new(
 							serialized_aptos_transaction,
 							transaction.sequence_number(),
-						);
-						let serialized_transaction = serde_json::to_vec(&movement_transaction)?;
-						transactions.push(BlobWrite { data: serialized_transaction });
+						)
+						.with_application_priority(transaction.gas_unit_price())
+						.with_sender(transaction.sender().to_vec())
+						.with_gas_estimate(transaction.max_gas_amount());
+						// light nodes which predate envelopes expect the transaction without its metadata
+						let serialized_transaction = match write_format {
+							WriteFormat::Envelope => {
+								movement_envelope::to_bytes(&movement_transaction, Codec::None)?
+							}
+							WriteFormat::Legacy => movement_envelope::to_legacy_bytes(
+								&legacy::Transaction::from(movement_transaction),
+								LegacyFormat::Json,
+							)?,
+						};
+						let write = PendingTransactionWrite {
+							hash: transaction.committed_hash().to_string(),
+							data: serialized_transaction,
+						};
+						// persist the transaction before it is acknowledged,
+						// so that an accepted transaction is not lost if the node crashes
+						let indices =
+							self.da_db.enqueue_transaction_writes(vec![write.clone()]).await?;
+						accepted.acknowledge();
+						transactions.extend(indices.into_iter().zip([write]));
 					}
 					None => {
 						// The transaction stream is closed, terminate the task.
@@ -98,16 +162,155 @@ impl Task {
 				transaction_count = transactions.len(),
 				"built_batch_write"
 			);
-			let batch_write = BatchWriteRequest { blobs: transactions };
-			// spawn the actual batch write request in the background
-			let mut da_light_node_client = self.da_light_node_client.clone();
-			tokio::spawn(async move {
-				if let Err(e) = da_light_node_client.batch_write(batch_write).await {
-					warn!("failed to write batch to DA: {:?}", e);
-				}
-			});
+			self.spawn_batch_write(transactions);
 		}
 
 		Ok(Continue(()))
 	}
+
+	/// Spawns the write of a batch of queued transactions to the DA in the background.
+	fn spawn_batch_write(&self, writes: Vec<(u64, PendingTransactionWrite)>) {
+		tokio::spawn(self.batch_write(writes));
+	}
+
+	/// Writes a batch of queued transactions to the DA and records the outcome.
+	fn batch_write(
+		&self,
+		writes: Vec<(u64, PendingTransactionWrite)>,
+	) -> impl Future<Output = ()> + Send + 'static {
+		let mut da_light_node_client = self.da_light_node_client.clone();
+		let da_db = self.da_db.clone();
+		let transaction_status = self.transaction_status.clone();
+		let retry_count = self.config.batch_write_retry_count;
+		let backoff = Duration::from_millis(self.config.batch_write_retry_backoff_milliseconds);
+		async move {
+			if let Err(e) = Self::write_batch(
+				&mut da_light_node_client,
+				&da_db,
+				&transaction_status,
+				writes,
+				retry_count,
+				backoff,
+			)
+			.await
+			{
+				error!("failed to record the outcome of a batch write: {:?}", e);
+			}
+			info!(
+				target: "movement_timing",
+				unacknowledged_transactions = da_db.pending_transaction_write_count(),
+				failed_transactions = da_db.failed_transaction_write_count(),
+				"transaction_write_queue"
+			);
+		}
+	}
+
+	/// Writes a batch of queued transactions to the DA, retrying with exponential backoff.
+	///
+	/// Once the DA light node acknowledges a transaction, it is removed from the queue.
+	/// The transactions the light node rejected for the time being, e.g., because its mempool was full, are
+	/// written again. Only the errors which may be transient are retried. If every retry fails, the write fails
+	/// with another error, or the light node rejected a transaction for good, the transaction is recorded as failed,
+	/// so that it can be reported to its submitter.
+	async fn write_batch(
+		da_light_node_client: &mut LightNodeServiceClient<tonic::transport::Channel>,
+		da_db: &DaDB,
+		transaction_status: &TransactionStatusIndex,
+		mut writes: Vec<(u64, PendingTransactionWrite)>,
+		retry_count: u64,
+		backoff: Duration,
+	) -> Result<(), anyhow::Error> {
+		let mut attempt = 0;
+		loop {
+			let batch_write = BatchWriteRequest {
+				blobs: writes
+					.iter()
+					.map(|(_, write)| BlobWrite { data: write.data.clone() })
+					.collect(),
+			};
+			match da_light_node_client.batch_write(batch_write).await {
+				Ok(response) => {
+					let mut rejected = response
+						.into_inner()
+						.rejected
+						.into_iter()
+						.map(|rejected| (rejected.index as usize, rejected))
+						.collect::<HashMap<_, _>>();
+					let mut acknowledged = Vec::new();
+					let mut retried = Vec::new();
+					for (position, (index, write)) in writes.into_iter().enumerate() {
+						match rejected.remove(&position) {
+							None => {
+								transaction_status.set(&write.hash, TransactionStatus::Batched);
+								acknowledged.push(index);
+							}
+							Some(rejected) if rejected.transient && attempt < retry_count => {
+								retried.push((index, write));
+							}
+							Some(rejected) => {
+								warn!(
+									hash = %write.hash,
+									"DA light node rejected transaction: {}",
+									rejected.reason
+								);
+								transaction_status.set(
+									&write.hash,
+									TransactionStatus::Failed { reason: rejected.reason.clone() },
+								);
+								da_db
+									.fail_transaction_writes(vec![(index, write)], rejected.reason)
+									.await?;
+							}
+						}
+					}
+					da_db.acknowledge_transaction_writes(acknowledged).await?;
+					if retried.is_empty() {
+						return Ok(());
+					}
+					warn!(
+						attempt,
+						"DA light node rejected {} transactions for the time being, retrying",
+						retried.len()
+					);
+					writes = retried;
+				}
+				Err(status) if is_retryable(&status) && attempt < retry_count => {
+					warn!(attempt, "failed to write batch to DA, retrying: {:?}", status);
+				}
+				Err(status) => {
+					let hashes =
+						writes.iter().map(|(_, write)| write.hash.as_str()).collect::<Vec<_>>();
+					error!(
+						?hashes,
+						"failed to write batch to DA, marking its transactions as failed: {:?}",
+						status
+					);
+					let reason = status.message().to_string();
+					for (_, write) in &writes {
+						transaction_status
+							.set(&write.hash, TransactionStatus::Failed { reason: reason.clone() });
+					}
+					return da_db.fail_transaction_writes(writes, reason).await;
+				}
+			}
+			let doublings = (attempt as u32).min(MAX_BACKOFF_DOUBLINGS);
+			tokio::time::sleep(backoff * 2u32.pow(doublings)).await;
+			attempt += 1;
+		}
+	}
+}
+
+/// Whether a batch write which failed with the given status may succeed if it is retried.
+fn is_retryable(status: &tonic::Status) -> bool {
+	use tonic::Code;
+	matches!(
+		status.code(),
+		Code::Unknown
+			| Code::Cancelled
+			| Code::DeadlineExceeded
+			| Code::ResourceExhausted
+			| Code::Aborted
+			| Code::Internal
+			| Code::Unavailable
+	)
 }
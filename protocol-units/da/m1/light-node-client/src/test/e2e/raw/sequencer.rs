@@ -1,5 +1,6 @@
 use crate::*;
-use movement_types::block::Block;
+use movement_envelope::Codec;
+use movement_types::{block::Block, transaction::Transaction};
 use tokio_stream::StreamExt;
 
 #[tokio::test]
@@ -7,7 +8,8 @@ async fn test_light_node_submits_blob_over_stream() -> Result<(), anyhow::Error>
 	let mut client = LightNodeServiceClient::connect("http://0.0.0.0:30730").await?;
 
 	let data = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
-	let blob_write = BlobWrite { data: data.clone() };
+	let transaction = Transaction::new(data.clone(), 0);
+	let blob_write = BlobWrite { data: movement_envelope::to_bytes(&transaction, Codec::None)? };
 	let batch_write_request = BatchWriteRequest { blobs: vec![blob_write.clone()] };
 	client.batch_write(batch_write_request).await?;
 
@@ -27,7 +29,7 @@ async fn test_light_node_submits_blob_over_stream() -> Result<(), anyhow::Error>
 				Some(blob) => {
 					match blob.blob_type.ok_or(anyhow::anyhow!("No blob type in response"))? {
 						blob_response::BlobType::SequencedBlobBlock(blob) => {
-							let block = serde_json::from_slice::<Block>(&blob.data)?;
+							let block = movement_envelope::from_bytes::<Block>(&blob.data)?;
 							// get 0th transaction from BTreeSet
 							let transaction_0th = block
 								.transactions()
@@ -1,44 +1,155 @@
+pub mod parameters;
 pub mod v1;
 
 pub use m1_da_light_node_grpc::*;
+use m1_da_light_node_util::ir_blob::IntermediateBlobRepresentation;
+use parameters::VerificationParameters;
 
+/// Verifies blobs read from the DA layer.
+///
+/// A blob which is invalid, e.g., because it cannot be decoded, is not signed by the expected signers
+/// or is not included at the height, verifies as `false`. Errors are reserved for verifications which
+/// could not be completed, e.g., because the DA layer could not be reached, and may be retried.
 #[tonic::async_trait]
 pub trait Verifier {
+	/// Verifies the bytes of an [IntermediateBlobRepresentation] read from the DA layer.
 	async fn verify(
 		&self,
-		verification_mode: VerificationMode,
+		parameters: &VerificationParameters,
 		blob: &[u8],
 		height: u64,
 	) -> Result<bool, anyhow::Error> {
-		match verification_mode {
-			VerificationMode::Cowboy => self.verify_cowboy(verification_mode, blob, height).await,
+		match parameters.mode {
+			VerificationMode::Cowboy => self.verify_cowboy(parameters, blob, height).await,
 			VerificationMode::ValidatorIn => {
-				self.verifiy_validator_in(verification_mode, blob, height).await
+				self.verify_validator_in(parameters, blob, height).await
 			}
-			VerificationMode::MOfN => self.verify_m_of_n(verification_mode, blob, height).await,
+			VerificationMode::MOfN => self.verify_m_of_n(parameters, blob, height).await,
 		}
 	}
 
+	/// Verifies that the blob was included in the DA layer at the given height.
+	async fn verify_inclusion(&self, blob: &[u8], height: u64) -> Result<bool, anyhow::Error>;
+
 	async fn verify_cowboy(
 		&self,
-		_verification_mode: VerificationMode,
-		_blob: &[u8],
-		_height: u64,
+		_parameters: &VerificationParameters,
+		blob: &[u8],
+		height: u64,
 	) -> Result<bool, anyhow::Error> {
-		Ok(true)
+		self.verify_inclusion(blob, height).await
 	}
 
-	async fn verifiy_validator_in(
+	async fn verify_validator_in(
 		&self,
-		_verification_mode: VerificationMode,
-		_blob: &[u8],
-		_height: u64,
-	) -> Result<bool, anyhow::Error>;
+		parameters: &VerificationParameters,
+		blob: &[u8],
+		height: u64,
+	) -> Result<bool, anyhow::Error> {
+		let Ok(ir_blob) = IntermediateBlobRepresentation::try_from_bytes(blob) else {
+			return Ok(false);
+		};
+		if !parameters.is_signed_by_validator(&ir_blob) {
+			return Ok(false);
+		}
+		self.verify_inclusion(blob, height).await
+	}
 
 	async fn verify_m_of_n(
 		&self,
-		_verification_mode: VerificationMode,
-		_blob: &[u8],
-		_height: u64,
-	) -> Result<bool, anyhow::Error>;
+		parameters: &VerificationParameters,
+		blob: &[u8],
+		height: u64,
+	) -> Result<bool, anyhow::Error> {
+		let Ok(ir_blob) = IntermediateBlobRepresentation::try_from_bytes(blob) else {
+			return Ok(false);
+		};
+		if !parameters.is_signed_by_m_of_n(&ir_blob) {
+			return Ok(false);
+		}
+		self.verify_inclusion(blob, height).await
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use k256::{ecdsa::SigningKey, elliptic_curve::rand_core::OsRng};
+	use m1_da_light_node_util::ir_blob::encode_public_key;
+
+	/// Treats every blob as included, so that only signatures are verified.
+	struct IncludedVerifier;
+
+	#[tonic::async_trait]
+	impl Verifier for IncludedVerifier {
+		async fn verify_inclusion(
+			&self,
+			_blob: &[u8],
+			_height: u64,
+		) -> Result<bool, anyhow::Error> {
+			Ok(true)
+		}
+	}
+
+	#[tokio::test]
+	async fn test_verify_dispatches_on_mode() -> Result<(), anyhow::Error> {
+		let validator = SigningKey::random(&mut OsRng);
+		let outsider = SigningKey::random(&mut OsRng);
+		let signers = vec![hex::encode(encode_public_key(validator.verifying_key()))];
+
+		let signed = IntermediateBlobRepresentation::new(vec![1, 2, 3], 0).sign(&validator);
+		let signed = signed.try_to_bytes()?;
+		let unsigned = IntermediateBlobRepresentation::new(vec![1, 2, 3], 0).sign(&outsider);
+		let unsigned = unsigned.try_to_bytes()?;
+
+		let cowboy = VerificationParameters::try_new(VerificationMode::Cowboy, &[], 0, 0)?;
+		assert!(IncludedVerifier.verify(&cowboy, &unsigned, 1).await?);
+		assert!(IncludedVerifier.verify(&cowboy, &[0xff], 1).await?);
+
+		let validator_in =
+			VerificationParameters::try_new(VerificationMode::ValidatorIn, &signers, 0, 0)?;
+		assert!(IncludedVerifier.verify(&validator_in, &signed, 1).await?);
+		assert!(!IncludedVerifier.verify(&validator_in, &unsigned, 1).await?);
+		assert!(!IncludedVerifier.verify(&validator_in, &[0xff], 1).await?);
+
+		let m_of_n = VerificationParameters::try_new(VerificationMode::MOfN, &signers, 1, 1)?;
+		assert!(IncludedVerifier.verify(&m_of_n, &signed, 1).await?);
+		assert!(!IncludedVerifier.verify(&m_of_n, &unsigned, 1).await?);
+		assert!(!IncludedVerifier.verify(&m_of_n, &[0xff], 1).await?);
+
+		Ok(())
+	}
+
+	/// Fails every inclusion check, as if the DA layer could not be reached.
+	struct UnreachableVerifier;
+
+	#[tonic::async_trait]
+	impl Verifier for UnreachableVerifier {
+		async fn verify_inclusion(
+			&self,
+			_blob: &[u8],
+			_height: u64,
+		) -> Result<bool, anyhow::Error> {
+			Err(anyhow::anyhow!("connection refused"))
+		}
+	}
+
+	#[tokio::test]
+	async fn test_verify_propagates_inclusion_errors() -> Result<(), anyhow::Error> {
+		let validator = SigningKey::random(&mut OsRng);
+		let outsider = SigningKey::random(&mut OsRng);
+		let signers = vec![hex::encode(encode_public_key(validator.verifying_key()))];
+		let m_of_n = VerificationParameters::try_new(VerificationMode::MOfN, &signers, 1, 1)?;
+
+		// a blob which is signed as required cannot be verified without the DA layer
+		let signed = IntermediateBlobRepresentation::new(vec![1, 2, 3], 0).sign(&validator);
+		assert!(UnreachableVerifier.verify(&m_of_n, &signed.try_to_bytes()?, 1).await.is_err());
+
+		// a blob which is not is invalid regardless
+		let unsigned = IntermediateBlobRepresentation::new(vec![1, 2, 3], 0).sign(&outsider);
+		assert!(!UnreachableVerifier.verify(&m_of_n, &unsigned.try_to_bytes()?, 1).await?);
+
+		Ok(())
+	}
 }
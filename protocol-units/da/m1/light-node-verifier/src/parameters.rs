@@ -0,0 +1,158 @@
+use m1_da_light_node_grpc::{UpdateVerificationParametersRequest, VerificationMode};
+use m1_da_light_node_util::ir_blob::{
+	decode_hex_public_key, encode_public_key, IntermediateBlobRepresentation,
+};
+use std::collections::BTreeSet;
+
+/// The parameters blobs are verified against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationParameters {
+	pub mode: VerificationMode,
+	/// The encoded public keys of the accepted signers.
+	signers: BTreeSet<Vec<u8>>,
+	/// The number of distinct signers required under [VerificationMode::MOfN].
+	m: u32,
+}
+
+impl VerificationParameters {
+	/// Creates verification parameters from hex-encoded signer public keys.
+	///
+	/// Under [VerificationMode::ValidatorIn] there must be at least one signer.
+	/// Under [VerificationMode::MOfN], `n` must be the number of distinct signers
+	/// and `m` must be in `1..=n`.
+	pub fn try_new(
+		mode: VerificationMode,
+		signers: &[String],
+		m: u32,
+		n: u32,
+	) -> Result<Self, anyhow::Error> {
+		let signers = signers
+			.iter()
+			.map(|signer| decode_hex_public_key(signer).map(|key| encode_public_key(&key)))
+			.collect::<Result<BTreeSet<_>, _>>()?;
+
+		match mode {
+			VerificationMode::Cowboy => {}
+			VerificationMode::ValidatorIn => {
+				if signers.is_empty() {
+					anyhow::bail!("VALIDATOR_IN verification requires at least one signer");
+				}
+			}
+			VerificationMode::MOfN => {
+				if n as usize != signers.len() {
+					anyhow::bail!(
+						"M_OF_N verification with n = {} requires {} distinct signers, got {}",
+						n,
+						n,
+						signers.len()
+					);
+				}
+				if m == 0 || m > n {
+					anyhow::bail!("M_OF_N verification requires 1 <= m <= n, got m = {}", m);
+				}
+			}
+		}
+
+		Ok(Self { mode, signers, m })
+	}
+
+	pub fn try_from_request(
+		request: &UpdateVerificationParametersRequest,
+	) -> Result<Self, anyhow::Error> {
+		Self::try_new(request.mode(), &request.signers, request.m, request.n)
+	}
+
+	/// Whether at least one accepted signer has validly signed the blob.
+	pub fn is_signed_by_validator(&self, blob: &IntermediateBlobRepresentation) -> bool {
+		blob.verified_signers().iter().any(|signer| self.signers.contains(signer))
+	}
+
+	/// Whether at least m distinct accepted signers have validly signed the blob.
+	pub fn is_signed_by_m_of_n(&self, blob: &IntermediateBlobRepresentation) -> bool {
+		let signed = blob
+			.verified_signers()
+			.iter()
+			.filter(|signer| self.signers.contains(*signer))
+			.count();
+		signed >= self.m as usize
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use k256::{ecdsa::SigningKey, elliptic_curve::rand_core::OsRng};
+
+	fn hex_public_key(signing_key: &SigningKey) -> String {
+		hex::encode(encode_public_key(signing_key.verifying_key()))
+	}
+
+	#[test]
+	fn test_rejects_invalid_parameters() {
+		let signers = vec![
+			hex_public_key(&SigningKey::random(&mut OsRng)),
+			hex_public_key(&SigningKey::random(&mut OsRng)),
+		];
+
+		assert!(VerificationParameters::try_new(VerificationMode::ValidatorIn, &[], 0, 0).is_err());
+		assert!(VerificationParameters::try_new(VerificationMode::MOfN, &signers, 1, 3).is_err());
+		assert!(VerificationParameters::try_new(VerificationMode::MOfN, &signers, 0, 2).is_err());
+		assert!(VerificationParameters::try_new(VerificationMode::MOfN, &signers, 3, 2).is_err());
+		assert!(VerificationParameters::try_new(
+			VerificationMode::MOfN,
+			&["not a key".to_string()],
+			1,
+			1
+		)
+		.is_err());
+		assert!(VerificationParameters::try_new(VerificationMode::MOfN, &signers, 2, 2).is_ok());
+		assert!(VerificationParameters::try_new(VerificationMode::Cowboy, &[], 0, 0).is_ok());
+	}
+
+	#[test]
+	fn test_validator_in() -> Result<(), anyhow::Error> {
+		let validator = SigningKey::random(&mut OsRng);
+		let outsider = SigningKey::random(&mut OsRng);
+		let parameters = VerificationParameters::try_new(
+			VerificationMode::ValidatorIn,
+			&[hex_public_key(&validator)],
+			0,
+			0,
+		)?;
+
+		let blob = IntermediateBlobRepresentation::new(vec![1, 2, 3], 0);
+		assert!(!parameters.is_signed_by_validator(&blob));
+		assert!(!parameters.is_signed_by_validator(&blob.clone().sign(&outsider)));
+		assert!(parameters.is_signed_by_validator(&blob.clone().sign(&validator)));
+		assert!(parameters.is_signed_by_validator(&blob.sign(&outsider).sign(&validator)));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_m_of_n() -> Result<(), anyhow::Error> {
+		let keys = (0..3).map(|_| SigningKey::random(&mut OsRng)).collect::<Vec<_>>();
+		let outsider = SigningKey::random(&mut OsRng);
+		let parameters = VerificationParameters::try_new(
+			VerificationMode::MOfN,
+			&keys.iter().map(hex_public_key).collect::<Vec<_>>(),
+			2,
+			3,
+		)?;
+
+		let blob = IntermediateBlobRepresentation::new(vec![1, 2, 3], 0);
+		assert!(!parameters.is_signed_by_m_of_n(&blob.clone().sign(&keys[0])));
+		// signing twice with the same key does not count twice
+		assert!(!parameters.is_signed_by_m_of_n(&blob.clone().sign(&keys[0]).sign(&keys[0])));
+		assert!(!parameters.is_signed_by_m_of_n(&blob.clone().sign(&keys[0]).sign(&outsider)));
+		assert!(parameters.is_signed_by_m_of_n(&blob.clone().sign(&keys[0]).sign(&keys[2])));
+
+		// a tampered signature does not count
+		let mut tampered = blob.sign(&keys[0]).sign(&keys[1]);
+		tampered.signatures[1].signature[0] ^= 1;
+		assert!(!parameters.is_signed_by_m_of_n(&tampered));
+
+		Ok(())
+	}
+}
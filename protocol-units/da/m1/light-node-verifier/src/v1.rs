@@ -1,9 +1,23 @@
 use crate::Verifier;
 use celestia_rpc::{BlobClient, Client, HeaderClient};
 use celestia_types::{nmt::Namespace, Blob};
-use m1_da_light_node_grpc::VerificationMode;
+use jsonrpsee_core::ClientError;
 use std::sync::Arc;
 
+/// The message of the error the Celestia node returns for a blob which is not at the height.
+const BLOB_NOT_FOUND: &str = "blob: not found";
+
+/// Whether an error from the Celestia node means that there is no such blob at the height.
+///
+/// The Celestia node returns its errors as JSON-RPC call errors with the same generic code,
+/// so the call error is identified by its message. Transport and client errors are never taken for it.
+pub fn is_blob_not_found(error: &ClientError) -> bool {
+	match error {
+		ClientError::Call(error) => error.message() == BLOB_NOT_FOUND,
+		_ => false,
+	}
+}
+
 #[derive(Clone)]
 pub struct V1Verifier {
 	pub client: Arc<Client>,
@@ -12,16 +26,12 @@ pub struct V1Verifier {
 
 #[tonic::async_trait]
 impl Verifier for V1Verifier {
-	/// All verification is the same for now
-	async fn verify(
-		&self,
-		_verification_mode: VerificationMode,
-		blob: &[u8],
-		height: u64,
-	) -> Result<bool, anyhow::Error> {
-		let celestia_blob = Blob::new(self.namespace.clone(), blob.to_vec())?;
-
-		celestia_blob.validate()?;
+	async fn verify_inclusion(&self, blob: &[u8], height: u64) -> Result<bool, anyhow::Error> {
+		// a blob which is not valid in the namespace cannot have been included
+		let celestia_blob = match Blob::new(self.namespace.clone(), blob.to_vec()) {
+			Ok(celestia_blob) if celestia_blob.validate().is_ok() => celestia_blob,
+			_ => return Ok(false),
+		};
 
 		// wait for the header to be at the correct height
 		self.client.header_wait_for_height(height).await?;
@@ -31,49 +41,44 @@ impl Verifier for V1Verifier {
 		let root_hash = dah.row_root(0).ok_or(anyhow::anyhow!("No root hash found"))?;
 
 		// get the proof
-		let proofs = self
+		let proofs = match self
 			.client
 			.blob_get_proof(height, self.namespace.clone(), celestia_blob.commitment)
-			.await?;
+			.await
+		{
+			Ok(proofs) => proofs,
+			Err(e) if is_blob_not_found(&e) => return Ok(false),
+			Err(e) => return Err(e.into()),
+		};
 
 		// get the leaves
 		let leaves = celestia_blob.to_shares()?;
 
 		// check if included
-		for proof in proofs.iter() {
+		let included = proofs.iter().all(|proof| {
 			proof
 				.verify_complete_namespace(&root_hash, &leaves, self.namespace.into())
-				.map_err(|e| anyhow::anyhow!("Failed to verify proof: {:?}", e))?;
-		}
+				.is_ok()
+		});
 
-		Ok(true)
+		Ok(included)
 	}
+}
 
-	async fn verify_cowboy(
-		&self,
-		_verification_mode: VerificationMode,
-		_blob: &[u8],
-		_height: u64,
-	) -> Result<bool, anyhow::Error> {
-		unimplemented!()
-	}
+#[cfg(test)]
+pub mod test {
+	use super::*;
 
-	async fn verify_m_of_n(
-		&self,
-		_verification_mode: VerificationMode,
-		_blob: &[u8],
-		_height: u64,
-	) -> Result<bool, anyhow::Error> {
-		unimplemented!()
-	}
+	#[test]
+	fn test_blob_not_found() -> Result<(), anyhow::Error> {
+		let not_found = serde_json::from_str(r#"{"code":1,"message":"blob: not found"}"#)?;
+		assert!(is_blob_not_found(&ClientError::Call(not_found)));
 
-	async fn verifiy_validator_in(
-		&self,
-		_verification_mode: VerificationMode,
-		_blob: &[u8],
-		_height: u64,
-	) -> Result<bool, anyhow::Error> {
-		unimplemented!()
+		let other = serde_json::from_str(r#"{"code":1,"message":"header: not found"}"#)?;
+		assert!(!is_blob_not_found(&ClientError::Call(other)));
+		assert!(!is_blob_not_found(&ClientError::Custom(BLOB_NOT_FOUND.to_string())));
+
+		Ok(())
 	}
 }
 
@@ -94,7 +99,7 @@ mod tests {
 
 		let height = client.blob_submit(&[blob], GasPrice::default()).await?;
 
-		let included = verifier.verify(VerificationMode::Cowboy, &data, height).await?;
+		let included = verifier.verify_inclusion(&data, height).await?;
 
 		assert!(included);
 
@@ -116,19 +121,19 @@ mod tests {
 
 		let height = client.blob_submit(&[blob], GasPrice::default()).await?;
 
-		let included = verifier.verify(VerificationMode::Cowboy, &data, height).await?;
+		let included = verifier.verify_inclusion(&data, height).await?;
 
 		assert!(included);
 
 		let absent_data = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 7];
 
-		let absent_included = verifier.verify(VerificationMode::Cowboy, &absent_data, height).await;
+		let absent_included = verifier.verify_inclusion(&absent_data, height).await;
 
 		match absent_included {
-			Ok(_) => {
+			Ok(true) => {
 				assert!(false, "Should not have verified")
 			}
-			Err(_) => {}
+			Ok(false) | Err(_) => {}
 		}
 
 		Ok(())
@@ -149,18 +154,17 @@ mod tests {
 
 		let height = client.blob_submit(&[blob], GasPrice::default()).await?;
 
-		let included = verifier.verify(VerificationMode::Cowboy, &data, height).await?;
+		let included = verifier.verify_inclusion(&data, height).await?;
 
 		assert!(included);
 
-		let wrong_height_included =
-			verifier.verify(VerificationMode::Cowboy, &data, height + 1).await;
+		let wrong_height_included = verifier.verify_inclusion(&data, height + 1).await;
 
 		match wrong_height_included {
-			Ok(_) => {
+			Ok(true) => {
 				assert!(false, "Should not have verified")
 			}
-			Err(_) => {}
+			Ok(false) | Err(_) => {}
 		}
 
 		Ok(())
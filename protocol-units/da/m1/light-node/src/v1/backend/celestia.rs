@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use celestia_rpc::{BlobClient, Client, HeaderClient};
+use celestia_types::{blob::GasPrice, nmt::Namespace, Blob as CelestiaBlob};
+use m1_da_light_node_grpc::Blob;
+use m1_da_light_node_verifier::v1::is_blob_not_found;
+use tokio_stream::StreamExt;
+use tracing::debug;
+
+use crate::v1::backend::{DaBackend, HeightStream};
+
+/// A [DaBackend] which submits to and reads from a Celestia node.
+#[derive(Clone)]
+pub struct CelestiaBackend {
+	pub client: Arc<Client>,
+	pub namespace: Namespace,
+}
+
+impl CelestiaBackend {
+	pub fn new(client: Arc<Client>, namespace: Namespace) -> Self {
+		Self { client, namespace }
+	}
+
+	/// Creates a new blob instance with the provided data.
+	pub fn create_new_celestia_blob(&self, data: Vec<u8>) -> Result<CelestiaBlob, anyhow::Error> {
+		CelestiaBlob::new(self.namespace, data)
+			.map_err(|e| anyhow::anyhow!("Failed to create a blob: {}", e))
+	}
+
+	/// Submits Celestia blobs to the Celestia node.
+	pub async fn submit_celestia_blobs(
+		&self,
+		blobs: &[CelestiaBlob],
+	) -> Result<u64, anyhow::Error> {
+		let height = self
+			.client
+			.blob_submit(blobs, GasPrice::default())
+			.await
+			.map_err(|e| anyhow::anyhow!("Failed submitting the blob: {}", e))?;
+
+		Ok(height)
+	}
+
+	/// Gets the Celestia blobs at a given height.
+	pub async fn get_celestia_blobs_at_height(
+		&self,
+		height: u64,
+	) -> Result<Vec<CelestiaBlob>, anyhow::Error> {
+		match self.client.blob_get_all(height, &[self.namespace]).await {
+			Ok(blobs) => Ok(blobs),
+			// the Celestia node reports a height without blobs in the namespace as an error
+			Err(e) if is_blob_not_found(&e) => Ok(vec![]),
+			Err(e) => Err(anyhow::anyhow!("Failed to get blobs at height {}: {}", height, e)),
+		}
+	}
+
+	pub fn celestia_blob_to_blob(blob: CelestiaBlob, height: u64) -> Result<Blob, anyhow::Error> {
+		let timestamp = chrono::Utc::now().timestamp_micros() as u64;
+
+		Ok(Blob {
+			data: blob.data,
+			blob_id: serde_json::to_string(&blob.commitment)
+				.map_err(|e| anyhow::anyhow!("Failed to serialize commitment: {}", e))?,
+			height,
+			signatures: vec![],
+			timestamp,
+		})
+	}
+}
+
+#[tonic::async_trait]
+impl DaBackend for CelestiaBackend {
+	async fn submit_blobs(&self, data: Vec<Vec<u8>>) -> Result<Vec<Blob>, anyhow::Error> {
+		let celestia_blobs = data
+			.into_iter()
+			.map(|data| self.create_new_celestia_blob(data))
+			.collect::<Result<Vec<_>, _>>()?;
+		let height = self.submit_celestia_blobs(&celestia_blobs).await?;
+		celestia_blobs
+			.into_iter()
+			.map(|celestia_blob| Self::celestia_blob_to_blob(celestia_blob, height))
+			.collect()
+	}
+
+	async fn get_blobs_at_height(&self, height: u64) -> Result<Vec<Blob>, anyhow::Error> {
+		self.get_celestia_blobs_at_height(height)
+			.await?
+			.into_iter()
+			.map(|celestia_blob| Self::celestia_blob_to_blob(celestia_blob, height))
+			.collect()
+	}
+
+	async fn subscribe_heights(&self) -> Result<HeightStream, anyhow::Error> {
+		let mut subscription = self.client.header_subscribe().await?;
+
+		let stream = async_stream::try_stream! {
+			while let Some(header_res) = subscription.next().await {
+				let header = header_res?;
+				debug!("Subscription got header: {:?}", header.height());
+				yield header.height().into();
+			}
+		};
+
+		Ok(Box::pin(stream) as HeightStream)
+	}
+
+	async fn head_height(&self) -> Result<u64, anyhow::Error> {
+		let head = self.client.header_network_head().await?;
+		Ok(head.height().into())
+	}
+}
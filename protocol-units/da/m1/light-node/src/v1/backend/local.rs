@@ -0,0 +1,231 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use m1_da_light_node_grpc::Blob;
+use m1_da_light_node_verifier::Verifier;
+use prost::Message;
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use tokio::sync::{broadcast, Mutex};
+use tracing::debug;
+
+use crate::v1::backend::{DaBackend, HeightStream};
+
+mod cf {
+	pub const BLOBS: &str = "blobs";
+	pub const HEAD: &str = "head";
+}
+
+const HEAD_HEIGHT_KEY: &str = "head_height";
+
+/// A [DaBackend] which keeps blobs in a RocksDB database owned by the light node.
+///
+/// Every submission is included at a new height, starting from 1.
+/// This allows the light node to run without Celestia, e.g., in tests.
+#[derive(Clone)]
+pub struct LocalBackend {
+	db: Arc<DB>,
+	head_height: Arc<Mutex<u64>>,
+	heights: broadcast::Sender<u64>,
+}
+
+impl LocalBackend {
+	pub fn try_new(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+		let mut options = Options::default();
+		options.create_if_missing(true);
+		options.create_missing_column_families(true);
+
+		let blobs_cf = ColumnFamilyDescriptor::new(cf::BLOBS, Options::default());
+		let head_cf = ColumnFamilyDescriptor::new(cf::HEAD, Options::default());
+
+		let db = DB::open_cf_descriptors(&options, path, vec![blobs_cf, head_cf])
+			.map_err(|e| anyhow::anyhow!("Failed to open local DA DB: {:?}", e))?;
+
+		let head_height = {
+			let cf = db.cf_handle(cf::HEAD).ok_or(anyhow::anyhow!("No head column family"))?;
+			match db.get_cf(&cf, HEAD_HEIGHT_KEY)? {
+				Some(bytes) => u64::from_be_bytes(
+					bytes
+						.as_slice()
+						.try_into()
+						.map_err(|_| anyhow::anyhow!("Invalid head height"))?,
+				),
+				None => 0,
+			}
+		};
+
+		let (heights, _) = broadcast::channel(1024);
+
+		Ok(Self { db: Arc::new(db), head_height: Arc::new(Mutex::new(head_height)), heights })
+	}
+
+	fn blob_key(height: u64, index: u64) -> String {
+		format!("{:032}:{:032}", height, index)
+	}
+
+	fn height_prefix(height: u64) -> String {
+		format!("{:032}:", height)
+	}
+}
+
+#[tonic::async_trait]
+impl DaBackend for LocalBackend {
+	async fn submit_blobs(&self, data: Vec<Vec<u8>>) -> Result<Vec<Blob>, anyhow::Error> {
+		// hold the head for the whole write so that heights are assigned in order
+		let mut head_height = self.head_height.lock().await;
+		let height = *head_height + 1;
+		let timestamp = chrono::Utc::now().timestamp_micros() as u64;
+
+		let blobs = data
+			.into_iter()
+			.map(|data| Blob {
+				blob_id: hex::encode(blake3::hash(&data).as_bytes()),
+				data,
+				height,
+				signatures: vec![],
+				timestamp,
+			})
+			.collect::<Vec<_>>();
+
+		let db = self.db.clone();
+		let to_write = blobs.clone();
+		tokio::task::spawn_blocking(move || {
+			let blobs_cf =
+				db.cf_handle(cf::BLOBS).ok_or(anyhow::anyhow!("No blobs column family"))?;
+			let head_cf = db.cf_handle(cf::HEAD).ok_or(anyhow::anyhow!("No head column family"))?;
+
+			let mut batch = WriteBatch::default();
+			for (index, blob) in to_write.iter().enumerate() {
+				batch.put_cf(&blobs_cf, Self::blob_key(height, index as u64), blob.encode_to_vec());
+			}
+			batch.put_cf(&head_cf, HEAD_HEIGHT_KEY, height.to_be_bytes());
+			db.write(batch).map_err(|e| anyhow::anyhow!("Failed to write blobs: {:?}", e))
+		})
+		.await??;
+
+		*head_height = height;
+		// there may be no subscribers, which is not an error
+		let _ = self.heights.send(height);
+
+		Ok(blobs)
+	}
+
+	async fn get_blobs_at_height(&self, height: u64) -> Result<Vec<Blob>, anyhow::Error> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = db.cf_handle(cf::BLOBS).ok_or(anyhow::anyhow!("No blobs column family"))?;
+			let prefix = Self::height_prefix(height);
+			let iter =
+				db.iterator_cf(&cf, IteratorMode::From(prefix.as_bytes(), Direction::Forward));
+
+			let mut blobs = Vec::new();
+			for item in iter {
+				let (key, value) = item?;
+				if !key.starts_with(prefix.as_bytes()) {
+					break;
+				}
+				blobs.push(Blob::decode(value.as_ref())?);
+			}
+			Ok(blobs)
+		})
+		.await?
+	}
+
+	async fn subscribe_heights(&self) -> Result<HeightStream, anyhow::Error> {
+		let mut receiver = self.heights.subscribe();
+
+		let stream = async_stream::stream! {
+			loop {
+				match receiver.recv().await {
+					Ok(height) => yield Ok(height),
+					Err(broadcast::error::RecvError::Closed) => break,
+					// the skipped heights are read by the light node when the next height arrives
+					Err(broadcast::error::RecvError::Lagged(skipped)) => {
+						debug!("Height subscription lagged by {} heights", skipped);
+					}
+				}
+			}
+		};
+
+		Ok(Box::pin(stream) as HeightStream)
+	}
+
+	async fn head_height(&self) -> Result<u64, anyhow::Error> {
+		Ok(*self.head_height.lock().await)
+	}
+}
+
+/// A [Verifier] for blobs read from the [LocalBackend].
+///
+/// The blobs were written to the light node's own database, so their inclusion holds trivially.
+#[derive(Clone)]
+pub struct LocalVerifier;
+
+#[tonic::async_trait]
+impl Verifier for LocalVerifier {
+	async fn verify_inclusion(&self, _blob: &[u8], _height: u64) -> Result<bool, anyhow::Error> {
+		Ok(true)
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use tokio_stream::StreamExt;
+
+	#[tokio::test]
+	async fn test_submit_and_get_blobs() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let backend = LocalBackend::try_new(temp_dir.path())?;
+		assert_eq!(backend.head_height().await?, 0);
+
+		let submitted = backend.submit_blobs(vec![vec![1, 2, 3], vec![4, 5, 6]]).await?;
+		assert_eq!(submitted.len(), 2);
+		assert!(submitted.iter().all(|blob| blob.height == 1));
+		assert_eq!(backend.head_height().await?, 1);
+
+		backend.submit_blobs(vec![vec![7]]).await?;
+		assert_eq!(backend.head_height().await?, 2);
+
+		let blobs = backend.get_blobs_at_height(1).await?;
+		assert_eq!(blobs, submitted);
+		let blobs = backend.get_blobs_at_height(2).await?;
+		assert_eq!(blobs.len(), 1);
+		assert_eq!(blobs[0].data, vec![7]);
+		assert!(backend.get_blobs_at_height(3).await?.is_empty());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_head_height_survives_reopen() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		{
+			let backend = LocalBackend::try_new(temp_dir.path())?;
+			backend.submit_blobs(vec![vec![1]]).await?;
+			backend.submit_blobs(vec![vec![2]]).await?;
+		}
+
+		let backend = LocalBackend::try_new(temp_dir.path())?;
+		assert_eq!(backend.head_height().await?, 2);
+		let blobs = backend.submit_blobs(vec![vec![3]]).await?;
+		assert_eq!(blobs[0].height, 3);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_subscribe_heights() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let backend = LocalBackend::try_new(temp_dir.path())?;
+		let mut heights = backend.subscribe_heights().await?;
+
+		backend.submit_blobs(vec![vec![1]]).await?;
+		backend.submit_blobs(vec![vec![2]]).await?;
+
+		assert_eq!(heights.next().await.transpose()?, Some(1));
+		assert_eq!(heights.next().await.transpose()?, Some(2));
+
+		Ok(())
+	}
+}
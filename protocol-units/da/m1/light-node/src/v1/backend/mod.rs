@@ -0,0 +1,30 @@
+pub mod celestia;
+pub mod local;
+
+use m1_da_light_node_grpc::Blob;
+use std::pin::Pin;
+use tokio_stream::Stream;
+
+/// A stream of the heights produced by a [DaBackend].
+pub type HeightStream = Pin<Box<dyn Stream<Item = Result<u64, anyhow::Error>> + Send>>;
+
+/// The data availability layer the light node submits blobs to and reads blobs from.
+///
+/// Blobs returned by a backend carry the raw data as it was submitted.
+/// Verification is left to the light node.
+#[tonic::async_trait]
+pub trait DaBackend: Send + Sync {
+	/// Submits the blob data, returning the blobs as they were included.
+	async fn submit_blobs(&self, data: Vec<Vec<u8>>) -> Result<Vec<Blob>, anyhow::Error>;
+
+	/// Gets the blobs included at a given height.
+	async fn get_blobs_at_height(&self, height: u64) -> Result<Vec<Blob>, anyhow::Error>;
+
+	/// Subscribes to the heights produced by the DA layer from now on.
+	///
+	/// Heights may be skipped, e.g., when the subscriber falls behind.
+	async fn subscribe_heights(&self) -> Result<HeightStream, anyhow::Error>;
+
+	/// Gets the height of the current head of the DA layer.
+	async fn head_height(&self) -> Result<u64, anyhow::Error>;
+}
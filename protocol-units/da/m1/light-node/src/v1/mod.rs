@@ -1,3 +1,4 @@
+pub mod backend;
 pub mod passthrough;
 #[cfg(feature = "sequencer")]
 pub mod sequencer;
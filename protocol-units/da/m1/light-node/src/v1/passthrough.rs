@@ -1,35 +1,50 @@
 use anyhow::Context;
+use std::collections::BTreeSet;
 use std::fmt::{self, Debug, Formatter};
 use std::sync::Arc;
+use std::time::Duration;
 
+use k256::ecdsa::SigningKey;
 use tokio::sync::RwLock;
 use tokio_stream::{Stream, StreamExt};
-use tracing::debug;
-
-use celestia_rpc::{BlobClient, Client, HeaderClient};
-use celestia_types::{blob::GasPrice, nmt::Namespace, Blob as CelestiaBlob};
+use tracing::{debug, info, warn};
 
 // FIXME: glob imports are bad style
 use m1_da_light_node_grpc::light_node_service_server::LightNodeService;
 use m1_da_light_node_grpc::*;
-use m1_da_light_node_util::config::Config;
-use m1_da_light_node_verifier::{v1::V1Verifier, Verifier};
-
-use crate::v1::LightNodeV1Operations;
+use m1_da_light_node_util::{
+	config::{local::m1_da_light_node::DaBackendKind, Config},
+	ir_blob::{decode_hex_public_key, encode_public_key, IntermediateBlobRepresentation},
+};
+use m1_da_light_node_verifier::{parameters::VerificationParameters, v1::V1Verifier, Verifier};
+
+use crate::v1::{
+	backend::{
+		celestia::CelestiaBackend,
+		local::{LocalBackend, LocalVerifier},
+		DaBackend,
+	},
+	LightNodeV1Operations,
+};
+
+/// The delay before the first retry of a failed read, growing linearly with each attempt.
+const READ_RETRY_BACKOFF: Duration = Duration::from_millis(500);
 
 #[derive(Clone)]
 pub struct LightNodeV1 {
 	pub config: Config,
-	pub celestia_namespace: Namespace,
-	pub default_client: Arc<Client>,
-	pub verification_mode: Arc<RwLock<VerificationMode>>,
+	pub backend: Arc<dyn DaBackend>,
+	pub verification_parameters: Arc<RwLock<VerificationParameters>>,
 	pub verifier: Arc<Box<dyn Verifier + Send + Sync>>,
+	pub signing_key: SigningKey,
 }
 
 impl Debug for LightNodeV1 {
 	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
 		f.debug_struct("LightNodeV1")
+			.field("da_backend", &self.config.da_backend())
 			.field("celestia_namespace", &self.config.celestia_namespace())
+			.field("signer", &hex::encode(encode_public_key(self.signing_key.verifying_key())))
 			.finish()
 	}
 }
@@ -37,20 +52,46 @@ impl Debug for LightNodeV1 {
 impl LightNodeV1Operations for LightNodeV1 {
 	/// Tries to create a new LightNodeV1 instance from the toml config file.
 	async fn try_from_config(config: Config) -> Result<Self, anyhow::Error> {
-		let client = Arc::new(config.connect_celestia().await?);
+		let (backend, verifier): (Arc<dyn DaBackend>, Box<dyn Verifier + Send + Sync>) =
+			match config.da_backend() {
+				DaBackendKind::Celestia => {
+					let client = Arc::new(config.connect_celestia().await?);
+					(
+						Arc::new(CelestiaBackend::new(client.clone(), config.celestia_namespace())),
+						Box::new(V1Verifier { client, namespace: config.celestia_namespace() }),
+					)
+				}
+				DaBackendKind::Local => (
+					Arc::new(LocalBackend::try_new(config.m1_da_light_node_local_da_path())?),
+					Box::new(LocalVerifier),
+				),
+			};
+
+		// by default, accept blobs signed by this node or any of the known signers
+		let signing_key = config.try_signing_key()?;
+		let mut signers = config
+			.m1_da_light_node_known_signers()
+			.iter()
+			.map(|signer| decode_hex_public_key(signer).map(|key| encode_public_key(&key)))
+			.collect::<Result<BTreeSet<_>, _>>()
+			.context("Invalid known signers")?;
+		// this node may also be among the known signers, but n counts distinct signers
+		signers.insert(encode_public_key(signing_key.verifying_key()));
+		let signers = signers.into_iter().map(hex::encode).collect::<Vec<_>>();
+		let verification_parameters = VerificationParameters::try_new(
+			VerificationMode::MOfN,
+			&signers,
+			1,
+			signers.len() as u32,
+		)
+		.context("Invalid known signers")?;
 
 		Ok(Self {
 			config: config.clone(),
-			celestia_namespace: config.celestia_namespace(),
-			default_client: client.clone(),
-			verification_mode: Arc::new(RwLock::new(
-				VerificationMode::from_str_name("M_OF_N")
-					.context("Failed to parse verification mode")?,
-			)),
-			verifier: Arc::new(Box::new(V1Verifier {
-				client,
-				namespace: config.celestia_namespace(),
-			})),
+			backend,
+			verification_parameters: Arc::new(RwLock::new(verification_parameters)),
+			verifier: Arc::new(verifier),
+			signing_key,
 		})
 	}
 
@@ -65,97 +106,140 @@ impl LightNodeV1Operations for LightNodeV1 {
 }
 
 impl LightNodeV1 {
-	/// Creates a new blob instance with the provided data.
-	pub fn create_new_celestia_blob(&self, data: Vec<u8>) -> Result<CelestiaBlob, anyhow::Error> {
-		CelestiaBlob::new(self.celestia_namespace, data)
-			.map_err(|e| anyhow::anyhow!("Failed to create a blob: {}", e))
-	}
-
-	/// Submits a CelestiaNlob to the Celestia node.
-	pub async fn submit_celestia_blob(&self, blob: CelestiaBlob) -> Result<u64, anyhow::Error> {
-		let height = self
-			.default_client
-			.blob_submit(&[blob], GasPrice::default())
-			.await
-			.map_err(|e| anyhow::anyhow!("Failed submitting the blob: {}", e))?;
-
-		Ok(height)
+	/// Signs blobs and submits them to the DA backend.
+	pub async fn submit_blobs(&self, data: Vec<Vec<u8>>) -> Result<Vec<Blob>, anyhow::Error> {
+		let timestamp = chrono::Utc::now().timestamp_micros() as u64;
+		let ir_blobs = data
+			.into_iter()
+			.map(|data| {
+				IntermediateBlobRepresentation::new(data, timestamp)
+					.sign(&self.signing_key)
+					.try_to_bytes()
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		self.backend
+			.submit_blobs(ir_blobs)
+			.await?
+			.into_iter()
+			.map(Self::ir_blob_to_blob)
+			.collect()
 	}
 
-	/// Submits Celestia blobs to the Celestia node.
-	pub async fn submit_celestia_blobs(
-		&self,
-		blobs: &[CelestiaBlob],
-	) -> Result<u64, anyhow::Error> {
-		let height = self
-			.default_client
-			.blob_submit(blobs, GasPrice::default())
-			.await
-			.map_err(|e| anyhow::anyhow!("Failed submitting the blob: {}", e))?;
-
-		Ok(height)
+	/// Submits a blob to the DA backend.
+	pub async fn submit_blob(&self, data: Vec<u8>) -> Result<Blob, anyhow::Error> {
+		self.submit_blobs(vec![data])
+			.await?
+			.pop()
+			.context("DA backend did not return the submitted blob")
 	}
 
-	/// Submits a blob to the Celestia node.
-	pub async fn submit_blob(&self, data: Vec<u8>) -> Result<Blob, anyhow::Error> {
-		let celestia_blob = self.create_new_celestia_blob(data)?;
-		let height = self.submit_celestia_blob(celestia_blob.clone()).await?;
-		Ok(Self::celestia_blob_to_blob(celestia_blob, height)?)
+	#[tracing::instrument(target = "movement_timing", level = "debug")]
+	async fn get_blobs_at_height(&self, height: u64) -> Result<Vec<Blob>, anyhow::Error> {
+		Ok(self
+			.get_indexed_blobs_at_height(height)
+			.await?
+			.into_iter()
+			.map(|(_, blob)| blob)
+			.collect())
 	}
 
-	/// Gets the blobs at a given height.
-	pub async fn get_celestia_blobs_at_height(
+	/// Gets the verified blobs at a height, along with their index among all the blobs at the height.
+	///
+	/// The index does not depend on the verification parameters, so it can be used to resume a stream.
+	async fn get_indexed_blobs_at_height(
 		&self,
 		height: u64,
-	) -> Result<Vec<CelestiaBlob>, anyhow::Error> {
-		let blobs = self.default_client.blob_get_all(height, &[self.celestia_namespace]).await;
-
-		if let Err(e) = &blobs {
-			debug!("Error getting blobs: {:?}", e);
-		}
-
-		let blobs = blobs.unwrap_or_default();
+	) -> Result<Vec<(u64, Blob)>, anyhow::Error> {
+		let blobs = self.backend.get_blobs_at_height(height).await?;
+		let verification_parameters = self.verification_parameters.read().await.clone();
 
 		let mut verified_blobs = Vec::new();
-		for blob in blobs {
+		for (index, blob) in blobs.into_iter().enumerate() {
 			debug!("Verifying blob");
 
-			let blob_data = blob.data.clone();
-
-			// todo: improve error boundary here to detect crashes
+			// invalid blobs are skipped, but a verification which could not complete fails the
+			// read, so that it is retried rather than the blob being skipped
 			let verified = self
 				.verifier
-				.verify(*self.verification_mode.read().await, &blob_data, height)
-				.await;
-
-			if let Err(e) = &verified {
-				debug!("Error verifying blob: {:?}", e);
+				.verify(&verification_parameters, &blob.data, height)
+				.await
+				.with_context(|| format!("Failed to verify blob {}", blob.blob_id))?;
+			if !verified {
+				warn!(blob_id = %blob.blob_id, "Skipping blob which failed verification");
+				continue;
 			}
 
-			// FIXME: check the implications of treating errors as verification success.
-			// @l-monninger: under the assumption we are running a light node in the same
-			// trusted setup and have not experience a highly intrusive(?), the vulnerability here
-			// is fairly low. The light node should take care of verification on its own.
-			let verified = verified.unwrap_or(true);
-
-			if verified {
-				verified_blobs.push(blob);
+			match Self::ir_blob_to_blob(blob) {
+				Ok(blob) => {
+					debug!(blob_id = %blob.blob_id, "got blob");
+					verified_blobs.push((index as u64, blob));
+				}
+				Err(e) => {
+					warn!(
+						"Skipping blob which is not an intermediate blob representation: {:?}",
+						e
+					);
+				}
 			}
 		}
 
 		Ok(verified_blobs)
 	}
 
-	#[tracing::instrument(target = "movement_timing", level = "debug")]
-	async fn get_blobs_at_height(&self, height: u64) -> Result<Vec<Blob>, anyhow::Error> {
-		let celestia_blobs = self.get_celestia_blobs_at_height(height).await?;
-		let mut blobs = Vec::new();
-		for celestia_blob in celestia_blobs {
-			let blob = Self::celestia_blob_to_blob(celestia_blob, height)?;
-			debug!(blob_id = %blob.blob_id, "got blob");
-			blobs.push(blob);
+	/// Gets the verified blobs at a height, retrying reads which fail.
+	///
+	/// An error is only returned once the retries are exhausted, so that the height is never skipped silently.
+	async fn read_height_with_retries(
+		&self,
+		height: u64,
+	) -> Result<Vec<(u64, Blob)>, anyhow::Error> {
+		let retries = self.config.m1_da_light_node_read_retries();
+		let mut attempt = 0;
+		loop {
+			match self.get_indexed_blobs_at_height(height).await {
+				Ok(blobs) => return Ok(blobs),
+				Err(e) if attempt < retries => {
+					attempt += 1;
+					warn!(height, attempt, "Failed to read blobs, retrying: {:?}", e);
+					tokio::time::sleep(READ_RETRY_BACKOFF * attempt).await;
+				}
+				Err(e) => {
+					return Err(e.context(format!("Failed to read blobs at height {}", height)))
+				}
+			}
 		}
-		Ok(blobs)
+	}
+
+	/// Streams the blobs from the position of the resume token up to and including the end height.
+	///
+	/// Heights are read in parallel, but the blobs are yielded in order, each with the token to resume after it.
+	fn stream_blobs_until(
+		&self,
+		from: ResumeToken,
+		end_height: u64,
+	) -> std::pin::Pin<Box<dyn Stream<Item = Result<(Blob, ResumeToken), anyhow::Error>> + Send>> {
+		let me = Arc::new(self.clone());
+		let concurrency = self.config.m1_da_light_node_backfill_concurrency().max(1);
+		let reads = (from.height..=end_height).map(move |height| {
+			let me = me.clone();
+			async move { (height, me.read_height_with_retries(height).await) }
+		});
+		let mut heights = futures::StreamExt::buffered(futures::stream::iter(reads), concurrency);
+
+		let stream = async_stream::try_stream! {
+			while let Some((height, blobs)) = heights.next().await {
+				for (index, blob) in blobs? {
+					// blobs before the resume token have already been read
+					if height == from.height && index < from.blob_index {
+						continue;
+					}
+					yield (blob, ResumeToken { height, blob_index: index + 1 });
+				}
+			}
+		};
+
+		Box::pin(stream)
 	}
 
 	/// Streams blobs until it can't get another one in the loop
@@ -167,87 +251,95 @@ impl LightNodeV1 {
 		std::pin::Pin<Box<dyn Stream<Item = Result<Blob, anyhow::Error>> + Send>>,
 		anyhow::Error,
 	> {
-		let mut height = start_height;
-		let end_height = end_height.unwrap_or_else(|| u64::MAX);
-		let me = Arc::new(self.clone());
-
-		let stream = async_stream::try_stream! {
-			loop {
-				if height > end_height {
-					break;
-				}
-
-				let blobs = me.get_blobs_at_height(height).await?;
-				for blob in blobs {
-					yield blob;
-				}
-				height += 1;
-			}
-		};
+		let end_height = end_height.unwrap_or(u64::MAX);
+		let from = ResumeToken { height: start_height, blob_index: 0 };
+		let stream = self.stream_blobs_until(from, end_height).map(|res| res.map(|(blob, _)| blob));
 
 		Ok(Box::pin(stream)
 			as std::pin::Pin<Box<dyn Stream<Item = Result<Blob, anyhow::Error>> + Send>>)
 	}
 
-	/// Streams the latest blobs that can subscribed to.
-	async fn stream_blobs_from_height_on(
+	/// Streams the blobs from the resume token on, or from the next height if there is none.
+	///
+	/// Heights from the token up to the current head are backfilled in parallel.
+	/// After that, the stream follows the head, reading any heights the subscription skipped.
+	/// Heights are never skipped: a height which cannot be read ends the stream with an error,
+	/// and the consumer can resume from the token of the last blob it received.
+	async fn stream_blobs_from(
 		&self,
-		start_height: Option<u64>,
+		from: Option<ResumeToken>,
 	) -> Result<
-		std::pin::Pin<Box<dyn Stream<Item = Result<Blob, anyhow::Error>> + Send>>,
+		std::pin::Pin<Box<dyn Stream<Item = Result<(Blob, ResumeToken), anyhow::Error>> + Send>>,
 		anyhow::Error,
 	> {
-		let start_height = start_height.unwrap_or_else(|| u64::MAX);
 		let me = Arc::new(self.clone());
-		let mut subscription = me.default_client.header_subscribe().await?;
+		// subscribe before getting the head, so that no height is missed in between
+		let mut subscription = me.backend.subscribe_heights().await?;
+		let head_height = me.backend.head_height().await?;
+
+		// heights start at 1
+		let mut next = match from {
+			Some(token) if token.height > 0 => token,
+			Some(_) => ResumeToken { height: 1, blob_index: 0 },
+			None => ResumeToken { height: head_height + 1, blob_index: 0 },
+		};
 
 		let stream = async_stream::try_stream! {
-			let mut first_flag = true;
-			while let Some(header_res) = subscription.next().await {
-
-				let header = header_res?;
-				let height = header.height().into();
-
-				debug!("Stream got header: {:?}", header.height());
-
-				// back fetch the blobs
-				if first_flag && (height > start_height) {
-
-					let mut blob_stream = me.stream_blobs_in_range(start_height, Some(height)).await?;
-
-					while let Some(blob) = blob_stream.next().await {
+			if next.height <= head_height {
+				debug!(from = next.height, to = head_height, "Stream backfilling heights");
+				let mut blob_stream = me.stream_blobs_until(next.clone(), head_height);
+				while let Some(blob) = blob_stream.next().await {
+					yield blob?;
+				}
+				next = ResumeToken { height: head_height + 1, blob_index: 0 };
+			}
 
-						debug!("Stream got blob: {:?}", blob);
+			while let Some(height) = subscription.next().await {
+				let height = height?;
 
-						yield blob?;
-					}
+				debug!("Stream got height: {:?}", height);
 
+				// the height was already read while backfilling
+				if height < next.height {
+					continue;
+				}
+				if height > next.height {
+					info!(from = next.height, to = height, "Stream reading heights the subscription skipped");
 				}
-				first_flag = false;
-
-				let blobs = me.get_blobs_at_height(height).await?;
-				for blob in blobs {
-
-					debug!("Stream got blob: {:?}", blob);
 
-					yield blob;
+				let mut blob_stream = me.stream_blobs_until(next.clone(), height);
+				while let Some(blob) = blob_stream.next().await {
+					yield blob?;
 				}
+				next = ResumeToken { height: height + 1, blob_index: 0 };
 			}
+
+			// the stream only ends on an error, so that the consumer resumes it rather than waiting for blobs
+			Err(anyhow::anyhow!("The height subscription ended before height {}", next.height))?;
 		};
 
 		Ok(Box::pin(stream)
-			as std::pin::Pin<Box<dyn Stream<Item = Result<Blob, anyhow::Error>> + Send>>)
+			as std::pin::Pin<
+				Box<dyn Stream<Item = Result<(Blob, ResumeToken), anyhow::Error>> + Send>,
+			>)
 	}
 
-	pub fn celestia_blob_to_blob(blob: CelestiaBlob, height: u64) -> Result<Blob, anyhow::Error> {
-		let timestamp = chrono::Utc::now().timestamp_micros() as u64;
-
+	/// Converts a blob as stored in the DA backend back into the blob that was submitted.
+	pub fn ir_blob_to_blob(blob: Blob) -> Result<Blob, anyhow::Error> {
+		let ir_blob = IntermediateBlobRepresentation::try_from_bytes(&blob.data)?;
 		Ok(Blob {
-			data: blob.data,
-			blob_id: serde_json::to_string(&blob.commitment)
-				.map_err(|e| anyhow::anyhow!("Failed to serialize commitment: {}", e))?,
-			height,
-			timestamp,
+			blob_id: blob.blob_id,
+			data: ir_blob.data,
+			height: blob.height,
+			signatures: ir_blob
+				.signatures
+				.into_iter()
+				.map(|signature| BlobSignature {
+					signer: signature.signer,
+					signature: signature.signature,
+				})
+				.collect(),
+			timestamp: ir_blob.timestamp,
 		})
 	}
 
@@ -283,16 +375,21 @@ impl LightNodeService for LightNodeV1 {
 		request: tonic::Request<StreamReadFromHeightRequest>,
 	) -> std::result::Result<tonic::Response<Self::StreamReadFromHeightStream>, tonic::Status> {
 		let me = Arc::new(self.clone());
-		let height = request.into_inner().height;
+		let request = request.into_inner();
+		let from = request
+			.resume_token
+			.unwrap_or(ResumeToken { height: request.height, blob_index: 0 });
 
 		let output = async_stream::try_stream! {
 
-			let mut blob_stream = me.stream_blobs_from_height_on(Some(height)).await.map_err(|e| tonic::Status::internal(e.to_string()))?;
+			let mut blob_stream = me.stream_blobs_from(Some(from)).await.map_err(|e| tonic::Status::unavailable(format!("{:#}", e)))?;
 
 			while let Some(blob) = blob_stream.next().await {
-				let blob = blob.map_err(|e| tonic::Status::internal(e.to_string()))?;
+				// a failed read is reported rather than skipped, the consumer can resume from its last token
+				let (blob, resume_token) = blob.map_err(|e| tonic::Status::unavailable(format!("{:#}", e)))?;
 				let response = StreamReadFromHeightResponse {
-					blob : Some(Self::blob_to_blob_read_response(blob).map_err(|e| tonic::Status::internal(e.to_string()))?)
+					blob : Some(Self::blob_to_blob_read_response(blob).map_err(|e| tonic::Status::internal(e.to_string()))?),
+					resume_token: Some(resume_token),
 				};
 				yield response;
 			}
@@ -316,9 +413,9 @@ impl LightNodeService for LightNodeV1 {
 
 		let output = async_stream::try_stream! {
 
-			let mut blob_stream = me.stream_blobs_from_height_on(None).await.map_err(|e| tonic::Status::internal(e.to_string()))?;
+			let mut blob_stream = me.stream_blobs_from(None).await.map_err(|e| tonic::Status::unavailable(format!("{:#}", e)))?;
 			while let Some(blob) = blob_stream.next().await {
-				let blob = blob.map_err(|e| tonic::Status::internal(e.to_string()))?;
+				let (blob, _) = blob.map_err(|e| tonic::Status::unavailable(format!("{:#}", e)))?;
 				let response = StreamReadLatestResponse {
 					blob : Some(Self::blob_to_blob_read_response(blob).map_err(|e| tonic::Status::internal(e.to_string()))?)
 				};
@@ -442,19 +539,326 @@ impl LightNodeService for LightNodeV1 {
 			);
 		}
 
-		Ok(tonic::Response::new(BatchWriteResponse { blobs: blob_responses }))
+		Ok(tonic::Response::new(BatchWriteResponse { blobs: blob_responses, rejected: Vec::new() }))
 	}
 	/// Update and manage verification parameters.
 	async fn update_verification_parameters(
 		&self,
 		request: tonic::Request<UpdateVerificationParametersRequest>,
 	) -> std::result::Result<tonic::Response<UpdateVerificationParametersResponse>, tonic::Status> {
-		let verification_mode = request.into_inner().mode();
-		let mut mode = self.verification_mode.write().await;
-		*mode = verification_mode;
+		let verification_parameters =
+			VerificationParameters::try_from_request(&request.into_inner())
+				.map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+		let mode = verification_parameters.mode;
+		*self.verification_parameters.write().await = verification_parameters;
 
-		Ok(tonic::Response::new(UpdateVerificationParametersResponse {
-			mode: verification_mode.into(),
-		}))
+		Ok(tonic::Response::new(UpdateVerificationParametersResponse { mode: mode.into() }))
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use crate::v1::backend::HeightStream;
+	use k256::elliptic_curve::rand_core::OsRng;
+	use m1_da_light_node_util::config::local;
+
+	async fn local_light_node(path: &std::path::Path) -> Result<LightNodeV1, anyhow::Error> {
+		let mut local_config = local::Config::default();
+		local_config.m1_da_light_node.m1_da_light_node_da_backend = DaBackendKind::Local;
+		local_config.m1_da_light_node.m1_da_light_node_local_da_path =
+			path.to_string_lossy().to_string();
+		LightNodeV1::try_from_config(Config::Local(local_config)).await
+	}
+
+	fn response_blob(response: BlobResponse) -> Result<Blob, anyhow::Error> {
+		match response.blob_type.context("No blob type")? {
+			blob_response::BlobType::PassedThroughBlob(blob) => Ok(blob),
+			blob_response::BlobType::SequencedBlobBlock(blob) => Ok(blob),
+			blob_response::BlobType::SequencedBlobIntent(blob) => Ok(blob),
+		}
+	}
+
+	/// A [DaBackend] whose reads always fail.
+	struct UnreadableBackend(LocalBackend);
+
+	#[tonic::async_trait]
+	impl DaBackend for UnreadableBackend {
+		async fn submit_blobs(&self, data: Vec<Vec<u8>>) -> Result<Vec<Blob>, anyhow::Error> {
+			self.0.submit_blobs(data).await
+		}
+
+		async fn get_blobs_at_height(&self, _height: u64) -> Result<Vec<Blob>, anyhow::Error> {
+			anyhow::bail!("DA layer unavailable")
+		}
+
+		async fn subscribe_heights(&self) -> Result<HeightStream, anyhow::Error> {
+			self.0.subscribe_heights().await
+		}
+
+		async fn head_height(&self) -> Result<u64, anyhow::Error> {
+			self.0.head_height().await
+		}
+	}
+
+	/// A [DaBackend] whose height subscription ends at once.
+	struct EndedSubscriptionBackend(LocalBackend);
+
+	#[tonic::async_trait]
+	impl DaBackend for EndedSubscriptionBackend {
+		async fn submit_blobs(&self, data: Vec<Vec<u8>>) -> Result<Vec<Blob>, anyhow::Error> {
+			self.0.submit_blobs(data).await
+		}
+
+		async fn get_blobs_at_height(&self, height: u64) -> Result<Vec<Blob>, anyhow::Error> {
+			self.0.get_blobs_at_height(height).await
+		}
+
+		async fn subscribe_heights(&self) -> Result<HeightStream, anyhow::Error> {
+			Ok(Box::pin(tokio_stream::empty()) as HeightStream)
+		}
+
+		async fn head_height(&self) -> Result<u64, anyhow::Error> {
+			self.0.head_height().await
+		}
+	}
+
+	async fn next_response(
+		stream: &mut <LightNodeV1 as LightNodeService>::StreamReadFromHeightStream,
+	) -> Result<StreamReadFromHeightResponse, anyhow::Error> {
+		let response = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+			.await?
+			.context("Stream ended")??;
+		Ok(response)
+	}
+
+	#[tokio::test]
+	async fn test_stream_read_from_resume_token() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let light_node = local_light_node(temp_dir.path()).await?;
+		light_node.submit_blobs(vec![vec![1], vec![2]]).await?;
+		light_node.submit_blob(vec![3]).await?;
+
+		// resume after the first blob at height 1
+		let mut stream = light_node
+			.stream_read_from_height(tonic::Request::new(StreamReadFromHeightRequest {
+				height: 1,
+				resume_token: Some(ResumeToken { height: 1, blob_index: 1 }),
+			}))
+			.await?
+			.into_inner();
+
+		let response = next_response(&mut stream).await?;
+		assert_eq!(response_blob(response.blob.context("No blob")?)?.data, vec![2]);
+		assert_eq!(response.resume_token, Some(ResumeToken { height: 1, blob_index: 2 }));
+
+		let response = next_response(&mut stream).await?;
+		assert_eq!(response_blob(response.blob.context("No blob")?)?.data, vec![3]);
+		assert_eq!(response.resume_token, Some(ResumeToken { height: 2, blob_index: 1 }));
+
+		// the stream follows the head once it has caught up
+		light_node.submit_blob(vec![4]).await?;
+		let response = next_response(&mut stream).await?;
+		assert_eq!(response_blob(response.blob.context("No blob")?)?.data, vec![4]);
+		assert_eq!(response.resume_token, Some(ResumeToken { height: 3, blob_index: 1 }));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_stream_read_reports_failed_reads() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let mut light_node = local_light_node(temp_dir.path()).await?;
+		if let Config::Local(local_config) = &mut light_node.config {
+			local_config.m1_da_light_node.m1_da_light_node_read_retries = 0;
+		}
+		let backend = LocalBackend::try_new(temp_dir.path().join("unreadable"))?;
+		backend.submit_blobs(vec![vec![1]]).await?;
+		light_node.backend = Arc::new(UnreadableBackend(backend));
+
+		let mut stream = light_node
+			.stream_read_from_height(tonic::Request::new(StreamReadFromHeightRequest {
+				height: 1,
+				resume_token: None,
+			}))
+			.await?
+			.into_inner();
+
+		// the height is not skipped as if it had no blobs
+		let status = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+			.await?
+			.context("Stream ended")?
+			.unwrap_err();
+		assert_eq!(status.code(), tonic::Code::Unavailable);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_stream_read_reports_ended_subscription() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let mut light_node = local_light_node(temp_dir.path()).await?;
+		let backend = LocalBackend::try_new(temp_dir.path().join("ended"))?;
+		light_node.backend = Arc::new(EndedSubscriptionBackend(backend));
+
+		let mut stream = light_node
+			.stream_read_from_height(tonic::Request::new(StreamReadFromHeightRequest {
+				height: 1,
+				resume_token: None,
+			}))
+			.await?
+			.into_inner();
+
+		// the stream does not end as if there were no more blobs to read
+		let status = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+			.await?
+			.context("Stream ended")?
+			.unwrap_err();
+		assert_eq!(status.code(), tonic::Code::Unavailable);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_batch_write_then_read_at_height() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let light_node = local_light_node(temp_dir.path()).await?;
+
+		let written = light_node
+			.batch_write(tonic::Request::new(BatchWriteRequest {
+				blobs: vec![BlobWrite { data: vec![1, 2, 3] }, BlobWrite { data: vec![4, 5, 6] }],
+			}))
+			.await?
+			.into_inner()
+			.blobs
+			.into_iter()
+			.map(response_blob)
+			.collect::<Result<Vec<_>, _>>()?;
+		assert_eq!(written.len(), 2);
+		// each write in the batch is its own submission
+		assert_eq!(written[0].height, 1);
+		assert_eq!(written[1].height, 2);
+
+		let read = light_node
+			.read_at_height(tonic::Request::new(ReadAtHeightRequest { height: 2 }))
+			.await?
+			.into_inner()
+			.blobs
+			.into_iter()
+			.map(response_blob)
+			.collect::<Result<Vec<_>, _>>()?;
+		assert_eq!(read, vec![written[1].clone()]);
+
+		let status = light_node
+			.read_at_height(tonic::Request::new(ReadAtHeightRequest { height: 3 }))
+			.await
+			.unwrap_err();
+		assert_eq!(status.code(), tonic::Code::NotFound);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_batch_read() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let light_node = local_light_node(temp_dir.path()).await?;
+		light_node.submit_blobs(vec![vec![1], vec![2]]).await?;
+		light_node.submit_blob(vec![3]).await?;
+
+		let responses = light_node
+			.batch_read(tonic::Request::new(BatchReadRequest { heights: vec![1, 2] }))
+			.await?
+			.into_inner()
+			.responses;
+		assert_eq!(responses.len(), 2);
+		assert_eq!(responses[0].blobs.len(), 2);
+		assert_eq!(responses[1].blobs.len(), 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_written_blobs_are_signed() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let light_node = local_light_node(temp_dir.path()).await?;
+		let written = light_node.submit_blob(vec![1, 2, 3]).await?;
+
+		assert_eq!(written.data, vec![1, 2, 3]);
+		assert_eq!(written.signatures.len(), 1);
+		assert_eq!(
+			written.signatures[0].signer,
+			encode_public_key(light_node.signing_key.verifying_key())
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_validator_in_skips_unknown_signers() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let light_node = local_light_node(temp_dir.path()).await?;
+		light_node.submit_blob(vec![1, 2, 3]).await?;
+
+		let other_signer = SigningKey::random(&mut OsRng);
+		light_node
+			.update_verification_parameters(tonic::Request::new(
+				UpdateVerificationParametersRequest {
+					mode: VerificationMode::ValidatorIn.into(),
+					signers: vec![hex::encode(encode_public_key(other_signer.verifying_key()))],
+					m: 0,
+					n: 0,
+				},
+			))
+			.await?;
+		let status = light_node
+			.read_at_height(tonic::Request::new(ReadAtHeightRequest { height: 1 }))
+			.await
+			.unwrap_err();
+		assert_eq!(status.code(), tonic::Code::NotFound);
+
+		light_node
+			.update_verification_parameters(tonic::Request::new(
+				UpdateVerificationParametersRequest {
+					mode: VerificationMode::ValidatorIn.into(),
+					signers: vec![hex::encode(encode_public_key(
+						light_node.signing_key.verifying_key(),
+					))],
+					m: 0,
+					n: 0,
+				},
+			))
+			.await?;
+		let read = light_node
+			.read_at_height(tonic::Request::new(ReadAtHeightRequest { height: 1 }))
+			.await?
+			.into_inner()
+			.blobs;
+		assert_eq!(read.len(), 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_update_verification_parameters_rejects_invalid() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let light_node = local_light_node(temp_dir.path()).await?;
+
+		let status = light_node
+			.update_verification_parameters(tonic::Request::new(
+				UpdateVerificationParametersRequest {
+					mode: VerificationMode::MOfN.into(),
+					signers: vec![hex::encode(encode_public_key(
+						light_node.signing_key.verifying_key(),
+					))],
+					m: 2,
+					n: 1,
+				},
+			))
+			.await
+			.unwrap_err();
+		assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+		Ok(())
 	}
 }
@@ -1,22 +1,27 @@
 use std::{
+	collections::VecDeque,
 	sync::{atomic::AtomicU64, Arc},
 	time::Duration,
 };
 use tokio_stream::Stream;
-use tracing::{debug, info};
+use tracing::{debug, error, info, warn};
 
-use celestia_rpc::HeaderClient;
 use m1_da_light_node_grpc::light_node_service_server::LightNodeService;
 use m1_da_light_node_util::config::Config;
 use std::{fmt::Debug, path::PathBuf};
 // FIXME: glob imports are bad style
 use m1_da_light_node_grpc::*;
-use memseq::{Sequencer, Transaction};
+use memseq::{RejectedTransactions, Sequencer, Transaction};
 use movement_algs::grouping_heuristic::{
-	apply::ToApply, binpacking::FirstFitBinpacking, drop_success::DropSuccess, skip::SkipFor,
-	splitting::Splitting, GroupingHeuristicStack, GroupingOutcome,
+	apply::ToApply,
+	binpacking::{BinpackingWeighted, FirstFitBinpacking},
+	drop_success::DropSuccess,
+	skip::SkipFor,
+	splitting::{Splitable, Splitting},
+	GroupingHeuristicStack, GroupingOutcome,
 };
-use movement_types::block::Block;
+use movement_envelope::LegacyFormat;
+use movement_types::{atomic_transaction_bundle::AtomicTransactionBundle, block::Block};
 use std::boxed::Box;
 use tokio::{
 	sync::mpsc::{Receiver, Sender},
@@ -27,10 +32,15 @@ use crate::v1::{passthrough::LightNodeV1 as LightNodeV1PassThrough, LightNodeV1O
 
 const LOGGING_UID: AtomicU64 = AtomicU64::new(0);
 
+/// The largest blob which is submitted to the DA layer, in bytes.
+const MAX_BLOB_BYTES: usize = 1_700_000;
+
 #[derive(Clone)]
 pub struct LightNodeV1 {
 	pub pass_through: LightNodeV1PassThrough,
 	pub memseq: Arc<memseq::Memseq<memseq::RocksdbMempool>>,
+	/// The shared sequencer over the memseq, if the sequencer is shared by several consumers.
+	pub shared_memseq: Option<memseq::SharedMemseq<memseq::RocksdbMempool>>,
 }
 
 impl Debug for LightNodeV1 {
@@ -49,15 +59,37 @@ impl LightNodeV1Operations for LightNodeV1 {
 		let memseq_path = pass_through.config.try_memseq_path()?;
 		info!("Memseq path: {:?}", memseq_path);
 		let (max_block_size, build_time) = pass_through.config.try_block_building_parameters()?;
-
-		let memseq = Arc::new(memseq::Memseq::try_move_rocks(
-			PathBuf::from(memseq_path),
-			max_block_size,
-			build_time,
-		)?);
+		let (ordering_policy, slot_seconds) =
+			pass_through.config.try_mempool_ordering_parameters()?;
+		info!("Mempool ordering policy: {}, slot seconds: {}", ordering_policy, slot_seconds);
+		let mempool_limits = pass_through.config.try_mempool_limits()?;
+		info!("Mempool limits: {:?}", mempool_limits);
+		let (max_block_bytes, max_block_gas) = pass_through.config.try_block_resource_limits()?;
+		info!("Max block bytes: {:?}, max block gas: {:?}", max_block_bytes, max_block_gas);
+
+		let memseq = Arc::new(
+			memseq::Memseq::try_move_rocks_with_ordering(
+				PathBuf::from(memseq_path),
+				max_block_size,
+				build_time,
+				ordering_policy,
+				slot_seconds,
+			)?
+			.with_mempool_limits(mempool_limits)
+			.with_max_block_bytes(max_block_bytes)
+			.with_max_block_gas(max_block_gas),
+		);
+		memseq.recover().await?;
 		info!("Initialized Memseq with Move Rocks for LightNodeV1 in sequencer mode.");
 
-		Ok(Self { pass_through, memseq })
+		// the shared sequencer publishes to the same mempool, so both share their state
+		let shared_memseq = pass_through
+			.config
+			.memseq_shared()
+			.then(|| memseq::SharedMemseq::new(memseq.as_ref().clone()));
+		info!("Shared sequencer: {}", shared_memseq.is_some());
+
+		Ok(Self { pass_through, memseq, shared_memseq })
 	}
 
 	fn try_service_address(&self) -> Result<String, anyhow::Error> {
@@ -98,45 +130,66 @@ impl LightNodeV1 {
 		for block in blocks {
 			info!(target: "movement_timing", block_id = %block.block.id(), "inner_submitting_block");
 		}
-		// get references to celestia blobs in the wrapped blocks
+		// get the blob data of the wrapped blocks
 		let block_blobs = blocks
 			.iter()
 			.map(|wrapped_block| &wrapped_block.blob)
 			.cloned() // hopefully, the compiler optimizes this out
 			.collect::<Vec<_>>();
-		// use deref on the wrapped block to get the blob
-		self.pass_through.submit_celestia_blobs(&block_blobs).await?;
+		self.pass_through.submit_blobs(block_blobs).await?;
 		for block in blocks {
 			info!(target: "movement_timing", block_id = %block.block.id(), "inner_submitted_block");
 		}
 		Ok(())
 	}
 
+	/// Submits the blocks, confirming each group of blocks as soon as it is written to the DA.
+	///
+	/// The blocks which are not confirmed stay in flight, and are resubmitted by the next tick of the publisher.
 	pub async fn submit_with_heuristic(&self, blocks: Vec<Block>) -> Result<(), anyhow::Error> {
 		for block in &blocks {
 			info!(target: "movement_timing", block_id = %block.id(), "submitting_block");
 		}
 
-		// wrap the blocks in a struct that can be split and compressed
+		// sign the blocks so that full nodes can authenticate the sequencer
+		// and wrap them in a struct that can be split and compressed
 		// spawn blocking because the compression is blocking and could be slow
-		let namespace = self.pass_through.celestia_namespace.clone();
-		let blocks = tokio::task::spawn_blocking(move || {
-			blocks
-				.into_iter()
-				.map(|block| block::WrappedBlock::try_new(block, namespace))
-				.collect::<Result<Vec<_>, anyhow::Error>>()
-		})
-		.await??;
+		let signing_key = self.pass_through.signing_key.clone();
+		let codec = self.pass_through.config.m1_da_light_node_block_codec();
+		let write_format = self.pass_through.config.m1_da_light_node_block_write_format();
+		let split_log = block::SplitLog::default();
+		let blocks = {
+			let split_log = split_log.clone();
+			tokio::task::spawn_blocking(move || {
+				blocks
+					.into_iter()
+					.map(|block| {
+						Ok(block::WrappedBlock::try_new(
+							block,
+							codec,
+							write_format,
+							signing_key.clone(),
+						)?
+						.with_split_log(split_log.clone()))
+					})
+					.collect::<Result<Vec<_>, anyhow::Error>>()
+			})
+			.await??
+		};
+
+		// a block which is too large for a blob never fits in a bin, so it is split up front
+		let blocks = self.fit_blocks(blocks, &split_log).await?;
 
 		let mut heuristic: GroupingHeuristicStack<block::WrappedBlock> =
 			GroupingHeuristicStack::new(vec![
 				DropSuccess::boxed(),
 				ToApply::boxed(),
 				SkipFor::boxed(1, Splitting::boxed(2)),
-				FirstFitBinpacking::boxed(1_700_000),
+				FirstFitBinpacking::boxed(MAX_BLOB_BYTES),
 			]);
 
 		let start_distribution = GroupingOutcome::new_apply_distribution(blocks);
+		let split_log = &split_log;
 		let block_group_results = heuristic
 			.run_async_sequential_with_metadata(
 				start_distribution,
@@ -150,9 +203,17 @@ impl LightNodeV1 {
 						return Ok((grouping.to_failures_prefer_instrumental(), flag));
 					}
 
+					// the blocks which were split are replaced in flight before their parts are submitted
+					self.record_splits(split_log).await?;
+
 					let blocks = grouping.into_original();
 					let outcome = match self.submit_blocks(&blocks).await {
-						Ok(_) => GroupingOutcome::new_all_success(blocks.len()),
+						Ok(_) => {
+							// confirm the group right away, so that it is not resubmitted if a later group fails
+							let ids = blocks.iter().map(|block| block.block.id()).collect();
+							self.memseq.confirm_blocks(ids).await?;
+							GroupingOutcome::new_all_success(blocks.len())
+						}
 						Err(_) => {
 							flag = true;
 							GroupingOutcome::new_apply(blocks)
@@ -170,6 +231,58 @@ impl LightNodeV1 {
 			info!(target: "movement_timing", block_group_result = ?block_group_result, "block_group_result");
 		}
 
+		if !block_group_results.iter().all(|result| result.all_succeeded()) {
+			warn!("Not all blocks were submitted, leaving them in flight");
+		}
+
+		Ok(())
+	}
+
+	/// Splits the blocks which are too large for a blob until they fit.
+	///
+	/// A block with a single transaction which is too large can never be submitted, so it is dropped rather than retried.
+	async fn fit_blocks(
+		&self,
+		blocks: Vec<block::WrappedBlock>,
+		split_log: &block::SplitLog,
+	) -> Result<Vec<block::WrappedBlock>, anyhow::Error> {
+		let mut fitted = Vec::with_capacity(blocks.len());
+		let mut blocks = VecDeque::from(blocks);
+		while let Some(block) = blocks.pop_front() {
+			if block.weight() <= MAX_BLOB_BYTES {
+				fitted.push(block);
+				continue;
+			}
+
+			let block_id = block.block.id();
+			if block.block.transactions().len() < 2 {
+				error!(
+					%block_id,
+					blob_bytes = block.weight(),
+					max_blob_bytes = MAX_BLOB_BYTES,
+					"Dropping block with a single transaction which is too large for a blob"
+				);
+				// forget the block, so that it is not resubmitted on restart
+				self.memseq.confirm_blocks(vec![block_id]).await?;
+				continue;
+			}
+
+			// the parts take the place of the block, in order
+			let parts = tokio::task::spawn_blocking(move || block.split(2)).await??;
+			for part in parts.into_iter().rev() {
+				blocks.push_front(part);
+			}
+			self.record_splits(split_log).await?;
+		}
+		Ok(fitted)
+	}
+
+	/// Replaces the blocks which were split with their parts in flight,
+	/// so that a restart resubmits the blocks which were actually submitted.
+	async fn record_splits(&self, split_log: &block::SplitLog) -> Result<(), anyhow::Error> {
+		for (block_id, blocks) in split_log.take_unrecorded()? {
+			self.memseq.split_block(block_id, blocks).await?;
+		}
 		Ok(())
 	}
 
@@ -223,10 +336,22 @@ impl LightNodeV1 {
 		receiver: &mut Receiver<Block>,
 	) -> Result<(), anyhow::Error> {
 		// get some blocks in a batch
-		let blocks = self.read_blocks(receiver).await?;
+		let new_blocks = self.read_blocks(receiver).await?;
+
+		// the new blocks are in flight until they are confirmed, behind the blocks which failed to be submitted
+		// before, so all the blocks in flight are submitted in the order they were built;
+		// the new blocks which were already submitted with earlier blocks are not in flight anymore
+		let blocks = self.memseq.in_flight_blocks().await?;
 		if blocks.is_empty() {
 			return Ok(());
 		}
+		if blocks.len() > new_blocks.len() {
+			info!(
+				new_block_count = new_blocks.len(),
+				in_flight_block_count = blocks.len(),
+				"Resubmitting blocks left in flight"
+			);
+		}
 		let ids = blocks.iter().map(|b| b.id()).collect::<Vec<_>>();
 
 		// submit the blobs, resizing as needed
@@ -257,6 +382,8 @@ impl LightNodeV1 {
 	}
 
 	pub async fn run_block_proposer(&self) -> Result<(), anyhow::Error> {
+		// the blocks which were built but not confirmed before a restart are still in flight,
+		// so the first tick of the publisher resubmits them ahead of any new blocks
 		let (sender, mut receiver) = tokio::sync::mpsc::channel(2 ^ 10);
 
 		loop {
@@ -305,6 +432,7 @@ impl LightNodeV1 {
 				data,
 				blob_id: "".to_string(),
 				height,
+				signatures: vec![],
 				timestamp: 0,
 			})),
 		})
@@ -375,12 +503,10 @@ impl LightNodeService for LightNodeV1 {
 		let blobs_for_submission = blobs_for_intent.clone();
 		let height: u64 = self
 			.pass_through
-			.default_client
-			.header_network_head()
+			.backend
+			.head_height()
 			.await
-			.map_err(|e| tonic::Status::internal(e.to_string()))?
-			.height()
-			.into();
+			.map_err(|e| tonic::Status::internal(e.to_string()))?;
 
 		let intents: Vec<BlobResponse> = blobs_for_intent
 			.into_iter()
@@ -390,22 +516,63 @@ impl LightNodeService for LightNodeV1 {
 			})
 			.collect::<Result<Vec<BlobResponse>, tonic::Status>>()?;
 
-		// make transactions from the blobs
-		let mut transactions = Vec::new();
-		for blob in blobs_for_submission {
-			let transaction: Transaction = serde_json::from_slice(&blob.data)
-				.map_err(|e| tonic::Status::internal(e.to_string()))?;
-			transactions.push(transaction);
-		}
+		// publish the transactions, or the bundles if the sequencer is shared
+		let (transaction_ids, published) = match &self.shared_memseq {
+			Some(shared_memseq) => {
+				let mut bundles = Vec::new();
+				let mut transaction_ids = Vec::new();
+				for blob in blobs_for_submission {
+					let bundle: AtomicTransactionBundle = movement_envelope::from_bytes(&blob.data)
+						.map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+					if bundle.transactions().is_empty() {
+						return Err(tonic::Status::invalid_argument(
+							"Cannot publish an empty atomic transaction bundle",
+						));
+					}
+					// the bundles are identified by the transactions they are sequenced as
+					let transaction = bundle
+						.try_to_transaction()
+						.map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+					transaction_ids.push(transaction.id());
+					bundles.push(bundle);
+				}
+				(transaction_ids, shared_memseq.publish_many(bundles).await)
+			}
+			None => {
+				let mut transactions = Vec::new();
+				for blob in blobs_for_submission {
+					// transactions from writers which predate envelopes are JSON
+					let transaction: Transaction =
+						movement_envelope::from_bytes_or_legacy(&blob.data, LegacyFormat::Json)
+							.map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+					transactions.push(transaction);
+				}
+				let transaction_ids = transactions.iter().map(Transaction::id).collect::<Vec<_>>();
+				(transaction_ids, self.memseq.publish_many(transactions).await)
+			}
+		};
 
-		// publish the transactions
-		let memseq = self.memseq.clone();
-		memseq
-			.publish_many(transactions)
-			.await
-			.map_err(|e| tonic::Status::internal(e.to_string()))?;
+		// the transactions the mempool did not admit are reported to the writer, which may write those which
+		// were rejected for the time being again, e.g., because the mempool was full
+		let rejected = match published {
+			Ok(()) => Vec::new(),
+			Err(e) => match e.downcast::<RejectedTransactions>() {
+				Ok(RejectedTransactions(rejected)) => rejected
+					.into_iter()
+					.filter_map(|(transaction_id, reason)| {
+						let index = transaction_ids.iter().position(|id| *id == transaction_id)?;
+						Some(RejectedBlobWrite {
+							index: index as u64,
+							reason: reason.to_string(),
+							transient: reason.is_transient(),
+						})
+					})
+					.collect(),
+				Err(e) => return Err(tonic::Status::internal(e.to_string())),
+			},
+		};
 
-		Ok(tonic::Response::new(BatchWriteResponse { blobs: intents }))
+		Ok(tonic::Response::new(BatchWriteResponse { blobs: intents, rejected }))
 	}
 	/// Update and manage verification parameters.
 	async fn update_verification_parameters(
@@ -418,46 +585,175 @@ impl LightNodeService for LightNodeV1 {
 
 mod block {
 
-	use celestia_types::{nmt::Namespace, Blob};
+	use k256::ecdsa::SigningKey;
 	use movement_algs::grouping_heuristic::{binpacking::BinpackingWeighted, splitting::Splitable};
-	use movement_types::block::Block;
+	use movement_envelope::{Codec, LegacyFormat, WriteFormat};
+	use movement_types::{
+		block::{self, Block},
+		legacy,
+	};
+	use std::sync::{Arc, Mutex};
+
+	/// The blocks which were split during a submission and the blocks they were split into, in the order they were split.
+	#[derive(Debug, Clone, Default)]
+	pub struct SplitLog(Arc<Mutex<(Vec<(block::Id, Vec<Block>)>, usize)>>);
+
+	impl SplitLog {
+		fn push(&self, block_id: block::Id, blocks: Vec<Block>) -> Result<(), anyhow::Error> {
+			let mut log = self.0.lock().map_err(|_| anyhow::anyhow!("Split log lock poisoned"))?;
+			log.0.push((block_id, blocks));
+			Ok(())
+		}
+
+		/// Takes the splits which were not taken before.
+		pub fn take_unrecorded(&self) -> Result<Vec<(block::Id, Vec<Block>)>, anyhow::Error> {
+			let mut log = self.0.lock().map_err(|_| anyhow::anyhow!("Split log lock poisoned"))?;
+			let (splits, recorded) = &mut *log;
+			let unrecorded = splits[*recorded..].to_vec();
+			*recorded = splits.len();
+			Ok(unrecorded)
+		}
+	}
 
 	#[derive(Debug)]
 	pub struct WrappedBlock {
 		pub block: Block,
-		pub blob: Blob,
+		pub blob: Vec<u8>,
+		codec: Codec,
+		write_format: WriteFormat,
+		signing_key: SigningKey,
+		split_log: SplitLog,
 	}
 
 	impl WrappedBlock {
-		pub fn try_new(block: Block, namespace: Namespace) -> Result<Self, anyhow::Error> {
-			// first serialize the block
-			let block_bytes = bcs::to_bytes(&block)?;
-
-			// then compress the block bytes
-			let compressed_block_bytes = zstd::encode_all(block_bytes.as_slice(), 0)?;
+		/// Signs the block and encodes it into the blob which is submitted.
+		///
+		/// In the legacy write format, the block is written in the legacy layout, which has no signature,
+		/// so that full nodes which predate envelopes can read it.
+		pub fn try_new(
+			mut block: Block,
+			codec: Codec,
+			write_format: WriteFormat,
+			signing_key: SigningKey,
+		) -> Result<Self, anyhow::Error> {
+			block.sign(&signing_key);
+
+			// serialize and compress the block into a versioned envelope, or into its legacy format
+			let blob = match write_format {
+				WriteFormat::Envelope => movement_envelope::to_bytes(&block, codec)?,
+				WriteFormat::Legacy => movement_envelope::to_legacy_bytes(
+					&legacy::Block::from(block.clone()),
+					LegacyFormat::ZstdBcs,
+				)?,
+			};
 
-			// then create a blob from the compressed block bytes
-			let blob = Blob::new(namespace, compressed_block_bytes)?;
+			Ok(Self {
+				block,
+				blob,
+				codec,
+				write_format,
+				signing_key,
+				split_log: SplitLog::default(),
+			})
+		}
 
-			Ok(Self { block, blob })
+		/// Records the splits of the block and its parts in the given log.
+		pub fn with_split_log(mut self, split_log: SplitLog) -> Self {
+			self.split_log = split_log;
+			self
 		}
 	}
 
 	impl Splitable for WrappedBlock {
+		/// Splits the block and signs and encodes each of the smaller blocks, so that each blob only holds its own transactions.
 		fn split(self, factor: usize) -> Result<Vec<Self>, anyhow::Error> {
-			let split_blocks = self.block.split(factor)?;
-			let mut wrapped_blocks = Vec::new();
-			for block in split_blocks {
-				let wrapped_block = WrappedBlock { block, blob: self.blob.clone() };
-				wrapped_blocks.push(wrapped_block);
+			let block_id = self.block.id();
+			let blocks = self.block.split(factor)?;
+			if blocks.len() > 1 {
+				self.split_log.push(block_id, blocks.clone())?;
 			}
-			Ok(wrapped_blocks)
+			blocks
+				.into_iter()
+				.map(|block| {
+					Ok(Self::try_new(
+						block,
+						self.codec,
+						self.write_format,
+						self.signing_key.clone(),
+					)?
+					.with_split_log(self.split_log.clone()))
+				})
+				.collect()
 		}
 	}
 
 	impl BinpackingWeighted for WrappedBlock {
 		fn weight(&self) -> usize {
-			self.blob.data.len()
+			self.blob.len()
+		}
+	}
+
+	#[cfg(test)]
+	pub mod test {
+
+		use super::*;
+		use k256::elliptic_curve::rand_core::OsRng;
+		use movement_types::{block::BlockMetadata, transaction::Transaction};
+
+		#[test]
+		fn test_split_re_encodes_blobs() -> Result<(), anyhow::Error> {
+			let signing_key = SigningKey::random(&mut OsRng);
+			let transactions = (0..8).map(|i| Transaction::new(vec![i; 1024], i as u64)).collect();
+			let block = Block::new(BlockMetadata::new(1, 1), Default::default(), transactions);
+			let wrapped_block = WrappedBlock::try_new(
+				block,
+				Codec::None,
+				WriteFormat::Envelope,
+				signing_key.clone(),
+			)?;
+			let weight = wrapped_block.weight();
+
+			let split_blocks = wrapped_block.split(2)?;
+			assert_eq!(split_blocks.len(), 2);
+			for split_block in split_blocks {
+				assert!(split_block.weight() < weight);
+				let decoded: Block = movement_envelope::from_bytes(&split_block.blob)?;
+				assert_eq!(decoded, split_block.block);
+				decoded.verify_sequencer(&[*signing_key.verifying_key()])?;
+			}
+
+			Ok(())
+		}
+
+		#[test]
+		fn test_split_is_logged() -> Result<(), anyhow::Error> {
+			let signing_key = SigningKey::random(&mut OsRng);
+			let transactions = (0..4).map(|i| Transaction::new(vec![i], i as u64)).collect();
+			let block = Block::new(BlockMetadata::new(1, 1), Default::default(), transactions);
+			let block_id = block.id();
+			let split_log = SplitLog::default();
+			let wrapped_block =
+				WrappedBlock::try_new(block, Codec::None, WriteFormat::Envelope, signing_key)?
+					.with_split_log(split_log.clone());
+
+			// the parts log their own splits
+			let mut parts = wrapped_block.split(2)?;
+			let part_ids = parts.iter().map(|part| part.block.id()).collect::<Vec<_>>();
+			let last_parts = parts.pop().ok_or(anyhow::anyhow!("No parts"))?.split(2)?;
+
+			let splits = split_log.take_unrecorded()?;
+			assert_eq!(splits.len(), 2);
+			assert_eq!(splits[0].0, block_id);
+			assert_eq!(splits[0].1.iter().map(|block| block.id()).collect::<Vec<_>>(), part_ids);
+			assert_eq!(splits[1].0, part_ids[1]);
+			assert_eq!(splits[1].1.len(), 2);
+			assert!(split_log.take_unrecorded()?.is_empty());
+
+			// a block which cannot be split is not logged
+			last_parts.into_iter().next().ok_or(anyhow::anyhow!("No parts"))?.split(2)?;
+			assert!(split_log.take_unrecorded()?.is_empty());
+
+			Ok(())
 		}
 	}
 }
@@ -88,6 +88,7 @@ impl Arabica {
 		let mut config =
 			common::celestia::initialize_celestia_config(dot_movement.clone(), config)?;
 		let mut config = common::memseq::initialize_memseq_config(dot_movement.clone(), config)?;
+		let config = common::signer::initialize_signer_config(config)?;
 		let mut config = common::celestia::make_dirs(dot_movement.clone(), config).await?;
 
 		// celestia light init --p2p.network arabica
@@ -1,3 +1,4 @@
 pub mod celestia;
 pub mod file;
 pub mod memseq;
+pub mod signer;
@@ -0,0 +1,21 @@
+use m1_da_light_node_util::{
+	config::local::Config,
+	ir_blob::{decode_hex_signing_key, encode_public_key, generate_hex_signing_key},
+};
+use tracing::info;
+
+/// Generates the key the M1 DA Light Node signs blobs with, unless one is already configured.
+pub fn initialize_signer_config(mut config: Config) -> Result<Config, anyhow::Error> {
+	if config.m1_da_light_node.m1_da_light_node_signer_private_key.is_empty() {
+		config.m1_da_light_node.m1_da_light_node_signer_private_key = generate_hex_signing_key();
+	}
+
+	let signing_key =
+		decode_hex_signing_key(&config.m1_da_light_node.m1_da_light_node_signer_private_key)?;
+	info!(
+		"M1 DA Light Node signer public key: {}",
+		hex::encode(encode_public_key(signing_key.verifying_key()))
+	);
+
+	Ok(config)
+}
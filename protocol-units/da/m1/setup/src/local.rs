@@ -23,6 +23,7 @@ impl Local {
 		let mut config =
 			common::celestia::initialize_celestia_config(dot_movement.clone(), config)?;
 		let mut config = common::memseq::initialize_memseq_config(dot_movement.clone(), config)?;
+		let config = common::signer::initialize_signer_config(config)?;
 		let mut config = common::celestia::make_dirs(dot_movement.clone(), config).await?;
 		info!("Setup config for Memseq and Celestia: {:?}", config);
 
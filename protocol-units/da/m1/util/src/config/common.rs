@@ -1,5 +1,7 @@
+use crate::config::local::m1_da_light_node::DaBackendKind;
 use celestia_types::nmt::Namespace;
 use godfig::env_default;
+use movement_envelope::{Codec, WriteFormat};
 
 // The default hostname for the Celestia RPC
 env_default!(
@@ -77,6 +79,62 @@ env_default!(
 	30730
 );
 
+// The default DA backend for the m1-da-light-node
+env_default!(
+	default_m1_da_light_node_da_backend,
+	"M1_DA_LIGHT_NODE_DA_BACKEND",
+	DaBackendKind,
+	DaBackendKind::Celestia
+);
+
+// The default path to the database of the local DA backend
+env_default!(
+	default_m1_da_light_node_local_da_path,
+	"M1_DA_LIGHT_NODE_LOCAL_DA_PATH",
+	String,
+	"/tmp/m1-da-light-node-local-da".to_string()
+);
+
+// The default number of heights the M1 DA Light Node reads in parallel when streaming from behind the head
+env_default!(
+	default_m1_da_light_node_backfill_concurrency,
+	"M1_DA_LIGHT_NODE_BACKFILL_CONCURRENCY",
+	usize,
+	8
+);
+
+// The default number of times the M1 DA Light Node retries reading a height before failing the stream
+env_default!(default_m1_da_light_node_read_retries, "M1_DA_LIGHT_NODE_READ_RETRIES", u32, 3);
+
+// The default codec the M1 DA Light Node compresses blocks with
+env_default!(
+	default_m1_da_light_node_block_codec,
+	"M1_DA_LIGHT_NODE_BLOCK_CODEC",
+	Codec,
+	Codec::default()
+);
+
+// The default format the M1 DA Light Node writes blocks in
+env_default!(
+	default_m1_da_light_node_block_write_format,
+	"M1_DA_LIGHT_NODE_BLOCK_WRITE_FORMAT",
+	WriteFormat,
+	WriteFormat::Envelope
+);
+
+// The default private key the M1 DA Light Node signs blobs with, empty if none is configured
+pub fn default_m1_da_light_node_signer_private_key() -> String {
+	std::env::var("M1_DA_LIGHT_NODE_SIGNER_PRIVATE_KEY").unwrap_or_default()
+}
+
+// The default signers, besides the M1 DA Light Node itself, whose blobs are accepted
+pub fn default_m1_da_light_node_known_signers() -> Vec<String> {
+	match std::env::var("M1_DA_LIGHT_NODE_KNOWN_SIGNERS") {
+		Ok(val) => val.split(',').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect(),
+		Err(_) => vec![],
+	}
+}
+
 // The default Celestia Namespace
 pub fn default_celestia_namespace() -> Namespace {
 	match std::env::var("CELESTIA_NAMESPACE") {
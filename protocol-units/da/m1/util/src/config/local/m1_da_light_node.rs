@@ -1,10 +1,37 @@
 use crate::config::common::{
 	default_celestia_rpc_connection_hostname, default_celestia_rpc_connection_port,
 	default_celestia_websocket_connection_hostname, default_celestia_websocket_connection_port,
-	default_m1_da_light_node_connection_hostname, default_m1_da_light_node_connection_port,
-	default_m1_da_light_node_listen_hostname, default_m1_da_light_node_listen_port,
+	default_m1_da_light_node_backfill_concurrency, default_m1_da_light_node_block_codec,
+	default_m1_da_light_node_block_write_format, default_m1_da_light_node_connection_hostname,
+	default_m1_da_light_node_connection_port, default_m1_da_light_node_da_backend,
+	default_m1_da_light_node_known_signers, default_m1_da_light_node_listen_hostname,
+	default_m1_da_light_node_listen_port, default_m1_da_light_node_local_da_path,
+	default_m1_da_light_node_read_retries, default_m1_da_light_node_signer_private_key,
 };
+use movement_envelope::{Codec, WriteFormat};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// The DA backend the m1-da-light-node submits blobs to and reads blobs from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DaBackendKind {
+	/// A Celestia node, as configured by the appd and bridge configurations.
+	Celestia,
+	/// A RocksDB database owned by the light node process. Intended for testing without Celestia.
+	Local,
+}
+
+impl FromStr for DaBackendKind {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_lowercase().as_str() {
+			"celestia" => Ok(DaBackendKind::Celestia),
+			"local" => Ok(DaBackendKind::Local),
+			_ => Err(anyhow::anyhow!("Unknown DA backend: {}", s)),
+		}
+	}
+}
 
 /// The inner configuration for the local Celestia Appd Runner
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -40,6 +67,39 @@ pub struct Config {
 	/// The port for m1-da-light-node connection
 	#[serde(default = "default_m1_da_light_node_connection_port")]
 	pub m1_da_light_node_connection_port: u16,
+
+	/// The DA backend for the m1-da-light-node
+	#[serde(default = "default_m1_da_light_node_da_backend")]
+	pub m1_da_light_node_da_backend: DaBackendKind,
+
+	/// The path to the database of the local DA backend
+	#[serde(default = "default_m1_da_light_node_local_da_path")]
+	pub m1_da_light_node_local_da_path: String,
+
+	/// The hex-encoded secp256k1 private key the m1-da-light-node signs blobs with
+	#[serde(default = "default_m1_da_light_node_signer_private_key")]
+	pub m1_da_light_node_signer_private_key: String,
+
+	/// The hex-encoded public keys of other signers whose blobs are accepted
+	#[serde(default = "default_m1_da_light_node_known_signers")]
+	pub m1_da_light_node_known_signers: Vec<String>,
+
+	/// The number of heights read in parallel when a stream starts behind the head
+	#[serde(default = "default_m1_da_light_node_backfill_concurrency")]
+	pub m1_da_light_node_backfill_concurrency: usize,
+
+	/// The number of times reading a height is retried before the stream fails
+	#[serde(default = "default_m1_da_light_node_read_retries")]
+	pub m1_da_light_node_read_retries: u32,
+
+	/// The codec blocks are compressed with before they are submitted, e.g., `zstd:3`, `lz4` or `none`
+	#[serde(default = "default_m1_da_light_node_block_codec")]
+	pub m1_da_light_node_block_codec: Codec,
+
+	/// The format blocks are written in, `envelope` or `legacy` while full nodes which predate envelopes remain.
+	/// Blocks in the legacy format are not signed, so full nodes must not verify the sequencers to execute them.
+	#[serde(default = "default_m1_da_light_node_block_write_format")]
+	pub m1_da_light_node_block_write_format: WriteFormat,
 }
 
 impl Default for Config {
@@ -54,6 +114,14 @@ impl Default for Config {
 			m1_da_light_node_listen_port: default_m1_da_light_node_listen_port(),
 			m1_da_light_node_connection_hostname: default_m1_da_light_node_connection_hostname(),
 			m1_da_light_node_connection_port: default_m1_da_light_node_connection_port(),
+			m1_da_light_node_da_backend: default_m1_da_light_node_da_backend(),
+			m1_da_light_node_local_da_path: default_m1_da_light_node_local_da_path(),
+			m1_da_light_node_signer_private_key: default_m1_da_light_node_signer_private_key(),
+			m1_da_light_node_known_signers: default_m1_da_light_node_known_signers(),
+			m1_da_light_node_backfill_concurrency: default_m1_da_light_node_backfill_concurrency(),
+			m1_da_light_node_read_retries: default_m1_da_light_node_read_retries(),
+			m1_da_light_node_block_codec: default_m1_da_light_node_block_codec(),
+			m1_da_light_node_block_write_format: default_m1_da_light_node_block_write_format(),
 		}
 	}
 }
@@ -1,6 +1,10 @@
 use anyhow::Context;
 use celestia_rpc::Client;
 use celestia_types::nmt::Namespace;
+use k256::ecdsa::SigningKey;
+use local::m1_da_light_node::DaBackendKind;
+use memseq_util::{MempoolLimits, OrderingPolicy};
+use movement_envelope::{Codec, WriteFormat};
 use serde::{Deserialize, Serialize};
 
 pub mod common;
@@ -161,6 +165,84 @@ impl Config {
 		}
 	}
 
+	/// Gets the DA backend for the M1 DA Light Node
+	pub fn da_backend(&self) -> DaBackendKind {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.m1_da_light_node_da_backend,
+			Config::Arabica(local) => local.m1_da_light_node.m1_da_light_node_da_backend,
+			Config::Mocha(local) => local.m1_da_light_node.m1_da_light_node_da_backend,
+		}
+	}
+
+	/// Gets the path to the database of the local DA backend
+	pub fn m1_da_light_node_local_da_path(&self) -> String {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.m1_da_light_node_local_da_path.clone(),
+			Config::Arabica(local) => local.m1_da_light_node.m1_da_light_node_local_da_path.clone(),
+			Config::Mocha(local) => local.m1_da_light_node.m1_da_light_node_local_da_path.clone(),
+		}
+	}
+
+	/// Gets the key the M1 DA Light Node signs blobs with
+	pub fn try_signing_key(&self) -> Result<SigningKey, anyhow::Error> {
+		let private_key = match self {
+			Config::Local(local) => &local.m1_da_light_node.m1_da_light_node_signer_private_key,
+			Config::Arabica(local) => &local.m1_da_light_node.m1_da_light_node_signer_private_key,
+			Config::Mocha(local) => &local.m1_da_light_node.m1_da_light_node_signer_private_key,
+		};
+		if private_key.is_empty() {
+			anyhow::bail!(
+				"No signer private key is configured, set m1_da_light_node_signer_private_key or M1_DA_LIGHT_NODE_SIGNER_PRIVATE_KEY"
+			);
+		}
+		crate::ir_blob::decode_hex_signing_key(private_key)
+	}
+
+	/// Gets the signers, besides the M1 DA Light Node itself, whose blobs are accepted
+	pub fn m1_da_light_node_known_signers(&self) -> Vec<String> {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.m1_da_light_node_known_signers.clone(),
+			Config::Arabica(local) => local.m1_da_light_node.m1_da_light_node_known_signers.clone(),
+			Config::Mocha(local) => local.m1_da_light_node.m1_da_light_node_known_signers.clone(),
+		}
+	}
+
+	/// Gets the number of heights the M1 DA Light Node reads in parallel when backfilling a stream
+	pub fn m1_da_light_node_backfill_concurrency(&self) -> usize {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.m1_da_light_node_backfill_concurrency,
+			Config::Arabica(local) => local.m1_da_light_node.m1_da_light_node_backfill_concurrency,
+			Config::Mocha(local) => local.m1_da_light_node.m1_da_light_node_backfill_concurrency,
+		}
+	}
+
+	/// Gets the number of times the M1 DA Light Node retries reading a height
+	pub fn m1_da_light_node_read_retries(&self) -> u32 {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.m1_da_light_node_read_retries,
+			Config::Arabica(local) => local.m1_da_light_node.m1_da_light_node_read_retries,
+			Config::Mocha(local) => local.m1_da_light_node.m1_da_light_node_read_retries,
+		}
+	}
+
+	/// Gets the codec the M1 DA Light Node compresses blocks with
+	pub fn m1_da_light_node_block_codec(&self) -> Codec {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.m1_da_light_node_block_codec,
+			Config::Arabica(local) => local.m1_da_light_node.m1_da_light_node_block_codec,
+			Config::Mocha(local) => local.m1_da_light_node.m1_da_light_node_block_codec,
+		}
+	}
+
+	/// Gets the format the M1 DA Light Node writes blocks in
+	pub fn m1_da_light_node_block_write_format(&self) -> WriteFormat {
+		match self {
+			Config::Local(local) => local.m1_da_light_node.m1_da_light_node_block_write_format,
+			Config::Arabica(local) => local.m1_da_light_node.m1_da_light_node_block_write_format,
+			Config::Mocha(local) => local.m1_da_light_node.m1_da_light_node_block_write_format,
+		}
+	}
+
 	/// Gets the memseq path
 	pub fn try_memseq_path(&self) -> Result<String, anyhow::Error> {
 		match self {
@@ -189,6 +271,48 @@ impl Config {
 			}
 		}
 	}
+
+	/// Gets the mempool ordering policy and slot length in seconds
+	pub fn try_mempool_ordering_parameters(&self) -> Result<(OrderingPolicy, u64), anyhow::Error> {
+		match self {
+			Config::Local(local) => {
+				Ok((local.memseq.memseq_ordering_policy, local.memseq.memseq_slot_seconds))
+			}
+			Config::Arabica(local) => {
+				Ok((local.memseq.memseq_ordering_policy, local.memseq.memseq_slot_seconds))
+			}
+			Config::Mocha(local) => {
+				Ok((local.memseq.memseq_ordering_policy, local.memseq.memseq_slot_seconds))
+			}
+		}
+	}
+
+	/// Gets the byte and gas limits of a block
+	pub fn try_block_resource_limits(&self) -> Result<(Option<u64>, Option<u64>), anyhow::Error> {
+		match self {
+			Config::Local(local) => Ok(local.memseq.block_resource_limits()),
+			Config::Arabica(local) => Ok(local.memseq.block_resource_limits()),
+			Config::Mocha(local) => Ok(local.memseq.block_resource_limits()),
+		}
+	}
+
+	/// Gets the mempool limits
+	pub fn try_mempool_limits(&self) -> Result<MempoolLimits, anyhow::Error> {
+		match self {
+			Config::Local(local) => Ok(local.memseq.mempool_limits()),
+			Config::Arabica(local) => Ok(local.memseq.mempool_limits()),
+			Config::Mocha(local) => Ok(local.memseq.mempool_limits()),
+		}
+	}
+
+	/// Gets whether the sequencer is shared by several consumers
+	pub fn memseq_shared(&self) -> bool {
+		match self {
+			Config::Local(local) => local.memseq.memseq_shared,
+			Config::Arabica(local) => local.memseq.memseq_shared,
+			Config::Mocha(local) => local.memseq.memseq_shared,
+		}
+	}
 }
 
 /// The M1 DA Light Node configuration as should be read from file.
@@ -0,0 +1,145 @@
+use k256::ecdsa::{
+	signature::{Signer, Verifier},
+	Signature, SigningKey, VerifyingKey,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// A signature over a blob, together with the public key of its signer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobSignature {
+	/// The SEC1-encoded public key of the signer.
+	pub signer: Vec<u8>,
+	/// The signature over the blob's signing message.
+	pub signature: Vec<u8>,
+}
+
+/// The intermediate representation of a blob, i.e., the form in which blobs are written to the DA layer.
+///
+/// The light node wraps the data it is asked to write with a timestamp and signs both.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntermediateBlobRepresentation {
+	pub data: Vec<u8>,
+	pub timestamp: u64,
+	pub signatures: Vec<BlobSignature>,
+}
+
+impl IntermediateBlobRepresentation {
+	pub fn new(data: Vec<u8>, timestamp: u64) -> Self {
+		Self { data, timestamp, signatures: Vec::new() }
+	}
+
+	/// The message that is signed: the big-endian timestamp followed by the data.
+	fn signing_message(&self) -> Vec<u8> {
+		let mut message = Vec::with_capacity(8 + self.data.len());
+		message.extend_from_slice(&self.timestamp.to_be_bytes());
+		message.extend_from_slice(&self.data);
+		message
+	}
+
+	/// Adds a signature by the given key.
+	pub fn sign(mut self, signing_key: &SigningKey) -> Self {
+		let signature: Signature = signing_key.sign(&self.signing_message());
+		self.signatures.push(BlobSignature {
+			signer: encode_public_key(signing_key.verifying_key()),
+			signature: signature.to_vec(),
+		});
+		self
+	}
+
+	/// Gets the encoded public keys of all signers with a valid signature over this blob.
+	///
+	/// Signatures which cannot be decoded or do not verify are ignored.
+	pub fn verified_signers(&self) -> BTreeSet<Vec<u8>> {
+		let message = self.signing_message();
+		self.signatures
+			.iter()
+			.filter_map(|blob_signature| {
+				let signer = VerifyingKey::from_sec1_bytes(&blob_signature.signer).ok()?;
+				let signature = Signature::from_slice(&blob_signature.signature).ok()?;
+				signer.verify(&message, &signature).ok()?;
+				Some(encode_public_key(&signer))
+			})
+			.collect()
+	}
+
+	pub fn try_to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
+		bcs::to_bytes(self).map_err(|e| anyhow::anyhow!("Failed to serialize blob: {}", e))
+	}
+
+	pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		bcs::from_bytes(bytes).map_err(|e| anyhow::anyhow!("Failed to deserialize blob: {}", e))
+	}
+}
+
+/// Encodes a public key in compressed SEC1 form, the form in which signers are identified.
+pub fn encode_public_key(public_key: &VerifyingKey) -> Vec<u8> {
+	public_key.to_encoded_point(true).as_bytes().to_vec()
+}
+
+/// Decodes a hex-encoded SEC1 public key, with or without a `0x` prefix.
+pub fn decode_hex_public_key(public_key: &str) -> Result<VerifyingKey, anyhow::Error> {
+	let bytes = hex::decode(public_key.trim_start_matches("0x"))
+		.map_err(|e| anyhow::anyhow!("Invalid hex in public key {}: {}", public_key, e))?;
+	VerifyingKey::from_sec1_bytes(&bytes)
+		.map_err(|e| anyhow::anyhow!("Invalid public key {}: {}", public_key, e))
+}
+
+/// Generates a new secp256k1 private key, hex-encoded.
+pub fn generate_hex_signing_key() -> String {
+	hex::encode(SigningKey::random(&mut k256::elliptic_curve::rand_core::OsRng).to_bytes())
+}
+
+/// Decodes a hex-encoded secp256k1 private key, with or without a `0x` prefix.
+pub fn decode_hex_signing_key(private_key: &str) -> Result<SigningKey, anyhow::Error> {
+	let bytes = hex::decode(private_key.trim_start_matches("0x"))
+		.map_err(|e| anyhow::anyhow!("Invalid hex in private key: {}", e))?;
+	SigningKey::from_slice(&bytes).map_err(|e| anyhow::anyhow!("Invalid private key: {}", e))
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use k256::elliptic_curve::rand_core::OsRng;
+
+	#[test]
+	fn test_signed_blob_round_trip() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::random(&mut OsRng);
+		let blob = IntermediateBlobRepresentation::new(vec![1, 2, 3], 42).sign(&signing_key);
+
+		let decoded = IntermediateBlobRepresentation::try_from_bytes(&blob.try_to_bytes()?)?;
+		assert_eq!(decoded, blob);
+		assert_eq!(
+			decoded.verified_signers(),
+			BTreeSet::from([encode_public_key(signing_key.verifying_key())])
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_tampered_blob_has_no_verified_signers() {
+		let signing_key = SigningKey::random(&mut OsRng);
+		let mut blob = IntermediateBlobRepresentation::new(vec![1, 2, 3], 42).sign(&signing_key);
+		blob.data = vec![3, 2, 1];
+		assert!(blob.verified_signers().is_empty());
+
+		let mut blob = IntermediateBlobRepresentation::new(vec![1, 2, 3], 42).sign(&signing_key);
+		blob.timestamp = 43;
+		assert!(blob.verified_signers().is_empty());
+	}
+
+	#[test]
+	fn test_decode_hex_keys() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::random(&mut OsRng);
+		let private_key = hex::encode(signing_key.to_bytes());
+		assert_eq!(decode_hex_signing_key(&format!("0x{}", private_key))?, signing_key);
+
+		let public_key = hex::encode(encode_public_key(signing_key.verifying_key()));
+		assert_eq!(decode_hex_public_key(&public_key)?, *signing_key.verifying_key());
+		assert!(decode_hex_public_key("not a key").is_err());
+
+		Ok(())
+	}
+}
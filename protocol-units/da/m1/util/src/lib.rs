@@ -1,2 +1,3 @@
 pub mod config;
+pub mod ir_blob;
 pub use config::*;
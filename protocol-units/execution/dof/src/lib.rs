@@ -12,7 +12,9 @@ pub use aptos_types::{
 	transaction::{SignedTransaction, Transaction},
 };
 use maptos_execution_util::config::Config;
-use movement_types::block::BlockCommitment;
+pub use maptos_opt_executor::{bootstrap::create_db_checkpoint, AcceptedTransaction};
+pub use movement_transaction_status::{TransactionStatus, TransactionStatusIndex};
+use movement_types::block::{BlockCommitment, Commitment};
 
 use async_trait::async_trait;
 use tokio::sync::mpsc::Sender;
@@ -26,7 +28,7 @@ pub trait DynOptFinExecutor {
 	/// Initialize the background task responsible for transaction processing.
 	fn background(
 		&self,
-		transaction_sender: Sender<SignedTransaction>,
+		transaction_sender: Sender<AcceptedTransaction>,
 		config: &Config,
 	) -> Result<
 		(Self::Context, impl Future<Output = Result<(), anyhow::Error>> + Send + 'static),
@@ -54,6 +56,12 @@ pub trait DynOptFinExecutor {
 	/// Get block head height.
 	fn get_block_head_height(&self) -> Result<u64, anyhow::Error>;
 
+	/// Get the timestamp of the block head, in microseconds.
+	fn get_block_head_timestamp(&self) -> Result<u64, anyhow::Error>;
+
+	/// Get the commitment to the state at the block head.
+	fn get_block_head_state_commitment(&self) -> Result<Commitment, anyhow::Error>;
+
 	/// Build block metadata for a timestamp
 	fn build_block_metadata(
 		&self,
@@ -69,6 +77,9 @@ pub trait DynOptFinExecutor {
 
 	/// Gets the config
 	fn config(&self) -> &Config;
+
+	/// Gets the index of the status of the transactions submitted to the executor
+	fn transaction_status_index(&self) -> &TransactionStatusIndex;
 }
 
 pub trait MakeOptFinServices {
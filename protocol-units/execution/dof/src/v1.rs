@@ -1,11 +1,11 @@
 use crate::{
-	BlockMetadata, DynOptFinExecutor, ExecutableBlock, HashValue, MakeOptFinServices, Services,
-	SignedTransaction,
+	AcceptedTransaction, BlockMetadata, DynOptFinExecutor, ExecutableBlock, ExecutableTransactions,
+	HashValue, MakeOptFinServices, Services, Transaction, TransactionStatusIndex,
 };
 use maptos_execution_util::config::Config;
 use maptos_fin_view::FinalityView;
 use maptos_opt_executor::{Context as OptContext, Executor as OptExecutor};
-use movement_types::block::BlockCommitment;
+use movement_types::block::{BlockCommitment, Commitment};
 
 use anyhow::format_err;
 use async_trait::async_trait;
@@ -52,7 +52,7 @@ impl DynOptFinExecutor for Executor {
 
 	fn background(
 		&self,
-		transaction_sender: Sender<SignedTransaction>,
+		transaction_sender: Sender<AcceptedTransaction>,
 		config: &Config,
 	) -> Result<
 		(Context, impl Future<Output = Result<(), anyhow::Error>> + Send + 'static),
@@ -87,11 +87,36 @@ impl DynOptFinExecutor for Executor {
 		block: ExecutableBlock,
 	) -> Result<BlockCommitment, anyhow::Error> {
 		debug!("Executing block: {:?}", block.block_id);
-		self.executor.execute_block(block).await
+		// the block is committed with a state checkpoint transaction
+		self.executor.check_pruning_retention(
+			self.finality_view.finalized_block_height(),
+			block.transactions.num_transactions() as u64 + 1,
+		)?;
+		let transaction_hashes = match &block.transactions {
+			// transactions with an invalid signature are discarded rather than executed
+			ExecutableTransactions::Unsharded(transactions) => transactions
+				.iter()
+				.filter(|transaction| transaction.is_valid())
+				.filter_map(|transaction| match transaction.borrow_into_inner() {
+					Transaction::UserTransaction(transaction) => {
+						Some(transaction.committed_hash().to_string())
+					}
+					_ => None,
+				})
+				.collect(),
+			_ => Vec::new(),
+		};
+		let commitment = self.executor.execute_block(block).await?;
+		self.executor
+			.transaction_status_index()
+			.set_executed(transaction_hashes, commitment.height());
+		Ok(commitment)
 	}
 
 	fn set_finalized_block_height(&self, height: u64) -> Result<(), anyhow::Error> {
-		self.finality_view.set_finalized_block_height(height)
+		self.finality_view.set_finalized_block_height(height)?;
+		self.executor.transaction_status_index().set_finalized_height(height);
+		Ok(())
 	}
 
 	async fn revert_block_head_to(&self, block_height: u64) -> Result<(), anyhow::Error> {
@@ -102,7 +127,9 @@ impl DynOptFinExecutor for Executor {
 				));
 			}
 		}
-		self.executor.revert_block_head_to(block_height).await
+		self.executor.revert_block_head_to(block_height).await?;
+		self.executor.transaction_status_index().revert_to_height(block_height);
+		Ok(())
 	}
 
 	/// Get block head height.
@@ -110,6 +137,16 @@ impl DynOptFinExecutor for Executor {
 		self.executor.get_block_head_height()
 	}
 
+	/// Get the timestamp of the block head, in microseconds.
+	fn get_block_head_timestamp(&self) -> Result<u64, anyhow::Error> {
+		self.executor.get_block_head_timestamp()
+	}
+
+	/// Get the commitment to the state at the block head.
+	fn get_block_head_state_commitment(&self) -> Result<Commitment, anyhow::Error> {
+		self.executor.get_block_head_state_commitment()
+	}
+
 	/// Build block metadata for a timestamp
 	fn build_block_metadata(
 		&self,
@@ -135,6 +172,10 @@ impl DynOptFinExecutor for Executor {
 	fn config(&self) -> &Config {
 		self.executor.config()
 	}
+
+	fn transaction_status_index(&self) -> &TransactionStatusIndex {
+		self.executor.transaction_status_index()
+	}
 }
 
 #[cfg(test)]
@@ -167,6 +208,16 @@ mod tests {
 
 	use std::collections::HashMap;
 
+	/// Receives the next accepted transaction and acknowledges it.
+	async fn acknowledge_next(
+		tx_receiver: &mut mpsc::Receiver<AcceptedTransaction>,
+	) -> SignedTransaction {
+		let accepted = tx_receiver.recv().await.unwrap();
+		let transaction = accepted.transaction().clone();
+		accepted.acknowledge();
+		transaction
+	}
+
 	fn create_signed_transaction(gas_unit_price: u64) -> SignedTransaction {
 		let private_key = Ed25519PrivateKey::generate_for_testing();
 		let public_key = private_key.public_key();
@@ -223,11 +274,14 @@ mod tests {
 		let bcs_user_transaction = bcs::to_bytes(&user_transaction)?;
 
 		let request = SubmitTransactionPost::Bcs(aptos_api::bcs_payload::Bcs(bcs_user_transaction));
-		api.transactions.submit_transaction(AcceptType::Bcs, request).await?;
+		let (submitted, received_transaction) = tokio::join!(
+			api.transactions.submit_transaction(AcceptType::Bcs, request),
+			acknowledge_next(&mut tx_receiver)
+		);
+		submitted?;
 
 		services_handle.abort();
 		background_handle.abort();
-		let received_transaction = tx_receiver.recv().await.unwrap();
 		assert_eq!(received_transaction, comparison_user_transaction);
 
 		Ok(())
@@ -251,9 +305,11 @@ mod tests {
 		let bcs_user_transaction = bcs::to_bytes(&user_transaction)?;
 
 		let request = SubmitTransactionPost::Bcs(aptos_api::bcs_payload::Bcs(bcs_user_transaction));
-		api.transactions.submit_transaction(AcceptType::Bcs, request).await?;
-
-		let received_transaction = tx_receiver.recv().await.unwrap();
+		let (submitted, received_transaction) = tokio::join!(
+			api.transactions.submit_transaction(AcceptType::Bcs, request),
+			acknowledge_next(&mut tx_receiver)
+		);
+		submitted?;
 		assert_eq!(received_transaction, comparison_user_transaction);
 
 		// Now execute the block
@@ -318,9 +374,11 @@ mod tests {
 
 			let request =
 				SubmitTransactionPost::Bcs(aptos_api::bcs_payload::Bcs(bcs_user_transaction));
-			api.transactions.submit_transaction(AcceptType::Bcs, request).await?;
-
-			let received_transaction = tx_receiver.recv().await.unwrap();
+			let (submitted, received_transaction) = tokio::join!(
+				api.transactions.submit_transaction(AcceptType::Bcs, request),
+				acknowledge_next(&mut tx_receiver)
+			);
+			submitted?;
 			assert_eq!(received_transaction, comparison_user_transaction);
 
 			// Now execute the block
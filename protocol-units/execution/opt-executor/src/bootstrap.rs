@@ -1,3 +1,8 @@
+use aptos_config::config::{
+	EpochSnapshotPrunerConfig, LedgerPrunerConfig, PrunerConfig, RocksdbConfigs,
+	StateMerklePrunerConfig, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS,
+	DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+};
 use aptos_crypto::ed25519::Ed25519PublicKey;
 use aptos_db::AptosDB;
 use aptos_executor::db_bootstrapper;
@@ -12,6 +17,7 @@ use aptos_vm::AptosVM;
 use aptos_vm_genesis::{
 	default_gas_schedule, encode_genesis_change_set, GenesisConfiguration, TestValidator, Validator,
 };
+use maptos_execution_util::config::storage;
 
 use std::path::Path;
 
@@ -62,13 +68,60 @@ fn genesis_change_set_and_validators(
 	(genesis, test_validators)
 }
 
+/// The pruning of the Aptos DB, run in the background as versions are committed.
+fn pruner_config(storage_config: &storage::Config) -> PrunerConfig {
+	let enable = storage_config.maptos_pruning_enabled;
+	let batch_size = storage_config.maptos_prune_batch_size;
+	PrunerConfig {
+		ledger_pruner_config: LedgerPrunerConfig {
+			enable,
+			prune_window: storage_config.maptos_ledger_prune_window,
+			batch_size,
+			user_pruning_window_offset: 0,
+		},
+		state_merkle_pruner_config: StateMerklePrunerConfig {
+			enable,
+			prune_window: storage_config.maptos_state_merkle_prune_window,
+			batch_size,
+		},
+		// the epoch snapshots are kept as long as the ledger
+		epoch_snapshot_pruner_config: EpochSnapshotPrunerConfig {
+			enable,
+			prune_window: storage_config.maptos_ledger_prune_window,
+			batch_size,
+		},
+	}
+}
+
+/// Creates a consistent copy of the database at `checkpoint_dir`, with RocksDB checkpoints.
+///
+/// The database is opened to take the checkpoints, so this fails if it is in use, e.g., by a running node.
+pub fn create_db_checkpoint(
+	db_dir: impl AsRef<Path>,
+	checkpoint_dir: impl AsRef<Path>,
+) -> Result<(), anyhow::Error> {
+	// the databases are opened without storage sharding, see `maybe_bootstrap_empty_db`
+	AptosDB::create_checkpoint(db_dir, checkpoint_dir, false)?;
+	Ok(())
+}
+
 /// Bootstrap a database with a genesis transaction if it is empty.
 pub fn maybe_bootstrap_empty_db(
 	db_dir: impl AsRef<Path> + Clone,
 	chain_id: ChainId,
 	public_key: &Ed25519PublicKey,
+	storage_config: &storage::Config,
 ) -> Result<(DbReaderWriter, ValidatorSigner), anyhow::Error> {
-	let db_rw = DbReaderWriter::new(AptosDB::new_for_test(db_dir));
+	let db = AptosDB::open(
+		StorageDirPaths::from_path(db_dir),
+		false,
+		pruner_config(storage_config),
+		RocksdbConfigs::default(),
+		false,
+		BUFFERED_STATE_TARGET_ITEMS,
+		DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+	)?;
+	let db_rw = DbReaderWriter::new(db);
 	let (genesis, validators) = genesis_change_set_and_validators(chain_id, Some(1), public_key);
 	let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
 	let validator_signer =
@@ -100,6 +100,61 @@ impl Executor {
 		Ok(new_block_event.height)
 	}
 
+	/// Checks that committing a block of the given number of transactions does not let the pruner
+	/// reach the versions retained below the finalized block.
+	///
+	/// The pruner of the Aptos DB counts back from the latest version, so it is bounded here instead.
+	/// Without a finalized block, as when the node does not settle, nothing is retained.
+	pub fn check_pruning_retention(
+		&self,
+		finalized_block_height: Option<u64>,
+		transaction_count: u64,
+	) -> Result<(), anyhow::Error> {
+		let storage = &self.config.storage;
+		let Some(finalized_block_height) = finalized_block_height else {
+			return Ok(());
+		};
+		if !storage.maptos_pruning_enabled {
+			return Ok(());
+		}
+		let (_, finalized_version, _) =
+			self.db().reader.get_block_info_by_height(finalized_block_height)?;
+		let retained_version =
+			finalized_version.saturating_sub(storage.maptos_finalized_versions_retained);
+		let next_version = self
+			.db()
+			.reader
+			.get_latest_ledger_info_version()?
+			.saturating_add(transaction_count);
+		let prune_window =
+			storage.maptos_ledger_prune_window.min(storage.maptos_state_merkle_prune_window);
+		let pruned_version = next_version.saturating_sub(prune_window);
+		if pruned_version > retained_version {
+			anyhow::bail!(
+				"Executing the block would prune the versions below {}, but the versions from {} are retained \
+				 below the finalized block at height {}: the prune windows do not cover the blocks which are not finalized",
+				pruned_version,
+				retained_version,
+				finalized_block_height
+			);
+		}
+		Ok(())
+	}
+
+	/// Gets the timestamp of the latest committed block, in microseconds.
+	pub fn get_block_head_timestamp(&self) -> Result<u64, anyhow::Error> {
+		let ledger_info = self.db().reader.get_latest_ledger_info()?;
+		Ok(ledger_info.ledger_info().timestamp_usecs())
+	}
+
+	/// Gets the commitment to the state at the latest committed block,
+	/// as posted for the block to settlement.
+	pub fn get_block_head_state_commitment(&self) -> Result<Commitment, anyhow::Error> {
+		let ledger_info = self.db().reader.get_latest_ledger_info()?;
+		let proof = self.db().reader.get_state_proof(ledger_info.ledger_info().version())?;
+		Ok(Commitment::digest_state_proof(&proof))
+	}
+
 	pub async fn revert_block_head_to(&self, block_height: u64) -> Result<(), anyhow::Error> {
 		let (_start_ver, end_ver, block_event) =
 			self.db().reader.get_block_info_by_height(block_height)?;
@@ -386,6 +441,7 @@ mod tests {
 			let expected_commitment = Commitment::digest_state_proof(&state_proof);
 			assert_eq!(block_commitment.height(), i + 2);
 			assert_eq!(block_commitment.commitment(), expected_commitment);
+			assert_eq!(executor.get_block_head_state_commitment()?, expected_commitment);
 		}
 
 		Ok(())
@@ -1,5 +1,5 @@
 use super::Executor;
-use crate::{bootstrap, Context, TransactionPipe};
+use crate::{bootstrap, AcceptedTransaction, Context, TransactionPipe};
 
 use aptos_config::config::NodeConfig;
 #[cfg(test)]
@@ -7,8 +7,8 @@ use aptos_crypto::ed25519::Ed25519PrivateKey;
 use aptos_crypto::PrivateKey;
 use aptos_executor::block_executor::BlockExecutor;
 use aptos_mempool::MempoolClientRequest;
-use aptos_types::transaction::SignedTransaction;
 use maptos_execution_util::config::Config;
+use movement_transaction_status::TransactionStatusIndex;
 
 use anyhow::Context as _;
 use futures::channel::mpsc as futures_mpsc;
@@ -30,11 +30,13 @@ impl Executor {
 			maptos_config.chain.maptos_db_path.as_ref().context("No db path provided.")?,
 			maptos_config.chain.maptos_chain_id.clone(),
 			&maptos_config.chain.maptos_private_key.public_key(),
+			&maptos_config.storage,
 		)?;
 		Ok(Self {
 			block_executor: Arc::new(BlockExecutor::new(db.clone())),
 			signer,
 			transactions_in_flight: Arc::new(AtomicU64::new(0)),
+			transaction_status: TransactionStatusIndex::default(),
 			config: maptos_config.clone(),
 		})
 	}
@@ -64,7 +66,7 @@ impl Executor {
 	/// task needs to be running.
 	pub fn background(
 		&self,
-		transaction_sender: mpsc::Sender<SignedTransaction>,
+		transaction_sender: mpsc::Sender<AcceptedTransaction>,
 		maptos_config: &Config,
 	) -> anyhow::Result<(Context, TransactionPipe)> {
 		let mut node_config = NodeConfig::default();
@@ -112,6 +114,7 @@ impl Executor {
 			&node_config,
 			Arc::clone(&self.transactions_in_flight),
 			maptos_config.load_shedding.max_transactions_in_flight,
+			self.transaction_status.clone(),
 		);
 
 		let cx = Context::new(
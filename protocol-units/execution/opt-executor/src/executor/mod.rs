@@ -12,6 +12,7 @@ use aptos_vm::AptosVM;
 use tracing::info;
 
 use maptos_execution_util::config::Config;
+use movement_transaction_status::TransactionStatusIndex;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
@@ -24,6 +25,8 @@ pub struct Executor {
 	pub signer: ValidatorSigner,
 	// Shared reference on the counter of transactions in flight.
 	transactions_in_flight: Arc<AtomicU64>,
+	// The status of the transactions submitted to the executor.
+	transaction_status: TransactionStatusIndex,
 	// The config for the executor.
 	pub(crate) config: Config,
 }
@@ -57,6 +60,10 @@ impl Executor {
 		&self.config
 	}
 
+	pub fn transaction_status_index(&self) -> &TransactionStatusIndex {
+		&self.transaction_status
+	}
+
 	pub fn has_executed_transaction(
 		&self,
 		transaction_hash: HashValue,
@@ -9,4 +9,4 @@ pub mod transaction_pipe;
 pub use context::Context;
 pub use executor::Executor;
 pub use service::Service;
-pub use transaction_pipe::TransactionPipe;
+pub use transaction_pipe::{AcceptedTransaction, TransactionPipe};
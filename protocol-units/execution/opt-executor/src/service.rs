@@ -132,15 +132,16 @@ mod tests {
 		// tick the transaction pipe
 		transaction_pipe.tick().await?;
 
+		// receive the transaction
+		let received_transaction = tx_receiver.recv().await.unwrap();
+		assert_eq!(received_transaction.transaction(), &user_transaction);
+		received_transaction.acknowledge();
+
 		// receive the callback
 		let (status, _vm_status_code) = callback.await??;
 		// dbg!(_vm_status_code);
 		assert_eq!(status.code, MempoolStatusCode::Accepted);
 
-		// receive the transaction
-		let received_transaction = tx_receiver.recv().await.unwrap();
-		assert_eq!(received_transaction, user_transaction);
-
 		handle.abort();
 
 		Ok(())
@@ -9,8 +9,10 @@ use aptos_types::mempool_status::{MempoolStatus, MempoolStatusCode};
 use aptos_types::transaction::SignedTransaction;
 use aptos_types::vm_status::DiscardedVMStatus;
 use aptos_vm_validator::vm_validator::{self, TransactionValidation, VMValidator};
+use movement_transaction_status::{TransactionStatus, TransactionStatusIndex};
 
 use futures::channel::mpsc as futures_mpsc;
+use futures::channel::oneshot;
 use futures::StreamExt;
 use thiserror::Error;
 use tokio::sync::mpsc;
@@ -38,11 +40,39 @@ impl From<anyhow::Error> for Error {
 	}
 }
 
+/// A transaction accepted into the mempool, sent on to be written to the DA.
+///
+/// The submitter is only told that the transaction was accepted once it is acknowledged,
+/// e.g., after it was durably queued, so that an accepted transaction is not lost if the node crashes.
+#[derive(Debug)]
+pub struct AcceptedTransaction {
+	transaction: SignedTransaction,
+	acknowledgement: oneshot::Sender<()>,
+}
+
+impl AcceptedTransaction {
+	/// Creates an accepted transaction and the receiver of its acknowledgement.
+	pub fn new(transaction: SignedTransaction) -> (Self, oneshot::Receiver<()>) {
+		let (acknowledgement, acknowledged) = oneshot::channel();
+		(Self { transaction, acknowledgement }, acknowledged)
+	}
+
+	pub fn transaction(&self) -> &SignedTransaction {
+		&self.transaction
+	}
+
+	/// Acknowledges the transaction, reporting its acceptance to the submitter.
+	pub fn acknowledge(self) {
+		// the submitter may have stopped waiting, which is not an error
+		let _ = self.acknowledgement.send(());
+	}
+}
+
 pub struct TransactionPipe {
 	// The receiver for the mempool client.
 	mempool_client_receiver: futures_mpsc::Receiver<MempoolClientRequest>,
 	// Sender for the channel with accepted transactions.
-	transaction_sender: mpsc::Sender<SignedTransaction>,
+	transaction_sender: mpsc::Sender<AcceptedTransaction>,
 	// Access to the ledger DB. TODO: reuse an instance of VMValidator
 	db_reader: Arc<dyn DbReader>,
 	// State of the Aptos mempool
@@ -53,16 +83,19 @@ pub struct TransactionPipe {
 	in_flight_limit: u64,
 	// Timestamp of the last garbage collection
 	last_gc: Instant,
+	// The status of the transactions accepted by the pipe
+	transaction_status: TransactionStatusIndex,
 }
 
 impl TransactionPipe {
 	pub(crate) fn new(
 		mempool_client_receiver: futures_mpsc::Receiver<MempoolClientRequest>,
-		transaction_sender: mpsc::Sender<SignedTransaction>,
+		transaction_sender: mpsc::Sender<AcceptedTransaction>,
 		db_reader: Arc<dyn DbReader>,
 		node_config: &NodeConfig,
 		transactions_in_flight: Arc<AtomicU64>,
 		transactions_in_flight_limit: u64,
+		transaction_status: TransactionStatusIndex,
 	) -> Self {
 		TransactionPipe {
 			mempool_client_receiver,
@@ -72,6 +105,7 @@ impl TransactionPipe {
 			transactions_in_flight,
 			in_flight_limit: transactions_in_flight_limit,
 			last_gc: Instant::now(),
+			transaction_status,
 		}
 	}
 
@@ -95,10 +129,25 @@ impl TransactionPipe {
 						sender = %transaction.sender(),
 						sequence_number = transaction.sequence_number(),
 					);
-					let status = self.submit_transaction(transaction).instrument(span).await?;
-					callback.send(Ok(status)).unwrap_or_else(|_| {
-						debug!("SubmitTransaction request canceled");
-					});
+					let (status, acknowledged) =
+						self.submit_transaction(transaction).instrument(span).await?;
+					match acknowledged {
+						// an accepted transaction is only reported once it is acknowledged,
+						// which is waited for in the background so that the pipe is not held up
+						Some(acknowledged) => {
+							tokio::spawn(async move {
+								let result = acknowledged.await.map(|()| status).map_err(|_| {
+									anyhow::anyhow!("Accepted transaction was not acknowledged")
+								});
+								callback.send(result).unwrap_or_else(|_| {
+									debug!("SubmitTransaction request canceled");
+								});
+							});
+						}
+						None => callback.send(Ok(status)).unwrap_or_else(|_| {
+							debug!("SubmitTransaction request canceled");
+						}),
+					}
 				}
 				MempoolClientRequest::GetTransactionByHash(hash, sender) => {
 					let mempool_result = self.core_mempool.get_by_hash(hash);
@@ -117,10 +166,12 @@ impl TransactionPipe {
 		Ok(())
 	}
 
+	/// Submits a transaction to the mempool.
+	/// If the transaction is accepted, the receiver of its acknowledgement is returned with its status.
 	async fn submit_transaction(
 		&mut self,
 		transaction: SignedTransaction,
-	) -> Result<SubmissionStatus, Error> {
+	) -> Result<(SubmissionStatus, Option<oneshot::Receiver<()>>), Error> {
 		// For now, we are going to consider a transaction in flight until it exits the mempool and is sent to the DA as is indicated by WriteBatch.
 		let in_flight = self.transactions_in_flight.load(std::sync::atomic::Ordering::Relaxed);
 		info!(
@@ -134,7 +185,7 @@ impl TransactionPipe {
 				"shedding_load"
 			);
 			let status = MempoolStatus::new(MempoolStatusCode::MempoolIsFull);
-			return Ok((status, None));
+			return Ok(((status, None), None));
 		}
 
 		// Pre-execute Tx to validate its content.
@@ -144,7 +195,7 @@ impl TransactionPipe {
 		match tx_result.status() {
 			Some(_) => {
 				let ms = MempoolStatus::new(MempoolStatusCode::VmError);
-				return Ok((ms, tx_result.status()));
+				return Ok(((ms, tx_result.status()), None));
 			}
 			None => {}
 		}
@@ -158,7 +209,7 @@ impl TransactionPipe {
 			vm_validator::get_account_sequence_number(&state_view, transaction.sender())?;
 		if transaction.sequence_number() < sequence_number {
 			let status = MempoolStatus::new(MempoolStatusCode::VmError);
-			return Ok((status, Some(DiscardedVMStatus::SEQUENCE_NUMBER_TOO_OLD)));
+			return Ok(((status, Some(DiscardedVMStatus::SEQUENCE_NUMBER_TOO_OLD)), None));
 		}
 
 		debug!(%sequence_number, "adding transaction to mempool: {:?}", transaction);
@@ -170,25 +221,31 @@ impl TransactionPipe {
 			true,
 		);
 
-		match status.code {
+		let acknowledged = match status.code {
 			MempoolStatusCode::Accepted => {
 				debug!("Transaction accepted: {:?}", transaction);
 				let sender = transaction.sender();
+				// the status is set before sending, so that it does not overwrite later statuses
+				self.transaction_status
+					.set(&transaction.committed_hash().to_string(), TransactionStatus::Mempool);
+				let (accepted, acknowledged) = AcceptedTransaction::new(transaction);
 				self.transaction_sender
-					.send(transaction)
+					.send(accepted)
 					.await
 					.map_err(|e| anyhow::anyhow!("Error sending transaction: {:?}", e))?;
 				// increment transactions in flight
 				self.transactions_in_flight.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 				self.core_mempool.commit_transaction(&sender, sequence_number);
+				Some(acknowledged)
 			}
 			_ => {
 				warn!("Transaction not accepted: {:?}", status);
+				None
 			}
-		}
+		};
 
 		// report status
-		Ok((status, None))
+		Ok(((status, None), acknowledged))
 	}
 }
 
@@ -217,7 +274,7 @@ mod tests {
 	use futures::SinkExt;
 	use maptos_execution_util::config::chain::Config;
 
-	fn setup() -> (TransactionPipe, MempoolClientSender, mpsc::Receiver<SignedTransaction>) {
+	fn setup() -> (TransactionPipe, MempoolClientSender, mpsc::Receiver<AcceptedTransaction>) {
 		let (tx_sender, tx_receiver) = mpsc::channel(16);
 		let (executor, config, _tempdir) =
 			Executor::try_test_default(GENESIS_KEYPAIR.0.clone()).unwrap();
@@ -225,6 +282,16 @@ mod tests {
 		(transaction_pipe, context.mempool_client_sender(), tx_receiver)
 	}
 
+	/// Receives the next accepted transaction and acknowledges it.
+	async fn acknowledge_next(
+		tx_receiver: &mut mpsc::Receiver<AcceptedTransaction>,
+	) -> SignedTransaction {
+		let accepted = tx_receiver.recv().await.unwrap();
+		let transaction = accepted.transaction().clone();
+		accepted.acknowledge();
+		transaction
+	}
+
 	fn create_signed_transaction(sequence_number: u64, chain_config: &Config) -> SignedTransaction {
 		let address = account_config::aptos_test_root_address();
 		transaction_test_helpers::get_test_txn_with_chain_id(
@@ -252,13 +319,20 @@ mod tests {
 		// tick the transaction pipe
 		transaction_pipe.tick().await?;
 
+		// receive the transaction
+		let received_transaction = tx_receiver.recv().await.unwrap();
+		assert_eq!(received_transaction.transaction(), &user_transaction);
+		received_transaction.acknowledge();
+
 		// receive the callback
 		let (status, _vm_status_code) = callback.await??;
 		assert_eq!(status.code, MempoolStatusCode::Accepted);
 
-		// receive the transaction
-		let received_transaction = tx_receiver.recv().await.unwrap();
-		assert_eq!(received_transaction, user_transaction);
+		// the transaction is tracked from the mempool
+		let status = transaction_pipe
+			.transaction_status
+			.get(&user_transaction.committed_hash().to_string());
+		assert_eq!(status, Some(TransactionStatus::Mempool));
 
 		Ok(())
 	}
@@ -301,14 +375,15 @@ mod tests {
 		// tick the transaction pipe
 		transaction_pipe.tick().await?;
 
+		// receive the transaction
+		let received_transaction = tx_receiver.recv().await.unwrap();
+		assert_eq!(received_transaction.transaction(), &user_transaction);
+		received_transaction.acknowledge();
+
 		// receive the callback
 		let (status, _vm_status_code) = callback.await??;
 		assert_eq!(status.code, MempoolStatusCode::Accepted);
 
-		// receive the transaction
-		let received_transaction = tx_receiver.recv().await.unwrap();
-		assert_eq!(received_transaction, user_transaction);
-
 		// send the same transaction again
 		let (req_sender, callback) = oneshot::channel();
 		mempool_client_sender
@@ -318,10 +393,11 @@ mod tests {
 		// tick the transaction pipe
 		transaction_pipe.tick().await?;
 
-		callback.await??;
-
 		let received_transaction = tx_receiver.recv().await.unwrap();
-		assert_eq!(received_transaction, user_transaction);
+		assert_eq!(received_transaction.transaction(), &user_transaction);
+		received_transaction.acknowledge();
+
+		callback.await??;
 
 		Ok(())
 	}
@@ -346,8 +422,11 @@ mod tests {
 		let comparison_user_transaction = user_transaction.clone();
 		let bcs_user_transaction = bcs::to_bytes(&user_transaction)?;
 		let request = SubmitTransactionPost::Bcs(aptos_api::bcs_payload::Bcs(bcs_user_transaction));
-		api.transactions.submit_transaction(AcceptType::Bcs, request).await?;
-		let received_transaction = tx_receiver.recv().await.unwrap();
+		let (submitted, received_transaction) = tokio::join!(
+			api.transactions.submit_transaction(AcceptType::Bcs, request),
+			acknowledge_next(&mut tx_receiver)
+		);
+		submitted?;
 		assert_eq!(received_transaction, comparison_user_transaction);
 
 		mempool_handle.abort();
@@ -380,9 +459,11 @@ mod tests {
 
 			let request =
 				SubmitTransactionPost::Bcs(aptos_api::bcs_payload::Bcs(bcs_user_transaction));
-			api.transactions.submit_transaction(AcceptType::Bcs, request).await?;
-
-			let received_transaction = tx_receiver.recv().await.unwrap();
+			let (submitted, received_transaction) = tokio::join!(
+				api.transactions.submit_transaction(AcceptType::Bcs, request),
+				acknowledge_next(&mut tx_receiver)
+			);
+			submitted?;
 			let bcs_received_transaction = bcs::to_bytes(&received_transaction)?;
 			comparison_user_transactions.insert(bcs_received_transaction.clone());
 		}
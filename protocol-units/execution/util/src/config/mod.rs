@@ -6,6 +6,7 @@ pub mod fin;
 pub mod indexer;
 pub mod indexer_processor;
 pub mod load_shedding;
+pub mod storage;
 
 use serde::{Deserialize, Serialize};
 
@@ -38,6 +39,10 @@ pub struct Config {
 	/// The load shedding parameters
 	#[serde(default)]
 	pub load_shedding: load_shedding::Config,
+
+	/// The storage retention parameters
+	#[serde(default)]
+	pub storage: storage::Config,
 }
 
 impl Default for Config {
@@ -50,6 +55,7 @@ impl Default for Config {
 			faucet: faucet::Config::default(),
 			fin: fin::Config::default(),
 			load_shedding: load_shedding::Config::default(),
+			storage: storage::Config::default(),
 		}
 	}
 }
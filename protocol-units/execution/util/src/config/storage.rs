@@ -0,0 +1,65 @@
+//! Configuration for the retention of the Aptos DB.
+
+use godfig::env_default;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+	/// Whether the Aptos DB is pruned. If not, every version is kept.
+	#[serde(default = "default_maptos_pruning_enabled")]
+	pub maptos_pruning_enabled: bool,
+
+	/// The number of versions kept below the version of the finalized block,
+	/// so that the finalized state can be read, e.g., to revert to it or to take a snapshot.
+	#[serde(default = "default_maptos_finalized_versions_retained")]
+	pub maptos_finalized_versions_retained: u64,
+
+	/// The number of ledger versions kept, counting back from the latest version.
+	///
+	/// The pruner counts back from the latest version, so the window has to cover the versions which are not
+	/// finalized yet on top of the versions retained below the finalized block. A block which would let the
+	/// pruner reach the retained versions is not executed.
+	#[serde(default = "default_maptos_ledger_prune_window")]
+	pub maptos_ledger_prune_window: u64,
+
+	/// The number of versions for which the state merkle tree is kept, counting back from the latest version.
+	/// It is bounded like the ledger prune window.
+	#[serde(default = "default_maptos_state_merkle_prune_window")]
+	pub maptos_state_merkle_prune_window: u64,
+
+	/// The number of versions pruned by each pruning pass.
+	#[serde(default = "default_maptos_prune_batch_size")]
+	pub maptos_prune_batch_size: usize,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			maptos_pruning_enabled: default_maptos_pruning_enabled(),
+			maptos_finalized_versions_retained: default_maptos_finalized_versions_retained(),
+			maptos_ledger_prune_window: default_maptos_ledger_prune_window(),
+			maptos_state_merkle_prune_window: default_maptos_state_merkle_prune_window(),
+			maptos_prune_batch_size: default_maptos_prune_batch_size(),
+		}
+	}
+}
+
+env_default!(default_maptos_pruning_enabled, "MAPTOS_PRUNING_ENABLED", bool, false);
+
+env_default!(
+	default_maptos_finalized_versions_retained,
+	"MAPTOS_FINALIZED_VERSIONS_RETAINED",
+	u64,
+	1_000_000
+);
+
+env_default!(default_maptos_ledger_prune_window, "MAPTOS_LEDGER_PRUNE_WINDOW", u64, 90_000_000);
+
+env_default!(
+	default_maptos_state_merkle_prune_window,
+	"MAPTOS_STATE_MERKLE_PRUNE_WINDOW",
+	u64,
+	2_000_000
+);
+
+env_default!(default_maptos_prune_batch_size, "MAPTOS_PRUNE_BATCH_SIZE", usize, 5_000);
@@ -1,26 +1,105 @@
 use anyhow::Error;
 use bcs;
-use mempool_util::{MempoolBlockOperations, MempoolTransaction, MempoolTransactionOperations};
+use mempool_util::{
+	Admission, DropReason, EvictionPolicy, MempoolBlockOperations, MempoolLimits,
+	MempoolTransaction, MempoolTransactionOperations, OrderingPolicy, SequencerHead,
+	SequencerStateOperations,
+};
 use movement_types::{
 	block::{self, Block},
-	transaction,
+	legacy, transaction,
 };
-use rocksdb::{ColumnFamilyDescriptor, Options, WriteBatch, DB};
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Write;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 mod cf {
 	pub const MEMPOOL_TRANSACTIONS: &str = "mempool_transactions";
 	pub const BLOCKS: &str = "blocks";
 	pub const TRANSACTION_LOOKUPS: &str = "transaction_lookups";
+	pub const SENDER_TRANSACTIONS: &str = "sender_transactions";
+	pub const DROPPED_TRANSACTIONS: &str = "dropped_transactions";
+	pub const DROPPED_TRANSACTION_TIMES: &str = "dropped_transaction_times";
+	pub const POPPED_TRANSACTIONS: &str = "popped_transactions";
+	pub const IN_FLIGHT_BLOCKS: &str = "in_flight_blocks";
+	pub const SEQUENCER_STATE: &str = "sequencer_state";
+}
+
+const SEQUENCER_HEAD_KEY: &str = "sequencer_head";
+const KEY_LAYOUT_KEY: &str = "key_layout";
+
+/// How long the reason a transaction was dropped is kept, in seconds.
+pub const DROP_RECORD_RETENTION_SECONDS: u64 = 60 * 60;
+
+/// The transactions added and evicted by a call which adds transactions, whose writes are not yet in the database.
+#[derive(Debug, Default)]
+struct PendingAdmissions {
+	/// The admissions of the transactions, in the order they were added.
+	admissions: Vec<Admission>,
+	/// The transactions added, by their key, with the index of their admission.
+	added: BTreeMap<Vec<u8>, (usize, MempoolTransaction)>,
+	added_ids: HashSet<transaction::Id>,
+	/// The keys of the stored transactions which were evicted.
+	evicted: HashSet<Vec<u8>>,
+	evicted_ids: HashSet<transaction::Id>,
+	/// The number of transactions in the mempool once the writes are applied.
+	size: u64,
+}
+
+impl PendingAdmissions {
+	fn new(size: u64) -> Self {
+		Self { size, ..Default::default() }
+	}
+
+	/// Records an added transaction, whose admission is the next to be recorded.
+	fn add(&mut self, key: Vec<u8>, transaction: MempoolTransaction) {
+		self.added_ids.insert(transaction.id());
+		self.added.insert(key, (self.admissions.len(), transaction));
+		self.size += 1;
+	}
+
+	/// Records an evicted transaction, which may have been added by the same call.
+	fn evict(&mut self, key: Vec<u8>, transaction: &MempoolTransaction) {
+		match self.added.remove(&key) {
+			Some((index, _)) => {
+				self.added_ids.remove(&transaction.id());
+				self.admissions[index] = Admission::Rejected(DropReason::Evicted);
+			}
+			None => {
+				self.evicted.insert(key);
+				self.evicted_ids.insert(transaction.id());
+			}
+		}
+		self.size = self.size.saturating_sub(1);
+	}
 }
 
 #[derive(Debug, Clone)]
 pub struct RocksdbMempool {
 	db: Arc<DB>,
+	// these values should not be changed after transactions have been added
+	ordering_policy: OrderingPolicy,
+	slot_seconds: u64,
+	limits: MempoolLimits,
+	// the number of transactions in the mempool
+	// the lock is held by every operation which adds or removes transactions
+	size: Arc<Mutex<u64>>,
 }
 impl RocksdbMempool {
 	pub fn try_new(path: &str) -> Result<Self, Error> {
+		Self::try_new_with_ordering_policy(path, OrderingPolicy::default())
+	}
+
+	/// Opens a mempool which orders transactions according to the given policy.
+	///
+	/// The policy determines the layout of the keys the transactions are stored under, which is recorded in the
+	/// database. Opening a mempool which holds transactions with a policy of another layout fails.
+	pub fn try_new_with_ordering_policy(
+		path: &str,
+		ordering_policy: OrderingPolicy,
+	) -> Result<Self, Error> {
 		let mut options = Options::default();
 		options.create_if_missing(true);
 		options.create_missing_column_families(true);
@@ -30,29 +109,974 @@ impl RocksdbMempool {
 		let blocks_cf = ColumnFamilyDescriptor::new(cf::BLOCKS, Options::default());
 		let transaction_lookups_cf =
 			ColumnFamilyDescriptor::new(cf::TRANSACTION_LOOKUPS, Options::default());
+		let sender_transactions_cf =
+			ColumnFamilyDescriptor::new(cf::SENDER_TRANSACTIONS, Options::default());
+		let dropped_transactions_cf =
+			ColumnFamilyDescriptor::new(cf::DROPPED_TRANSACTIONS, Options::default());
+		let dropped_transaction_times_cf =
+			ColumnFamilyDescriptor::new(cf::DROPPED_TRANSACTION_TIMES, Options::default());
+		let popped_transactions_cf =
+			ColumnFamilyDescriptor::new(cf::POPPED_TRANSACTIONS, Options::default());
+		let in_flight_blocks_cf =
+			ColumnFamilyDescriptor::new(cf::IN_FLIGHT_BLOCKS, Options::default());
+		let sequencer_state_cf =
+			ColumnFamilyDescriptor::new(cf::SEQUENCER_STATE, Options::default());
 
 		let db = DB::open_cf_descriptors(
 			&options,
 			path,
-			[mempool_transactions_cf, blocks_cf, transaction_lookups_cf],
+			[
+				mempool_transactions_cf,
+				blocks_cf,
+				transaction_lookups_cf,
+				sender_transactions_cf,
+				dropped_transactions_cf,
+				dropped_transaction_times_cf,
+				popped_transactions_cf,
+				in_flight_blocks_cf,
+				sequencer_state_cf,
+			],
 		)
 		.map_err(|e| Error::new(e))?;
 
-		Ok(RocksdbMempool { db: Arc::new(db) })
+		// the records written by earlier versions are re-encoded before they are read
+		Self::internal_migrate_legacy_layout(&db)?;
+
+		Self::internal_check_key_layout(&db, ordering_policy)?;
+
+		// the transactions which were popped before a restart but never built into a block would otherwise be lost
+		Self::internal_restore_popped_transactions(&db)?;
+
+		// every transaction in the mempool has an entry in the lookup table
+		let size = {
+			let cf_handle = db
+				.cf_handle(cf::TRANSACTION_LOOKUPS)
+				.ok_or_else(|| Error::msg("CF handle not found"))?;
+			let mut size = 0;
+			for res in db.iterator_cf(&cf_handle, IteratorMode::Start) {
+				res?;
+				size += 1;
+			}
+			size
+		};
+
+		Ok(RocksdbMempool {
+			db: Arc::new(db),
+			ordering_policy,
+			slot_seconds: MempoolTransaction::DEFAULT_SLOT_SECONDS,
+			limits: MempoolLimits::default(),
+			size: Arc::new(Mutex::new(size)),
+		})
+	}
+
+	pub fn with_limits(mut self, limits: MempoolLimits) -> Self {
+		self.limits = limits;
+		self
+	}
+
+	pub fn limits(&self) -> MempoolLimits {
+		self.limits
+	}
+
+	/// Gets the number of transactions in the mempool.
+	pub fn size(&self) -> Result<u64, Error> {
+		Ok(*self.size.lock().map_err(|_| Error::msg("Mempool size lock poisoned"))?)
+	}
+
+	fn now() -> u64 {
+		std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.unwrap()
+			.as_secs()
+	}
+
+	pub fn with_slot_seconds(mut self, slot_seconds: u64) -> Self {
+		self.slot_seconds = slot_seconds;
+		self
+	}
+
+	pub fn ordering_policy(&self) -> OrderingPolicy {
+		self.ordering_policy
+	}
+
+	/// The layout of the keys the transactions are stored under with the given ordering policy.
+	fn key_layout(ordering_policy: OrderingPolicy) -> &'static str {
+		match ordering_policy {
+			OrderingPolicy::Age => "age",
+			OrderingPolicy::Priority | OrderingPolicy::SenderFairness { .. } => "priority",
+		}
+	}
+
+	/// Records the key layout of the ordering policy, unless transactions are stored under another layout.
+	///
+	/// A mempool which has not recorded its layout stores its transactions under the age layout.
+	fn internal_check_key_layout(db: &DB, ordering_policy: OrderingPolicy) -> Result<(), Error> {
+		let sequencer_state_cf_handle = db
+			.cf_handle(cf::SEQUENCER_STATE)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let key_layout = Self::key_layout(ordering_policy);
+		let stored_key_layout = db.get_cf(&sequencer_state_cf_handle, KEY_LAYOUT_KEY)?;
+		let current_key_layout = stored_key_layout
+			.as_deref()
+			.unwrap_or(Self::key_layout(OrderingPolicy::Age).as_bytes());
+		if current_key_layout != key_layout.as_bytes() {
+			// the layout can only change while no transaction is stored under the previous one
+			for cf_name in [cf::MEMPOOL_TRANSACTIONS, cf::POPPED_TRANSACTIONS] {
+				let cf_handle =
+					db.cf_handle(cf_name).ok_or_else(|| Error::msg("CF handle not found"))?;
+				if db.iterator_cf(&cf_handle, IteratorMode::Start).next().is_some() {
+					anyhow::bail!(
+						"The mempool holds transactions ordered by {}, it cannot be opened with the {} ordering policy",
+						String::from_utf8_lossy(current_key_layout),
+						ordering_policy
+					);
+				}
+			}
+		}
+		if stored_key_layout.as_deref() != Some(key_layout.as_bytes()) {
+			db.put_cf(&sequencer_state_cf_handle, KEY_LAYOUT_KEY, key_layout)?;
+		}
+		Ok(())
+	}
+
+	/// Re-encodes the records of a column family which cannot be decoded in the current layout
+	/// from the legacy layout.
+	fn internal_migrate_legacy_records<T, L>(
+		db: &DB,
+		cf_name: &str,
+		migrate: impl Fn(L) -> T,
+	) -> Result<(), Error>
+	where
+		T: Serialize + DeserializeOwned,
+		L: DeserializeOwned,
+	{
+		let cf_handle = db.cf_handle(cf_name).ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		let mut batch = WriteBatch::default();
+		for res in db.iterator_cf(&cf_handle, IteratorMode::Start) {
+			let (key, value) = res?;
+			if bcs::from_bytes::<T>(&value).is_ok() {
+				continue;
+			}
+			let record = migrate(bcs::from_bytes::<L>(&value)?);
+			batch.put_cf(&cf_handle, key, bcs::to_bytes(&record)?);
+		}
+		db.write(batch)?;
+		Ok(())
+	}
+
+	/// Migrates the transactions and blocks written before transactions had a priority, a sender
+	/// and a gas estimate, which the current layout cannot decode.
+	fn internal_migrate_legacy_layout(db: &DB) -> Result<(), Error> {
+		// a mempool transaction in the legacy layout is its transaction, timestamp and slot length
+		fn migrate_transaction(
+			(transaction, timestamp, slot_seconds): (legacy::Transaction, u64, u64),
+		) -> MempoolTransaction {
+			MempoolTransaction { transaction: transaction.into(), timestamp, slot_seconds }
+		}
+
+		Self::internal_migrate_legacy_records(db, cf::MEMPOOL_TRANSACTIONS, migrate_transaction)?;
+		Self::internal_migrate_legacy_records(
+			db,
+			cf::POPPED_TRANSACTIONS,
+			|(key, transaction): (Vec<u8>, _)| (key, migrate_transaction(transaction)),
+		)?;
+		Self::internal_migrate_legacy_records(db, cf::BLOCKS, |block: legacy::Block| {
+			Block::from(block)
+		})?;
+		Self::internal_migrate_legacy_records(db, cf::IN_FLIGHT_BLOCKS, |block: legacy::Block| {
+			Block::from(block)
+		})?;
+		Ok(())
 	}
 
-	pub fn construct_mempool_transaction_key(transaction: &MempoolTransaction) -> String {
+	pub fn construct_mempool_transaction_key(
+		transaction: &MempoolTransaction,
+		ordering_policy: OrderingPolicy,
+	) -> String {
 		// Pre-allocate a string with the required capacity
-		let mut key = String::with_capacity(32 + 1 + 32 + 1 + 32);
+		let mut key = String::with_capacity(32 + 1 + 32 + 1 + 32 + 1 + 64);
 		// Write key components. The numbers are zero-padded to 32 characters.
+		match ordering_policy {
+			OrderingPolicy::Age => key.write_fmt(format_args!(
+				"{:032}:{:032}:{}",
+				transaction.timestamp,
+				transaction.transaction.sequence_number(),
+				transaction.transaction.id(),
+			)),
+			// the priority is inverted so that the highest priority comes first
+			OrderingPolicy::Priority | OrderingPolicy::SenderFairness { .. } => {
+				key.write_fmt(format_args!(
+					"{:032}:{:032}:{:032}:{}",
+					transaction.timestamp,
+					u64::MAX - transaction.transaction.application_priority(),
+					transaction.transaction.sequence_number(),
+					transaction.transaction.id(),
+				))
+			}
+		}
+		.unwrap();
+		key
+	}
+
+	/// Constructs the key of a transaction in the index of transactions by sender, if its sender is known.
+	/// Within a sender, the keys are ordered by sequence number.
+	fn construct_sender_transaction_key(transaction: &MempoolTransaction) -> Option<String> {
+		let sender = transaction.transaction.sender();
+		if sender.is_empty() {
+			return None;
+		}
+		let mut key = Self::construct_sender_prefix(sender);
 		key.write_fmt(format_args!(
-			"{:032}:{:032}:{}",
-			transaction.timestamp,
+			"{:032}:{}",
 			transaction.transaction.sequence_number(),
 			transaction.transaction.id(),
 		))
 		.unwrap();
-		key
+		Some(key)
+	}
+
+	fn construct_sender_prefix(sender: &[u8]) -> String {
+		let mut prefix = String::with_capacity(sender.len() * 2 + 1);
+		for byte in sender {
+			prefix.write_fmt(format_args!("{:02x}", byte)).unwrap();
+		}
+		prefix.push(':');
+		prefix
+	}
+
+	/// Adds the writes for a mempool transaction stored under the given key to the batch.
+	fn batch_put_mempool_transaction_with_key(
+		db: &DB,
+		batch: &mut WriteBatch,
+		key: &[u8],
+		transaction: &MempoolTransaction,
+	) -> Result<(), Error> {
+		let mempool_transactions_cf_handle = db
+			.cf_handle(cf::MEMPOOL_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let transaction_lookups_cf_handle = db
+			.cf_handle(cf::TRANSACTION_LOOKUPS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let sender_transactions_cf_handle = db
+			.cf_handle(cf::SENDER_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		let serialized_transaction = bcs::to_bytes(transaction)?;
+		batch.put_cf(&mempool_transactions_cf_handle, key, &serialized_transaction);
+		batch.put_cf(&transaction_lookups_cf_handle, transaction.transaction.id().to_vec(), key);
+		if let Some(sender_key) = Self::construct_sender_transaction_key(transaction) {
+			batch.put_cf(&sender_transactions_cf_handle, sender_key, key);
+		}
+		Ok(())
+	}
+
+	/// Adds the deletes for a mempool transaction stored under the given key to the batch.
+	fn batch_delete_mempool_transaction(
+		db: &DB,
+		batch: &mut WriteBatch,
+		key: &[u8],
+		transaction: &MempoolTransaction,
+	) -> Result<(), Error> {
+		let mempool_transactions_cf_handle = db
+			.cf_handle(cf::MEMPOOL_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let transaction_lookups_cf_handle = db
+			.cf_handle(cf::TRANSACTION_LOOKUPS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let sender_transactions_cf_handle = db
+			.cf_handle(cf::SENDER_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		batch.delete_cf(&mempool_transactions_cf_handle, key);
+		batch.delete_cf(&transaction_lookups_cf_handle, transaction.transaction.id().to_vec());
+		if let Some(sender_key) = Self::construct_sender_transaction_key(transaction) {
+			batch.delete_cf(&sender_transactions_cf_handle, sender_key);
+		}
+		Ok(())
+	}
+
+	fn construct_dropped_transaction_time_key(
+		dropped_at: u64,
+		transaction_id: transaction::Id,
+	) -> String {
+		format!("{:032}:{}", dropped_at, transaction_id)
+	}
+
+	/// Adds the writes recording why a transaction was dropped to the batch.
+	fn batch_put_drop_record(
+		db: &DB,
+		batch: &mut WriteBatch,
+		transaction_id: transaction::Id,
+		reason: DropReason,
+		dropped_at: u64,
+	) -> Result<(), Error> {
+		let dropped_transactions_cf_handle = db
+			.cf_handle(cf::DROPPED_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let dropped_transaction_times_cf_handle = db
+			.cf_handle(cf::DROPPED_TRANSACTION_TIMES)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		batch.put_cf(
+			&dropped_transactions_cf_handle,
+			transaction_id.to_vec(),
+			bcs::to_bytes(&(reason, dropped_at))?,
+		);
+		batch.put_cf(
+			&dropped_transaction_times_cf_handle,
+			Self::construct_dropped_transaction_time_key(dropped_at, transaction_id),
+			transaction_id.to_vec(),
+		);
+		Ok(())
+	}
+
+	fn internal_get_drop_record(
+		db: &DB,
+		transaction_id: transaction::Id,
+	) -> Result<Option<(DropReason, u64)>, Error> {
+		let cf_handle = db
+			.cf_handle(cf::DROPPED_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		match db.get_cf(&cf_handle, transaction_id.to_vec())? {
+			Some(value) => Ok(Some(bcs::from_bytes(&value)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Whether a transaction is in the mempool, taking the pending admissions into account.
+	fn internal_has_pending_transaction(
+		db: &DB,
+		pending: &PendingAdmissions,
+		transaction_id: transaction::Id,
+	) -> Result<bool, Error> {
+		if pending.added_ids.contains(&transaction_id) {
+			return Ok(true);
+		}
+		if pending.evicted_ids.contains(&transaction_id) {
+			return Ok(false);
+		}
+		Self::internal_has_mempool_transaction(db, transaction_id)
+	}
+
+	/// Counts the transactions from the given sender in the mempool, up to the given limit,
+	/// taking the pending admissions into account.
+	fn internal_count_sender_transactions(
+		db: &DB,
+		pending: &PendingAdmissions,
+		sender: &[u8],
+		limit: u64,
+	) -> Result<u64, Error> {
+		let cf_handle = db
+			.cf_handle(cf::SENDER_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let prefix = Self::construct_sender_prefix(sender);
+
+		let mut count = pending
+			.added
+			.values()
+			.filter(|(_, transaction)| transaction.transaction.sender() == sender)
+			.count() as u64;
+		let iter =
+			db.iterator_cf(&cf_handle, IteratorMode::From(prefix.as_bytes(), Direction::Forward));
+		for res in iter {
+			let (key, mempool_key) = res?;
+			if count >= limit || !key.starts_with(prefix.as_bytes()) {
+				break;
+			}
+			if !pending.evicted.contains(mempool_key.as_ref()) {
+				count += 1;
+			}
+		}
+		Ok(count)
+	}
+
+	/// Whether a later transaction from the same sender remains in the mempool,
+	/// taking the pending admissions into account.
+	fn internal_has_later_sender_transaction(
+		db: &DB,
+		pending: &PendingAdmissions,
+		transaction: &MempoolTransaction,
+	) -> Result<bool, Error> {
+		let sender_key = match Self::construct_sender_transaction_key(transaction) {
+			Some(sender_key) => sender_key,
+			None => return Ok(false),
+		};
+		let sender = transaction.transaction.sender();
+
+		let later_added = pending.added.values().any(|(_, added)| {
+			added.transaction.sender() == sender
+				&& Self::construct_sender_transaction_key(added)
+					.is_some_and(|added_key| added_key > sender_key)
+		});
+		if later_added {
+			return Ok(true);
+		}
+
+		let cf_handle = db
+			.cf_handle(cf::SENDER_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let prefix = Self::construct_sender_prefix(sender);
+		let iter = db
+			.iterator_cf(&cf_handle, IteratorMode::From(sender_key.as_bytes(), Direction::Forward));
+		for res in iter {
+			let (key, mempool_key) = res?;
+			if !key.starts_with(prefix.as_bytes()) {
+				break;
+			}
+			if key.as_ref() != sender_key.as_bytes()
+				&& !pending.evicted.contains(mempool_key.as_ref())
+			{
+				return Ok(true);
+			}
+		}
+		Ok(false)
+	}
+
+	/// Selects the next transaction to evict from a full mempool, taking the pending admissions into account.
+	///
+	/// The oldest slot goes first and, within a slot, the lowest priority transaction.
+	/// A transaction is not evicted while later transactions from its sender remain.
+	fn internal_select_eviction(
+		db: &DB,
+		pending: &PendingAdmissions,
+	) -> Result<Option<(Vec<u8>, MempoolTransaction)>, Error> {
+		let cf_handle = db
+			.cf_handle(cf::MEMPOOL_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		// the keys start with the slot timestamp, so the stored and the added transactions are merged in slot order
+		let mut stored = db.iterator_cf(&cf_handle, IteratorMode::Start);
+		let mut next_stored = || -> Result<Option<(Vec<u8>, MempoolTransaction)>, Error> {
+			for res in stored.by_ref() {
+				let (key, value) = res?;
+				if !pending.evicted.contains(key.as_ref()) {
+					return Ok(Some((key.to_vec(), bcs::from_bytes(&value)?)));
+				}
+			}
+			Ok(None)
+		};
+		let mut added = pending
+			.added
+			.iter()
+			.map(|(key, (_, transaction))| (key.clone(), transaction.clone()));
+
+		let (mut stored_entry, mut added_entry) = (next_stored()?, added.next());
+		let mut slot: Vec<(Vec<u8>, MempoolTransaction)> = Vec::new();
+		loop {
+			let next = match (&stored_entry, &added_entry) {
+				(Some((stored_key, _)), Some((added_key, _))) if stored_key <= added_key => {
+					std::mem::replace(&mut stored_entry, next_stored()?)
+				}
+				(Some(_), None) => std::mem::replace(&mut stored_entry, next_stored()?),
+				(_, Some(_)) => std::mem::replace(&mut added_entry, added.next()),
+				(None, None) => None,
+			};
+
+			let slot_ended = match (&next, slot.first()) {
+				(Some((key, _)), Some((first, _))) => key.get(..32) != first.get(..32),
+				(None, _) => true,
+				(Some(_), None) => false,
+			};
+			if slot_ended {
+				// the lowest priority and the latest transactions come last in a slot
+				for (key, transaction) in slot.iter().rev() {
+					if !Self::internal_has_later_sender_transaction(db, pending, transaction)? {
+						return Ok(Some((key.clone(), transaction.clone())));
+					}
+				}
+				slot.clear();
+			}
+
+			match next {
+				Some(entry) => slot.push(entry),
+				None => return Ok(None),
+			}
+		}
+	}
+
+	/// Adds the writes for a new mempool transaction to the batch, enforcing the limits of the mempool.
+	///
+	/// A transaction which is not admitted is recorded as dropped rather than failing the call.
+	/// The admission is to be recorded in the pending admissions by the caller.
+	fn batch_add_mempool_transaction(
+		db: &DB,
+		batch: &mut WriteBatch,
+		pending: &mut PendingAdmissions,
+		ordering_policy: OrderingPolicy,
+		limits: MempoolLimits,
+		transaction: MempoolTransaction,
+		now: u64,
+	) -> Result<Admission, Error> {
+		let transaction_id = transaction.transaction.id();
+		if Self::internal_has_pending_transaction(db, pending, transaction_id)? {
+			return Ok(Admission::Admitted);
+		}
+
+		if limits.is_expired(transaction.timestamp, now) {
+			Self::batch_put_drop_record(db, batch, transaction_id, DropReason::Expired, now)?;
+			return Ok(Admission::Rejected(DropReason::Expired));
+		}
+
+		if limits.is_oversized(&transaction.transaction) {
+			Self::batch_put_drop_record(db, batch, transaction_id, DropReason::Oversized, now)?;
+			return Ok(Admission::Rejected(DropReason::Oversized));
+		}
+
+		let sender = transaction.transaction.sender();
+		if let (Some(max_per_sender), false) =
+			(limits.max_transactions_per_sender, sender.is_empty())
+		{
+			if Self::internal_count_sender_transactions(db, pending, sender, max_per_sender)?
+				>= max_per_sender
+			{
+				Self::batch_put_drop_record(
+					db,
+					batch,
+					transaction_id,
+					DropReason::SenderLimitReached,
+					now,
+				)?;
+				return Ok(Admission::Rejected(DropReason::SenderLimitReached));
+			}
+		}
+
+		if let Some(max_transactions) = limits.max_transactions {
+			match limits.eviction_policy {
+				EvictionPolicy::RejectNew => {
+					if pending.size >= max_transactions {
+						Self::batch_put_drop_record(
+							db,
+							batch,
+							transaction_id,
+							DropReason::MempoolFull,
+							now,
+						)?;
+						return Ok(Admission::Rejected(DropReason::MempoolFull));
+					}
+				}
+				EvictionPolicy::EvictOldest => {
+					while pending.size >= max_transactions {
+						let (key, evicted) = match Self::internal_select_eviction(db, pending)? {
+							Some(eviction) => eviction,
+							None => break,
+						};
+						Self::batch_delete_mempool_transaction(db, batch, &key, &evicted)?;
+						Self::batch_put_drop_record(
+							db,
+							batch,
+							evicted.id(),
+							DropReason::Evicted,
+							now,
+						)?;
+						pending.evict(key, &evicted);
+					}
+				}
+			}
+		}
+
+		let key = Self::construct_mempool_transaction_key(&transaction, ordering_policy);
+		Self::batch_put_mempool_transaction_with_key(db, batch, key.as_bytes(), &transaction)?;
+		// a transaction which is added again is no longer dropped or popped
+		let dropped_transactions_cf_handle = db
+			.cf_handle(cf::DROPPED_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let popped_transactions_cf_handle = db
+			.cf_handle(cf::POPPED_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		batch.delete_cf(&dropped_transactions_cf_handle, transaction_id.to_vec());
+		batch.delete_cf(&popped_transactions_cf_handle, transaction_id.to_vec());
+		pending.add(key.into_bytes(), transaction);
+
+		Ok(Admission::Admitted)
+	}
+
+	/// Drops the transactions which have outlived the time-to-live and forgets old drop records.
+	fn internal_expire_mempool_transactions(
+		db: &DB,
+		limits: MempoolLimits,
+		size: &mut u64,
+		now: u64,
+	) -> Result<Vec<transaction::Id>, Error> {
+		let cf_handle = db
+			.cf_handle(cf::MEMPOOL_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let dropped_transactions_cf_handle = db
+			.cf_handle(cf::DROPPED_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let dropped_transaction_times_cf_handle = db
+			.cf_handle(cf::DROPPED_TRANSACTION_TIMES)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		let mut batch = WriteBatch::default();
+
+		// the keys start with the slot timestamp, so the expired transactions come first
+		let mut expired = Vec::new();
+		for res in db.iterator_cf(&cf_handle, IteratorMode::Start) {
+			let (key, value) = res?;
+			let transaction: MempoolTransaction = bcs::from_bytes(&value)?;
+			if !limits.is_expired(transaction.timestamp, now) {
+				break;
+			}
+			Self::batch_delete_mempool_transaction(db, &mut batch, &key, &transaction)?;
+			Self::batch_put_drop_record(
+				db,
+				&mut batch,
+				transaction.id(),
+				DropReason::Expired,
+				now,
+			)?;
+			expired.push(transaction.id());
+		}
+
+		for res in db.iterator_cf(&dropped_transaction_times_cf_handle, IteratorMode::Start) {
+			let (key, value) = res?;
+			let dropped_at = key
+				.get(..32)
+				.and_then(|dropped_at| std::str::from_utf8(dropped_at).ok())
+				.and_then(|dropped_at| dropped_at.parse::<u64>().ok())
+				.ok_or_else(|| Error::msg("Invalid dropped transaction time key"))?;
+			if dropped_at.saturating_add(DROP_RECORD_RETENTION_SECONDS) > now {
+				break;
+			}
+			batch.delete_cf(&dropped_transaction_times_cf_handle, &key);
+
+			// the transaction may have been dropped again since
+			let transaction_id = transaction::Id::new(
+				value.as_ref().try_into().map_err(|_| Error::msg("Invalid transaction id"))?,
+			);
+			if let Some((_, record_dropped_at)) =
+				Self::internal_get_drop_record(db, transaction_id)?
+			{
+				if record_dropped_at == dropped_at {
+					batch.delete_cf(&dropped_transactions_cf_handle, value.as_ref());
+				}
+			}
+		}
+
+		db.write(batch)?;
+
+		*size = size.saturating_sub(expired.len() as u64);
+		Ok(expired)
+	}
+
+	/// Gets the transactions from the same sender with a lower sequence number, in sequence number order.
+	///
+	/// The scan starts after the given key in the index of transactions by sender if there is one,
+	/// so that the transactions already popped from the sender are not scanned again.
+	fn internal_get_preceding_transactions(
+		db: &DB,
+		transaction: &MempoolTransaction,
+		after: Option<&[u8]>,
+	) -> Result<Vec<(Vec<u8>, MempoolTransaction)>, Error> {
+		let sender_key = match Self::construct_sender_transaction_key(transaction) {
+			Some(sender_key) => sender_key,
+			None => return Ok(Vec::new()),
+		};
+		let prefix = Self::construct_sender_prefix(transaction.transaction.sender());
+
+		let cf_handle = db
+			.cf_handle(cf::MEMPOOL_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let sender_transactions_cf_handle = db
+			.cf_handle(cf::SENDER_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		let mut preceding = Vec::new();
+		let iter = db.iterator_cf(
+			&sender_transactions_cf_handle,
+			IteratorMode::From(after.unwrap_or(prefix.as_bytes()), Direction::Forward),
+		);
+		for res in iter {
+			let (key, mempool_key) = res?;
+			if after.is_some_and(|after| key.as_ref() == after) {
+				continue;
+			}
+			if !key.starts_with(prefix.as_bytes()) || key.as_ref() >= sender_key.as_bytes() {
+				break;
+			}
+			if let Some(value) = db.get_cf(&cf_handle, &mempool_key)? {
+				preceding.push((mempool_key.to_vec(), bcs::from_bytes(&value)?));
+			}
+		}
+		Ok(preceding)
+	}
+
+	/// Pops up to n mempool transactions in the order given by the ordering policy.
+	///
+	/// Before a transaction is popped, the transactions from the same sender with a lower sequence number are popped.
+	/// Transactions which have outlived the time-to-live are dropped instead of popped.
+	fn internal_pop_mempool_transactions(
+		db: &DB,
+		ordering_policy: OrderingPolicy,
+		limits: MempoolLimits,
+		size: &mut u64,
+		n: usize,
+		now: u64,
+	) -> Result<Vec<MempoolTransaction>, Error> {
+		let cf_handle = db
+			.cf_handle(cf::MEMPOOL_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let popped_transactions_cf_handle = db
+			.cf_handle(cf::POPPED_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let max_per_sender = match ordering_policy {
+			OrderingPolicy::SenderFairness { max_per_sender } => Some(max_per_sender),
+			OrderingPolicy::Age | OrderingPolicy::Priority => None,
+		};
+
+		// Remove the transactions and their lookup table entries
+		// atomically in a single write batch.
+		// https://github.com/movementlabsxyz/movement/issues/322
+
+		let mut batch = WriteBatch::default();
+		let mut mempool_transactions = Vec::with_capacity(n);
+		let mut popped = HashSet::new();
+		let mut popped_per_sender: HashMap<Vec<u8>, u32> = HashMap::new();
+		// the key in the index of transactions by sender of the last transaction popped from each sender,
+		// the transactions from a sender are popped in sequence number order, so the earlier ones need no scan
+		let mut popped_through: HashMap<Vec<u8>, String> = HashMap::new();
+		for res in db.iterator_cf(&cf_handle, IteratorMode::Start) {
+			if mempool_transactions.len() >= n {
+				break;
+			}
+
+			let (key, value) = res?;
+			let transaction: MempoolTransaction = bcs::from_bytes(&value)?;
+			if popped.contains(&transaction.id()) {
+				continue;
+			}
+			let sender = transaction.transaction.sender();
+			if let (Some(max_per_sender), Some(count)) =
+				(max_per_sender, popped_per_sender.get(sender))
+			{
+				if *count >= max_per_sender {
+					continue;
+				}
+			}
+
+			let mut to_pop = Self::internal_get_preceding_transactions(
+				db,
+				&transaction,
+				popped_through.get(sender).map(|sender_key| sender_key.as_bytes()),
+			)?;
+			to_pop.retain(|(_, preceding)| !popped.contains(&preceding.id()));
+			to_pop.push((key.to_vec(), transaction));
+
+			for (key, transaction) in to_pop {
+				if mempool_transactions.len() >= n {
+					break;
+				}
+
+				if limits.is_expired(transaction.timestamp, now) {
+					Self::batch_delete_mempool_transaction(db, &mut batch, &key, &transaction)?;
+					Self::batch_put_drop_record(
+						db,
+						&mut batch,
+						transaction.id(),
+						DropReason::Expired,
+						now,
+					)?;
+					popped.insert(transaction.id());
+					if let Some(sender_key) = Self::construct_sender_transaction_key(&transaction) {
+						popped_through
+							.insert(transaction.transaction.sender().to_vec(), sender_key);
+					}
+					continue;
+				}
+
+				// senders which are not known are not limited
+				let sender = transaction.transaction.sender();
+				if let (Some(max_per_sender), false) = (max_per_sender, sender.is_empty()) {
+					let count = popped_per_sender.entry(sender.to_vec()).or_default();
+					if *count >= max_per_sender {
+						break;
+					}
+					*count += 1;
+				}
+
+				// the popped transaction is kept until the block which includes it is added
+				Self::batch_delete_mempool_transaction(db, &mut batch, &key, &transaction)?;
+				batch.put_cf(
+					&popped_transactions_cf_handle,
+					transaction.id().to_vec(),
+					bcs::to_bytes(&(key, &transaction))?,
+				);
+				popped.insert(transaction.id());
+				if let Some(sender_key) = Self::construct_sender_transaction_key(&transaction) {
+					popped_through.insert(sender.to_vec(), sender_key);
+				}
+				mempool_transactions.push(transaction);
+			}
+		}
+		db.write(batch)?;
+
+		*size = size.saturating_sub(popped.len() as u64);
+		Ok(mempool_transactions)
+	}
+
+	/// Returns the popped transactions which were never built into a block to the mempool.
+	fn internal_restore_popped_transactions(db: &DB) -> Result<(), Error> {
+		let popped_transactions_cf_handle = db
+			.cf_handle(cf::POPPED_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		let mut batch = WriteBatch::default();
+		for res in db.iterator_cf(&popped_transactions_cf_handle, IteratorMode::Start) {
+			let (id, value) = res?;
+			let (key, transaction): (Vec<u8>, MempoolTransaction) = bcs::from_bytes(&value)?;
+			// the transaction may have been added again since it was popped
+			if !Self::internal_has_mempool_transaction(db, transaction.id())? {
+				Self::batch_put_mempool_transaction_with_key(db, &mut batch, &key, &transaction)?;
+			}
+			batch.delete_cf(&popped_transactions_cf_handle, id);
+		}
+		db.write(batch)?;
+		Ok(())
+	}
+
+	/// Returns the given popped transactions to the mempool under the key they were popped from.
+	///
+	/// The limits of the mempool are not enforced, the transactions were admitted when they were added.
+	fn internal_return_popped_transactions(
+		db: &DB,
+		ordering_policy: OrderingPolicy,
+		size: &mut u64,
+		transactions: Vec<MempoolTransaction>,
+	) -> Result<(), Error> {
+		let popped_transactions_cf_handle = db
+			.cf_handle(cf::POPPED_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		// Return the transactions and their lookup table entries
+		// atomically in a single write batch.
+		// https://github.com/movementlabsxyz/movement/issues/322
+
+		let mut batch = WriteBatch::default();
+		let mut returned = HashSet::new();
+		for transaction in transactions {
+			let transaction_id = transaction.id();
+			// the transaction may have been added again since it was popped
+			if returned.contains(&transaction_id)
+				|| Self::internal_has_mempool_transaction(db, transaction_id)?
+			{
+				batch.delete_cf(&popped_transactions_cf_handle, transaction_id.to_vec());
+				continue;
+			}
+
+			let key = match db.get_cf(&popped_transactions_cf_handle, transaction_id.to_vec())? {
+				Some(value) => bcs::from_bytes::<(Vec<u8>, MempoolTransaction)>(&value)?.0,
+				None => Self::construct_mempool_transaction_key(&transaction, ordering_policy)
+					.into_bytes(),
+			};
+			Self::batch_put_mempool_transaction_with_key(db, &mut batch, &key, &transaction)?;
+			batch.delete_cf(&popped_transactions_cf_handle, transaction_id.to_vec());
+			returned.insert(transaction_id);
+		}
+		db.write(batch)?;
+
+		*size += returned.len() as u64;
+		Ok(())
+	}
+
+	fn internal_add_in_flight_block(db: &DB, block: &Block) -> Result<(), Error> {
+		let popped_transactions_cf_handle = db
+			.cf_handle(cf::POPPED_TRANSACTIONS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let in_flight_blocks_cf_handle = db
+			.cf_handle(cf::IN_FLIGHT_BLOCKS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let sequencer_state_cf_handle = db
+			.cf_handle(cf::SEQUENCER_STATE)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		// the in-flight blocks are keyed by the order in which they were built
+		let index = match db.iterator_cf(&in_flight_blocks_cf_handle, IteratorMode::End).next() {
+			Some(res) => {
+				let (key, _) = res?;
+				// the blocks a block was split into extend its key, so the index is the prefix
+				let index: [u8; 8] = key
+					.get(..8)
+					.and_then(|index| index.try_into().ok())
+					.ok_or_else(|| Error::msg("Invalid in-flight block key"))?;
+				u64::from_be_bytes(index) + 1
+			}
+			None => 0,
+		};
+
+		// Record the block, the new head and the transactions it includes
+		// atomically in a single write batch.
+		let mut batch = WriteBatch::default();
+		batch.put_cf(&in_flight_blocks_cf_handle, index.to_be_bytes(), bcs::to_bytes(block)?);
+		batch.put_cf(
+			&sequencer_state_cf_handle,
+			SEQUENCER_HEAD_KEY,
+			bcs::to_bytes(&SequencerHead::from_block(block))?,
+		);
+		for transaction in block.transactions() {
+			batch.delete_cf(&popped_transactions_cf_handle, transaction.id().to_vec());
+		}
+		db.write(batch)?;
+		Ok(())
+	}
+
+	fn internal_split_in_flight_block(
+		db: &DB,
+		block_id: block::Id,
+		blocks: &[Block],
+	) -> Result<(), Error> {
+		let in_flight_blocks_cf_handle = db
+			.cf_handle(cf::IN_FLIGHT_BLOCKS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let sequencer_state_cf_handle = db
+			.cf_handle(cf::SEQUENCER_STATE)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+		// Replace the block and move the head atomically in a single write batch.
+		let mut batch = WriteBatch::default();
+
+		// the blocks take the place of the split block by extending its key with their index
+		let key = Self::internal_get_in_flight_blocks(db)?
+			.into_iter()
+			.find_map(|(key, block)| (block.id() == block_id).then_some(key));
+		if let Some(key) = key {
+			batch.delete_cf(&in_flight_blocks_cf_handle, &key);
+			for (index, block) in blocks.iter().enumerate() {
+				let mut block_key = key.clone();
+				block_key.extend_from_slice(&(index as u32).to_be_bytes());
+				batch.put_cf(&in_flight_blocks_cf_handle, block_key, bcs::to_bytes(block)?);
+			}
+		}
+
+		let head: Option<SequencerHead> =
+			match db.get_cf(&sequencer_state_cf_handle, SEQUENCER_HEAD_KEY)? {
+				Some(value) => Some(bcs::from_bytes(&value)?),
+				None => None,
+			};
+		if let (Some(head), Some(last)) = (head, blocks.last()) {
+			if head.block_id == block_id {
+				batch.put_cf(
+					&sequencer_state_cf_handle,
+					SEQUENCER_HEAD_KEY,
+					bcs::to_bytes(&SequencerHead::from_block(last))?,
+				);
+			}
+		}
+
+		db.write(batch)?;
+		Ok(())
+	}
+
+	fn internal_get_in_flight_blocks(db: &DB) -> Result<Vec<(Vec<u8>, Block)>, Error> {
+		let cf_handle = db
+			.cf_handle(cf::IN_FLIGHT_BLOCKS)
+			.ok_or_else(|| Error::msg("CF handle not found"))?;
+		let mut blocks = Vec::new();
+		for res in db.iterator_cf(&cf_handle, IteratorMode::Start) {
+			let (key, value) = res?;
+			blocks.push((key.to_vec(), bcs::from_bytes(&value)?));
+		}
+		Ok(blocks)
 	}
 
 	fn internal_get_mempool_transaction_key(
@@ -110,88 +1134,60 @@ impl MempoolTransactionOperations for RocksdbMempool {
 	async fn add_mempool_transactions(
 		&self,
 		transactions: Vec<MempoolTransaction>,
-	) -> Result<(), anyhow::Error> {
+	) -> Result<Vec<Admission>, anyhow::Error> {
 		let db = self.db.clone();
+		let ordering_policy = self.ordering_policy;
+		let limits = self.limits;
+		let size = self.size.clone();
 		tokio::task::spawn_blocking(move || {
-			let mempool_transactions_cf_handle = db
-				.cf_handle(cf::MEMPOOL_TRANSACTIONS)
-				.ok_or_else(|| Error::msg("CF handle not found"))?;
-			let transaction_lookups_cf_handle = db
-				.cf_handle(cf::TRANSACTION_LOOKUPS)
-				.ok_or_else(|| Error::msg("CF handle not found"))?;
+			let mut size = size.lock().map_err(|_| Error::msg("Mempool size lock poisoned"))?;
+			let now = Self::now();
 
-			// Add the transactions and update the lookup table atomically
-			// in a single write batch.
+			// Add the transactions and update the lookup tables
+			// atomically in a single write batch.
 			// https://github.com/movementlabsxyz/movement/issues/322
 
 			let mut batch = WriteBatch::default();
-
+			let mut pending = PendingAdmissions::new(*size);
 			for transaction in transactions {
-				if Self::internal_has_mempool_transaction(&db, transaction.transaction.id())? {
-					continue;
-				}
-
-				let serialized_transaction = bcs::to_bytes(&transaction)?;
-				let key = Self::construct_mempool_transaction_key(&transaction);
-				batch.put_cf(&mempool_transactions_cf_handle, &key, &serialized_transaction);
-				batch.put_cf(
-					&transaction_lookups_cf_handle,
-					transaction.transaction.id().to_vec(),
-					&key,
-				);
+				let admission = Self::batch_add_mempool_transaction(
+					&db,
+					&mut batch,
+					&mut pending,
+					ordering_policy,
+					limits,
+					transaction,
+					now,
+				)?;
+				pending.admissions.push(admission);
 			}
-
 			db.write(batch)?;
 
-			Ok::<(), Error>(())
+			*size = pending.size;
+			Ok(pending.admissions)
 		})
-		.await??;
-		Ok(())
+		.await?
 	}
 
-	async fn add_mempool_transaction(&self, transaction: MempoolTransaction) -> Result<(), Error> {
-		let serialized_transaction = bcs::to_bytes(&transaction)?;
-		let db = self.db.clone();
-
-		tokio::task::spawn_blocking(move || {
-			let mempool_transactions_cf_handle = db
-				.cf_handle(cf::MEMPOOL_TRANSACTIONS)
-				.ok_or_else(|| Error::msg("CF handle not found"))?;
-			let transaction_lookups_cf_handle = db
-				.cf_handle(cf::TRANSACTION_LOOKUPS)
-				.ok_or_else(|| Error::msg("CF handle not found"))?;
-
-			// Add the transaction and update the lookup table atomically
-			// in a single write batch.
-			// https://github.com/movementlabsxyz/movement/issues/322
-
-			let mut batch = WriteBatch::default();
-
-			let key = Self::construct_mempool_transaction_key(&transaction);
-			batch.put_cf(&mempool_transactions_cf_handle, &key, &serialized_transaction);
-			batch.put_cf(
-				&transaction_lookups_cf_handle,
-				transaction.transaction.id().to_vec(),
-				&key,
-			);
-
-			db.write(batch)?;
-
-			Ok::<(), Error>(())
-		})
-		.await??;
-
-		Ok(())
+	async fn add_mempool_transaction(
+		&self,
+		transaction: MempoolTransaction,
+	) -> Result<Admission, Error> {
+		self.add_mempool_transactions(vec![transaction])
+			.await?
+			.pop()
+			.ok_or_else(|| Error::msg("No admission for the added transaction"))
 	}
 
 	async fn remove_mempool_transaction(
 		&self,
 		transaction_id: transaction::Id,
 	) -> Result<(), Error> {
-		let key = self.get_mempool_transaction_key(transaction_id).await?;
 		let db = self.db.clone();
+		let size = self.size.clone();
 		tokio::task::spawn_blocking(move || {
-			match key {
+			let mut size = size.lock().map_err(|_| Error::msg("Mempool size lock poisoned"))?;
+			match Self::internal_get_mempool_transaction_key(&db, transaction_id)? {
 				Some(k) => {
 					let cf_handle = db
 						.cf_handle(cf::MEMPOOL_TRANSACTIONS)
@@ -200,13 +1196,26 @@ impl MempoolTransactionOperations for RocksdbMempool {
 						.cf_handle(cf::TRANSACTION_LOOKUPS)
 						.ok_or_else(|| Error::msg("CF handle not found"))?;
 
-					// Remove the transaction and its entry in the lookup table
+					// Remove the transaction and its entries in the lookup tables
 					// atomically in a single write batch.
 					// https://github.com/movementlabsxyz/movement/issues/322
 
 					let mut batch = WriteBatch::default();
-					batch.delete_cf(&cf_handle, k);
-					batch.delete_cf(&lookups_cf_handle, transaction_id.to_vec());
+					match db.get_cf(&cf_handle, &k)? {
+						Some(value) => {
+							let transaction: MempoolTransaction = bcs::from_bytes(&value)?;
+							Self::batch_delete_mempool_transaction(
+								&db,
+								&mut batch,
+								&k,
+								&transaction,
+							)?;
+							*size = size.saturating_sub(1);
+						}
+						None => {
+							batch.delete_cf(&lookups_cf_handle, transaction_id.to_vec());
+						}
+					}
 					db.write(batch)?;
 				}
 				None => (),
@@ -217,6 +1226,20 @@ impl MempoolTransactionOperations for RocksdbMempool {
 		Ok(())
 	}
 
+	async fn return_mempool_transactions(
+		&self,
+		transactions: Vec<MempoolTransaction>,
+	) -> Result<(), Error> {
+		let db = self.db.clone();
+		let ordering_policy = self.ordering_policy;
+		let size = self.size.clone();
+		tokio::task::spawn_blocking(move || {
+			let mut size = size.lock().map_err(|_| Error::msg("Mempool size lock poisoned"))?;
+			Self::internal_return_popped_transactions(&db, ordering_policy, &mut size, transactions)
+		})
+		.await?
+	}
+
 	// Updated method signatures and implementations go here
 	async fn get_mempool_transaction(
 		&self,
@@ -243,36 +1266,7 @@ impl MempoolTransactionOperations for RocksdbMempool {
 	}
 
 	async fn pop_mempool_transaction(&self) -> Result<Option<MempoolTransaction>, Error> {
-		let db = self.db.clone();
-		tokio::task::spawn_blocking(move || {
-			let cf_handle = db
-				.cf_handle(cf::MEMPOOL_TRANSACTIONS)
-				.ok_or_else(|| Error::msg("CF handle not found"))?;
-			let lookups_cf_handle = db
-				.cf_handle(cf::TRANSACTION_LOOKUPS)
-				.ok_or_else(|| Error::msg("CF handle not found"))?;
-			let mut iter = db.iterator_cf(&cf_handle, rocksdb::IteratorMode::Start);
-
-			match iter.next() {
-				None => return Ok(None), // No transactions to pop
-				Some(res) => {
-					let (key, value) = res?;
-					let transaction: MempoolTransaction = bcs::from_bytes(&value)?;
-
-					// Remove the transaction and its entry in the lookup table
-					// atomically in a single write batch.
-					// https://github.com/movementlabsxyz/movement/issues/322
-
-					let mut batch = WriteBatch::default();
-					batch.delete_cf(&cf_handle, &key);
-					batch.delete_cf(&lookups_cf_handle, transaction.transaction.id().to_vec());
-					db.write(batch)?;
-
-					Ok(Some(transaction))
-				}
-			}
-		})
-		.await?
+		Ok(self.pop_mempool_transactions(1).await?.pop())
 	}
 
 	async fn pop_mempool_transactions(
@@ -280,39 +1274,48 @@ impl MempoolTransactionOperations for RocksdbMempool {
 		n: usize,
 	) -> Result<Vec<MempoolTransaction>, anyhow::Error> {
 		let db = self.db.clone();
+		let ordering_policy = self.ordering_policy;
+		let limits = self.limits;
+		let size = self.size.clone();
 		tokio::task::spawn_blocking(move || {
-			let cf_handle = db
-				.cf_handle(cf::MEMPOOL_TRANSACTIONS)
-				.ok_or_else(|| Error::msg("CF handle not found"))?;
-			let lookups_cf_handle = db
-				.cf_handle(cf::TRANSACTION_LOOKUPS)
-				.ok_or_else(|| Error::msg("CF handle not found"))?;
-			let mut iter = db.iterator_cf(&cf_handle, rocksdb::IteratorMode::Start);
-
-			// Remove the transactions and their lookup table entries
-			// atomically in a single write batch.
-			// https://github.com/movementlabsxyz/movement/issues/322
-
-			let mut batch = WriteBatch::default();
-			let mut mempool_transactions = Vec::with_capacity(n as usize);
-			while let Some(res) = iter.next() {
-				let (key, value) = res?;
-				let transaction: MempoolTransaction = bcs::from_bytes(&value)?;
-
-				batch.delete_cf(&cf_handle, &key);
-				batch.delete_cf(&lookups_cf_handle, transaction.transaction.id().to_vec());
+			let mut size = size.lock().map_err(|_| Error::msg("Mempool size lock poisoned"))?;
+			Self::internal_pop_mempool_transactions(
+				&db,
+				ordering_policy,
+				limits,
+				&mut size,
+				n,
+				Self::now(),
+			)
+		})
+		.await?
+	}
 
-				mempool_transactions.push(transaction);
-				if mempool_transactions.len() >= n {
-					break;
-				}
-			}
-			db.write(batch)?;
+	async fn expire_mempool_transactions(&self, now: u64) -> Result<Vec<transaction::Id>, Error> {
+		let db = self.db.clone();
+		let limits = self.limits;
+		let size = self.size.clone();
+		tokio::task::spawn_blocking(move || {
+			let mut size = size.lock().map_err(|_| Error::msg("Mempool size lock poisoned"))?;
+			Self::internal_expire_mempool_transactions(&db, limits, &mut size, now)
+		})
+		.await?
+	}
 
-			Ok(mempool_transactions)
+	async fn get_drop_reason(
+		&self,
+		transaction_id: transaction::Id,
+	) -> Result<Option<DropReason>, Error> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			Ok(Self::internal_get_drop_record(&db, transaction_id)?.map(|(reason, _)| reason))
 		})
 		.await?
 	}
+
+	fn slot_seconds(&self) -> u64 {
+		self.slot_seconds
+	}
 }
 
 impl MempoolBlockOperations for RocksdbMempool {
@@ -338,30 +1341,103 @@ impl MempoolBlockOperations for RocksdbMempool {
 		.await?
 	}
 
-	async fn remove_block(&self, block_id: block::Id) -> Result<(), Error> {
+	async fn remove_block(&self, block_id: block::Id) -> Result<(), Error> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle =
+				db.cf_handle(cf::BLOCKS).ok_or_else(|| Error::msg("CF handle not found"))?;
+			db.delete_cf(&cf_handle, block_id.to_vec())?;
+			Ok(())
+		})
+		.await?
+	}
+
+	async fn get_block(&self, block_id: block::Id) -> Result<Option<Block>, Error> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle =
+				db.cf_handle(cf::BLOCKS).ok_or_else(|| Error::msg("CF handle not found"))?;
+			let serialized_block = db.get_cf(&cf_handle, block_id.to_vec())?;
+			match serialized_block {
+				Some(serialized_block) => {
+					let block: Block = bcs::from_bytes(&serialized_block)?;
+					Ok(Some(block))
+				}
+				None => Ok(None),
+			}
+		})
+		.await?
+	}
+}
+
+impl SequencerStateOperations for RocksdbMempool {
+	async fn get_sequencer_head(&self) -> Result<Option<SequencerHead>, Error> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle = db
+				.cf_handle(cf::SEQUENCER_STATE)
+				.ok_or_else(|| Error::msg("CF handle not found"))?;
+			match db.get_cf(&cf_handle, SEQUENCER_HEAD_KEY)? {
+				Some(value) => Ok(Some(bcs::from_bytes(&value)?)),
+				None => Ok(None),
+			}
+		})
+		.await?
+	}
+
+	async fn add_in_flight_block(&self, block: Block) -> Result<(), Error> {
+		let db = self.db.clone();
+		let size = self.size.clone();
+		tokio::task::spawn_blocking(move || {
+			let _size = size.lock().map_err(|_| Error::msg("Mempool size lock poisoned"))?;
+			Self::internal_add_in_flight_block(&db, &block)
+		})
+		.await?
+	}
+
+	async fn confirm_in_flight_blocks(&self, block_ids: Vec<block::Id>) -> Result<(), Error> {
+		let db = self.db.clone();
+		let size = self.size.clone();
+		tokio::task::spawn_blocking(move || {
+			let _size = size.lock().map_err(|_| Error::msg("Mempool size lock poisoned"))?;
+			let cf_handle = db
+				.cf_handle(cf::IN_FLIGHT_BLOCKS)
+				.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+			let block_ids = HashSet::<block::Id>::from_iter(block_ids);
+			let mut batch = WriteBatch::default();
+			for (key, block) in Self::internal_get_in_flight_blocks(&db)? {
+				if block_ids.contains(&block.id()) {
+					batch.delete_cf(&cf_handle, key);
+				}
+			}
+			db.write(batch)?;
+			Ok(())
+		})
+		.await?
+	}
+
+	async fn get_in_flight_blocks(&self) -> Result<Vec<Block>, Error> {
 		let db = self.db.clone();
 		tokio::task::spawn_blocking(move || {
-			let cf_handle =
-				db.cf_handle(cf::BLOCKS).ok_or_else(|| Error::msg("CF handle not found"))?;
-			db.delete_cf(&cf_handle, block_id.to_vec())?;
-			Ok(())
+			Ok(Self::internal_get_in_flight_blocks(&db)?
+				.into_iter()
+				.map(|(_, block)| block)
+				.collect())
 		})
 		.await?
 	}
 
-	async fn get_block(&self, block_id: block::Id) -> Result<Option<Block>, Error> {
+	async fn split_in_flight_block(
+		&self,
+		block_id: block::Id,
+		blocks: Vec<Block>,
+	) -> Result<(), Error> {
 		let db = self.db.clone();
+		let size = self.size.clone();
 		tokio::task::spawn_blocking(move || {
-			let cf_handle =
-				db.cf_handle(cf::BLOCKS).ok_or_else(|| Error::msg("CF handle not found"))?;
-			let serialized_block = db.get_cf(&cf_handle, block_id.to_vec())?;
-			match serialized_block {
-				Some(serialized_block) => {
-					let block: Block = bcs::from_bytes(&serialized_block)?;
-					Ok(Some(block))
-				}
-				None => Ok(None),
-			}
+			let _size = size.lock().map_err(|_| Error::msg("Mempool size lock poisoned"))?;
+			Self::internal_split_in_flight_block(&db, block_id, &blocks)
 		})
 		.await?
 	}
@@ -371,7 +1447,8 @@ impl MempoolBlockOperations for RocksdbMempool {
 pub mod test {
 
 	use super::*;
-	use movement_types::transaction::Transaction;
+	use movement_types::{block::BlockMetadata, transaction::Transaction};
+	use std::collections::BTreeSet;
 	use tempfile::tempdir;
 
 	#[tokio::test]
@@ -484,4 +1561,733 @@ pub mod test {
 
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn test_priority_based_ordering() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new_with_ordering_policy(path, OrderingPolicy::Priority)?;
+
+		let transaction1 = MempoolTransaction::at_time(
+			Transaction::new(vec![1], 0).with_application_priority(100),
+			2,
+		);
+		let transaction2 = MempoolTransaction::at_time(
+			Transaction::new(vec![2], 0).with_application_priority(1),
+			2,
+		);
+		// high priority does not jump ahead of earlier slots
+		let transaction3 = MempoolTransaction::at_time(
+			Transaction::new(vec![3], 0).with_application_priority(1000),
+			64,
+		);
+
+		mempool.add_mempool_transaction(transaction3.clone()).await?;
+		mempool.add_mempool_transaction(transaction2.clone()).await?;
+		mempool.add_mempool_transaction(transaction1.clone()).await?;
+
+		let transactions = mempool.pop_mempool_transactions(3).await?;
+		assert_eq!(transactions, vec![transaction1, transaction2, transaction3]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_priority_preserves_sender_sequence_order() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new_with_ordering_policy(path, OrderingPolicy::Priority)?;
+
+		// the sender's later transaction pays more, but must not be popped first
+		let transaction1 = MempoolTransaction::at_time(
+			Transaction::new(vec![1], 0).with_application_priority(1).with_sender(vec![1]),
+			2,
+		);
+		let transaction2 = MempoolTransaction::at_time(
+			Transaction::new(vec![2], 1).with_application_priority(100).with_sender(vec![1]),
+			2,
+		);
+		let transaction3 = MempoolTransaction::at_time(
+			Transaction::new(vec![3], 0).with_application_priority(50).with_sender(vec![2]),
+			2,
+		);
+
+		mempool.add_mempool_transaction(transaction1.clone()).await?;
+		mempool.add_mempool_transaction(transaction2.clone()).await?;
+		mempool.add_mempool_transaction(transaction3.clone()).await?;
+
+		assert_eq!(mempool.pop_mempool_transaction().await?, Some(transaction1));
+		let transactions = mempool.pop_mempool_transactions(3).await?;
+		assert_eq!(transactions, vec![transaction2, transaction3]);
+		assert_eq!(mempool.pop_mempool_transaction().await?, None);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_sender_fairness() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new_with_ordering_policy(
+			path,
+			OrderingPolicy::SenderFairness { max_per_sender: 2 },
+		)?;
+
+		// a spamming sender with higher priority than everyone else
+		let mut spam = Vec::new();
+		for i in 0..5 {
+			let transaction = MempoolTransaction::at_time(
+				Transaction::new(vec![0, i], i as u64)
+					.with_application_priority(100)
+					.with_sender(vec![0]),
+				2,
+			);
+			mempool.add_mempool_transaction(transaction.clone()).await?;
+			spam.push(transaction);
+		}
+		let transaction = MempoolTransaction::at_time(
+			Transaction::new(vec![1], 0).with_application_priority(1).with_sender(vec![1]),
+			2,
+		);
+		mempool.add_mempool_transaction(transaction.clone()).await?;
+
+		let transactions = mempool.pop_mempool_transactions(4).await?;
+		assert_eq!(transactions, vec![spam[0].clone(), spam[1].clone(), transaction]);
+
+		// the rest of the spam remains, in order
+		let transactions = mempool.pop_mempool_transactions(4).await?;
+		assert_eq!(transactions, vec![spam[2].clone(), spam[3].clone()]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pop_sender_transactions_in_reverse_priority() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new_with_ordering_policy(path, OrderingPolicy::Priority)?;
+
+		// each later transaction pays more, so every pop pulls in the transactions preceding it
+		let mut transactions = Vec::new();
+		for i in 0..10 {
+			let transaction = MempoolTransaction::at_time(
+				Transaction::new(vec![i], i as u64)
+					.with_application_priority(i as u64)
+					.with_sender(vec![0]),
+				2,
+			);
+			mempool.add_mempool_transaction(transaction.clone()).await?;
+			transactions.push(transaction);
+		}
+
+		assert_eq!(mempool.pop_mempool_transactions(4).await?, transactions[..4].to_vec());
+		assert_eq!(mempool.pop_mempool_transactions(10).await?, transactions[4..].to_vec());
+		assert_eq!(mempool.pop_mempool_transaction().await?, None);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_key_layout_cannot_change_with_transactions() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+
+		let transaction = MempoolTransaction::at_time(Transaction::new(vec![1], 0), 2);
+		{
+			let mempool = RocksdbMempool::try_new(path)?;
+			mempool.add_mempool_transaction(transaction.clone()).await?;
+		}
+		// sender fairness shares the layout of the priority policy
+		assert!(
+			RocksdbMempool::try_new_with_ordering_policy(path, OrderingPolicy::Priority).is_err()
+		);
+		assert!(RocksdbMempool::try_new_with_ordering_policy(
+			path,
+			OrderingPolicy::SenderFairness { max_per_sender: 2 }
+		)
+		.is_err());
+
+		{
+			let mempool = RocksdbMempool::try_new(path)?;
+			assert_eq!(mempool.pop_mempool_transaction().await?, Some(transaction.clone()));
+			mempool
+				.add_in_flight_block(Block::new(
+					BlockMetadata::new(1, 1_000),
+					block::Id::default(),
+					BTreeSet::from([transaction.transaction.clone()]),
+				))
+				.await?;
+		}
+		// once the mempool is empty, the policy can change
+		let mempool = RocksdbMempool::try_new_with_ordering_policy(path, OrderingPolicy::Priority)?;
+		assert_eq!(mempool.ordering_policy(), OrderingPolicy::Priority);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_open_mempool_in_legacy_layout() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+
+		let transaction1 = MempoolTransaction::at_time(Transaction::new(vec![1], 0), 2);
+		let transaction2 = MempoolTransaction::at_time(Transaction::new(vec![2], 0), 64);
+		let block = legacy::Block::from(Block::new(
+			BlockMetadata::default(),
+			block::Id::default(),
+			BTreeSet::from([transaction1.transaction.clone()]),
+		));
+		{
+			// the column families and records of a mempool written before transactions had a priority,
+			// a sender and a gas estimate, and before the key layout was recorded
+			let mut options = Options::default();
+			options.create_if_missing(true);
+			options.create_missing_column_families(true);
+			let db = DB::open_cf_descriptors(
+				&options,
+				path,
+				[cf::MEMPOOL_TRANSACTIONS, cf::BLOCKS, cf::TRANSACTION_LOOKUPS]
+					.map(|cf_name| ColumnFamilyDescriptor::new(cf_name, Options::default())),
+			)?;
+			let mempool_transactions_cf_handle = db.cf_handle(cf::MEMPOOL_TRANSACTIONS).unwrap();
+			let transaction_lookups_cf_handle = db.cf_handle(cf::TRANSACTION_LOOKUPS).unwrap();
+			let blocks_cf_handle = db.cf_handle(cf::BLOCKS).unwrap();
+			for transaction in [&transaction1, &transaction2] {
+				let key = RocksdbMempool::construct_mempool_transaction_key(
+					transaction,
+					OrderingPolicy::Age,
+				);
+				let legacy_transaction = (
+					legacy::Transaction::from(transaction.transaction.clone()),
+					transaction.timestamp,
+					transaction.slot_seconds,
+				);
+				db.put_cf(
+					&mempool_transactions_cf_handle,
+					&key,
+					bcs::to_bytes(&legacy_transaction)?,
+				)?;
+				db.put_cf(&transaction_lookups_cf_handle, transaction.id().to_vec(), &key)?;
+			}
+			db.put_cf(&blocks_cf_handle, block.id().to_vec(), bcs::to_bytes(&block)?)?;
+		}
+
+		// the transactions are stored under the age layout
+		assert!(
+			RocksdbMempool::try_new_with_ordering_policy(path, OrderingPolicy::Priority).is_err()
+		);
+
+		let mempool = RocksdbMempool::try_new(path)?;
+		assert_eq!(mempool.size()?, 2);
+		assert!(mempool.has_mempool_transaction(transaction1.id()).await?);
+		assert_eq!(mempool.get_block(block.id()).await?.map(|block| block.id()), Some(block.id()));
+		assert_eq!(mempool.pop_mempool_transactions(2).await?, vec![transaction1, transaction2]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_remove_from_sender_index() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new_with_ordering_policy(path, OrderingPolicy::Priority)?;
+
+		let transaction1 = MempoolTransaction::at_time(
+			Transaction::new(vec![1], 0).with_application_priority(1).with_sender(vec![1]),
+			2,
+		);
+		let transaction2 = MempoolTransaction::at_time(
+			Transaction::new(vec![2], 1).with_application_priority(100).with_sender(vec![1]),
+			2,
+		);
+		mempool.add_mempool_transaction(transaction1.clone()).await?;
+		mempool.add_mempool_transaction(transaction2.clone()).await?;
+
+		// once the earlier transaction is removed, the later one no longer waits for it
+		mempool.remove_mempool_transaction(transaction1.id()).await?;
+		assert_eq!(mempool.pop_mempool_transactions(2).await?, vec![transaction2]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_configured_slot_seconds() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new(path)?.with_slot_seconds(60);
+
+		mempool.add_transaction(Transaction::test()).await?;
+		let transaction = mempool.pop_mempool_transaction().await?.unwrap();
+		assert_eq!(transaction.slot_seconds, 60);
+		assert_eq!(transaction.timestamp % 60, 0);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_transaction_ttl_expiry() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new(path)?
+			.with_limits(MempoolLimits { transaction_ttl_seconds: Some(10), ..Default::default() });
+
+		let now = RocksdbMempool::now();
+		let transaction = MempoolTransaction::at_time(Transaction::new(vec![1], 0), now);
+		mempool.add_mempool_transaction(transaction.clone()).await?;
+		assert!(mempool.expire_mempool_transactions(now).await?.is_empty());
+		assert_eq!(mempool.get_drop_reason(transaction.id()).await?, None);
+
+		let expired = mempool.expire_mempool_transactions(transaction.timestamp + 10).await?;
+		assert_eq!(expired, vec![transaction.id()]);
+		assert!(!mempool.has_mempool_transaction(transaction.id()).await?);
+		assert_eq!(mempool.get_drop_reason(transaction.id()).await?, Some(DropReason::Expired));
+		assert_eq!(mempool.size()?, 0);
+
+		// a transaction which has already expired is not admitted
+		let stale = MempoolTransaction::at_time(Transaction::new(vec![2], 0), 2);
+		mempool.add_mempool_transaction(stale.clone()).await?;
+		assert!(!mempool.has_mempool_transaction(stale.id()).await?);
+		assert_eq!(mempool.get_drop_reason(stale.id()).await?, Some(DropReason::Expired));
+
+		// drop records are forgotten after the retention period
+		mempool
+			.expire_mempool_transactions(transaction.timestamp + 10 + DROP_RECORD_RETENTION_SECONDS)
+			.await?;
+		assert_eq!(mempool.get_drop_reason(transaction.id()).await?, None);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_reject_new_when_full() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let limits = MempoolLimits { max_transactions: Some(2), ..Default::default() };
+		let mempool = RocksdbMempool::try_new(path)?.with_limits(limits);
+
+		let transaction1 = MempoolTransaction::at_time(Transaction::new(vec![1], 0), 2);
+		let transaction2 = MempoolTransaction::at_time(Transaction::new(vec![2], 0), 64);
+		let transaction3 = MempoolTransaction::at_time(Transaction::new(vec![3], 0), 128);
+		let admissions = mempool
+			.add_mempool_transactions(vec![
+				transaction1.clone(),
+				transaction2.clone(),
+				transaction3.clone(),
+			])
+			.await?;
+		assert_eq!(
+			admissions,
+			vec![
+				Admission::Admitted,
+				Admission::Admitted,
+				Admission::Rejected(DropReason::MempoolFull)
+			]
+		);
+
+		assert!(!mempool.has_mempool_transaction(transaction3.id()).await?);
+		assert_eq!(
+			mempool.get_drop_reason(transaction3.id()).await?,
+			Some(DropReason::MempoolFull)
+		);
+		assert_eq!(mempool.size()?, 2);
+
+		// the size is recovered when the mempool is reopened
+		drop(mempool);
+		let mempool = RocksdbMempool::try_new(path)?.with_limits(limits);
+		assert_eq!(mempool.size()?, 2);
+
+		// once there is room, a dropped transaction can be added again
+		mempool.pop_mempool_transaction().await?;
+		assert_eq!(
+			mempool.add_mempool_transaction(transaction3.clone()).await?,
+			Admission::Admitted
+		);
+		assert!(mempool.has_mempool_transaction(transaction3.id()).await?);
+		assert_eq!(mempool.get_drop_reason(transaction3.id()).await?, None);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_evict_oldest_when_full() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new(path)?.with_limits(MempoolLimits {
+			max_transactions: Some(2),
+			eviction_policy: EvictionPolicy::EvictOldest,
+			..Default::default()
+		});
+
+		let transaction1 = MempoolTransaction::at_time(Transaction::new(vec![1], 0), 2);
+		let transaction2 = MempoolTransaction::at_time(Transaction::new(vec![2], 0), 64);
+		let transaction3 = MempoolTransaction::at_time(Transaction::new(vec![3], 0), 128);
+		mempool.add_mempool_transaction(transaction2.clone()).await?;
+		mempool.add_mempool_transaction(transaction1.clone()).await?;
+		mempool.add_mempool_transaction(transaction3.clone()).await?;
+
+		assert_eq!(mempool.get_drop_reason(transaction1.id()).await?, Some(DropReason::Evicted));
+		assert_eq!(mempool.size()?, 2);
+		let transactions = mempool.pop_mempool_transactions(3).await?;
+		assert_eq!(transactions, vec![transaction2, transaction3]);
+		assert_eq!(mempool.size()?, 0);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_evict_lowest_priority_in_oldest_slot() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new_with_ordering_policy(path, OrderingPolicy::Priority)?
+			.with_limits(MempoolLimits {
+				max_transactions: Some(3),
+				eviction_policy: EvictionPolicy::EvictOldest,
+				..Default::default()
+			});
+
+		let high = MempoolTransaction::at_time(
+			Transaction::new(vec![1], 0).with_application_priority(10),
+			2,
+		);
+		let low = MempoolTransaction::at_time(
+			Transaction::new(vec![2], 0).with_application_priority(1),
+			2,
+		);
+		let newer = MempoolTransaction::at_time(Transaction::new(vec![3], 0), 64);
+		let newest = MempoolTransaction::at_time(Transaction::new(vec![4], 0), 128);
+		mempool
+			.add_mempool_transactions(vec![high.clone(), low.clone(), newer.clone()])
+			.await?;
+		mempool.add_mempool_transaction(newest.clone()).await?;
+
+		// the oldest slot is evicted from, but its highest priority transaction is kept
+		assert_eq!(mempool.get_drop_reason(low.id()).await?, Some(DropReason::Evicted));
+		let transactions = mempool.pop_mempool_transactions(3).await?;
+		assert_eq!(transactions, vec![high, newer, newest]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_evict_keeps_sender_sequence_order() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new_with_ordering_policy(path, OrderingPolicy::Priority)?
+			.with_limits(MempoolLimits {
+				max_transactions: Some(3),
+				eviction_policy: EvictionPolicy::EvictOldest,
+				..Default::default()
+			});
+
+		// the sender's first transaction has the lowest priority in the oldest slot,
+		// but evicting it would leave a gap before its second transaction
+		let first = MempoolTransaction::at_time(
+			Transaction::new(vec![1], 0).with_sender(vec![1]).with_application_priority(1),
+			2,
+		);
+		let second = MempoolTransaction::at_time(
+			Transaction::new(vec![2], 1).with_sender(vec![1]).with_application_priority(5),
+			64,
+		);
+		let other = MempoolTransaction::at_time(
+			Transaction::new(vec![3], 0).with_application_priority(10),
+			2,
+		);
+		let newest = MempoolTransaction::at_time(Transaction::new(vec![4], 0), 128);
+		mempool
+			.add_mempool_transactions(vec![first.clone(), second.clone(), other.clone()])
+			.await?;
+		mempool.add_mempool_transaction(newest.clone()).await?;
+
+		assert_eq!(mempool.get_drop_reason(other.id()).await?, Some(DropReason::Evicted));
+		let transactions = mempool.pop_mempool_transactions(3).await?;
+		assert_eq!(transactions, vec![first, second, newest]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_evict_within_a_single_add() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new(path)?.with_limits(MempoolLimits {
+			max_transactions: Some(2),
+			eviction_policy: EvictionPolicy::EvictOldest,
+			..Default::default()
+		});
+
+		// the transactions added by the same call are written together, so they evict each other
+		let transaction1 = MempoolTransaction::at_time(Transaction::new(vec![1], 0), 2);
+		let transaction2 = MempoolTransaction::at_time(Transaction::new(vec![2], 0), 64);
+		let transaction3 = MempoolTransaction::at_time(Transaction::new(vec![3], 0), 128);
+		let admissions = mempool
+			.add_mempool_transactions(vec![
+				transaction1.clone(),
+				transaction2.clone(),
+				transaction3.clone(),
+				transaction3.clone(),
+			])
+			.await?;
+		assert_eq!(
+			admissions,
+			vec![
+				Admission::Rejected(DropReason::Evicted),
+				Admission::Admitted,
+				Admission::Admitted,
+				Admission::Admitted
+			]
+		);
+		assert_eq!(mempool.size()?, 2);
+		assert_eq!(mempool.get_drop_reason(transaction1.id()).await?, Some(DropReason::Evicted));
+
+		// the size matches what was written
+		drop(mempool);
+		let mempool = RocksdbMempool::try_new(path)?;
+		assert_eq!(mempool.size()?, 2);
+		let transactions = mempool.pop_mempool_transactions(3).await?;
+		assert_eq!(transactions, vec![transaction2, transaction3]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_return_popped_transactions_when_full() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new(path)?.with_limits(MempoolLimits {
+			max_transactions: Some(2),
+			eviction_policy: EvictionPolicy::EvictOldest,
+			..Default::default()
+		});
+
+		let transaction1 = MempoolTransaction::at_time(Transaction::new(vec![1], 0), 2);
+		let transaction2 = MempoolTransaction::at_time(Transaction::new(vec![2], 0), 64);
+		let transaction3 = MempoolTransaction::at_time(Transaction::new(vec![3], 0), 128);
+		mempool
+			.add_mempool_transactions(vec![transaction1.clone(), transaction2.clone()])
+			.await?;
+		let popped = mempool.pop_mempool_transactions(1).await?;
+		assert_eq!(popped, vec![transaction1.clone()]);
+		mempool.add_mempool_transaction(transaction3.clone()).await?;
+
+		// the returned transaction goes over the limit rather than evicting or being dropped
+		mempool.return_mempool_transactions(popped).await?;
+		assert_eq!(mempool.size()?, 3);
+		for transaction in [&transaction1, &transaction2, &transaction3] {
+			assert_eq!(mempool.get_drop_reason(transaction.id()).await?, None);
+		}
+
+		// it keeps its place and is not restored again on a restart
+		drop(mempool);
+		let mempool = RocksdbMempool::try_new(path)?;
+		assert_eq!(mempool.size()?, 3);
+		let transactions = mempool.pop_mempool_transactions(3).await?;
+		assert_eq!(transactions, vec![transaction1, transaction2, transaction3]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_max_transactions_per_sender() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new(path)?.with_limits(MempoolLimits {
+			max_transactions_per_sender: Some(1),
+			..Default::default()
+		});
+
+		let transaction1 =
+			MempoolTransaction::at_time(Transaction::new(vec![1], 0).with_sender(vec![1]), 2);
+		let transaction2 =
+			MempoolTransaction::at_time(Transaction::new(vec![2], 1).with_sender(vec![1]), 2);
+		let transaction3 =
+			MempoolTransaction::at_time(Transaction::new(vec![3], 0).with_sender(vec![2]), 2);
+		// senders which are not known are not limited
+		let transaction4 = MempoolTransaction::at_time(Transaction::new(vec![4], 0), 2);
+		let transaction5 = MempoolTransaction::at_time(Transaction::new(vec![5], 0), 2);
+		let admissions = mempool
+			.add_mempool_transactions(vec![
+				transaction1.clone(),
+				transaction2.clone(),
+				transaction3.clone(),
+				transaction4.clone(),
+				transaction5.clone(),
+			])
+			.await?;
+		assert_eq!(admissions[1], Admission::Rejected(DropReason::SenderLimitReached));
+
+		assert_eq!(
+			mempool.get_drop_reason(transaction2.id()).await?,
+			Some(DropReason::SenderLimitReached)
+		);
+		assert_eq!(mempool.size()?, 4);
+		assert!(!mempool.has_mempool_transaction(transaction2.id()).await?);
+		for transaction in [&transaction1, &transaction3, &transaction4, &transaction5] {
+			assert!(mempool.has_mempool_transaction(transaction.id()).await?);
+		}
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_sequencer_state_survives_restart() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+
+		let transaction1 = MempoolTransaction::at_time(Transaction::new(vec![1], 0), 2);
+		let transaction2 = MempoolTransaction::at_time(Transaction::new(vec![2], 0), 64);
+		let block = Block::new(
+			BlockMetadata::new(1, 1_000),
+			block::Id::default(),
+			BTreeSet::from([transaction1.transaction.clone()]),
+		);
+		{
+			let mempool = RocksdbMempool::try_new(path)?;
+			assert_eq!(mempool.get_sequencer_head().await?, None);
+
+			mempool.add_mempool_transaction(transaction1.clone()).await?;
+			mempool.add_mempool_transaction(transaction2.clone()).await?;
+			mempool.pop_mempool_transactions(2).await?;
+
+			// only the first transaction makes it into a block before the restart
+			mempool.add_in_flight_block(block.clone()).await?;
+		}
+
+		let mempool = RocksdbMempool::try_new(path)?;
+		assert_eq!(mempool.get_sequencer_head().await?, Some(SequencerHead::from_block(&block)));
+		assert_eq!(mempool.get_in_flight_blocks().await?, vec![block.clone()]);
+		assert!(!mempool.has_mempool_transaction(transaction1.id()).await?);
+		assert_eq!(mempool.pop_mempool_transactions(2).await?, vec![transaction2]);
+
+		mempool.confirm_in_flight_blocks(vec![block.id()]).await?;
+		assert!(mempool.get_in_flight_blocks().await?.is_empty());
+		assert_eq!(mempool.get_sequencer_head().await?, Some(SequencerHead::from_block(&block)));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_in_flight_blocks_are_ordered() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new(path)?;
+
+		let mut blocks = Vec::new();
+		let mut parent = block::Id::default();
+		for i in 0..3 {
+			let block = Block::new(
+				Default::default(),
+				parent,
+				BTreeSet::from([Transaction::new(vec![i], 0)]),
+			);
+			parent = block.id();
+			mempool.add_in_flight_block(block.clone()).await?;
+			blocks.push(block);
+		}
+		assert_eq!(mempool.get_in_flight_blocks().await?, blocks);
+
+		mempool.confirm_in_flight_blocks(vec![blocks[1].id()]).await?;
+		assert_eq!(
+			mempool.get_in_flight_blocks().await?,
+			vec![blocks[0].clone(), blocks[2].clone()]
+		);
+		assert_eq!(
+			mempool.get_sequencer_head().await?,
+			Some(SequencerHead::from_block(&blocks[2]))
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_split_in_flight_block() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+
+		let transactions: BTreeSet<_> = (0..4).map(|i| Transaction::new(vec![i], 0)).collect();
+		let block = Block::new(BlockMetadata::new(1, 1_000), block::Id::default(), transactions);
+		let split_blocks: Vec<_> = block
+			.transactions()
+			.enumerate()
+			.map(|(i, transaction)| {
+				Block::new(
+					BlockMetadata::new(1 + i as u64, 1_000 + i as u64),
+					block::Id::default(),
+					BTreeSet::from([transaction.clone()]),
+				)
+			})
+			.collect();
+		let next_block = Block::new(
+			SequencerHead::from_block(&block).next_block_metadata(0),
+			block.id(),
+			BTreeSet::from([Transaction::new(vec![9], 0)]),
+		);
+		{
+			let mempool = RocksdbMempool::try_new(path)?;
+			mempool.add_in_flight_block(block.clone()).await?;
+			mempool.split_in_flight_block(block.id(), split_blocks[..2].to_vec()).await?;
+			assert_eq!(
+				mempool.get_sequencer_head().await?,
+				Some(SequencerHead::from_block(&split_blocks[1]))
+			);
+			mempool.add_in_flight_block(next_block.clone()).await?;
+
+			// a block which was split can be split again
+			mempool
+				.split_in_flight_block(split_blocks[1].id(), split_blocks[1..].to_vec())
+				.await?;
+		}
+
+		// the blocks keep the place of the block they were split from across restarts
+		let mempool = RocksdbMempool::try_new(path)?;
+		let mut in_flight_blocks = split_blocks.clone();
+		in_flight_blocks.push(next_block.clone());
+		assert_eq!(mempool.get_in_flight_blocks().await?, in_flight_blocks);
+		assert_eq!(
+			mempool.get_sequencer_head().await?,
+			Some(SequencerHead::from_block(&next_block))
+		);
+
+		// blocks built after the split follow the last block
+		let last_block = Block::new(Default::default(), next_block.id(), BTreeSet::new());
+		mempool.add_in_flight_block(last_block.clone()).await?;
+		in_flight_blocks.push(last_block);
+		assert_eq!(mempool.get_in_flight_blocks().await?, in_flight_blocks);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_oversized_transactions_are_rejected() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new(path)?.with_limits(MempoolLimits {
+			max_transaction_bytes: Some(4),
+			max_transaction_gas: Some(1_000),
+			..Default::default()
+		});
+
+		let transaction1 = MempoolTransaction::at_time(Transaction::new(vec![1; 4], 0), 2);
+		let transaction2 = MempoolTransaction::at_time(Transaction::new(vec![2; 5], 0), 2);
+		let transaction3 =
+			MempoolTransaction::at_time(Transaction::new(vec![3], 0).with_gas_estimate(1_001), 2);
+		mempool
+			.add_mempool_transactions(vec![
+				transaction1.clone(),
+				transaction2.clone(),
+				transaction3.clone(),
+			])
+			.await?;
+
+		assert!(mempool.has_mempool_transaction(transaction1.id()).await?);
+		for transaction in [&transaction2, &transaction3] {
+			assert!(!mempool.has_mempool_transaction(transaction.id()).await?);
+			assert_eq!(
+				mempool.get_drop_reason(transaction.id()).await?,
+				Some(DropReason::Oversized)
+			);
+		}
+
+		Ok(())
+	}
 }
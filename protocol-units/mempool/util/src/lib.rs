@@ -1,18 +1,27 @@
 use serde::{Deserialize, Serialize};
 
 use movement_types::{
-	block::{self, Block},
+	block::{self, Block, BlockMetadata},
 	transaction::{self, Transaction},
 };
 use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
 
 pub trait MempoolTransactionOperations {
 	// todo: move mempool_transaction methods into separate trait
 
+	/// The length of the slots that transactions are grouped into by the time they were added.
+	fn slot_seconds(&self) -> u64 {
+		MempoolTransaction::DEFAULT_SLOT_SECONDS
+	}
+
+	/// Adds mempool transactions to the mempool, enforcing the mempool's limits.
+	/// Returns whether each transaction was admitted, in the order they were given.
 	async fn add_mempool_transactions(
 		&self,
 		transactions: Vec<MempoolTransaction>,
-	) -> Result<(), anyhow::Error>;
+	) -> Result<Vec<Admission>, anyhow::Error>;
 
 	/// Checks whether a mempool transaction exists in the mempool.
 	async fn has_mempool_transaction(
@@ -24,7 +33,7 @@ pub trait MempoolTransactionOperations {
 	async fn add_mempool_transaction(
 		&self,
 		transaction: MempoolTransaction,
-	) -> Result<(), anyhow::Error>;
+	) -> Result<Admission, anyhow::Error>;
 
 	/// Removes a mempool transaction from the mempool.
 	async fn remove_mempool_transaction(
@@ -32,9 +41,31 @@ pub trait MempoolTransactionOperations {
 		transaction_id: transaction::Id,
 	) -> Result<(), anyhow::Error>;
 
+	/// Returns popped mempool transactions which were not built into a block to the mempool.
+	///
+	/// The transactions keep their place in the mempool and are not subject to the mempool's limits,
+	/// since they were admitted when they were first added.
+	async fn return_mempool_transactions(
+		&self,
+		transactions: Vec<MempoolTransaction>,
+	) -> Result<(), anyhow::Error>;
+
 	/// Pops mempool transaction from the mempool.
 	async fn pop_mempool_transaction(&self) -> Result<Option<MempoolTransaction>, anyhow::Error>;
 
+	/// Drops the mempool transactions which have outlived the mempool's time-to-live as of the given time in seconds.
+	/// Returns the ids of the dropped transactions.
+	async fn expire_mempool_transactions(
+		&self,
+		now: u64,
+	) -> Result<Vec<transaction::Id>, anyhow::Error>;
+
+	/// Gets the reason a transaction was dropped from the mempool, if it was dropped recently.
+	async fn get_drop_reason(
+		&self,
+		transaction_id: transaction::Id,
+	) -> Result<Option<DropReason>, anyhow::Error>;
+
 	/// Gets a mempool transaction from the mempool.
 	async fn get_mempool_transaction(
 		&self,
@@ -65,19 +96,29 @@ pub trait MempoolTransactionOperations {
 		self.has_mempool_transaction(transaction_id).await
 	}
 
-	async fn add_transactions(&self, transactions: Vec<Transaction>) -> Result<(), anyhow::Error> {
-		let mempool_transactions =
-			transactions.into_iter().map(MempoolTransaction::slot_now).collect();
+	/// Adds transactions to the mempool, returning whether each was admitted.
+	async fn add_transactions(
+		&self,
+		transactions: Vec<Transaction>,
+	) -> Result<Vec<Admission>, anyhow::Error> {
+		let slot_seconds = self.slot_seconds();
+		let mempool_transactions = transactions
+			.into_iter()
+			.map(|transaction| {
+				MempoolTransaction::slot_now_with_slot_seconds(transaction, slot_seconds)
+			})
+			.collect();
 		self.add_mempool_transactions(mempool_transactions).await
 	}
 
-	/// Adds a transaction to the mempool.
-	async fn add_transaction(&self, transaction: Transaction) -> Result<(), anyhow::Error> {
+	/// Adds a transaction to the mempool, returning whether it was admitted.
+	async fn add_transaction(&self, transaction: Transaction) -> Result<Admission, anyhow::Error> {
 		if self.has_transaction(transaction.id()).await? {
-			return Ok(());
+			return Ok(Admission::Admitted);
 		}
 
-		let mempool_transaction = MempoolTransaction::slot_now(transaction);
+		let mempool_transaction =
+			MempoolTransaction::slot_now_with_slot_seconds(transaction, self.slot_seconds());
 		self.add_mempool_transaction(mempool_transaction).await
 	}
 
@@ -104,6 +145,15 @@ pub trait MempoolTransactionOperations {
 		Ok(mempool_transaction.map(|mempool_transaction| mempool_transaction.transaction))
 	}
 
+	/// Drops the transactions which have outlived the mempool's time-to-live.
+	async fn expire_transactions(&self) -> Result<Vec<transaction::Id>, anyhow::Error> {
+		let now = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.unwrap()
+			.as_secs();
+		self.expire_mempool_transactions(now).await
+	}
+
 	/// Pops the next n transactions from the mempool.
 	async fn pop_transactions(&self, n: usize) -> Result<Vec<Transaction>, anyhow::Error> {
 		let mempool_transactions = self.pop_mempool_transactions(n).await?;
@@ -128,6 +178,65 @@ pub trait MempoolBlockOperations {
 	async fn get_block(&self, block_id: block::Id) -> Result<Option<Block>, anyhow::Error>;
 }
 
+/// The state a sequencer keeps alongside its mempool so that it can resume after a restart.
+///
+/// A block is in flight from the time it is built until it is confirmed to be written to the DA layer.
+pub trait SequencerStateOperations {
+	/// Gets the last block built by the sequencer.
+	async fn get_sequencer_head(&self) -> Result<Option<SequencerHead>, anyhow::Error>;
+
+	/// Records a newly built block as in flight and as the new sequencer head.
+	async fn add_in_flight_block(&self, block: Block) -> Result<(), anyhow::Error>;
+
+	/// Forgets the in-flight blocks which have been confirmed.
+	async fn confirm_in_flight_blocks(
+		&self,
+		block_ids: Vec<block::Id>,
+	) -> Result<(), anyhow::Error>;
+
+	/// Gets the in-flight blocks in the order they were built.
+	async fn get_in_flight_blocks(&self) -> Result<Vec<Block>, anyhow::Error>;
+
+	/// Replaces an in-flight block with the blocks it was split into, which take its place in the order.
+	/// If the block is the sequencer head, the last of the blocks it was split into becomes the head.
+	async fn split_in_flight_block(
+		&self,
+		block_id: block::Id,
+		blocks: Vec<Block>,
+	) -> Result<(), anyhow::Error>;
+}
+
+/// The last block built by a sequencer, from which the next block follows.
+///
+/// A block reserves a height and a timestamp for each of its transactions,
+/// so that the blocks it may be split into fit before the next block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequencerHead {
+	pub block_id: block::Id,
+	/// The last height reserved by the block.
+	pub height: u64,
+	/// The last timestamp reserved by the block, in microseconds.
+	pub timestamp: u64,
+}
+
+impl SequencerHead {
+	pub fn from_block(block: &Block) -> Self {
+		let reserved = (block.transactions().len() as u64).saturating_sub(1);
+		Self {
+			block_id: block.id(),
+			height: block.metadata().height() + reserved,
+			timestamp: block.metadata().timestamp() + reserved,
+		}
+	}
+
+	/// Gets the metadata of the next block at the given time in microseconds.
+	///
+	/// The timestamp is strictly increasing, even if the clock goes backwards.
+	pub fn next_block_metadata(&self, now: u64) -> BlockMetadata {
+		BlockMetadata::new(self.height + 1, now.max(self.timestamp + 1))
+	}
+}
+
 /// Wraps a transaction with a timestamp for help ordering.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MempoolTransaction {
@@ -158,16 +267,31 @@ impl Ord for MempoolTransaction {
 }
 
 impl MempoolTransaction {
-	const SLOT_SECONDS: u64 = 2;
+	pub const DEFAULT_SLOT_SECONDS: u64 = 2;
 
 	/// Creates a test MempoolTransaction.
 	pub fn test() -> Self {
-		Self { transaction: Transaction::test(), timestamp: 0, slot_seconds: Self::SLOT_SECONDS }
+		Self {
+			transaction: Transaction::test(),
+			timestamp: 0,
+			slot_seconds: Self::DEFAULT_SLOT_SECONDS,
+		}
 	}
 
 	pub fn at_time(transaction: Transaction, timestamp: u64) -> Self {
-		let floor = (timestamp / Self::SLOT_SECONDS) * Self::SLOT_SECONDS;
-		Self { transaction, timestamp: floor, slot_seconds: Self::SLOT_SECONDS }
+		Self::at_time_with_slot_seconds(transaction, timestamp, Self::DEFAULT_SLOT_SECONDS)
+	}
+
+	/// Creates a new MempoolTransaction with the timestamp floored to the nearest slot of the given length.
+	pub fn at_time_with_slot_seconds(
+		transaction: Transaction,
+		timestamp: u64,
+		slot_seconds: u64,
+	) -> Self {
+		// a zero slot length would divide by zero, treat it as one second slots
+		let slot_seconds = slot_seconds.max(1);
+		let floor = (timestamp / slot_seconds) * slot_seconds;
+		Self { transaction, timestamp: floor, slot_seconds }
 	}
 
 	pub fn new(transaction: Transaction, timestamp: u64, slot_seconds: u64) -> Self {
@@ -177,15 +301,316 @@ impl MempoolTransaction {
 	/// Creates a new MempoolTransaction with the current timestamp floored to the nearest slot.
 	/// todo: probably want to move this out to a factory.
 	pub fn slot_now(transaction: Transaction) -> MempoolTransaction {
+		Self::slot_now_with_slot_seconds(transaction, Self::DEFAULT_SLOT_SECONDS)
+	}
+
+	/// Creates a new MempoolTransaction with the current timestamp floored to the nearest slot of the given length.
+	pub fn slot_now_with_slot_seconds(
+		transaction: Transaction,
+		slot_seconds: u64,
+	) -> MempoolTransaction {
 		let timestamp = std::time::SystemTime::now()
 			.duration_since(std::time::UNIX_EPOCH)
 			.unwrap()
 			.as_secs();
 
-		Self::at_time(transaction, timestamp)
+		Self::at_time_with_slot_seconds(transaction, timestamp, slot_seconds)
 	}
 
 	pub fn id(&self) -> transaction::Id {
 		self.transaction.id()
 	}
 }
+
+/// How a mempool orders the transactions within a slot.
+///
+/// Regardless of the policy, transactions from the same sender are popped in sequence number order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderingPolicy {
+	/// Transactions are ordered by sequence number, so that older transactions go first.
+	#[default]
+	Age,
+	/// Transactions are ordered by application priority, highest first.
+	Priority,
+	/// Transactions are ordered by application priority, but no more than `max_per_sender`
+	/// transactions from the same sender are popped at once.
+	SenderFairness { max_per_sender: u32 },
+}
+
+impl fmt::Display for OrderingPolicy {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			OrderingPolicy::Age => write!(f, "age"),
+			OrderingPolicy::Priority => write!(f, "priority"),
+			OrderingPolicy::SenderFairness { max_per_sender } => {
+				write!(f, "sender_fairness:{}", max_per_sender)
+			}
+		}
+	}
+}
+
+/// Parses `age`, `priority` or `sender_fairness:<max_per_sender>`.
+impl FromStr for OrderingPolicy {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.split_once(':') {
+			None if s == "age" => Ok(OrderingPolicy::Age),
+			None if s == "priority" => Ok(OrderingPolicy::Priority),
+			Some(("sender_fairness", max_per_sender)) => {
+				let max_per_sender = max_per_sender.parse()?;
+				if max_per_sender == 0 {
+					anyhow::bail!("max_per_sender must be positive");
+				}
+				Ok(OrderingPolicy::SenderFairness { max_per_sender })
+			}
+			_ => Err(anyhow::anyhow!("Invalid ordering policy: {}", s)),
+		}
+	}
+}
+
+/// Why a transaction was dropped from the mempool without being popped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DropReason {
+	/// The transaction outlived the mempool's time-to-live.
+	Expired,
+	/// The transaction was evicted to make room for a newer one.
+	Evicted,
+	/// The transaction was rejected because the mempool was full.
+	MempoolFull,
+	/// The transaction was rejected because its sender has too many transactions in the mempool.
+	SenderLimitReached,
+	/// The transaction was rejected because it is too large to fit in a block.
+	Oversized,
+}
+
+impl fmt::Display for DropReason {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			DropReason::Expired => write!(f, "expired"),
+			DropReason::Evicted => write!(f, "evicted"),
+			DropReason::MempoolFull => write!(f, "mempool full"),
+			DropReason::SenderLimitReached => write!(f, "sender limit reached"),
+			DropReason::Oversized => write!(f, "oversized"),
+		}
+	}
+}
+
+impl DropReason {
+	/// Whether a transaction dropped for this reason may be admitted if it is added again later.
+	pub fn is_transient(&self) -> bool {
+		match self {
+			DropReason::Evicted | DropReason::MempoolFull | DropReason::SenderLimitReached => true,
+			DropReason::Expired | DropReason::Oversized => false,
+		}
+	}
+}
+
+/// Whether a transaction added to the mempool was admitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Admission {
+	/// The transaction is in the mempool, including if it already was.
+	Admitted,
+	/// The transaction was not admitted, for the given reason.
+	Rejected(DropReason),
+}
+
+/// The transactions a mempool did not admit, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedTransactions(pub Vec<(transaction::Id, DropReason)>);
+
+impl RejectedTransactions {
+	/// Collects the rejected transactions from the admissions of the given transactions.
+	pub fn from_admissions(
+		transaction_ids: impl IntoIterator<Item = transaction::Id>,
+		admissions: impl IntoIterator<Item = Admission>,
+	) -> Option<Self> {
+		let rejected: Vec<_> = transaction_ids
+			.into_iter()
+			.zip(admissions)
+			.filter_map(|(transaction_id, admission)| match admission {
+				Admission::Admitted => None,
+				Admission::Rejected(reason) => Some((transaction_id, reason)),
+			})
+			.collect();
+		(!rejected.is_empty()).then_some(Self(rejected))
+	}
+
+	/// Whether all the transactions may be admitted if they are added again later.
+	pub fn is_transient(&self) -> bool {
+		self.0.iter().all(|(_, reason)| reason.is_transient())
+	}
+}
+
+impl fmt::Display for RejectedTransactions {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} transactions were not admitted to the mempool:", self.0.len())?;
+		for (transaction_id, reason) in &self.0 {
+			write!(f, " {} ({})", transaction_id, reason)?;
+		}
+		Ok(())
+	}
+}
+
+impl std::error::Error for RejectedTransactions {}
+
+/// What a full mempool does with new transactions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionPolicy {
+	/// New transactions are rejected.
+	#[default]
+	RejectNew,
+	/// The oldest transactions are evicted to make room for new ones.
+	/// Within a slot, the lowest priority transactions are evicted first. A transaction is not evicted while
+	/// later transactions from its sender remain, so that no gaps are left in their sequence.
+	EvictOldest,
+}
+
+impl FromStr for EvictionPolicy {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"reject_new" => Ok(EvictionPolicy::RejectNew),
+			"evict_oldest" => Ok(EvictionPolicy::EvictOldest),
+			_ => Err(anyhow::anyhow!("Invalid eviction policy: {}", s)),
+		}
+	}
+}
+
+/// The limits a mempool enforces on the transactions it holds. By default, there are none.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolLimits {
+	/// How long a transaction may stay in the mempool, in seconds.
+	pub transaction_ttl_seconds: Option<u64>,
+	/// The maximum number of transactions in the mempool.
+	pub max_transactions: Option<u64>,
+	/// The maximum number of transactions from a single sender in the mempool.
+	pub max_transactions_per_sender: Option<u64>,
+	/// What to do with new transactions when the mempool is full.
+	pub eviction_policy: EvictionPolicy,
+	/// The maximum size of a transaction's data in bytes.
+	pub max_transaction_bytes: Option<u64>,
+	/// The maximum gas estimate of a transaction.
+	pub max_transaction_gas: Option<u64>,
+}
+
+impl MempoolLimits {
+	/// Whether a transaction with the given slot timestamp has outlived the time-to-live as of the given time.
+	pub fn is_expired(&self, timestamp: u64, now: u64) -> bool {
+		match self.transaction_ttl_seconds {
+			Some(ttl) => timestamp.saturating_add(ttl) <= now,
+			None => false,
+		}
+	}
+
+	/// Whether a transaction exceeds the size or gas limits for a single transaction.
+	pub fn is_oversized(&self, transaction: &Transaction) -> bool {
+		self.max_transaction_bytes
+			.is_some_and(|max| transaction.data().len() as u64 > max)
+			|| self.max_transaction_gas.is_some_and(|max| transaction.gas_estimate() > max)
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	#[test]
+	fn test_ordering_policy_from_str() -> Result<(), anyhow::Error> {
+		for policy in [
+			OrderingPolicy::Age,
+			OrderingPolicy::Priority,
+			OrderingPolicy::SenderFairness { max_per_sender: 4 },
+		] {
+			assert_eq!(policy.to_string().parse::<OrderingPolicy>()?, policy);
+		}
+		assert!("sender_fairness:0".parse::<OrderingPolicy>().is_err());
+		assert!("sender_fairness".parse::<OrderingPolicy>().is_err());
+		assert!("fifo".parse::<OrderingPolicy>().is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_rejected_transactions_from_admissions() {
+		let ids: Vec<_> = (0..3).map(|i| Transaction::new(vec![i], 0).id()).collect();
+		let admissions = [
+			Admission::Admitted,
+			Admission::Rejected(DropReason::MempoolFull),
+			Admission::Rejected(DropReason::SenderLimitReached),
+		];
+		let rejected = RejectedTransactions::from_admissions(ids.clone(), admissions)
+			.expect("transactions were rejected");
+		assert_eq!(
+			rejected.0,
+			vec![(ids[1], DropReason::MempoolFull), (ids[2], DropReason::SenderLimitReached)]
+		);
+		assert!(rejected.is_transient());
+
+		let rejected = RejectedTransactions::from_admissions(
+			ids.clone(),
+			[Admission::Rejected(DropReason::Oversized), Admission::Rejected(DropReason::Evicted)],
+		)
+		.expect("transactions were rejected");
+		assert!(!rejected.is_transient());
+
+		assert_eq!(RejectedTransactions::from_admissions(ids, [Admission::Admitted; 3]), None);
+	}
+
+	#[test]
+	fn test_is_oversized() {
+		let limits = MempoolLimits {
+			max_transaction_bytes: Some(2),
+			max_transaction_gas: Some(100),
+			..Default::default()
+		};
+		assert!(!limits.is_oversized(&Transaction::new(vec![1, 2], 0).with_gas_estimate(100)));
+		assert!(limits.is_oversized(&Transaction::new(vec![1, 2, 3], 0)));
+		assert!(limits.is_oversized(&Transaction::new(vec![1], 0).with_gas_estimate(101)));
+		assert!(!MempoolLimits::default().is_oversized(&Transaction::new(vec![0; 1024], 0)));
+	}
+
+	#[test]
+	fn test_next_block_metadata() {
+		let head = SequencerHead { block_id: block::Id::default(), height: 4, timestamp: 1_000 };
+		let metadata = head.next_block_metadata(2_000);
+		assert_eq!((metadata.height(), metadata.timestamp()), (5, 2_000));
+
+		// the clock went backwards
+		let metadata = head.next_block_metadata(500);
+		assert_eq!((metadata.height(), metadata.timestamp()), (5, 1_001));
+	}
+
+	#[test]
+	fn test_head_reserves_a_height_per_transaction() {
+		let transactions = (0..3).map(|i| Transaction::new(vec![i], i as u64)).collect();
+		let block = Block::new(BlockMetadata::new(4, 1_000), block::Id::default(), transactions);
+		let head = SequencerHead::from_block(&block);
+		assert_eq!((head.height, head.timestamp), (6, 1_002));
+
+		let metadata = head.next_block_metadata(1_001);
+		assert_eq!((metadata.height(), metadata.timestamp()), (7, 1_003));
+	}
+
+	#[test]
+	fn test_is_expired() {
+		let limits = MempoolLimits { transaction_ttl_seconds: Some(10), ..Default::default() };
+		assert!(!limits.is_expired(100, 109));
+		assert!(limits.is_expired(100, 110));
+		assert!(!MempoolLimits::default().is_expired(0, u64::MAX));
+	}
+
+	#[test]
+	fn test_at_time_floors_to_slot() {
+		let transaction = MempoolTransaction::at_time_with_slot_seconds(Transaction::test(), 7, 5);
+		assert_eq!(transaction.timestamp, 5);
+		assert_eq!(transaction.slot_seconds, 5);
+
+		let transaction = MempoolTransaction::at_time(Transaction::test(), 7);
+		assert_eq!(transaction.timestamp, 6);
+	}
+}
@@ -1,55 +1,81 @@
 use aptos_api::Context;
+use movement_transaction_status::{TransactionStatusIndex, TransactionStatusUpdate};
 
 use anyhow::Error;
 use futures::prelude::*;
 use poem::listener::TcpListener;
 use poem::{
 	get, handler,
+	http::StatusCode,
 	middleware::Tracing,
-	web::{Data, Path},
+	web::{
+		sse::{Event, SSE},
+		Data, Json, Path,
+	},
 	EndpointExt, IntoResponse, Response, Route, Server,
 };
+use prometheus::{Encoder, TextEncoder};
 use tracing::info;
 
 use std::env;
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// The interval of the keep-alive events sent on transaction status streams.
+const STATUS_STREAM_KEEP_ALIVE: Duration = Duration::from_secs(15);
 
 #[derive(Debug)]
 pub struct MovementRest {
 	/// The URL to bind the REST service to.
 	pub url: String,
 	pub context: Option<Arc<Context>>,
+	/// The status of the transactions submitted to the node.
+	pub transaction_status: TransactionStatusIndex,
 	// More fields to be added here, log verboisty, etc.
 }
 
 impl MovementRest {
 	pub const MOVEMENT_REST_ENV_VAR: &'static str = "MOVEMENT_REST_URL";
+	/// The URL the REST service binds to when none is configured.
+	pub const DEFAULT_URL: &'static str = "http://0.0.0.0:30832";
+
+	pub fn new(url: String) -> Self {
+		Self { url, context: None, transaction_status: TransactionStatusIndex::default() }
+	}
 
 	pub fn try_from_env() -> Result<Self, Error> {
-		let url = env::var(Self::MOVEMENT_REST_ENV_VAR)
-			.unwrap_or_else(|_| "http://0.0.0.0:30832".to_string());
-		Ok(Self { url, context: None })
+		let url =
+			env::var(Self::MOVEMENT_REST_ENV_VAR).unwrap_or_else(|_| Self::DEFAULT_URL.to_string());
+		Ok(Self::new(url))
 	}
 
 	pub fn set_context(&mut self, context: Arc<Context>) {
 		self.context = Some(context);
 	}
 
+	pub fn set_transaction_status_index(&mut self, transaction_status: TransactionStatusIndex) {
+		self.transaction_status = transaction_status;
+	}
+
 	pub fn run_service(&self) -> impl Future<Output = Result<(), Error>> + Send {
 		info!("Starting movement rest service at {}", self.url);
 		let movement_rest = self.create_routes();
-		Server::new(TcpListener::bind(self.url.clone()))
-			.run(movement_rest)
-			.map_err(Into::into)
+		// the listener binds to a socket address, without the scheme of the URL
+		let address = self.url.trim_start_matches("http://").to_string();
+		Server::new(TcpListener::bind(address)).run(movement_rest).map_err(Into::into)
 	}
 
 	pub fn create_routes(&self) -> impl EndpointExt {
 		Route::new()
 			.at("/health", get(health))
+			.at("/metrics", get(metrics))
 			.at("/movement/v1/state-root-hash/:blockheight", get(state_root_hash))
 			.at("movement/v1/richard", get(richard))
+			.at("/movement/v1/transactions/:hash/status", get(transaction_status))
+			.at("/movement/v1/transactions/:hash/status/stream", get(transaction_status_stream))
 			.data(self.context.clone())
+			.data(self.transaction_status.clone())
 			.with(Tracing)
 	}
 }
@@ -59,6 +85,18 @@ pub async fn health() -> Response {
 	"OK".into_response()
 }
 
+/// Gets the metrics of the node in the Prometheus text format.
+#[handler]
+pub async fn metrics() -> Response {
+	let encoder = TextEncoder::new();
+	let mut buffer = Vec::new();
+	match encoder.encode(&prometheus::gather(), &mut buffer) {
+		Ok(()) => Response::builder().content_type(encoder.format_type()).body(buffer),
+		Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to encode metrics: {}", e))
+			.into_response(),
+	}
+}
+
 #[handler]
 pub async fn richard() -> Response {
 	"Well Done".into_response()
@@ -86,6 +124,35 @@ pub async fn state_root_hash(
 	Ok(state_root_hash.to_string().into_response())
 }
 
+/// Gets the current status of a transaction.
+#[handler]
+pub async fn transaction_status(
+	Path(hash): Path<String>,
+	transaction_status: Data<&TransactionStatusIndex>,
+) -> Response {
+	match transaction_status.lookup(&hash).await {
+		Ok(Some(status)) => Json(TransactionStatusUpdate { hash, status }).into_response(),
+		Ok(None) => (StatusCode::NOT_FOUND, "Transaction not found").into_response(),
+		Err(e) => {
+			(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to look up transaction: {}", e))
+				.into_response()
+		}
+	}
+}
+
+/// Streams the status of a transaction as server-sent events, until the status is terminal.
+#[handler]
+pub async fn transaction_status_stream(
+	Path(hash): Path<String>,
+	transaction_status: Data<&TransactionStatusIndex>,
+) -> SSE {
+	let events = transaction_status.watch(&hash).map(move |status| {
+		let update = TransactionStatusUpdate { hash: hash.clone(), status };
+		Event::message(serde_json::to_string(&update).unwrap_or_default()).event_type("status")
+	});
+	SSE::new(events).keep_alive(STATUS_STREAM_KEEP_ALIVE)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -102,4 +169,45 @@ mod tests {
 		let response = client.get("/health").send().await;
 		assert!(response.0.status().is_success());
 	}
+
+	#[tokio::test]
+	async fn test_metrics_endpoint() {
+		let rest_service = MovementRest::try_from_env().expect("Failed to create MovementRest");
+		let client = TestClient::new(rest_service.create_routes());
+
+		let counter = prometheus::register_int_counter!(
+			"movement_rest_test_metrics_endpoint_total",
+			"Counter exposed by the metrics endpoint test"
+		)
+		.expect("Failed to register the test counter");
+		counter.inc();
+
+		let response = client.get("/metrics").send().await;
+		response.assert_status_is_ok();
+		let metrics = response.0.into_body().into_string().await.expect("Failed to read metrics");
+		assert!(metrics.contains("movement_rest_test_metrics_endpoint_total 1"));
+	}
+
+	#[tokio::test]
+	async fn test_transaction_status_endpoint() {
+		let rest_service = MovementRest::try_from_env().expect("Failed to create MovementRest");
+		let client = TestClient::new(rest_service.create_routes());
+
+		let response = client.get("/movement/v1/transactions/0xab/status").send().await;
+		response.assert_status(StatusCode::NOT_FOUND);
+
+		rest_service.transaction_status.set(
+			"ab",
+			movement_transaction_status::TransactionStatus::Executed { block_height: 3 },
+		);
+		let response = client.get("/movement/v1/transactions/0xab/status").send().await;
+		response.assert_status_is_ok();
+		response
+			.assert_json(serde_json::json!({
+				"hash": "0xab",
+				"status": "executed",
+				"block_height": 3,
+			}))
+			.await;
+	}
 }
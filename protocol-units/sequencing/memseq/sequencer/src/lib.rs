@@ -1,32 +1,54 @@
-use mempool_util::{MempoolBlockOperations, MempoolTransactionOperations};
+pub use mempool_util::{Admission, DropReason, RejectedTransactions};
+use mempool_util::{
+	MempoolBlockOperations, MempoolLimits, MempoolTransactionOperations, OrderingPolicy,
+	SequencerHead, SequencerStateOperations,
+};
 pub use move_rocks::RocksdbMempool;
 pub use movement_types::{
 	block::{self, Block},
 	transaction::{self, Transaction},
 };
 pub use sequencing_util::Sequencer;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
 use std::{path::PathBuf, sync::Arc};
 use tokio::sync::RwLock;
 
+pub mod shared;
+pub use shared::SharedMemseq;
+
 #[derive(Clone)]
-pub struct Memseq<T: MempoolBlockOperations + MempoolTransactionOperations> {
+pub struct Memseq<
+	T: MempoolBlockOperations + MempoolTransactionOperations + SequencerStateOperations,
+> {
 	mempool: T,
 	// this value should not be changed after initialization
 	block_size: u32,
-	pub parent_block: Arc<RwLock<block::Id>>,
+	/// The last block built, from which the next block follows.
+	pub head: Arc<RwLock<SequencerHead>>,
 	// this value should not be changed after initialization
 	building_time_ms: u64,
+	// the limits on the total data size and gas estimate of the transactions in a block
+	max_block_bytes: Option<u64>,
+	max_block_gas: Option<u64>,
 }
 
-impl<T: MempoolBlockOperations + MempoolTransactionOperations> Memseq<T> {
+impl<T: MempoolBlockOperations + MempoolTransactionOperations + SequencerStateOperations>
+	Memseq<T>
+{
 	pub fn new(
 		mempool: T,
 		block_size: u32,
-		parent_block: Arc<RwLock<block::Id>>,
+		head: Arc<RwLock<SequencerHead>>,
 		building_time_ms: u64,
 	) -> Self {
-		Self { mempool, block_size, parent_block, building_time_ms }
+		Self {
+			mempool,
+			block_size,
+			head,
+			building_time_ms,
+			max_block_bytes: None,
+			max_block_gas: None,
+		}
 	}
 
 	pub fn with_block_size(mut self, block_size: u32) -> Self {
@@ -39,9 +61,62 @@ impl<T: MempoolBlockOperations + MempoolTransactionOperations> Memseq<T> {
 		self
 	}
 
+	pub fn with_max_block_bytes(mut self, max_block_bytes: Option<u64>) -> Self {
+		self.max_block_bytes = max_block_bytes;
+		self
+	}
+
+	pub fn with_max_block_gas(mut self, max_block_gas: Option<u64>) -> Self {
+		self.max_block_gas = max_block_gas;
+		self
+	}
+
 	pub fn building_time_ms(&self) -> u64 {
 		self.building_time_ms
 	}
+
+	/// Restores the head persisted by the mempool, so that the chain of blocks continues across restarts.
+	pub async fn recover(&self) -> Result<(), anyhow::Error> {
+		if let Some(head) = self.mempool.get_sequencer_head().await? {
+			*self.head.write().await = head;
+		}
+		Ok(())
+	}
+
+	/// Gets the blocks which were built but not yet confirmed, in the order they were built.
+	pub async fn in_flight_blocks(&self) -> Result<Vec<Block>, anyhow::Error> {
+		self.mempool.get_in_flight_blocks().await
+	}
+
+	/// Confirms that the blocks have been written to the DA layer.
+	pub async fn confirm_blocks(&self, block_ids: Vec<block::Id>) -> Result<(), anyhow::Error> {
+		self.mempool.confirm_in_flight_blocks(block_ids).await
+	}
+
+	/// Gets the reason a transaction was dropped from the mempool, if it was dropped recently.
+	pub async fn drop_reason(
+		&self,
+		transaction_id: transaction::Id,
+	) -> Result<Option<DropReason>, anyhow::Error> {
+		self.mempool.get_drop_reason(transaction_id).await
+	}
+
+	/// Replaces a block which was built but not yet confirmed with the blocks it was split into.
+	///
+	/// If no block was built since, the next block follows the last of the blocks.
+	pub async fn split_block(
+		&self,
+		block_id: block::Id,
+		blocks: Vec<Block>,
+	) -> Result<(), anyhow::Error> {
+		// hold the head so that no block is built on the split block in the meantime
+		let mut head = self.head.write().await;
+		self.mempool.split_in_flight_block(block_id, blocks.clone()).await?;
+		if let (true, Some(last)) = (head.block_id == block_id, blocks.last()) {
+			*head = SequencerHead::from_block(last);
+		}
+		Ok(())
+	}
 }
 
 impl Memseq<RocksdbMempool> {
@@ -50,11 +125,36 @@ impl Memseq<RocksdbMempool> {
 		block_size: u32,
 		building_time_ms: u64,
 	) -> Result<Self, anyhow::Error> {
-		let mempool = RocksdbMempool::try_new(
+		Self::try_move_rocks_with_ordering(
+			path,
+			block_size,
+			building_time_ms,
+			OrderingPolicy::default(),
+			mempool_util::MempoolTransaction::DEFAULT_SLOT_SECONDS,
+		)
+	}
+
+	/// Creates a Memseq over a RocksDB mempool which orders transactions according to the given policy and slot length.
+	pub fn try_move_rocks_with_ordering(
+		path: PathBuf,
+		block_size: u32,
+		building_time_ms: u64,
+		ordering_policy: OrderingPolicy,
+		slot_seconds: u64,
+	) -> Result<Self, anyhow::Error> {
+		let mempool = RocksdbMempool::try_new_with_ordering_policy(
 			path.to_str().ok_or(anyhow::anyhow!("PathBuf to str failed"))?,
-		)?;
-		let parent_block = Arc::new(RwLock::new(block::Id::default()));
-		Ok(Self::new(mempool, block_size, parent_block, building_time_ms))
+			ordering_policy,
+		)?
+		.with_slot_seconds(slot_seconds);
+		let head = Arc::new(RwLock::new(SequencerHead::default()));
+		Ok(Self::new(mempool, block_size, head, building_time_ms))
+	}
+
+	/// Sets the limits of the underlying mempool.
+	pub fn with_mempool_limits(mut self, limits: MempoolLimits) -> Self {
+		self.mempool = self.mempool.with_limits(limits);
+		self
 	}
 
 	pub fn try_from_env_toml_file() -> Result<Self, anyhow::Error> {
@@ -62,31 +162,72 @@ impl Memseq<RocksdbMempool> {
 	}
 }
 
-impl<T: MempoolBlockOperations + MempoolTransactionOperations> Sequencer for Memseq<T> {
+impl<T: MempoolBlockOperations + MempoolTransactionOperations + SequencerStateOperations> Sequencer
+	for Memseq<T>
+{
+	/// Fails with [RejectedTransactions] if any of the transactions was not admitted to the mempool.
+	/// The other transactions are published regardless.
 	async fn publish_many(&self, transactions: Vec<Transaction>) -> Result<(), anyhow::Error> {
-		self.mempool.add_transactions(transactions).await?;
-		Ok(())
+		let transaction_ids: Vec<_> = transactions.iter().map(Transaction::id).collect();
+		let admissions = self.mempool.add_transactions(transactions).await?;
+		match RejectedTransactions::from_admissions(transaction_ids, admissions) {
+			Some(rejected) => Err(rejected.into()),
+			None => Ok(()),
+		}
 	}
 
+	/// Fails with [RejectedTransactions] if the transaction was not admitted to the mempool.
 	async fn publish(&self, transaction: Transaction) -> Result<(), anyhow::Error> {
-		self.mempool.add_transaction(transaction).await?;
-		Ok(())
+		let transaction_id = transaction.id();
+		let admission = self.mempool.add_transaction(transaction).await?;
+		match RejectedTransactions::from_admissions([transaction_id], [admission]) {
+			Some(rejected) => Err(rejected.into()),
+			None => Ok(()),
+		}
 	}
 
 	async fn wait_for_next_block(&self) -> Result<Option<Block>, anyhow::Error> {
 		let mut transactions = Vec::with_capacity(self.block_size as usize);
+		let (mut block_bytes, mut block_gas) = (0u64, 0u64);
+		// the transactions which do not fit in this block, and their senders
+		let mut deferred = Vec::new();
+		let mut deferred_senders = HashSet::new();
 
 		let now = std::time::Instant::now();
 
 		loop {
 			let current_block_size = transactions.len() as u32;
-			if current_block_size >= self.block_size {
+			// once a transaction has been deferred, the block is full
+			if current_block_size >= self.block_size || !deferred.is_empty() {
 				break;
 			}
 
 			let remaining = self.block_size - current_block_size;
-			let mut transactions_to_add = self.mempool.pop_transactions(remaining as usize).await?;
-			transactions.append(&mut transactions_to_add);
+			for mempool_transaction in
+				self.mempool.pop_mempool_transactions(remaining as usize).await?
+			{
+				let transaction = &mempool_transaction.transaction;
+				let bytes = transaction.data().len() as u64;
+				let gas = transaction.gas_estimate();
+
+				// a transaction always fits in an empty block, so that it cannot stall the sequencer
+				let fits = transactions.is_empty()
+					|| (self.max_block_bytes.map_or(true, |max| block_bytes + bytes <= max)
+						&& self.max_block_gas.map_or(true, |max| block_gas + gas <= max));
+				// the later transactions of a deferred sender are deferred too, to keep them in order
+				let sender = transaction.sender();
+				if !fits || (!sender.is_empty() && deferred_senders.contains(sender)) {
+					if !sender.is_empty() {
+						deferred_senders.insert(sender.to_vec());
+					}
+					deferred.push(mempool_transaction);
+					continue;
+				}
+
+				block_bytes += bytes;
+				block_gas += gas;
+				transactions.push(mempool_transaction.transaction);
+			}
 
 			// sleep to yield to other tasks and wait for more transactions
 			tokio::task::yield_now().await;
@@ -96,19 +237,33 @@ impl<T: MempoolBlockOperations + MempoolTransactionOperations> Sequencer for Mem
 			}
 		}
 
+		// return the deferred transactions to the mempool for the next block
+		// they were already admitted, so they must not be dropped or evict others on the way back
+		if !deferred.is_empty() {
+			self.mempool.return_mempool_transactions(deferred).await?;
+		}
+
+		// drop the transactions which have outlived the mempool's time-to-live
+		self.mempool.expire_transactions().await?;
+
 		if transactions.is_empty() {
 			Ok(None)
 		} else {
-			let new_block = {
-				let parent_block = self.parent_block.read().await.clone();
-				Block::new(Default::default(), parent_block, BTreeSet::from_iter(transactions))
-			};
-
-			// update the parent block
-			{
-				let mut parent_block = self.parent_block.write().await;
-				*parent_block = new_block.id();
-			}
+			// hold the head until the block is persisted, so that heights are assigned in order
+			let mut head = self.head.write().await;
+			let now = std::time::SystemTime::now()
+				.duration_since(std::time::UNIX_EPOCH)
+				.unwrap()
+				.as_micros() as u64;
+			let new_block = Block::new(
+				head.next_block_metadata(now),
+				head.block_id,
+				BTreeSet::from_iter(transactions),
+			);
+
+			// persist the block before it is handed out, so that it is not lost on a restart
+			self.mempool.add_in_flight_block(new_block.clone()).await?;
+			*head = SequencerHead::from_block(&new_block);
 
 			Ok(Some(new_block))
 		}
@@ -121,7 +276,8 @@ pub mod test {
 	use super::*;
 	use futures::stream::FuturesUnordered;
 	use futures::StreamExt;
-	use mempool_util::MempoolTransaction;
+	use mempool_util::{DropReason, MempoolTransaction};
+	use movement_types::block::BlockMetadata;
 	use tempfile::tempdir;
 
 	#[tokio::test]
@@ -152,8 +308,8 @@ pub mod test {
 	#[tokio::test]
 	async fn test_publish_error_propagation() -> Result<(), anyhow::Error> {
 		let mempool = MockMempool;
-		let parent_block = Arc::new(RwLock::new(block::Id::default()));
-		let memseq = Memseq::new(mempool, 10, parent_block, 1000);
+		let head = Arc::new(RwLock::new(SequencerHead::default()));
+		let memseq = Memseq::new(mempool, 10, head, 1000);
 
 		let transaction = Transaction::new(vec![1, 2, 3], 0);
 		let result = memseq.publish(transaction).await;
@@ -244,13 +400,13 @@ pub mod test {
 		)?;
 		let block_size = 50;
 		let building_time_ms = 2000;
-		let parent_block = Arc::new(RwLock::new(block::Id::default()));
+		let head = Arc::new(RwLock::new(SequencerHead::default()));
 
-		let memseq = Memseq::new(mem_pool, block_size, Arc::clone(&parent_block), building_time_ms);
+		let memseq = Memseq::new(mem_pool, block_size, Arc::clone(&head), building_time_ms);
 
 		assert_eq!(memseq.block_size, block_size);
 		assert_eq!(memseq.building_time_ms, building_time_ms);
-		assert_eq!(*memseq.parent_block.read().await, *parent_block.read().await);
+		assert_eq!(*memseq.head.read().await, *head.read().await);
 
 		Ok(())
 	}
@@ -265,9 +421,9 @@ pub mod test {
 		)?;
 		let block_size = 50;
 		let building_time_ms = 2000;
-		let parent_block = Arc::new(RwLock::new(block::Id::default()));
+		let head = Arc::new(RwLock::new(SequencerHead::default()));
 
-		let memseq = Memseq::new(mem_pool, block_size, Arc::clone(&parent_block), building_time_ms);
+		let memseq = Memseq::new(mem_pool, block_size, Arc::clone(&head), building_time_ms);
 
 		// Test with_block_size
 		let new_block_size = 100;
@@ -296,6 +452,27 @@ pub mod test {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_wait_for_next_block_skips_expired_transactions() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let memseq = Memseq::try_move_rocks(path, 128, 250)?.with_mempool_limits(MempoolLimits {
+			transaction_ttl_seconds: Some(60),
+			..Default::default()
+		});
+
+		let stale = MempoolTransaction::at_time(Transaction::new(vec![1], 0), 2);
+		memseq.mempool.add_mempool_transaction(stale.clone()).await?;
+		let transaction = Transaction::new(vec![2], 0);
+		memseq.publish(transaction.clone()).await?;
+
+		let block = memseq.wait_for_next_block().await?.expect("Block should be built");
+		assert_eq!(block.transactions().cloned().collect::<Vec<_>>(), vec![transaction]);
+		assert_eq!(memseq.mempool.get_drop_reason(stale.id()).await?, Some(DropReason::Expired));
+
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_memseq() -> Result<(), anyhow::Error> {
 		let dir = tempdir()?;
@@ -313,6 +490,202 @@ pub mod test {
 			.next()
 			.ok_or(anyhow::anyhow!("No transactions in block"))?;
 		assert_eq!(transaction_0th, &transaction);
+		assert_eq!(block.metadata().height(), 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_recover_after_restart() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+
+		let block = {
+			let memseq = Memseq::try_move_rocks(path.clone(), 128, 250)?;
+			memseq.publish(Transaction::new(vec![1], 0)).await?;
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?
+		};
+
+		let memseq = Memseq::try_move_rocks(path, 128, 250)?;
+		memseq.recover().await?;
+		assert_eq!(*memseq.head.read().await, SequencerHead::from_block(&block));
+		assert_eq!(memseq.in_flight_blocks().await?, vec![block.clone()]);
+
+		// the next block continues the chain
+		memseq.publish(Transaction::new(vec![2], 0)).await?;
+		let next_block =
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?;
+		assert_eq!(next_block.parent(), block.id());
+		assert_eq!(next_block.metadata().height(), block.metadata().height() + 1);
+		assert!(next_block.metadata().timestamp() > block.metadata().timestamp());
+
+		memseq.confirm_blocks(vec![block.id()]).await?;
+		assert_eq!(memseq.in_flight_blocks().await?, vec![next_block]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_next_block_follows_split_block() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let memseq = Memseq::try_move_rocks(path, 128, 250)?;
+
+		for i in 0..4 {
+			memseq.publish(Transaction::new(vec![i], 0)).await?;
+		}
+		let block =
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?;
+		let (metadata, parent, transactions, _) = block.clone().into_parts();
+		let transactions = Vec::from_iter(transactions);
+		let first = Block::new(
+			metadata.clone(),
+			parent,
+			BTreeSet::from_iter(transactions[..2].iter().cloned()),
+		);
+		let last = Block::new(
+			BlockMetadata::new(metadata.height() + 2, metadata.timestamp() + 2),
+			first.id(),
+			BTreeSet::from_iter(transactions[2..].iter().cloned()),
+		);
+		memseq.split_block(block.id(), vec![first.clone(), last.clone()]).await?;
+		assert_eq!(memseq.in_flight_blocks().await?, vec![first, last.clone()]);
+
+		memseq.publish(Transaction::new(vec![4], 0)).await?;
+		let next_block =
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?;
+		assert_eq!(next_block.parent(), last.id());
+		assert_eq!(next_block.metadata().height(), last.metadata().height() + 2);
+		assert!(next_block.metadata().timestamp() > last.metadata().timestamp() + 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_respects_block_bytes() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let memseq = Memseq::try_move_rocks(path, 128, 250)?.with_max_block_bytes(Some(10));
+
+		for i in 0..3 {
+			memseq.publish(Transaction::new(vec![i; 4], 0)).await?;
+		}
+
+		// two transactions fit in a block, the third is left for the next block
+		let block =
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?;
+		assert_eq!(block.transactions().len(), 2);
+		let block =
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?;
+		assert_eq!(block.transactions().len(), 1);
+
+		// a transaction which is too large on its own still gets a block
+		memseq.publish(Transaction::new(vec![0; 16], 0)).await?;
+		let block =
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?;
+		assert_eq!(block.transactions().len(), 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_publish_reports_rejected_transactions() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let memseq = Memseq::try_move_rocks(path, 128, 250)?
+			.with_mempool_limits(MempoolLimits { max_transactions: Some(1), ..Default::default() });
+
+		let transactions: Vec<_> = (0..2).map(|i| Transaction::new(vec![i], 0)).collect();
+		let error = memseq.publish_many(transactions.clone()).await.unwrap_err();
+		let rejected = error.downcast_ref::<RejectedTransactions>().expect("rejected transactions");
+		assert_eq!(rejected.0, vec![(transactions[1].id(), DropReason::MempoolFull)]);
+		assert!(rejected.is_transient());
+
+		// the admitted transaction was published regardless
+		assert!(memseq.mempool.has_transaction(transactions[0].id()).await?);
+		assert_eq!(memseq.drop_reason(transactions[1].id()).await?, Some(DropReason::MempoolFull));
+
+		// publishing an admitted transaction again succeeds
+		memseq.publish(transactions[0].clone()).await?;
+		let error = memseq.publish(transactions[1].clone()).await.unwrap_err();
+		assert!(error.downcast_ref::<RejectedTransactions>().is_some());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_deferred_transactions_bypass_mempool_limits() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let limits = MempoolLimits {
+			max_transactions: Some(3),
+			max_transactions_per_sender: Some(3),
+			..Default::default()
+		};
+		let memseq = Memseq::try_move_rocks(path, 128, 250)?
+			.with_max_block_bytes(Some(4))
+			.with_mempool_limits(limits);
+
+		// fill the mempool to capacity
+		let transactions: Vec<_> = (0..3)
+			.map(|i| Transaction::new(vec![i; 4], i as u64).with_sender(vec![1]))
+			.collect();
+		memseq.publish_many(transactions.clone()).await?;
+
+		// the block takes one transaction and defers the other two
+		let block =
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?;
+		assert_eq!(
+			block.transactions().cloned().collect::<Vec<_>>(),
+			vec![transactions[0].clone()]
+		);
+		// the mempool is full again
+		let late = Transaction::new(vec![9; 4], 0).with_sender(vec![2]);
+		memseq.publish(late.clone()).await?;
+		assert_eq!(memseq.mempool.size()?, 3);
+
+		// none of the deferred transactions were dropped and they follow in order
+		for transaction in &transactions[1..] {
+			assert_eq!(memseq.mempool.get_drop_reason(transaction.id()).await?, None);
+		}
+		let mut built = Vec::new();
+		while let Some(block) = memseq.wait_for_next_block().await? {
+			built.extend(block.transactions().cloned());
+		}
+		let from_sender: Vec<_> = built
+			.iter()
+			.filter(|transaction| transaction.sender() == &[1])
+			.cloned()
+			.collect();
+		assert_eq!(from_sender, transactions[1..].to_vec());
+		assert!(built.contains(&late));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_respects_block_gas_in_sender_order() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let memseq = Memseq::try_move_rocks(path, 128, 250)?.with_max_block_gas(Some(100));
+
+		let transaction1 = Transaction::new(vec![1], 0).with_sender(vec![1]).with_gas_estimate(60);
+		let transaction2 = Transaction::new(vec![2], 1).with_sender(vec![1]).with_gas_estimate(60);
+		let transaction3 = Transaction::new(vec![3], 2).with_sender(vec![1]).with_gas_estimate(10);
+		for transaction in [&transaction1, &transaction2, &transaction3] {
+			memseq.publish(transaction.clone()).await?;
+		}
+
+		// the third transaction would fit, but must not go ahead of the second
+		let block =
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?;
+		assert_eq!(block.transactions().cloned().collect::<Vec<_>>(), vec![transaction1]);
+		let block =
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?;
+		assert_eq!(
+			block.transactions().cloned().collect::<Vec<_>>(),
+			vec![transaction2, transaction3]
+		);
 
 		Ok(())
 	}
@@ -420,14 +793,14 @@ pub mod test {
 		async fn add_mempool_transactions(
 			&self,
 			_transactions: Vec<MempoolTransaction>,
-		) -> Result<(), anyhow::Error> {
+		) -> Result<Vec<Admission>, anyhow::Error> {
 			Err(anyhow::anyhow!("Mock add_mempool_transactions"))
 		}
 
 		async fn add_mempool_transaction(
 			&self,
 			_tx: MempoolTransaction,
-		) -> Result<(), anyhow::Error> {
+		) -> Result<Admission, anyhow::Error> {
 			Err(anyhow::anyhow!("Mock add_mempool_transaction"))
 		}
 
@@ -438,6 +811,13 @@ pub mod test {
 			Err(anyhow::anyhow!("Mock remove_mempool_transaction"))
 		}
 
+		async fn return_mempool_transactions(
+			&self,
+			_transactions: Vec<MempoolTransaction>,
+		) -> Result<(), anyhow::Error> {
+			Err(anyhow::anyhow!("Mock return_mempool_transactions"))
+		}
+
 		async fn pop_mempool_transaction(
 			&self,
 		) -> Result<Option<MempoolTransaction>, anyhow::Error> {
@@ -451,7 +831,24 @@ pub mod test {
 			Err(anyhow::anyhow!("Mock get_mempool_transaction"))
 		}
 
-		async fn add_transaction(&self, _transaction: Transaction) -> Result<(), anyhow::Error> {
+		async fn expire_mempool_transactions(
+			&self,
+			_now: u64,
+		) -> Result<Vec<transaction::Id>, anyhow::Error> {
+			Err(anyhow::anyhow!("Mock expire_mempool_transactions"))
+		}
+
+		async fn get_drop_reason(
+			&self,
+			_transaction_id: transaction::Id,
+		) -> Result<Option<DropReason>, anyhow::Error> {
+			Err(anyhow::anyhow!("Mock get_drop_reason"))
+		}
+
+		async fn add_transaction(
+			&self,
+			_transaction: Transaction,
+		) -> Result<Admission, anyhow::Error> {
 			Err(anyhow::anyhow!("Mock add_transaction"))
 		}
 
@@ -460,6 +857,35 @@ pub mod test {
 		}
 	}
 
+	impl SequencerStateOperations for MockMempool {
+		async fn get_sequencer_head(&self) -> Result<Option<SequencerHead>, anyhow::Error> {
+			Err(anyhow::anyhow!("Mock get_sequencer_head"))
+		}
+
+		async fn add_in_flight_block(&self, _block: Block) -> Result<(), anyhow::Error> {
+			Err(anyhow::anyhow!("Mock add_in_flight_block"))
+		}
+
+		async fn confirm_in_flight_blocks(
+			&self,
+			_block_ids: Vec<block::Id>,
+		) -> Result<(), anyhow::Error> {
+			Err(anyhow::anyhow!("Mock confirm_in_flight_blocks"))
+		}
+
+		async fn get_in_flight_blocks(&self) -> Result<Vec<Block>, anyhow::Error> {
+			Err(anyhow::anyhow!("Mock get_in_flight_blocks"))
+		}
+
+		async fn split_in_flight_block(
+			&self,
+			_block_id: block::Id,
+			_blocks: Vec<Block>,
+		) -> Result<(), anyhow::Error> {
+			Err(anyhow::anyhow!("Mock split_in_flight_block"))
+		}
+	}
+
 	impl MempoolBlockOperations for MockMempool {
 		async fn has_block(&self, _block_id: block::Id) -> Result<bool, anyhow::Error> {
 			todo!()
@@ -0,0 +1,130 @@
+use crate::Memseq;
+use mempool_util::{
+	MempoolBlockOperations, MempoolTransactionOperations, SequencerStateOperations,
+};
+use movement_types::{atomic_transaction_bundle::AtomicTransactionBundle, block::Block};
+use sequencing_util::{Sequencer, SharedSequencer};
+
+/// A shared sequencer over a [Memseq].
+///
+/// Each bundle is published as a single transaction, so a block contains either all of a bundle or none of it.
+/// Consumers read the transactions addressed to them with
+/// [consumer_transactions](movement_types::atomic_transaction_bundle::consumer_transactions).
+#[derive(Clone)]
+pub struct SharedMemseq<
+	T: MempoolBlockOperations + MempoolTransactionOperations + SequencerStateOperations,
+> {
+	memseq: Memseq<T>,
+}
+
+impl<T: MempoolBlockOperations + MempoolTransactionOperations + SequencerStateOperations>
+	SharedMemseq<T>
+{
+	pub fn new(memseq: Memseq<T>) -> Self {
+		Self { memseq }
+	}
+
+	pub fn memseq(&self) -> &Memseq<T> {
+		&self.memseq
+	}
+
+	/// Publishes the bundles, each as a single transaction.
+	///
+	/// Fails with [RejectedTransactions](crate::RejectedTransactions) if any of the bundles was not admitted to
+	/// the mempool, identified by the id of the transaction [AtomicTransactionBundle::try_to_transaction] gives.
+	/// The other bundles are published regardless.
+	pub async fn publish_many(
+		&self,
+		atbs: Vec<AtomicTransactionBundle>,
+	) -> Result<(), anyhow::Error> {
+		let mut transactions = Vec::with_capacity(atbs.len());
+		for atb in atbs {
+			if atb.transactions().is_empty() {
+				anyhow::bail!("Cannot publish an empty atomic transaction bundle");
+			}
+			transactions.push(atb.try_to_transaction()?);
+		}
+		self.memseq.publish_many(transactions).await
+	}
+}
+
+impl<T: MempoolBlockOperations + MempoolTransactionOperations + SequencerStateOperations>
+	SharedSequencer for SharedMemseq<T>
+{
+	async fn publish(&self, atb: AtomicTransactionBundle) -> Result<(), anyhow::Error> {
+		if atb.transactions().is_empty() {
+			anyhow::bail!("Cannot publish an empty atomic transaction bundle");
+		}
+		self.memseq.publish(atb.try_to_transaction()?).await
+	}
+
+	async fn wait_for_next_block(&self) -> Result<Option<Block>, anyhow::Error> {
+		self.memseq.wait_for_next_block().await
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use movement_types::atomic_transaction_bundle::{
+		consumer_transactions, Id as ConsumerId, TransactionEntry,
+	};
+	use movement_types::transaction::Transaction;
+	use tempfile::tempdir;
+
+	#[tokio::test]
+	async fn test_bundles_are_sequenced_atomically() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		// each block holds a single bundle
+		let shared = SharedMemseq::new(Memseq::try_move_rocks(path, 1, 250)?);
+
+		let (rollup_a, rollup_b) = (ConsumerId::new([1; 32]), ConsumerId::new([2; 32]));
+		let bundle1 = AtomicTransactionBundle::new(
+			ConsumerId::default(),
+			vec![
+				TransactionEntry::new(rollup_a, Transaction::new(vec![1], 0)),
+				TransactionEntry::new(rollup_b, Transaction::new(vec![2], 0)),
+			],
+		);
+		let bundle2 = AtomicTransactionBundle::new(
+			ConsumerId::default(),
+			vec![TransactionEntry::new(rollup_b, Transaction::new(vec![3], 0))],
+		);
+		shared.publish(bundle1).await?;
+		shared.publish(bundle2).await?;
+
+		let mut transactions_a = Vec::new();
+		let mut transactions_b = Vec::new();
+		for _ in 0..2 {
+			let block =
+				shared.wait_for_next_block().await?.ok_or(anyhow::anyhow!("Block not found"))?;
+			assert_eq!(block.transactions().len(), 1);
+			transactions_a.extend(consumer_transactions(&block, rollup_a));
+			transactions_b.extend(consumer_transactions(&block, rollup_b));
+		}
+
+		assert_eq!(transactions_a, vec![Transaction::new(vec![1], 0)]);
+		// the order of the bundles is up to the mempool
+		transactions_b.sort();
+		let mut expected_b = vec![Transaction::new(vec![2], 0), Transaction::new(vec![3], 0)];
+		expected_b.sort();
+		assert_eq!(transactions_b, expected_b);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_empty_bundle_is_rejected() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let shared = SharedMemseq::new(Memseq::try_move_rocks(path, 128, 250)?);
+
+		let bundle = AtomicTransactionBundle::new(ConsumerId::default(), vec![]);
+		assert!(shared.publish(bundle.clone()).await.is_err());
+		assert!(shared.publish_many(vec![bundle]).await.is_err());
+
+		Ok(())
+	}
+}
@@ -1,5 +1,6 @@
 use dot_movement::DotMovement;
 use godfig::env_default;
+pub use mempool_util::{EvictionPolicy, MempoolLimits, OrderingPolicy};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -21,12 +22,101 @@ pub struct Config {
 	/// The memseq max block size
 	#[serde(default = "default_memseq_max_block_size")]
 	pub memseq_max_block_size: u32,
+
+	/// The maximum total size of the transaction data in a block in bytes, 0 for no limit
+	#[serde(default = "default_memseq_max_block_bytes")]
+	pub memseq_max_block_bytes: u64,
+
+	/// The maximum total gas estimate of the transactions in a block, 0 for no limit
+	#[serde(default = "default_memseq_max_block_gas")]
+	pub memseq_max_block_gas: u64,
+
+	/// How the mempool orders transactions within a slot
+	#[serde(default = "default_memseq_ordering_policy")]
+	pub memseq_ordering_policy: OrderingPolicy,
+
+	/// The length of the mempool slots in seconds
+	#[serde(default = "default_memseq_slot_seconds")]
+	pub memseq_slot_seconds: u64,
+
+	/// How long a transaction may stay in the mempool in seconds, 0 for no limit
+	#[serde(default = "default_memseq_transaction_ttl_seconds")]
+	pub memseq_transaction_ttl_seconds: u64,
+
+	/// The maximum number of transactions in the mempool, 0 for no limit
+	#[serde(default = "default_memseq_max_mempool_transactions")]
+	pub memseq_max_mempool_transactions: u64,
+
+	/// The maximum number of transactions from a single sender in the mempool, 0 for no limit
+	#[serde(default = "default_memseq_max_transactions_per_sender")]
+	pub memseq_max_transactions_per_sender: u64,
+
+	/// What the mempool does with new transactions when it is full
+	#[serde(default = "default_memseq_eviction_policy")]
+	pub memseq_eviction_policy: EvictionPolicy,
+
+	/// Whether the sequencer is shared by several consumers, in which case every blob written to it
+	/// is an atomic transaction bundle
+	#[serde(default = "default_memseq_shared")]
+	pub memseq_shared: bool,
 }
 
 env_default!(default_memseq_build_time, "MEMSEQ_BUILD_TIME", u64, 1000);
 
 env_default!(default_memseq_max_block_size, "MEMSEQ_MAX_BLOCK_SIZE", u32, 2048);
 
+env_default!(default_memseq_max_block_bytes, "MEMSEQ_MAX_BLOCK_BYTES", u64, 1_700_000);
+
+env_default!(default_memseq_max_block_gas, "MEMSEQ_MAX_BLOCK_GAS", u64, 0);
+
+env_default!(
+	default_memseq_ordering_policy,
+	"MEMSEQ_ORDERING_POLICY",
+	OrderingPolicy,
+	OrderingPolicy::Age
+);
+
+env_default!(default_memseq_slot_seconds, "MEMSEQ_SLOT_SECONDS", u64, 2);
+
+env_default!(
+	default_memseq_transaction_ttl_seconds,
+	"MEMSEQ_TRANSACTION_TTL_SECONDS",
+	u64,
+	60 * 60
+);
+
+env_default!(
+	default_memseq_max_mempool_transactions,
+	"MEMSEQ_MAX_MEMPOOL_TRANSACTIONS",
+	u64,
+	1_000_000
+);
+
+env_default!(
+	default_memseq_max_transactions_per_sender,
+	"MEMSEQ_MAX_TRANSACTIONS_PER_SENDER",
+	u64,
+	0
+);
+
+env_default!(
+	default_memseq_eviction_policy,
+	"MEMSEQ_EVICTION_POLICY",
+	EvictionPolicy,
+	EvictionPolicy::RejectNew
+);
+
+env_default!(default_memseq_shared, "MEMSEQ_SHARED", bool, false);
+
+/// Maps a configured limit of 0 to no limit.
+fn limit(value: u64) -> Option<u64> {
+	if value == 0 {
+		None
+	} else {
+		Some(value)
+	}
+}
+
 impl Default for Config {
 	fn default() -> Self {
 		Config {
@@ -34,6 +124,15 @@ impl Default for Config {
 			sequencer_database_path: Config::default_sequencer_database_path(),
 			memseq_build_time: default_memseq_build_time(),
 			memseq_max_block_size: default_memseq_max_block_size(),
+			memseq_max_block_bytes: default_memseq_max_block_bytes(),
+			memseq_max_block_gas: default_memseq_max_block_gas(),
+			memseq_ordering_policy: default_memseq_ordering_policy(),
+			memseq_slot_seconds: default_memseq_slot_seconds(),
+			memseq_transaction_ttl_seconds: default_memseq_transaction_ttl_seconds(),
+			memseq_max_mempool_transactions: default_memseq_max_mempool_transactions(),
+			memseq_max_transactions_per_sender: default_memseq_max_transactions_per_sender(),
+			memseq_eviction_policy: default_memseq_eviction_policy(),
+			memseq_shared: default_memseq_shared(),
 		}
 	}
 }
@@ -65,6 +164,25 @@ impl Config {
 			.ok_or(anyhow::anyhow!("No sequencer database path provided"))
 	}
 
+	/// Gets the limits of the mempool, where a limit of 0 means no limit.
+	///
+	/// A transaction which could not fit in a block on its own is not admitted.
+	pub fn mempool_limits(&self) -> MempoolLimits {
+		MempoolLimits {
+			transaction_ttl_seconds: limit(self.memseq_transaction_ttl_seconds),
+			max_transactions: limit(self.memseq_max_mempool_transactions),
+			max_transactions_per_sender: limit(self.memseq_max_transactions_per_sender),
+			eviction_policy: self.memseq_eviction_policy,
+			max_transaction_bytes: limit(self.memseq_max_block_bytes),
+			max_transaction_gas: limit(self.memseq_max_block_gas),
+		}
+	}
+
+	/// Gets the byte and gas limits of a block, where a limit of 0 means no limit.
+	pub fn block_resource_limits(&self) -> (Option<u64>, Option<u64>) {
+		(limit(self.memseq_max_block_bytes), limit(self.memseq_max_block_gas))
+	}
+
 	/// Try to read the location of the config file from the environment and then read the config from the file
 	pub fn try_from_env_toml_file() -> Result<Self, anyhow::Error> {
 		let path = DotMovement::try_from_env()?;
@@ -0,0 +1,212 @@
+use alloy_primitives::{Address, U256};
+use clap::{Parser, Subcommand};
+use godfig::{backend::config_file::ConfigFile, Godfig};
+use mcr_settlement_client::{McrEthSettlementClient, McrSettlementStakingOperations};
+use mcr_settlement_config::Config;
+use std::io::Write;
+use tokio_stream::StreamExt;
+
+#[derive(Parser)]
+#[command(name = "MCR settlement CLI")]
+#[command(about = "Command line interface to manage the stake of MCR attesters", long_about = None)]
+pub struct CliOptions {
+	#[command(subcommand)]
+	pub command: Commands,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+	/// Stake an amount of the staking token for the configured signer
+	Stake {
+		/// The amount to stake, in the smallest unit of the token
+		amount: U256,
+	},
+	/// Unstake an amount of the staking token for the configured signer, at the next epoch
+	Unstake {
+		/// The amount to unstake, in the smallest unit of the token
+		amount: U256,
+	},
+	/// Print the current epoch
+	Epoch,
+	/// Print the stake of an attester at an epoch
+	StakeAtEpoch {
+		/// The epoch, the current epoch if not given
+		#[arg(long)]
+		epoch: Option<u64>,
+
+		/// The attester, the configured signer if not given
+		#[arg(long)]
+		attester: Option<Address>,
+
+		/// Sum the stake over all the tokens of the MCR contract
+		#[arg(long)]
+		all: bool,
+	},
+	/// List the attesters with their stake at the current epoch
+	Attesters,
+	/// Print the epochs as the MCR contract rolls over to them
+	WatchEpochs,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+	use tracing_subscriber::EnvFilter;
+
+	tracing_subscriber::fmt()
+		.with_env_filter(
+			EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+		)
+		.init();
+
+	let cli = CliOptions::parse();
+
+	// get the config file
+	let dot_movement = dot_movement::DotMovement::try_from_env()?;
+	let config_file = dot_movement.try_get_or_create_config_file().await?;
+	let godfig: Godfig<Config, ConfigFile> =
+		Godfig::new(ConfigFile::new(config_file), vec!["mcr_settlement".to_string()]);
+	let config = godfig.try_wait_for_ready().await?;
+
+	let client = McrEthSettlementClient::build_with_config(&config).await?;
+	run_command(&client, client.signer_address, cli.command, &mut std::io::stdout()).await?;
+
+	Ok(())
+}
+
+/// Runs a command with the client of the configured signer, writing its output.
+async fn run_command<C>(
+	client: &C,
+	signer_address: Address,
+	command: Commands,
+	out: &mut impl Write,
+) -> Result<(), anyhow::Error>
+where
+	C: McrSettlementStakingOperations + Sync,
+{
+	match command {
+		Commands::Stake { amount } => {
+			client.stake(amount).await?;
+			writeln!(out, "Staked {} for {}", amount, signer_address)?;
+		}
+		Commands::Unstake { amount } => {
+			client.unstake(amount).await?;
+			writeln!(out, "Unstaked {} for {}", amount, signer_address)?;
+		}
+		Commands::Epoch => {
+			writeln!(out, "{}", client.get_current_epoch().await?)?;
+		}
+		Commands::StakeAtEpoch { epoch, attester, all } => {
+			let epoch = match epoch {
+				Some(epoch) => epoch,
+				None => client.get_current_epoch().await?,
+			};
+			let attester = attester.unwrap_or(signer_address);
+			let stake = if all {
+				client.compute_all_stake_at_epoch(epoch, attester).await?
+			} else {
+				client.get_stake_at_epoch(epoch, attester).await?
+			};
+			writeln!(out, "{}", stake)?;
+		}
+		Commands::Attesters => {
+			let epoch = client.get_current_epoch().await?;
+			for attester in client.get_attesters().await? {
+				let stake = client.compute_all_stake_at_epoch(epoch, attester).await?;
+				writeln!(out, "{} {}", attester, stake)?;
+			}
+		}
+		Commands::WatchEpochs => {
+			let mut epochs = client.stream_epoch_rollovers().await?;
+			while let Some(epoch) = epochs.next().await {
+				writeln!(out, "{}", epoch?)?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+	use super::*;
+	use mcr_settlement_client::simulator::McrSimulator;
+
+	async fn run_to_string<C>(
+		client: &C,
+		signer_address: Address,
+		command: Commands,
+	) -> Result<String, anyhow::Error>
+	where
+		C: McrSettlementStakingOperations + Sync,
+	{
+		let mut out = Vec::new();
+		run_command(client, signer_address, command, &mut out).await?;
+		Ok(String::from_utf8(out)?)
+	}
+
+	#[test]
+	fn test_parse_commands() -> Result<(), anyhow::Error> {
+		let cli = CliOptions::try_parse_from(["mcr-settlement-cli", "stake", "100"])?;
+		assert!(matches!(cli.command, Commands::Stake { amount } if amount == U256::from(100)));
+
+		let attester = Address::repeat_byte(1);
+		let cli = CliOptions::try_parse_from([
+			"mcr-settlement-cli",
+			"stake-at-epoch",
+			"--epoch",
+			"2",
+			"--attester",
+			&attester.to_string(),
+			"--all",
+		])?;
+		assert!(matches!(
+			cli.command,
+			Commands::StakeAtEpoch { epoch: Some(2), attester: Some(a), all: true } if a == attester
+		));
+
+		assert!(CliOptions::try_parse_from(["mcr-settlement-cli", "stake"]).is_err());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_staking_commands() -> Result<(), anyhow::Error> {
+		let simulator = McrSimulator::new(16, U256::ZERO);
+		let signer_address = Address::repeat_byte(1);
+		let client = simulator.add_attester(signer_address, U256::from(10)).await;
+
+		let output = run_to_string(&client, signer_address, Commands::Epoch).await?;
+		assert_eq!(output, "0\n");
+
+		let output = run_to_string(&client, signer_address, Commands::Attesters).await?;
+		assert_eq!(output, format!("{} 10\n", signer_address));
+
+		let output = run_to_string(
+			&client,
+			signer_address,
+			Commands::StakeAtEpoch { epoch: None, attester: None, all: false },
+		)
+		.await?;
+		assert_eq!(output, "10\n");
+
+		// the stake takes effect at the next epoch
+		let output =
+			run_to_string(&client, signer_address, Commands::Stake { amount: U256::from(5) })
+				.await?;
+		assert_eq!(output, format!("Staked 5 for {}\n", signer_address));
+		let output = run_to_string(
+			&client,
+			signer_address,
+			Commands::StakeAtEpoch { epoch: Some(1), attester: Some(signer_address), all: true },
+		)
+		.await?;
+		assert_eq!(output, "5\n");
+
+		let output =
+			run_to_string(&client, signer_address, Commands::Unstake { amount: U256::from(3) })
+				.await?;
+		assert_eq!(output, format!("Unstaked 3 for {}\n", signer_address));
+
+		Ok(())
+	}
+}
@@ -0,0 +1,172 @@
+//! Tracking of the events of the MCR contract on L1 until they are confirmed.
+
+use crate::EventCursorStore;
+use alloy_primitives::B256;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The position of the stream of events in L1, persisted to resume the stream after a restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventCursor {
+	/// The first L1 block whose events have not all been delivered.
+	pub next_block: u64,
+}
+
+impl EventCursor {
+	/// Loads the cursor from a file, if it has been persisted.
+	pub async fn load(path: &Path) -> Result<Option<Self>, anyhow::Error> {
+		match tokio::fs::read(path).await {
+			Ok(bytes) => Ok(Some(
+				serde_json::from_slice(&bytes).context("Failed to parse the event cursor")?,
+			)),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+			Err(e) => Err(e).context("Failed to read the event cursor"),
+		}
+	}
+
+	/// Persists the cursor to a file, replacing the previous one atomically.
+	pub async fn store(&self, path: &Path) -> Result<(), anyhow::Error> {
+		let tmp_path = path.with_extension("tmp");
+		tokio::fs::write(&tmp_path, serde_json::to_vec(self)?).await?;
+		tokio::fs::rename(&tmp_path, path).await?;
+		Ok(())
+	}
+}
+
+/// Persists the event cursor to a file.
+pub struct FileEventCursorStore {
+	path: PathBuf,
+}
+
+impl FileEventCursorStore {
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		Self { path: path.into() }
+	}
+}
+
+#[async_trait::async_trait]
+impl EventCursorStore for FileEventCursorStore {
+	async fn load_event_cursor(&self) -> Result<Option<EventCursor>, anyhow::Error> {
+		EventCursor::load(&self.path).await
+	}
+
+	async fn store_event_cursor(&self, cursor: EventCursor) -> Result<(), anyhow::Error> {
+		cursor.store(&self.path).await
+	}
+}
+
+/// The events observed in the L1 blocks which do not have the required number of confirmations yet.
+///
+/// The hashes of the observed blocks are kept to detect reorgs: the events of the blocks removed
+/// by a reorg are retracted before they are delivered. The blocks are forgotten once they are
+/// confirmed, so a reorg deeper than the confirmations does not retract the delivered events.
+pub struct ConfirmationWindow<E> {
+	confirmations: u64,
+	blocks: BTreeMap<u64, (B256, Vec<E>)>,
+}
+
+impl<E> ConfirmationWindow<E> {
+	pub fn new(confirmations: u64) -> Self {
+		Self { confirmations, blocks: BTreeMap::new() }
+	}
+
+	/// Records the events of a block. A block is also recorded without events
+	/// to detect the reorgs of the blocks scanned up to it.
+	pub fn record(&mut self, number: u64, hash: B256, events: Vec<E>) {
+		let (recorded_hash, recorded_events) =
+			self.blocks.entry(number).or_insert_with(|| (hash, Vec::new()));
+		if *recorded_hash != hash {
+			// the block has been replaced since it was recorded
+			*recorded_hash = hash;
+			recorded_events.clear();
+		}
+		recorded_events.extend(events);
+	}
+
+	/// The recorded blocks, from the most recent.
+	pub fn recorded_blocks(&self) -> impl Iterator<Item = (u64, B256)> + '_ {
+		self.blocks.iter().rev().map(|(number, (hash, _))| (*number, *hash))
+	}
+
+	/// Retracts the events of the blocks above the given block number, which were removed by a reorg.
+	pub fn retract_above(&mut self, number: u64) -> Vec<E> {
+		self.blocks
+			.split_off(&number.saturating_add(1))
+			.into_values()
+			.flat_map(|(_, events)| events)
+			.collect()
+	}
+
+	/// Releases the events of the blocks which are confirmed at the given L1 head, in block order.
+	pub fn confirm(&mut self, head: u64) -> Vec<E> {
+		let Some(confirmed) = head.checked_sub(self.confirmations) else {
+			return Vec::new();
+		};
+		let unconfirmed = self.blocks.split_off(&confirmed.saturating_add(1));
+		std::mem::replace(&mut self.blocks, unconfirmed)
+			.into_values()
+			.flat_map(|(_, events)| events)
+			.collect()
+	}
+
+	/// The first block to scan again to resume the stream, given the next block to scan.
+	///
+	/// The events of the blocks which are not confirmed yet have not been delivered.
+	pub fn resume_block(&self, next_block: u64) -> u64 {
+		self.blocks.keys().next().map_or(next_block, |&first| first.min(next_block))
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	#[test]
+	fn test_events_are_released_once_confirmed() {
+		let mut window = ConfirmationWindow::new(2);
+		window.record(10, B256::with_last_byte(10), vec![1]);
+		window.record(11, B256::with_last_byte(11), vec![2, 3]);
+
+		assert_eq!(window.confirm(11), Vec::<u32>::new());
+		assert_eq!(window.resume_block(12), 10);
+		assert_eq!(window.confirm(12), vec![1]);
+		assert_eq!(window.confirm(14), vec![2, 3]);
+		assert_eq!(window.resume_block(15), 15);
+	}
+
+	#[test]
+	fn test_reorged_events_are_retracted() {
+		let mut window = ConfirmationWindow::new(3);
+		window.record(10, B256::with_last_byte(10), vec![1]);
+		window.record(11, B256::with_last_byte(11), vec![2]);
+		window.record(12, B256::with_last_byte(12), vec![]);
+		assert_eq!(window.recorded_blocks().next(), Some((12, B256::with_last_byte(12))));
+
+		// the blocks above 10 were reorged out
+		assert_eq!(window.retract_above(10), vec![2]);
+		assert_eq!(window.resume_block(11), 10);
+
+		// a block observed again with another hash only keeps the events of the new block
+		window.record(10, B256::with_last_byte(10), vec![4]);
+		window.record(11, B256::with_last_byte(21), vec![5]);
+		window.record(11, B256::with_last_byte(22), vec![6]);
+		assert_eq!(window.confirm(14), vec![1, 4, 6]);
+	}
+
+	#[tokio::test]
+	async fn test_event_cursor_persistence() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("event-cursor.json");
+		assert_eq!(EventCursor::load(&path).await?, None);
+
+		let cursor = EventCursor { next_block: 42 };
+		cursor.store(&path).await?;
+		assert_eq!(EventCursor::load(&path).await?, Some(cursor));
+
+		Ok(())
+	}
+}
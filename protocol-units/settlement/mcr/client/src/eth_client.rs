@@ -1,8 +1,15 @@
+use crate::commitment_stream::{ConfirmationWindow, EventCursor, FileEventCursorStore};
 use crate::send_eth_transaction::InsufficentFunds;
 use crate::send_eth_transaction::SendTransactionErrorRule;
+use crate::send_eth_transaction::TransactionSender;
 use crate::send_eth_transaction::UnderPriced;
 use crate::send_eth_transaction::VerifyRule;
-use crate::{CommitmentStream, McrSettlementClientOperations};
+use crate::send_eth_transaction::RECEIPT_POLL_INTERVAL;
+use crate::{
+	CommitmentStream, EpochStream, EventCursorStore, McrSettlementClientOperations,
+	McrSettlementStakingOperations, PostedTransactionStatus, TransactionHash,
+};
+use alloy::eips::BlockNumberOrTag;
 use alloy::providers::fillers::ChainIdFiller;
 use alloy::providers::fillers::FillProvider;
 use alloy::providers::fillers::GasFiller;
@@ -15,19 +22,25 @@ use alloy::signers::local::PrivateKeySigner;
 use alloy_network::Ethereum;
 use alloy_network::EthereumWallet;
 use alloy_primitives::Address;
+use alloy_primitives::B256;
 use alloy_primitives::U256;
 use alloy_sol_types::sol;
 use alloy_transport::BoxTransport;
 use alloy_transport_ws::WsConnect;
 use anyhow::Context;
+use mcr_settlement_config::common::events::Config as EventsConfig;
+use mcr_settlement_config::common::transactions::Config as TransactionsConfig;
 use mcr_settlement_config::Config;
 use movement_types::block::{BlockCommitment, Commitment, Id};
 use serde_json::Value as JsonValue;
 use std::array::TryFromSliceError;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 use tokio_stream::StreamExt;
+use tracing::{debug, warn};
 
 #[derive(Error, Debug)]
 pub enum McrEthConnectorError {
@@ -41,6 +54,16 @@ pub enum McrEthConnectorError {
 	SendTransactionError(#[from] alloy_contract::Error),
 	#[error("MCR Settlement Transaction send failed during its execution :{0}")]
 	RpcTransactionExecution(String),
+	#[error("MCR Settlement Transaction gas estimation failed :{0}")]
+	GasEstimation(String),
+	#[error("MCR Settlement Transaction fee estimation failed :{0}")]
+	FeeEstimation(String),
+	#[error(
+		"MCR Settlement Transaction fails because max fee per gas is too high. Max fee per gas:{0} cap:{1}"
+	)]
+	FeeCapExceeded(u128, u128),
+	#[error("MCR Settlement Transaction send failed after {0} retries")]
+	MaxRetriesExceeded(u32),
 	#[error("MCR Settlement BlockAccepted event notification error :{0}")]
 	EventNotificationError(#[from] alloy_sol_types::Error),
 	#[error("MCR Settlement BlockAccepted event notification stream close")]
@@ -74,11 +97,14 @@ sol!(
 pub struct Client<P> {
 	rpc_provider: P,
 	ws_provider: RootProvider<PubSubFrontend>,
+	ws_url: String,
 	pub signer_address: Address,
 	contract_address: Address,
-	send_transaction_error_rules: Vec<Box<dyn VerifyRule>>,
-	gas_limit: u64,
-	send_transaction_retries: u32,
+	transaction_sender: TransactionSender,
+	events: EventsConfig,
+	event_cursor_store: Option<Arc<dyn EventCursorStore>>,
+	// The custodian of the stakes, the staked token if not configured
+	staking_custodian_address: Option<Address>,
 }
 
 impl
@@ -111,15 +137,22 @@ impl
 			.await
 			.context("Failed to create the RPC provider for the MCR settlement client")?;
 
-		let client = Client::build_with_provider(
+		let mut client = Client::build_with_provider(
 			rpc_provider,
 			ws_url,
 			signer_address,
 			contract_address,
-			config.transactions.gas_limit,
-			config.transactions.transaction_send_retries,
+			config.transactions.clone(),
+			config.events.clone(),
 		)
 		.await?;
+		client.staking_custodian_address = config
+			.settle
+			.staking_custodian_address
+			.as_deref()
+			.map(str::parse)
+			.transpose()
+			.context("Failed to parse the staking custodian address")?;
 		Ok(client)
 	}
 }
@@ -130,14 +163,15 @@ impl<P> Client<P> {
 		ws_url: S,
 		signer_address: Address,
 		contract_address: Address,
-		gas_limit: u64,
-		send_transaction_retries: u32,
+		transactions: TransactionsConfig,
+		events: EventsConfig,
 	) -> Result<Self, anyhow::Error>
 	where
 		P: Provider + Clone,
 		S: Into<String>,
 	{
-		let ws = WsConnect::new(ws_url);
+		let ws_url = ws_url.into();
+		let ws = WsConnect::new(ws_url.clone());
 
 		let ws_provider = ProviderBuilder::new().on_ws(ws).await?;
 
@@ -145,17 +179,53 @@ impl<P> Client<P> {
 		let rule2: Box<dyn VerifyRule> =
 			Box::new(SendTransactionErrorRule::<InsufficentFunds>::new());
 		let send_transaction_error_rules = vec![rule1, rule2];
+		let transaction_sender =
+			TransactionSender::new(signer_address, send_transaction_error_rules, &transactions);
+
+		let event_cursor_store = events
+			.cursor_path
+			.as_ref()
+			.map(|path| Arc::new(FileEventCursorStore::new(path)) as Arc<dyn EventCursorStore>);
 
 		Ok(Client {
 			rpc_provider,
 			ws_provider,
+			ws_url,
 			signer_address,
 			contract_address,
-			send_transaction_error_rules,
-			gas_limit,
-			send_transaction_retries,
+			transaction_sender,
+			events,
+			event_cursor_store,
+			staking_custodian_address: None,
 		})
 	}
+
+	/// Gets the address of the staking contract of the MCR contract.
+	async fn staking_contract_address(&self) -> Result<Address, anyhow::Error> {
+		let contract = MCR::new(self.contract_address, &self.ws_provider);
+		let MCR::stakingContractReturn { _0: staking_address } =
+			contract.stakingContract().call().await?;
+		Ok(staking_address)
+	}
+
+	/// Gets the address of the token staked on the staking contract.
+	async fn staking_token_address(
+		&self,
+		staking_address: Address,
+	) -> Result<Address, anyhow::Error> {
+		let staking = MovementStaking::new(staking_address, &self.ws_provider);
+		let MovementStaking::tokenReturn { _0: token_address } = staking.token().call().await?;
+		Ok(token_address)
+	}
+
+	/// Gets the address of the custodian of the stakes: the configured one,
+	/// or else the staked token, which is the custodian in the deployments of `DeployMCRDev`.
+	async fn custodian_address(&self, staking_address: Address) -> Result<Address, anyhow::Error> {
+		match self.staking_custodian_address {
+			Some(custodian_address) => Ok(custodian_address),
+			None => self.staking_token_address(staking_address).await,
+		}
+	}
 }
 
 #[async_trait::async_trait]
@@ -166,7 +236,7 @@ where
 	async fn post_block_commitment(
 		&self,
 		block_commitment: BlockCommitment,
-	) -> Result<(), anyhow::Error> {
+	) -> Result<TransactionHash, anyhow::Error> {
 		let contract = MCR::new(self.contract_address, &self.rpc_provider);
 
 		let eth_block_commitment = MCR::BlockCommitment {
@@ -180,19 +250,14 @@ where
 
 		let call_builder = contract.submitBlockCommitment(eth_block_commitment);
 
-		crate::send_eth_transaction::send_transaction(
-			call_builder,
-			&self.send_transaction_error_rules,
-			self.send_transaction_retries,
-			self.gas_limit as u128,
-		)
-		.await
+		let transaction_hash = self.transaction_sender.send_transaction(call_builder).await?;
+		Ok(TransactionHash(transaction_hash.0))
 	}
 
 	async fn post_block_commitment_batch(
 		&self,
 		block_commitments: Vec<BlockCommitment>,
-	) -> Result<(), anyhow::Error> {
+	) -> Result<TransactionHash, anyhow::Error> {
 		let contract = MCR::new(self.contract_address, &self.rpc_provider);
 
 		let eth_block_commitment: Vec<_> = block_commitments
@@ -213,40 +278,117 @@ where
 
 		let call_builder = contract.submitBatchBlockCommitment(eth_block_commitment);
 
-		crate::send_eth_transaction::send_transaction(
-			call_builder,
-			&self.send_transaction_error_rules,
-			self.send_transaction_retries,
-			self.gas_limit as u128,
-		)
-		.await
+		let transaction_hash = self.transaction_sender.send_transaction(call_builder).await?;
+		Ok(TransactionHash(transaction_hash.0))
 	}
 
 	async fn stream_block_commitments(&self) -> Result<CommitmentStream, anyhow::Error> {
-		// Register to contract BlockCommitmentSubmitted event
+		let cursor_store = self.event_cursor_store.clone();
+		let cursor = match &cursor_store {
+			Some(store) => store.load_event_cursor().await?,
+			None => None,
+		};
+		// without a persisted cursor, the acceptances not confirmed yet are still delivered
+		let mut next_block = match cursor {
+			Some(cursor) => cursor.next_block,
+			None => (self.ws_provider.get_block_number().await? + 1)
+				.saturating_sub(self.events.confirmations),
+		};
+		debug!("Streaming accepted commitments from L1 block {}", next_block);
 
-		let contract = MCR::new(self.contract_address, &self.ws_provider);
-		let event_filter = contract.BlockAccepted_filter().watch().await?;
-
-		let stream = event_filter.into_stream().map(|event| {
-			event
-				.and_then(|(commitment, _)| {
-					let height = commitment.height.try_into().map_err(
-						|err: alloy::primitives::ruint::FromUintError<u64>| {
-							alloy_sol_types::Error::Other(err.to_string().into())
-						},
-					)?;
-					Ok(BlockCommitment::new(
-						height,
-						Id::new(commitment.blockHash.0),
-						Commitment::new(commitment.stateCommitment.0),
-					))
-				})
-				.map_err(|err| McrEthConnectorError::EventNotificationError(err).into())
-		});
+		let ws_url = self.ws_url.clone();
+		let contract_address = self.contract_address;
+		let backfill_block_range = self.events.backfill_block_range.max(1);
+		let reconnect_interval = Duration::from_millis(self.events.reconnect_interval_milliseconds);
+		let mut window = ConfirmationWindow::new(self.events.confirmations);
+		let mut stored_cursor = cursor;
+
+		let stream = async_stream::stream! {
+			loop {
+				let connection = async {
+					let ws = WsConnect::new(ws_url.clone());
+					let ws_provider = ProviderBuilder::new().on_ws(ws).await?;
+					let heads = ws_provider.subscribe_blocks().await?.into_stream();
+					Ok::<_, anyhow::Error>((ws_provider, heads))
+				};
+				let (ws_provider, mut heads) = match connection.await {
+					Ok(connection) => connection,
+					Err(e) => {
+						warn!("Failed to connect to the L1 websocket, retrying: {:?}", e);
+						tokio::time::sleep(reconnect_interval).await;
+						continue;
+					}
+				};
+
+				// the events missed while disconnected are backfilled before waiting for a new head
+				loop {
+					match poll_accepted_commitments(
+						&ws_provider,
+						contract_address,
+						&mut window,
+						&mut next_block,
+						backfill_block_range,
+					)
+					.await
+					{
+						Ok(commitments) => {
+							for commitment in commitments {
+								yield Ok(commitment);
+							}
+							let cursor =
+								EventCursor { next_block: window.resume_block(next_block) };
+							let changed = stored_cursor != Some(cursor);
+							if let Some(store) = cursor_store.as_ref().filter(|_| changed) {
+								match store.store_event_cursor(cursor).await {
+									Ok(()) => stored_cursor = Some(cursor),
+									Err(e) => warn!("Failed to persist the event cursor: {:?}", e),
+								}
+							}
+						}
+						Err(e) => {
+							warn!("Failed to poll accepted commitments, reconnecting: {:?}", e);
+							break;
+						}
+					}
+					if heads.next().await.is_none() {
+						warn!("L1 websocket subscription closed, reconnecting");
+						break;
+					}
+				}
+				tokio::time::sleep(reconnect_interval).await;
+			}
+		};
 		Ok(Box::pin(stream) as CommitmentStream)
 	}
 
+	fn set_event_cursor_store(&mut self, store: Arc<dyn EventCursorStore>) {
+		self.event_cursor_store = Some(store);
+	}
+
+	async fn get_posted_transaction_status(
+		&self,
+		transaction_hash: TransactionHash,
+	) -> Result<PostedTransactionStatus, anyhow::Error> {
+		let hash = B256::from(transaction_hash.0);
+		let wait = async {
+			loop {
+				if let Some(receipt) = self.rpc_provider.get_transaction_receipt(hash).await? {
+					return Ok::<_, anyhow::Error>(receipt.status());
+				}
+				tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+			}
+		};
+		match tokio::time::timeout(self.transaction_sender.receipt_timeout(), wait).await {
+			Ok(success) => Ok(if success? {
+				PostedTransactionStatus::Succeeded
+			} else {
+				PostedTransactionStatus::Failed
+			}),
+			// a transaction which is not mined in time is considered dropped
+			Err(_) => Ok(PostedTransactionStatus::Missing),
+		}
+	}
+
 	async fn get_commitment_at_height(
 		&self,
 		height: u64,
@@ -280,6 +422,181 @@ where
 	}
 }
 
+#[async_trait::async_trait]
+impl<P> McrSettlementStakingOperations for Client<P>
+where
+	P: Provider + Clone,
+{
+	async fn stake(&self, amount: U256) -> Result<(), anyhow::Error> {
+		let staking_address = self.staking_contract_address().await?;
+		let custodian_address = self.custodian_address(staking_address).await?;
+
+		// the staking contract transfers the stake from the attester
+		let token_address = self.staking_token_address(staking_address).await?;
+		let token = MOVEToken::new(token_address, &self.rpc_provider);
+		self.transaction_sender
+			.send_transaction(token.approve(staking_address, amount))
+			.await
+			.context("Failed to approve the staking contract")?;
+
+		let staking = MovementStaking::new(staking_address, &self.rpc_provider);
+		self.transaction_sender
+			.send_transaction(staking.stake(self.contract_address, custodian_address, amount))
+			.await?;
+		Ok(())
+	}
+
+	async fn unstake(&self, amount: U256) -> Result<(), anyhow::Error> {
+		let staking_address = self.staking_contract_address().await?;
+		let custodian_address = self.custodian_address(staking_address).await?;
+
+		let staking = MovementStaking::new(staking_address, &self.rpc_provider);
+		self.transaction_sender
+			.send_transaction(staking.unstake(self.contract_address, custodian_address, amount))
+			.await?;
+		Ok(())
+	}
+
+	async fn get_current_epoch(&self) -> Result<u64, anyhow::Error> {
+		let contract = MCR::new(self.contract_address, &self.ws_provider);
+		let MCR::getCurrentEpochReturn { _0: epoch } = contract.getCurrentEpoch().call().await?;
+		Ok(epoch.try_into().context("Failed to convert the epoch from U256 to u64")?)
+	}
+
+	async fn get_stake_at_epoch(
+		&self,
+		epoch: u64,
+		attester: Address,
+	) -> Result<U256, anyhow::Error> {
+		let staking_address = self.staking_contract_address().await?;
+		let custodian_address = self.custodian_address(staking_address).await?;
+
+		let contract = MCR::new(self.contract_address, &self.ws_provider);
+		let MCR::getStakeAtEpochReturn { _0: stake } = contract
+			.getStakeAtEpoch(U256::from(epoch), custodian_address, attester)
+			.call()
+			.await?;
+		Ok(stake)
+	}
+
+	async fn compute_all_stake_at_epoch(
+		&self,
+		epoch: u64,
+		attester: Address,
+	) -> Result<U256, anyhow::Error> {
+		let contract = MCR::new(self.contract_address, &self.ws_provider);
+		let MCR::computeAllStakeAtEpochReturn { _0: stake } =
+			contract.computeAllStakeAtEpoch(U256::from(epoch), attester).call().await?;
+		Ok(stake)
+	}
+
+	async fn get_attesters(&self) -> Result<Vec<Address>, anyhow::Error> {
+		let contract = MCR::new(self.contract_address, &self.ws_provider);
+		let MCR::getAttestersReturn { _0: attesters } = contract.getAttesters().call().await?;
+		Ok(attesters)
+	}
+
+	async fn stream_epoch_rollovers(&self) -> Result<EpochStream, anyhow::Error> {
+		let staking_address = self.staking_contract_address().await?;
+
+		// the staking contract rolls over the epochs of every domain, so only those of the MCR contract are kept
+		let staking = MovementStaking::new(staking_address, &self.ws_provider);
+		let event_filter = staking.EpochRolledOver_filter().watch().await?;
+		let domain = self.contract_address;
+
+		let stream = event_filter.into_stream().filter_map(move |event| match event {
+			Ok((rollover, _)) if rollover.domain != domain => None,
+			Ok((rollover, _)) => Some(
+				rollover
+					.epoch
+					.try_into()
+					.context("Failed to convert the epoch from U256 to u64"),
+			),
+			Err(err) => Some(Err(McrEthConnectorError::EventNotificationError(err).into())),
+		});
+		Ok(Box::pin(stream) as EpochStream)
+	}
+}
+
+/// Gets the hash of the L1 block at the given number, if the block exists.
+async fn block_hash(
+	provider: &RootProvider<PubSubFrontend>,
+	number: u64,
+) -> Result<Option<B256>, anyhow::Error> {
+	let block = provider.get_block_by_number(BlockNumberOrTag::Number(number), false).await?;
+	Ok(block.and_then(|block| block.header.hash))
+}
+
+/// Scans the L1 blocks from `next_block` up to the head for accepted commitments,
+/// returning the commitments which have been confirmed since the last poll.
+///
+/// The acceptances in the blocks removed by a reorg are retracted and the blocks are scanned again.
+async fn poll_accepted_commitments(
+	provider: &RootProvider<PubSubFrontend>,
+	contract_address: Address,
+	window: &mut ConfirmationWindow<BlockCommitment>,
+	next_block: &mut u64,
+	backfill_block_range: u64,
+) -> Result<Vec<BlockCommitment>, anyhow::Error> {
+	let head = provider
+		.get_block_by_number(BlockNumberOrTag::Latest, false)
+		.await?
+		.context("No L1 head block")?;
+	let head_number = head.header.number.context("No number for the L1 head block")?;
+	let head_hash = head.header.hash.context("No hash for the L1 head block")?;
+
+	// find the most recent recorded block which is still in the chain
+	let mut rescan_from = None;
+	for (number, hash) in window.recorded_blocks().collect::<Vec<_>>() {
+		if block_hash(provider, number).await? == Some(hash) {
+			break;
+		}
+		rescan_from = Some(number);
+	}
+	if let Some(rescan_from) = rescan_from {
+		let retracted = window.retract_above(rescan_from.saturating_sub(1));
+		warn!(
+			"L1 reorg detected from block {}, retracted {} unconfirmed accepted commitments",
+			rescan_from,
+			retracted.len()
+		);
+		*next_block = (*next_block).min(rescan_from);
+	}
+
+	let contract = MCR::new(contract_address, provider);
+	while *next_block <= head_number {
+		let to_block = head_number.min(*next_block + backfill_block_range - 1);
+		let events = contract
+			.BlockAccepted_filter()
+			.from_block(*next_block)
+			.to_block(to_block)
+			.query()
+			.await?;
+		for (commitment, log) in events {
+			if log.removed {
+				continue;
+			}
+			let number = log.block_number.context("No block number for the BlockAccepted log")?;
+			let hash = log.block_hash.context("No block hash for the BlockAccepted log")?;
+			let height = commitment
+				.height
+				.try_into()
+				.context("Failed to convert the commitment height from U256 to u64")?;
+			let commitment = BlockCommitment::new(
+				height,
+				Id::new(commitment.blockHash.0),
+				Commitment::new(commitment.stateCommitment.0),
+			);
+			window.record(number, hash, vec![commitment]);
+		}
+		*next_block = to_block + 1;
+	}
+	// the head is recorded to detect the reorgs of the blocks without acceptances
+	window.record(head_number, head_hash, Vec::new());
+
+	Ok(window.confirm(head_number))
+}
+
 pub struct AnvilAddressEntry {
 	pub address: String,
 	pub private_key: String,
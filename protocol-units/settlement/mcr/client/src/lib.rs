@@ -1,6 +1,11 @@
+use alloy_primitives::{Address, U256};
+pub use commitment_stream::EventCursor;
 use movement_types::block::BlockCommitment;
+use serde::{Deserialize, Serialize};
 use tokio_stream::Stream;
 
+use std::sync::Arc;
+
 #[cfg(test)]
 pub mod tests;
 
@@ -9,6 +14,10 @@ pub mod mock;
 #[cfg(feature = "mock")]
 pub use mock::*;
 
+pub mod commitment_stream;
+
+pub mod simulator;
+
 pub mod eth_client;
 
 #[cfg(feature = "eth")]
@@ -19,23 +28,65 @@ pub mod send_eth_transaction;
 type CommitmentStream =
 	std::pin::Pin<Box<dyn Stream<Item = Result<BlockCommitment, anyhow::Error>> + Send>>;
 
+/// The hash of a transaction posted to the settlement contract.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TransactionHash(pub [u8; 32]);
+
+/// The outcome of a transaction posted to the settlement contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostedTransactionStatus {
+	/// The transaction has been mined and succeeded.
+	Succeeded,
+	/// The transaction has been mined and reverted.
+	Failed,
+	/// The transaction has not been mined in time. It may have been dropped.
+	Missing,
+}
+
+/// Persistence of the position of the stream of settled commitments in L1.
+#[async_trait::async_trait]
+pub trait EventCursorStore: Send + Sync {
+	/// Loads the persisted position, if any.
+	async fn load_event_cursor(&self) -> Result<Option<EventCursor>, anyhow::Error>;
+
+	/// Persists the position.
+	async fn store_event_cursor(&self, cursor: EventCursor) -> Result<(), anyhow::Error>;
+}
+
+/// The stream of the epochs the MCR contract rolls over to.
+pub type EpochStream = std::pin::Pin<Box<dyn Stream<Item = Result<u64, anyhow::Error>> + Send>>;
+
 #[async_trait::async_trait]
 pub trait McrSettlementClientOperations {
 	/// Posts a block commitment to the settlement client.
+	/// Returns the hash of the transaction which posted it.
 	async fn post_block_commitment(
 		&self,
 		block_commitment: BlockCommitment,
-	) -> Result<(), anyhow::Error>;
+	) -> Result<TransactionHash, anyhow::Error>;
 
 	/// Posts a batch of block commitments to the settlement client.
+	/// Returns the hash of the transaction which posted it.
 	async fn post_block_commitment_batch(
 		&self,
 		block_commitment: Vec<BlockCommitment>,
-	) -> Result<(), anyhow::Error>;
+	) -> Result<TransactionHash, anyhow::Error>;
 
 	/// Streams block commitments from the settlement client.
 	async fn stream_block_commitments(&self) -> Result<CommitmentStream, anyhow::Error>;
 
+	/// Persists the position of the stream of block commitments to the given store,
+	/// in place of the one configured for the client, if any.
+	/// Clients without a position to persist ignore the store.
+	fn set_event_cursor_store(&mut self, _store: Arc<dyn EventCursorStore>) {}
+
+	/// Gets the outcome of a transaction posted by the client,
+	/// waiting for the transaction to be mined if it is still pending.
+	async fn get_posted_transaction_status(
+		&self,
+		transaction_hash: TransactionHash,
+	) -> Result<PostedTransactionStatus, anyhow::Error>;
+
 	/// Gets the accepted commitment at the given height.
 	async fn get_commitment_at_height(
 		&self,
@@ -45,3 +96,39 @@ pub trait McrSettlementClientOperations {
 	/// Gets the max tolerable block height.
 	async fn get_max_tolerable_block_height(&self) -> Result<u64, anyhow::Error>;
 }
+
+/// Staking and attester management on the MCR contract, for the attester signing with the client.
+///
+/// The stakes are held by the staking contract of the MCR contract, in the token of the staking contract.
+#[async_trait::async_trait]
+pub trait McrSettlementStakingOperations {
+	/// Stakes an amount of the token for the attester, approving the staking contract to transfer it.
+	/// The stake counts from the next epoch.
+	async fn stake(&self, amount: U256) -> Result<(), anyhow::Error>;
+
+	/// Unstakes an amount of the token for the attester. The unstake takes effect at the next epoch.
+	async fn unstake(&self, amount: U256) -> Result<(), anyhow::Error>;
+
+	/// Gets the current epoch of the MCR contract.
+	async fn get_current_epoch(&self) -> Result<u64, anyhow::Error>;
+
+	/// Gets the stake of an attester in the token at the given epoch.
+	async fn get_stake_at_epoch(
+		&self,
+		epoch: u64,
+		attester: Address,
+	) -> Result<U256, anyhow::Error>;
+
+	/// Gets the stake of an attester at the given epoch, summed over all the tokens of the MCR contract.
+	async fn compute_all_stake_at_epoch(
+		&self,
+		epoch: u64,
+		attester: Address,
+	) -> Result<U256, anyhow::Error>;
+
+	/// Lists the attesters which have staked on the MCR contract.
+	async fn get_attesters(&self) -> Result<Vec<Address>, anyhow::Error>;
+
+	/// Streams the epochs the MCR contract rolls over to.
+	async fn stream_epoch_rollovers(&self) -> Result<EpochStream, anyhow::Error>;
+}
@@ -1,7 +1,9 @@
-use crate::{CommitmentStream, McrSettlementClientOperations};
+use crate::{
+	CommitmentStream, McrSettlementClientOperations, PostedTransactionStatus, TransactionHash,
+};
 use mcr_settlement_config::Config;
 use movement_types::block::BlockCommitment;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::{Arc, Mutex};
 use tokio::sync::{mpsc, RwLock};
 use tokio_stream::wrappers::ReceiverStream;
@@ -11,6 +13,7 @@ pub struct McrSettlementClient {
 	commitments: Arc<RwLock<BTreeMap<u64, BlockCommitment>>>,
 	stream_sender: mpsc::Sender<Result<BlockCommitment, anyhow::Error>>,
 	stream_receiver: Arc<Mutex<Option<mpsc::Receiver<Result<BlockCommitment, anyhow::Error>>>>>,
+	transactions: Arc<RwLock<HashSet<TransactionHash>>>,
 	pub current_height: Arc<RwLock<u64>>,
 	pub block_lead_tolerance: u64,
 	paused_at_height: Arc<RwLock<Option<u64>>>,
@@ -23,12 +26,20 @@ impl McrSettlementClient {
 			commitments: Arc::new(RwLock::new(BTreeMap::new())),
 			stream_sender,
 			stream_receiver: Arc::new(Mutex::new(Some(receiver))),
+			transactions: Arc::new(RwLock::new(HashSet::new())),
 			current_height: Arc::new(RwLock::new(0)),
 			block_lead_tolerance: 16,
 			paused_at_height: Arc::new(RwLock::new(None)),
 		}
 	}
 
+	/// The mock transactions are identified by the height of the last commitment they post.
+	fn transaction_hash(height: u64) -> TransactionHash {
+		let mut hash = [0; 32];
+		hash[24..].copy_from_slice(&height.to_be_bytes());
+		TransactionHash(hash)
+	}
+
 	pub async fn build_with_config(_config: &Config) -> Result<Self, anyhow::Error> {
 		Ok(Self::new())
 	}
@@ -73,7 +84,7 @@ impl McrSettlementClientOperations for McrSettlementClient {
 	async fn post_block_commitment(
 		&self,
 		block_commitment: BlockCommitment,
-	) -> Result<(), anyhow::Error> {
+	) -> Result<TransactionHash, anyhow::Error> {
 		let height = block_commitment.height();
 
 		let settled = {
@@ -97,17 +108,20 @@ impl McrSettlementClientOperations for McrSettlementClient {
 			}
 		}
 
-		Ok(())
+		let transaction_hash = Self::transaction_hash(height);
+		self.transactions.write().await.insert(transaction_hash);
+		Ok(transaction_hash)
 	}
 
 	async fn post_block_commitment_batch(
 		&self,
 		block_commitment: Vec<BlockCommitment>,
-	) -> Result<(), anyhow::Error> {
+	) -> Result<TransactionHash, anyhow::Error> {
+		let mut transaction_hash = TransactionHash::default();
 		for commitment in block_commitment {
-			self.post_block_commitment(commitment).await?;
+			transaction_hash = self.post_block_commitment(commitment).await?;
 		}
-		Ok(())
+		Ok(transaction_hash)
 	}
 
 	async fn stream_block_commitments(&self) -> Result<CommitmentStream, anyhow::Error> {
@@ -120,6 +134,18 @@ impl McrSettlementClientOperations for McrSettlementClient {
 		Ok(Box::pin(ReceiverStream::new(receiver)))
 	}
 
+	async fn get_posted_transaction_status(
+		&self,
+		transaction_hash: TransactionHash,
+	) -> Result<PostedTransactionStatus, anyhow::Error> {
+		// the mock transactions succeed once posted
+		if self.transactions.read().await.contains(&transaction_hash) {
+			Ok(PostedTransactionStatus::Succeeded)
+		} else {
+			Ok(PostedTransactionStatus::Missing)
+		}
+	}
+
 	async fn get_commitment_at_height(
 		&self,
 		height: u64,
@@ -3,8 +3,13 @@ use alloy::providers::Provider;
 use alloy_contract::CallBuilder;
 use alloy_contract::CallDecoder;
 use alloy_network::Ethereum;
+use alloy_primitives::{Address, TxHash};
 use alloy_transport::{Transport, TransportError};
+use mcr_settlement_config::common::transactions::Config as TransactionsConfig;
 use std::marker::PhantomData;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
 
 // Define a rule to verify the error generated when a transaction is send to determine if:
 // * the Transaction must me resend with more gas: return Ok(true)
@@ -35,6 +40,7 @@ impl VerifyRule for SendTransactionErrorRule<UnderPriced> {
 			return Ok(false);
 		};
 
+		// also matches the replacements of pending transactions with too low fees
 		if payload.code == -32000 && payload.message.contains("transaction underpriced") {
 			Ok(true)
 		} else {
@@ -58,90 +64,752 @@ impl VerifyRule for SendTransactionErrorRule<InsufficentFunds> {
 	}
 }
 
-pub async fn send_transaction<
-	P: Provider<T, Ethereum> + Clone,
-	T: Transport + Clone,
-	D: CallDecoder + Clone,
->(
-	base_call_builder: CallBuilder<T, &&P, D, Ethereum>,
-	send_transaction_error_rules: &[Box<dyn VerifyRule>],
+// The nonce of the transaction has been used by a mined transaction.
+fn is_nonce_too_low(error: &alloy_contract::Error) -> bool {
+	let alloy_contract::Error::TransportError(TransportError::ErrorResp(payload)) = error else {
+		return false;
+	};
+	payload.message.contains("nonce too low")
+}
+
+/// The minimum raise of the fees accepted by the nodes to replace a pending transaction.
+const MIN_REPLACEMENT_FEE_PERCENT: u64 = 10;
+
+/// The EIP-1559 fees of a transaction, in wei per gas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fees {
+	pub max_fee_per_gas: u128,
+	pub max_priority_fee_per_gas: u128,
+}
+
+impl Fees {
+	/// The highest of each fee.
+	pub fn max(self, other: Fees) -> Fees {
+		Fees {
+			max_fee_per_gas: self.max_fee_per_gas.max(other.max_fee_per_gas),
+			max_priority_fee_per_gas: self
+				.max_priority_fee_per_gas
+				.max(other.max_priority_fee_per_gas),
+		}
+	}
+}
+
+/// Chooses the fees of the transactions, from the fees estimated by the node
+/// and raised each time a transaction is replaced, within configured caps.
+#[derive(Debug, Clone)]
+pub struct FeeStrategy {
+	max_fee_per_gas_cap: u128,
+	max_priority_fee_per_gas_cap: u128,
+	escalation_percent: u64,
+}
+
+impl FeeStrategy {
+	pub fn new(
+		max_fee_per_gas_cap: u128,
+		max_priority_fee_per_gas_cap: u128,
+		escalation_percent: u64,
+	) -> Self {
+		Self {
+			max_fee_per_gas_cap,
+			max_priority_fee_per_gas_cap,
+			escalation_percent: escalation_percent.max(MIN_REPLACEMENT_FEE_PERCENT),
+		}
+	}
+
+	/// The fees of a new transaction, from the fees estimated by the node.
+	pub fn initial(&self, estimated: Fees) -> Result<Fees, McrEthConnectorError> {
+		self.capped(estimated)
+	}
+
+	/// The fees of a transaction replacing one sent with the given fees.
+	pub fn escalate(&self, fees: Fees) -> Result<Fees, McrEthConnectorError> {
+		// rounded up, as the nodes require the replacements to raise the fees by a minimum
+		let raise = |fee: u128| fee + (fee * self.escalation_percent as u128).div_ceil(100).max(1);
+		self.capped(Fees {
+			max_fee_per_gas: raise(fees.max_fee_per_gas),
+			max_priority_fee_per_gas: raise(fees.max_priority_fee_per_gas),
+		})
+	}
+
+	// A max fee above the cap can't be lowered, as the transaction would not be mined
+	// while the base fee is above it. The priority fee only speeds up the inclusion.
+	fn capped(&self, fees: Fees) -> Result<Fees, McrEthConnectorError> {
+		if fees.max_fee_per_gas > self.max_fee_per_gas_cap {
+			return Err(McrEthConnectorError::FeeCapExceeded(
+				fees.max_fee_per_gas,
+				self.max_fee_per_gas_cap,
+			));
+		}
+		Ok(Fees {
+			max_fee_per_gas: fees.max_fee_per_gas,
+			max_priority_fee_per_gas: fees
+				.max_priority_fee_per_gas
+				.min(self.max_priority_fee_per_gas_cap)
+				.min(fees.max_fee_per_gas),
+		})
+	}
+}
+
+/// A transaction which has been mined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MinedTransaction {
+	hash: TxHash,
+	success: bool,
+	gas_used: u128,
+}
+
+/// The failures to send a transaction which the sender handles.
+#[derive(Debug)]
+enum SendError {
+	/// A transaction with the nonce has been mined.
+	NonceTooLow,
+	/// The node requires higher fees, to accept the transaction or a replacement.
+	FeesTooLow,
+	Other(anyhow::Error),
+}
+
+/// The operations of the chain used to send the transactions of a signer,
+/// apart from the provider so that the nonce handling can be tested.
+trait TransactionChain {
+	/// The nonce of the next transaction of the signer, counting the pending transactions.
+	async fn pending_nonce(&self) -> Result<u64, anyhow::Error>;
+
+	/// Sends the transaction with the given nonce, gas and fees.
+	async fn send(&self, nonce: u64, gas: u128, fees: Fees) -> Result<TxHash, SendError>;
+
+	/// Gets the transaction if it has been mined.
+	async fn get_mined(&self, hash: TxHash) -> Result<Option<MinedTransaction>, anyhow::Error>;
+
+	/// Waits for the transaction to be mined, returning `None` if it is not mined in time.
+	async fn wait_for_mined(
+		&self,
+		hash: TxHash,
+		timeout: Duration,
+	) -> Result<Option<MinedTransaction>, anyhow::Error>;
+}
+
+// The transactions sent with a nonce which may still be pending.
+struct InFlight {
+	nonce: u64,
+	// the fees of the last transaction, which a replacement must raise
+	fees: Fees,
+	// the transactions sent by the current call
+	hashes: Vec<TxHash>,
+	// the transactions sent by previous calls, which failed while their transactions were pending
+	abandoned: Vec<TxHash>,
+}
+
+// Whether a transaction with the in-flight nonce has been mined.
+enum InFlightOutcome {
+	// a transaction of the current call
+	Mined(MinedTransaction),
+	// a transaction of a previous call, which the current call must not report as its own
+	AbandonedMined,
+	Pending,
+}
+
+#[derive(Default)]
+struct NonceState {
+	// The nonce of the next transaction of the signer, once read from the node.
+	next: Option<u64>,
+	in_flight: Option<InFlight>,
+}
+
+impl NonceState {
+	// The transaction with the in-flight nonce has been mined.
+	fn nonce_used(&mut self, nonce: u64) {
+		self.next = Some(nonce + 1);
+		self.in_flight = None;
+	}
+}
+
+/// Sends the transactions of a signer one at a time, tracking their nonce.
+///
+/// A transaction which is not mined in time is replaced by one with the same nonce
+/// and higher fees, so that a transaction stuck in the mempool is not duplicated.
+/// The transactions left pending by a failed call are replaced by the next call,
+/// unless they have been mined or dropped in the meantime.
+pub struct TransactionSender {
+	signer_address: Address,
+	send_transaction_error_rules: Vec<Box<dyn VerifyRule>>,
+	fee_strategy: FeeStrategy,
 	number_retry: u32,
 	gas_limit: u128,
-) -> Result<(), anyhow::Error> {
-	println!("Sending transaction with gas limit: {}", gas_limit);
-	//validate gas price.
-	let mut estimate_gas = base_call_builder.estimate_gas().await.expect("Failed to estimate gas");
-	// Add 20% because initial gas estimate are too low.
-	estimate_gas += (estimate_gas * 20) / 100;
+	receipt_timeout: Duration,
+	nonce: Mutex<NonceState>,
+}
+
+impl TransactionSender {
+	pub fn new(
+		signer_address: Address,
+		send_transaction_error_rules: Vec<Box<dyn VerifyRule>>,
+		config: &TransactionsConfig,
+	) -> Self {
+		Self {
+			signer_address,
+			send_transaction_error_rules,
+			fee_strategy: FeeStrategy::new(
+				config.max_fee_per_gas_cap,
+				config.max_priority_fee_per_gas_cap,
+				config.fee_escalation_percent,
+			),
+			number_retry: config.transaction_send_retries,
+			gas_limit: config.gas_limit as u128,
+			receipt_timeout: Duration::from_millis(config.receipt_timeout),
+			nonce: Mutex::new(NonceState::default()),
+		}
+	}
+
+	/// The time to wait for a sent transaction to be mined.
+	pub fn receipt_timeout(&self) -> Duration {
+		self.receipt_timeout
+	}
 
-	println!("estimated_gas: {}", estimate_gas);
+	/// Sends a transaction and waits for it to be mined, returning its hash.
+	pub async fn send_transaction<
+		P: Provider<T, Ethereum> + Clone,
+		T: Transport + Clone,
+		D: CallDecoder + Clone,
+	>(
+		&self,
+		base_call_builder: CallBuilder<T, &&P, D, Ethereum>,
+	) -> Result<TxHash, anyhow::Error> {
+		let mut estimate_gas = base_call_builder
+			.estimate_gas()
+			.await
+			.map_err(|e| McrEthConnectorError::GasEstimation(e.to_string()))?;
+		// Add 20% because initial gas estimate are too low.
+		estimate_gas += (estimate_gas * 20) / 100;
+		debug!("estimated_gas: {}", estimate_gas);
 
-	// Sending Transaction automatically can lead to errors that depend on the state for Eth.
-	// It's convenient to manage some of them automatically to avoid to fail commitment Transaction.
-	// I define a first one but other should be added depending on the test with mainnet.
-	for _ in 0..number_retry {
-		let call_builder = base_call_builder.clone().gas(estimate_gas);
+		let estimated = base_call_builder
+			.provider
+			.estimate_eip1559_fees(None)
+			.await
+			.map_err(|e| McrEthConnectorError::FeeEstimation(e.to_string()))?;
+		let fees = self.fee_strategy.initial(Fees {
+			max_fee_per_gas: estimated.max_fee_per_gas,
+			max_priority_fee_per_gas: estimated.max_priority_fee_per_gas,
+		})?;
+
+		let chain = ProviderChain {
+			call_builder: base_call_builder,
+			signer_address: self.signer_address,
+			send_transaction_error_rules: &self.send_transaction_error_rules,
+		};
+		self.send_with_chain(&chain, estimate_gas, fees).await
+	}
 
-		//detect if the gas price doesn't execeed the limit.
-		let gas_price = call_builder.provider.get_gas_price().await?;
-		let transaction_fee_wei = estimate_gas * gas_price;
-		if transaction_fee_wei > gas_limit {
-			return Err(McrEthConnectorError::GasLimitExceed(transaction_fee_wei, gas_limit).into());
+	async fn send_with_chain<C: TransactionChain>(
+		&self,
+		chain: &C,
+		mut estimate_gas: u128,
+		mut fees: Fees,
+	) -> Result<TxHash, anyhow::Error> {
+		// the transactions are sent one at a time, so that each one gets the next nonce
+		let mut state = self.nonce.lock().await;
+		// the transactions left in flight belong to a previous call which failed
+		if let Some(in_flight) = &mut state.in_flight {
+			let hashes = std::mem::take(&mut in_flight.hashes);
+			in_flight.abandoned.extend(hashes);
+			self.resolve_abandoned(chain, &mut state).await?;
 		}
 
-		println!("Sending transaction with gas: {}", estimate_gas);
+		// Sending Transaction automatically can lead to errors that depend on the state for Eth.
+		// It's convenient to manage some of them automatically to avoid to fail commitment Transaction.
+		for _ in 0..self.number_retry {
+			let nonce = match (&state.in_flight, state.next) {
+				(Some(in_flight), _) => in_flight.nonce,
+				(None, Some(nonce)) => nonce,
+				(None, None) => {
+					let nonce = chain.pending_nonce().await?;
+					state.next = Some(nonce);
+					nonce
+				}
+			};
+			// the transactions pending with the nonce are replaced
+			if let Some(in_flight) = &state.in_flight {
+				fees = fees.max(self.fee_strategy.escalate(in_flight.fees)?);
+			}
 
-		//send the Transaction and detect send error.
-		let pending_transaction = match call_builder.send().await {
-			Ok(pending_transaction) => pending_transaction,
-			Err(err) => {
-				//apply defined rules.
-				for rule in send_transaction_error_rules {
-					// Verify all rules. If one rule return true or an error stop verification.
-					// If true retry with more gas else return the error.
-					if rule.verify(&err)? {
-						//increase gas of 10% and retry
-						estimate_gas += (estimate_gas * 10) / 100;
-						tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+			//detect if the fees don't exceed the limit.
+			let transaction_fee_wei = estimate_gas * fees.max_fee_per_gas;
+			if transaction_fee_wei > self.gas_limit {
+				return Err(McrEthConnectorError::GasLimitExceed(
+					transaction_fee_wei,
+					self.gas_limit,
+				)
+				.into());
+			}
+
+			debug!(
+				"Sending transaction with nonce: {} gas: {} fees: {:?}",
+				nonce, estimate_gas, fees
+			);
+			//send the Transaction and detect send error.
+			let hash = match chain.send(nonce, estimate_gas, fees).await {
+				Ok(hash) => hash,
+				Err(SendError::NonceTooLow) => {
+					// one of the transactions sent with the nonce may have been mined
+					match self.take_mined(chain, &mut state).await? {
+						InFlightOutcome::Mined(mined) => {
+							match self.handle_mined(mined, &mut estimate_gas)? {
+								Some(hash) => return Ok(hash),
+								None => continue,
+							}
+						}
+						InFlightOutcome::AbandonedMined => continue,
+						InFlightOutcome::Pending => {
+							info!(
+								"Nonce {} has been used, reading the next nonce from the node",
+								nonce
+							);
+							state.next = None;
+							state.in_flight = None;
+							continue;
+						}
+					}
+				}
+				Err(SendError::FeesTooLow) => {
+					fees = self.fee_strategy.escalate(fees)?;
+					continue;
+				}
+				Err(SendError::Other(err)) => return Err(err),
+			};
+			let in_flight = state.in_flight.get_or_insert_with(|| InFlight {
+				nonce,
+				fees,
+				hashes: Vec::new(),
+				abandoned: Vec::new(),
+			});
+			in_flight.fees = fees;
+			in_flight.hashes.push(hash);
+
+			let mined = match chain.wait_for_mined(hash, self.receipt_timeout).await? {
+				Some(mined) => {
+					state.nonce_used(nonce);
+					mined
+				}
+				// a replaced transaction may have been mined in the meantime
+				None => match self.take_mined(chain, &mut state).await? {
+					InFlightOutcome::Mined(mined) => mined,
+					InFlightOutcome::AbandonedMined => continue,
+					InFlightOutcome::Pending => {
+						warn!(
+							"Transaction {} with nonce {} was not mined in {:?}, replacing it",
+							hash, nonce, self.receipt_timeout
+						);
 						continue;
 					}
+				},
+			};
+			match self.handle_mined(mined, &mut estimate_gas)? {
+				Some(hash) => return Ok(hash),
+				None => continue,
+			}
+		}
+
+		//Max retry exceed
+		Err(McrEthConnectorError::MaxRetriesExceeded(self.number_retry).into())
+	}
+
+	// Returns the hash of a successful transaction, or `None` if the transaction
+	// must be sent again with more gas.
+	fn handle_mined(
+		&self,
+		mined: MinedTransaction,
+		estimate_gas: &mut u128,
+	) -> Result<Option<TxHash>, anyhow::Error> {
+		if mined.success {
+			return Ok(Some(mined.hash));
+		}
+		// Transaction execution fail
+		debug!("transaction gas_used: {} / estimate_gas: {}", mined.gas_used, estimate_gas);
+		// Some valid Tx can abort cause of insufficient gas without consuming all its gas.
+		// Define a threshold a little less than estimated gas to detect them.
+		let tx_gas_consumption_threshold = *estimate_gas - (*estimate_gas * 10) / 100;
+		if mined.gas_used >= tx_gas_consumption_threshold {
+			info!("Send commitment Transaction fail because of insufficient gas, transaction:{mined:?}");
+			*estimate_gas += (*estimate_gas * 30) / 100;
+			Ok(None)
+		} else {
+			Err(McrEthConnectorError::RpcTransactionExecution(format!(
+				"Send commitment Transaction fail, abort Transaction, transaction:{mined:?}"
+			))
+			.into())
+		}
+	}
+
+	// Looks for a mined transaction among the ones in flight, moving to the next nonce if there is one.
+	async fn take_mined<C: TransactionChain>(
+		&self,
+		chain: &C,
+		state: &mut NonceState,
+	) -> Result<InFlightOutcome, anyhow::Error> {
+		let Some(in_flight) = &state.in_flight else {
+			return Ok(InFlightOutcome::Pending);
+		};
+		let nonce = in_flight.nonce;
+		for hash in &in_flight.hashes {
+			if let Some(mined) = chain.get_mined(*hash).await? {
+				state.nonce_used(nonce);
+				return Ok(InFlightOutcome::Mined(mined));
+			}
+		}
+		for hash in &in_flight.abandoned {
+			if let Some(mined) = chain.get_mined(*hash).await? {
+				warn!(
+					"Transaction {} of a previous call was mined with nonce {}, success: {}",
+					mined.hash, nonce, mined.success
+				);
+				state.nonce_used(nonce);
+				return Ok(InFlightOutcome::AbandonedMined);
+			}
+		}
+		Ok(InFlightOutcome::Pending)
+	}
+
+	// Checks whether the transactions abandoned by a previous call have been mined or dropped,
+	// or are still pending and have to be replaced.
+	async fn resolve_abandoned<C: TransactionChain>(
+		&self,
+		chain: &C,
+		state: &mut NonceState,
+	) -> Result<(), anyhow::Error> {
+		self.take_mined(chain, state).await?;
+		let Some(in_flight) = &state.in_flight else {
+			return Ok(());
+		};
+		let pending_nonce = chain.pending_nonce().await?;
+		if pending_nonce <= in_flight.nonce {
+			info!(
+				"Transactions with nonce {} have been dropped, sending with nonce {}",
+				in_flight.nonce, pending_nonce
+			);
+			state.next = Some(pending_nonce);
+			state.in_flight = None;
+		}
+		Ok(())
+	}
+}
+
+// The chain as seen through the provider of a contract call.
+struct ProviderChain<'r, B> {
+	call_builder: B,
+	signer_address: Address,
+	send_transaction_error_rules: &'r [Box<dyn VerifyRule>],
+}
+
+/// The interval to poll the node for the receipt of a sent transaction.
+pub(crate) const RECEIPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+impl<'r, 'a, 'b, P, T, D> TransactionChain
+	for ProviderChain<'r, CallBuilder<T, &'a &'b P, D, Ethereum>>
+where
+	P: Provider<T, Ethereum> + Clone,
+	T: Transport + Clone,
+	D: CallDecoder + Clone,
+{
+	async fn pending_nonce(&self) -> Result<u64, anyhow::Error> {
+		Ok(self
+			.call_builder
+			.provider
+			.get_transaction_count(self.signer_address)
+			.pending()
+			.await?)
+	}
+
+	async fn send(&self, nonce: u64, gas: u128, fees: Fees) -> Result<TxHash, SendError> {
+		let call_builder = self
+			.call_builder
+			.clone()
+			.gas(gas)
+			.nonce(nonce)
+			.max_fee_per_gas(fees.max_fee_per_gas)
+			.max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+		match call_builder.send().await {
+			Ok(pending_transaction) => Ok(*pending_transaction.tx_hash()),
+			Err(err) if is_nonce_too_low(&err) => Err(SendError::NonceTooLow),
+			Err(err) => {
+				// Verify all rules. If one rule return true or an error stop verification.
+				// If true retry with higher fees else return the error.
+				for rule in self.send_transaction_error_rules {
+					match rule.verify(&err) {
+						Ok(true) => return Err(SendError::FeesTooLow),
+						Ok(false) => {}
+						Err(err) => return Err(SendError::Other(err.into())),
+					}
 				}
+				Err(SendError::Other(McrEthConnectorError::from(err).into()))
+			}
+		}
+	}
+
+	async fn get_mined(&self, hash: TxHash) -> Result<Option<MinedTransaction>, anyhow::Error> {
+		let transaction_receipt = self.call_builder.provider.get_transaction_receipt(hash).await?;
+		Ok(transaction_receipt.map(|transaction_receipt| MinedTransaction {
+			hash: transaction_receipt.transaction_hash,
+			success: transaction_receipt.status(),
+			gas_used: transaction_receipt.gas_used,
+		}))
+	}
 
-				return Err(McrEthConnectorError::from(err).into());
+	async fn wait_for_mined(
+		&self,
+		hash: TxHash,
+		timeout: Duration,
+	) -> Result<Option<MinedTransaction>, anyhow::Error> {
+		let wait = async {
+			loop {
+				if let Some(mined) = self.get_mined(hash).await? {
+					return Ok::<_, anyhow::Error>(mined);
+				}
+				tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
 			}
 		};
+		match tokio::time::timeout(timeout, wait).await {
+			Ok(mined) => mined.map(Some),
+			Err(_) => Ok(None),
+		}
+	}
+}
 
-		match pending_transaction.get_receipt().await {
-			// Transaction execution fail
-			Ok(transaction_receipt) if !transaction_receipt.status() => {
-				tracing::debug!(
-					"transaction_receipt.gas_used: {} / estimate_gas: {estimate_gas}",
-					transaction_receipt.gas_used
-				);
-				// Some valid Tx can abort cause of insufficient gas without consuming all its gas.
-				// Define a threshold a little less than estimated gas to detect them.
-				let tx_gas_consumption_threshold = estimate_gas - (estimate_gas * 10) / 100;
-				if transaction_receipt.gas_used >= tx_gas_consumption_threshold {
-					tracing::info!("Send commitment Transaction  fail because of insufficient gas, receipt:{transaction_receipt:?} ");
-					estimate_gas += (estimate_gas * 30) / 100;
-					continue;
-				} else {
-					return Err(McrEthConnectorError::RpcTransactionExecution(format!(
-						"Send commitment Transaction fail, abort Transaction, receipt:{transaction_receipt:?}"
-					))
-					.into());
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use std::collections::{BTreeMap, HashMap};
+
+	#[derive(Default)]
+	struct MockChainState {
+		// the nonce of the next transaction to mine
+		mined_nonce: u64,
+		// the pending transactions by nonce
+		pending: BTreeMap<u64, (TxHash, Fees)>,
+		mined: HashMap<TxHash, MinedTransaction>,
+		sent: Vec<(u64, TxHash, Fees)>,
+		// the number of waits for a transaction to be mined which time out
+		stalled_waits: u32,
+	}
+
+	/// A chain mining the pending transactions of the signer when asked to.
+	#[derive(Default)]
+	struct MockChain {
+		state: std::sync::Mutex<MockChainState>,
+	}
+
+	impl MockChain {
+		fn mine_pending(&self) {
+			let mut state = self.state.lock().unwrap();
+			loop {
+				let nonce = state.mined_nonce;
+				let Some((hash, _)) = state.pending.remove(&nonce) else {
+					break;
+				};
+				state.mined.insert(hash, MinedTransaction { hash, success: true, gas_used: 1 });
+				state.mined_nonce += 1;
+			}
+		}
+
+		fn stall_waits(&self, stalled_waits: u32) {
+			self.state.lock().unwrap().stalled_waits = stalled_waits;
+		}
+
+		fn sent(&self) -> Vec<(u64, TxHash, Fees)> {
+			self.state.lock().unwrap().sent.clone()
+		}
+	}
+
+	impl TransactionChain for MockChain {
+		async fn pending_nonce(&self) -> Result<u64, anyhow::Error> {
+			let state = self.state.lock().unwrap();
+			let mut nonce = state.mined_nonce;
+			while state.pending.contains_key(&nonce) {
+				nonce += 1;
+			}
+			Ok(nonce)
+		}
+
+		async fn send(&self, nonce: u64, _gas: u128, fees: Fees) -> Result<TxHash, SendError> {
+			let mut state = self.state.lock().unwrap();
+			if nonce < state.mined_nonce {
+				return Err(SendError::NonceTooLow);
+			}
+			if let Some((_, pending_fees)) = state.pending.get(&nonce) {
+				if fees.max_fee_per_gas * 100 < pending_fees.max_fee_per_gas * 110 {
+					return Err(SendError::FeesTooLow);
 				}
 			}
-			Ok(_) => return Ok(()),
-			Err(err) => {
-				return Err(McrEthConnectorError::RpcTransactionExecution(err.to_string()).into())
+			let hash = TxHash::with_last_byte(state.sent.len() as u8 + 1);
+			state.pending.insert(nonce, (hash, fees));
+			state.sent.push((nonce, hash, fees));
+			Ok(hash)
+		}
+
+		async fn get_mined(&self, hash: TxHash) -> Result<Option<MinedTransaction>, anyhow::Error> {
+			Ok(self.state.lock().unwrap().mined.get(&hash).cloned())
+		}
+
+		async fn wait_for_mined(
+			&self,
+			hash: TxHash,
+			_timeout: Duration,
+		) -> Result<Option<MinedTransaction>, anyhow::Error> {
+			{
+				let mut state = self.state.lock().unwrap();
+				if state.stalled_waits > 0 {
+					state.stalled_waits -= 1;
+					return Ok(None);
+				}
 			}
+			self.mine_pending();
+			self.get_mined(hash).await
+		}
+	}
+
+	fn sender(number_retry: u32, max_fee_per_gas_cap: u128) -> TransactionSender {
+		let config = TransactionsConfig {
+			transaction_send_retries: number_retry,
+			max_fee_per_gas_cap,
+			max_priority_fee_per_gas_cap: max_fee_per_gas_cap,
+			fee_escalation_percent: 10,
+			gas_limit: u64::MAX,
+			..Default::default()
 		};
+		TransactionSender::new(Address::ZERO, Vec::new(), &config)
+	}
+
+	const FEES: Fees = Fees { max_fee_per_gas: 100, max_priority_fee_per_gas: 10 };
+
+	#[tokio::test]
+	async fn test_stuck_transaction_is_replaced() -> Result<(), anyhow::Error> {
+		let sender = sender(5, 1_000);
+		let chain = MockChain::default();
+
+		chain.stall_waits(2);
+		let hash = sender.send_with_chain(&chain, 10, FEES).await?;
+		let sent = chain.sent();
+		assert_eq!(sent.iter().map(|(nonce, _, _)| *nonce).collect::<Vec<_>>(), vec![0, 0, 0]);
+		assert_eq!(sent[1].2, Fees { max_fee_per_gas: 110, max_priority_fee_per_gas: 11 });
+		assert_eq!(sent[2].2, Fees { max_fee_per_gas: 121, max_priority_fee_per_gas: 13 });
+		assert_eq!(hash, sent[2].1);
+
+		// the next transaction gets the next nonce
+		let hash = sender.send_with_chain(&chain, 10, FEES).await?;
+		assert_eq!(chain.sent()[3], (1, hash, FEES));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_failed_call_hash_is_not_reported() -> Result<(), anyhow::Error> {
+		let sender = sender(2, 1_000);
+		let chain = MockChain::default();
+
+		chain.stall_waits(2);
+		let err = sender.send_with_chain(&chain, 10, FEES).await.unwrap_err();
+		assert!(matches!(
+			err.downcast_ref::<McrEthConnectorError>(),
+			Some(McrEthConnectorError::MaxRetriesExceeded(2))
+		));
+		let abandoned = chain.sent();
+
+		// the transaction of the failed call is mined before the next call
+		chain.mine_pending();
+		let hash = sender.send_with_chain(&chain, 10, FEES).await?;
+		assert!(abandoned.iter().all(|(_, abandoned, _)| *abandoned != hash));
+		assert_eq!(chain.sent().last(), Some(&(1, hash, FEES)));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_abandoned_transaction_is_replaced() -> Result<(), anyhow::Error> {
+		let sender = sender(5, 1_000);
+		let chain = MockChain::default();
+
+		chain.stall_waits(5);
+		assert!(sender.send_with_chain(&chain, 10, FEES).await.is_err());
+		let abandoned = chain.sent();
+
+		// the next call replaces the pending transaction with its own
+		let hash = sender.send_with_chain(&chain, 10, FEES).await?;
+		let (nonce, replacement, fees) = *chain.sent().last().unwrap();
+		assert_eq!((nonce, replacement), (0, hash));
+		assert!(fees.max_fee_per_gas > abandoned.last().unwrap().2.max_fee_per_gas);
+		for (_, abandoned, _) in abandoned {
+			assert_eq!(chain.get_mined(abandoned).await?, None);
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_transaction_at_fee_cap_is_resolved() -> Result<(), anyhow::Error> {
+		let sender = sender(5, 115);
+		let chain = MockChain::default();
+
+		// the fees can be raised once before reaching the cap
+		chain.stall_waits(5);
+		let err = sender.send_with_chain(&chain, 10, FEES).await.unwrap_err();
+		assert!(matches!(
+			err.downcast_ref::<McrEthConnectorError>(),
+			Some(McrEthConnectorError::FeeCapExceeded(..))
+		));
+		assert!(sender.send_with_chain(&chain, 10, FEES).await.is_err());
+
+		// once the pending transaction is mined, the next call moves on to the next nonce
+		chain.mine_pending();
+		chain.stall_waits(0);
+		let hash = sender.send_with_chain(&chain, 10, FEES).await?;
+		assert_eq!(chain.sent().last(), Some(&(1, hash, FEES)));
+
+		// a dropped transaction is sent again with its nonce
+		chain.stall_waits(5);
+		assert!(sender.send_with_chain(&chain, 10, FEES).await.is_err());
+		chain.state.lock().unwrap().pending.clear();
+		chain.stall_waits(0);
+		let hash = sender.send_with_chain(&chain, 10, FEES).await?;
+		assert_eq!(chain.sent().last(), Some(&(2, hash, FEES)));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_nonce_used_by_another_sender() -> Result<(), anyhow::Error> {
+		let sender = sender(5, 1_000);
+		let chain = MockChain::default();
+		sender.send_with_chain(&chain, 10, FEES).await?;
+
+		// another sender of the signer used the next nonce
+		chain.state.lock().unwrap().mined_nonce = 2;
+		let hash = sender.send_with_chain(&chain, 10, FEES).await?;
+		assert_eq!(chain.sent().last(), Some(&(2, hash, FEES)));
+		Ok(())
 	}
 
-	//Max retry exceed
-	Err(McrEthConnectorError::RpcTransactionExecution(
-		"Send commitment Transaction fail because of exceed max retry".to_string(),
-	)
-	.into())
+	#[test]
+	fn test_initial_fees_are_capped() {
+		let strategy = FeeStrategy::new(100, 10, 25);
+		let fees = strategy
+			.initial(Fees { max_fee_per_gas: 80, max_priority_fee_per_gas: 20 })
+			.unwrap();
+		assert_eq!(fees, Fees { max_fee_per_gas: 80, max_priority_fee_per_gas: 10 });
+
+		assert!(matches!(
+			strategy.initial(Fees { max_fee_per_gas: 120, max_priority_fee_per_gas: 2 }),
+			Err(McrEthConnectorError::FeeCapExceeded(120, 100))
+		));
+	}
+
+	#[test]
+	fn test_fees_escalate_up_to_the_cap() {
+		// the escalation is raised to the minimum accepted for a replacement
+		let strategy = FeeStrategy::new(140, 100, 5);
+		let fees = Fees { max_fee_per_gas: 100, max_priority_fee_per_gas: 5 };
+
+		let fees = strategy.escalate(fees).unwrap();
+		assert_eq!(fees, Fees { max_fee_per_gas: 110, max_priority_fee_per_gas: 6 });
+		let fees = strategy.escalate(fees).unwrap();
+		assert_eq!(fees, Fees { max_fee_per_gas: 121, max_priority_fee_per_gas: 7 });
+		let fees = strategy.escalate(fees).unwrap();
+		assert_eq!(fees, Fees { max_fee_per_gas: 134, max_priority_fee_per_gas: 8 });
+		assert!(matches!(strategy.escalate(fees), Err(McrEthConnectorError::FeeCapExceeded(..))));
+	}
 }
@@ -0,0 +1,555 @@
+//! In-process simulation of the MCR contract and of its staking contract,
+//! to settle commitments with several attesters without an L1 node.
+
+use crate::{
+	CommitmentStream, EpochStream, McrSettlementClientOperations, McrSettlementStakingOperations,
+	PostedTransactionStatus, TransactionHash,
+};
+use alloy::signers::local::PrivateKeySigner;
+use alloy_primitives::{Address, U256};
+use anyhow::Context;
+use mcr_settlement_config::Config;
+use movement_types::block::{BlockCommitment, Commitment};
+use thiserror::Error;
+use tokio::sync::{mpsc, Mutex};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// The reverts of the simulated MCR contract.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum McrSimulatorError {
+	#[error("MCR simulator: attester {0} has already committed to a block at height {1}")]
+	AttesterAlreadyCommitted(Address, u64),
+	#[error("MCR simulator: block height {0} is above the max tolerable block height {1}")]
+	AboveMaxTolerableBlockHeight(u64, u64),
+	#[error("MCR simulator: block at height {0} is not assigned to the current epoch {1}")]
+	UnacceptableBlockCommitment(u64, u64),
+}
+
+/// The events emitted by the simulated contracts.
+enum Event {
+	BlockAccepted(BlockCommitment),
+	EpochRolledOver(u64),
+}
+
+/// The state of the MCR contract and of its staking contract, with a single custodian.
+#[derive(Clone, Default)]
+struct Contract {
+	leading_block_tolerance: u64,
+	slash_amount: U256,
+	last_accepted_block_height: u64,
+	current_epoch: u64,
+	epoch_by_block_time: u64,
+	// the attesters in the order they started staking
+	attesters: Vec<Address>,
+	stakes: HashMap<(u64, Address), U256>,
+	unstakes: HashMap<(u64, Address), U256>,
+	commitments: BTreeMap<u64, HashMap<Address, BlockCommitment>>,
+	commitment_stakes: HashMap<(u64, Commitment), U256>,
+	block_height_epoch_assignments: BTreeMap<u64, u64>,
+	accepted_blocks: BTreeMap<u64, BlockCommitment>,
+}
+
+impl Contract {
+	fn add_attester(&mut self, attester: Address) {
+		if !self.attesters.contains(&attester) {
+			self.attesters.push(attester);
+		}
+	}
+
+	fn stake_at_epoch(&self, epoch: u64, attester: Address) -> U256 {
+		self.stakes.get(&(epoch, attester)).copied().unwrap_or_default()
+	}
+
+	fn total_stake_at_epoch(&self, epoch: u64) -> U256 {
+		self.attesters
+			.iter()
+			.fold(U256::ZERO, |total, attester| total + self.stake_at_epoch(epoch, *attester))
+	}
+
+	fn max_tolerable_block_height(&self) -> u64 {
+		self.last_accepted_block_height + self.leading_block_tolerance
+	}
+
+	// Stakes and unstakes take effect at the epoch following the one of the block time.
+	fn stake(&mut self, attester: Address, amount: U256) {
+		self.add_attester(attester);
+		*self.stakes.entry((self.epoch_by_block_time + 1, attester)).or_default() += amount;
+	}
+
+	fn unstake(&mut self, attester: Address, amount: U256) {
+		*self.unstakes.entry((self.epoch_by_block_time + 1, attester)).or_default() += amount;
+	}
+
+	fn submit_block_commitment(
+		&mut self,
+		attester: Address,
+		block_commitment: BlockCommitment,
+		events: &mut Vec<Event>,
+	) -> Result<(), McrSimulatorError> {
+		let height = block_commitment.height();
+		let max_tolerable_block_height = self.max_tolerable_block_height();
+		let attester_commitments = self.commitments.entry(height).or_default();
+		if attester_commitments.contains_key(&attester) {
+			return Err(McrSimulatorError::AttesterAlreadyCommitted(attester, height));
+		}
+		// commitments to already accepted heights are allowed for the lagging attesters
+		if height > max_tolerable_block_height {
+			return Err(McrSimulatorError::AboveMaxTolerableBlockHeight(
+				height,
+				max_tolerable_block_height,
+			));
+		}
+		attester_commitments.insert(attester, block_commitment.clone());
+		self.block_height_epoch_assignments
+			.entry(height)
+			.or_insert(self.epoch_by_block_time);
+
+		let stake = self.stake_at_epoch(self.current_epoch, attester);
+		*self
+			.commitment_stakes
+			.entry((height, block_commitment.commitment()))
+			.or_default() += stake;
+
+		// the heights committed to ahead are accepted in order
+		while self.tick_on_block_height(self.last_accepted_block_height + 1, events)? {}
+		Ok(())
+	}
+
+	fn tick_on_block_height(
+		&mut self,
+		height: u64,
+		events: &mut Vec<Event>,
+	) -> Result<bool, McrSimulatorError> {
+		let Some(&block_epoch) = self.block_height_epoch_assignments.get(&height) else {
+			return Ok(false);
+		};
+		// no block may have been committed to during the epochs in between
+		while self.current_epoch < block_epoch {
+			self.roll_over_epoch(events);
+		}
+
+		let supermajority = U256::from(2) * self.total_stake_at_epoch(block_epoch) / U256::from(3);
+		let accepted = self.commitments.get(&height).and_then(|commitments| {
+			self.attesters.iter().filter_map(|attester| commitments.get(attester)).find(
+				|block_commitment| {
+					self.commitment_stakes
+						.get(&(height, block_commitment.commitment()))
+						.is_some_and(|stake| *stake > supermajority)
+				},
+			)
+		});
+		match accepted.cloned() {
+			Some(block_commitment) => {
+				self.accept_block_commitment(block_commitment, events)?;
+				Ok(true)
+			}
+			None => Ok(false),
+		}
+	}
+
+	fn accept_block_commitment(
+		&mut self,
+		block_commitment: BlockCommitment,
+		events: &mut Vec<Event>,
+	) -> Result<(), McrSimulatorError> {
+		let height = block_commitment.height();
+		if self.block_height_epoch_assignments.get(&height) != Some(&self.current_epoch) {
+			return Err(McrSimulatorError::UnacceptableBlockCommitment(height, self.current_epoch));
+		}
+		self.accepted_blocks.insert(height, block_commitment.clone());
+		self.last_accepted_block_height = height;
+		self.slash_minority(&block_commitment);
+		events.push(Event::BlockAccepted(block_commitment));
+
+		if self.epoch_by_block_time > self.current_epoch {
+			self.roll_over_epoch(events);
+		}
+		Ok(())
+	}
+
+	// The attesters which committed to another commitment at the height of the accepted one
+	// lose the slash amount from their stake in the current epoch.
+	fn slash_minority(&mut self, block_commitment: &BlockCommitment) {
+		let Some(commitments) = self.commitments.get(&block_commitment.height()) else {
+			return;
+		};
+		for (attester, commitment) in commitments {
+			if commitment.commitment() != block_commitment.commitment() {
+				let stake = self.stakes.entry((self.current_epoch, *attester)).or_default();
+				*stake = stake.saturating_sub(self.slash_amount);
+			}
+		}
+	}
+
+	// The stakes are rolled over to the next epoch, less the unstakes for it.
+	fn roll_over_epoch(&mut self, events: &mut Vec<Event>) {
+		let epoch = self.current_epoch;
+		let remainders = self
+			.attesters
+			.iter()
+			.map(|attester| {
+				let stake = self.stake_at_epoch(epoch, *attester);
+				let unstake =
+					self.unstakes.get(&(epoch + 1, *attester)).copied().unwrap_or_default();
+				(*attester, stake - unstake.min(stake))
+			})
+			.collect::<Vec<_>>();
+		for (attester, remainder) in remainders {
+			*self.stakes.entry((epoch + 1, attester)).or_default() += remainder;
+		}
+		self.current_epoch = epoch + 1;
+		events.push(Event::EpochRolledOver(epoch));
+	}
+}
+
+struct Inner {
+	contract: Contract,
+	transaction_count: u64,
+	commitment_senders: Vec<mpsc::UnboundedSender<Result<BlockCommitment, anyhow::Error>>>,
+	epoch_senders: Vec<mpsc::UnboundedSender<Result<u64, anyhow::Error>>>,
+}
+
+impl Inner {
+	fn emit(&mut self, events: Vec<Event>) {
+		for event in events {
+			match event {
+				Event::BlockAccepted(block_commitment) => self
+					.commitment_senders
+					.retain(|sender| sender.send(Ok(block_commitment.clone())).is_ok()),
+				Event::EpochRolledOver(epoch) => {
+					self.epoch_senders.retain(|sender| sender.send(Ok(epoch)).is_ok())
+				}
+			}
+		}
+	}
+}
+
+/// Simulates the MCR contract accepting the commitments of several attesters.
+///
+/// The acceptance rules of the contract are modeled: a commitment is accepted at a height
+/// once it has more than two thirds of the stake of the epoch of the height, in the order
+/// of the heights and within the leading block tolerance. The epochs are rolled over by the
+/// acceptances, up to the epoch of the simulated block time, which is set with
+/// [`McrSimulator::set_epoch_by_block_time`].
+///
+/// The attesters settle through the clients returned by [`McrSimulator::attester`].
+#[derive(Clone)]
+pub struct McrSimulator {
+	inner: Arc<Mutex<Inner>>,
+}
+
+impl McrSimulator {
+	/// Creates a simulator where the attesters committing to a rejected commitment
+	/// are slashed of the given amount.
+	pub fn new(leading_block_tolerance: u64, slash_amount: U256) -> Self {
+		let contract = Contract { leading_block_tolerance, slash_amount, ..Default::default() };
+		McrSimulator {
+			inner: Arc::new(Mutex::new(Inner {
+				contract,
+				transaction_count: 0,
+				commitment_senders: Vec::new(),
+				epoch_senders: Vec::new(),
+			})),
+		}
+	}
+
+	/// Creates a simulator from the configuration, with the signer of the settlement configuration
+	/// as its attester, and returns the client of the attester.
+	pub async fn build_with_config(
+		config: &Config,
+	) -> Result<(Self, SimulatedAttester), anyhow::Error> {
+		let simulator_config = config.simulator.clone().unwrap_or_default();
+		let signer = config
+			.settle
+			.signer_private_key
+			.parse::<PrivateKeySigner>()
+			.context("Failed to parse the signer private key")?;
+		let simulator = Self::new(simulator_config.leading_block_tolerance, U256::ZERO);
+		let attester = simulator
+			.add_attester(signer.address(), U256::from(simulator_config.attester_stake))
+			.await;
+		Ok((simulator, attester))
+	}
+
+	/// Adds an attester with a stake in the current epoch, as the genesis ceremony does,
+	/// and returns its client.
+	pub async fn add_attester(&self, attester: Address, stake: U256) -> SimulatedAttester {
+		{
+			let mut inner = self.inner.lock().await;
+			let contract = &mut inner.contract;
+			contract.add_attester(attester);
+			*contract.stakes.entry((contract.current_epoch, attester)).or_default() += stake;
+		}
+		self.attester(attester)
+	}
+
+	/// Gets the client of an attester.
+	pub fn attester(&self, attester: Address) -> SimulatedAttester {
+		SimulatedAttester { address: attester, simulator: self.clone() }
+	}
+
+	/// Moves the simulated block time to the given epoch.
+	pub async fn set_epoch_by_block_time(&self, epoch: u64) {
+		self.inner.lock().await.contract.epoch_by_block_time = epoch;
+	}
+
+	/// Gets the commitment of an attester at the given height, if it committed to one.
+	pub async fn get_attester_commitment_at_height(
+		&self,
+		height: u64,
+		attester: Address,
+	) -> Option<BlockCommitment> {
+		let inner = self.inner.lock().await;
+		inner.contract.commitments.get(&height)?.get(&attester).cloned()
+	}
+
+	/// Submits the commitments of an attester in a single transaction,
+	/// which leaves the contracts unchanged if it reverts.
+	async fn submit(
+		&self,
+		attester: Address,
+		block_commitments: Vec<BlockCommitment>,
+	) -> Result<TransactionHash, anyhow::Error> {
+		let mut inner = self.inner.lock().await;
+		let mut contract = inner.contract.clone();
+		let mut events = Vec::new();
+		for block_commitment in block_commitments {
+			contract.submit_block_commitment(attester, block_commitment, &mut events)?;
+		}
+		inner.contract = contract;
+		inner.emit(events);
+
+		// the simulated transactions are identified by their order
+		inner.transaction_count += 1;
+		let mut hash = [0; 32];
+		hash[24..].copy_from_slice(&inner.transaction_count.to_be_bytes());
+		Ok(TransactionHash(hash))
+	}
+}
+
+/// The settlement client of an attester of the [`McrSimulator`].
+#[derive(Clone)]
+pub struct SimulatedAttester {
+	address: Address,
+	simulator: McrSimulator,
+}
+
+impl SimulatedAttester {
+	pub fn address(&self) -> Address {
+		self.address
+	}
+}
+
+#[async_trait::async_trait]
+impl McrSettlementClientOperations for SimulatedAttester {
+	async fn post_block_commitment(
+		&self,
+		block_commitment: BlockCommitment,
+	) -> Result<TransactionHash, anyhow::Error> {
+		self.simulator.submit(self.address, vec![block_commitment]).await
+	}
+
+	async fn post_block_commitment_batch(
+		&self,
+		block_commitment: Vec<BlockCommitment>,
+	) -> Result<TransactionHash, anyhow::Error> {
+		self.simulator.submit(self.address, block_commitment).await
+	}
+
+	async fn stream_block_commitments(&self) -> Result<CommitmentStream, anyhow::Error> {
+		let (sender, receiver) = mpsc::unbounded_channel();
+		self.simulator.inner.lock().await.commitment_senders.push(sender);
+		Ok(Box::pin(UnboundedReceiverStream::new(receiver)))
+	}
+
+	async fn get_posted_transaction_status(
+		&self,
+		transaction_hash: TransactionHash,
+	) -> Result<PostedTransactionStatus, anyhow::Error> {
+		// the reverted submissions have no transaction, so every transaction counted has succeeded
+		let inner = self.simulator.inner.lock().await;
+		let (prefix, count) = transaction_hash.0.split_at(24);
+		let count = u64::from_be_bytes(count.try_into()?);
+		if prefix.iter().all(|byte| *byte == 0) && (1..=inner.transaction_count).contains(&count) {
+			Ok(PostedTransactionStatus::Succeeded)
+		} else {
+			Ok(PostedTransactionStatus::Missing)
+		}
+	}
+
+	async fn get_commitment_at_height(
+		&self,
+		height: u64,
+	) -> Result<Option<BlockCommitment>, anyhow::Error> {
+		let inner = self.simulator.inner.lock().await;
+		Ok(inner.contract.accepted_blocks.get(&height).cloned())
+	}
+
+	async fn get_max_tolerable_block_height(&self) -> Result<u64, anyhow::Error> {
+		Ok(self.simulator.inner.lock().await.contract.max_tolerable_block_height())
+	}
+}
+
+#[async_trait::async_trait]
+impl McrSettlementStakingOperations for SimulatedAttester {
+	async fn stake(&self, amount: U256) -> Result<(), anyhow::Error> {
+		self.simulator.inner.lock().await.contract.stake(self.address, amount);
+		Ok(())
+	}
+
+	async fn unstake(&self, amount: U256) -> Result<(), anyhow::Error> {
+		self.simulator.inner.lock().await.contract.unstake(self.address, amount);
+		Ok(())
+	}
+
+	async fn get_current_epoch(&self) -> Result<u64, anyhow::Error> {
+		Ok(self.simulator.inner.lock().await.contract.current_epoch)
+	}
+
+	async fn get_stake_at_epoch(
+		&self,
+		epoch: u64,
+		attester: Address,
+	) -> Result<U256, anyhow::Error> {
+		Ok(self.simulator.inner.lock().await.contract.stake_at_epoch(epoch, attester))
+	}
+
+	async fn compute_all_stake_at_epoch(
+		&self,
+		epoch: u64,
+		attester: Address,
+	) -> Result<U256, anyhow::Error> {
+		// the simulated staking contract has a single custodian
+		self.get_stake_at_epoch(epoch, attester).await
+	}
+
+	async fn get_attesters(&self) -> Result<Vec<Address>, anyhow::Error> {
+		Ok(self.simulator.inner.lock().await.contract.attesters.clone())
+	}
+
+	async fn stream_epoch_rollovers(&self) -> Result<EpochStream, anyhow::Error> {
+		let (sender, receiver) = mpsc::unbounded_channel();
+		self.simulator.inner.lock().await.epoch_senders.push(sender);
+		Ok(Box::pin(UnboundedReceiverStream::new(receiver)))
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use tokio_stream::StreamExt;
+
+	fn commitment(height: u64, data: u8) -> BlockCommitment {
+		BlockCommitment::new(height, Default::default(), Commitment::new([data; 32]))
+	}
+
+	async fn attesters(simulator: &McrSimulator, stakes: &[u64]) -> Vec<SimulatedAttester> {
+		let mut attesters = Vec::new();
+		for (i, stake) in stakes.iter().enumerate() {
+			let address = Address::with_last_byte(i as u8 + 1);
+			attesters.push(simulator.add_attester(address, U256::from(*stake)).await);
+		}
+		attesters
+	}
+
+	#[tokio::test]
+	async fn test_supermajority_accepts_and_slashes_minority() -> Result<(), anyhow::Error> {
+		let simulator = McrSimulator::new(4, U256::from(5));
+		let attesters = attesters(&simulator, &[40, 40, 20]).await;
+		let mut stream = attesters[0].stream_block_commitments().await?;
+
+		// the dissenting attester alone is short of the supermajority
+		attesters[2].post_block_commitment(commitment(1, 2)).await?;
+		attesters[0].post_block_commitment(commitment(1, 1)).await?;
+		assert_eq!(attesters[0].get_commitment_at_height(1).await?, None);
+		assert_eq!(attesters[0].get_max_tolerable_block_height().await?, 4);
+
+		attesters[1].post_block_commitment(commitment(1, 1)).await?;
+		assert_eq!(stream.next().await.expect("stream has ended")?, commitment(1, 1));
+		assert_eq!(attesters[0].get_commitment_at_height(1).await?, Some(commitment(1, 1)));
+		assert_eq!(attesters[0].get_max_tolerable_block_height().await?, 5);
+
+		let dissenter = attesters[2].address();
+		assert_eq!(attesters[0].get_stake_at_epoch(0, dissenter).await?, U256::from(15));
+		assert_eq!(
+			attesters[0].get_stake_at_epoch(0, attesters[1].address()).await?,
+			U256::from(40)
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_commitments_beyond_tolerance_revert() -> Result<(), anyhow::Error> {
+		let simulator = McrSimulator::new(2, U256::ZERO);
+		let attesters = attesters(&simulator, &[1]).await;
+		let attester = attesters[0].address();
+
+		// the batch reverts as a whole
+		let err = attesters[0]
+			.post_block_commitment_batch(vec![commitment(2, 2), commitment(3, 3)])
+			.await
+			.unwrap_err();
+		assert_eq!(
+			err.downcast_ref::<McrSimulatorError>(),
+			Some(&McrSimulatorError::AboveMaxTolerableBlockHeight(3, 2))
+		);
+		assert_eq!(simulator.get_attester_commitment_at_height(2, attester).await, None);
+
+		// once the blocks below are accepted, the window moves on
+		attesters[0]
+			.post_block_commitment_batch(vec![commitment(2, 2), commitment(1, 1)])
+			.await?;
+		attesters[0].post_block_commitment(commitment(3, 3)).await?;
+		assert_eq!(attesters[0].get_commitment_at_height(3).await?, Some(commitment(3, 3)));
+
+		let err = attesters[0].post_block_commitment(commitment(3, 4)).await.unwrap_err();
+		assert_eq!(
+			err.downcast_ref::<McrSimulatorError>(),
+			Some(&McrSimulatorError::AttesterAlreadyCommitted(attester, 3))
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_epoch_rollover() -> Result<(), anyhow::Error> {
+		let simulator = McrSimulator::new(4, U256::ZERO);
+		let attesters = attesters(&simulator, &[10, 10]).await;
+		let mut epochs = attesters[0].stream_epoch_rollovers().await?;
+
+		// the stake and unstake take effect at the next epoch
+		attesters[0].stake(U256::from(20)).await?;
+		attesters[1].unstake(U256::from(4)).await?;
+		assert_eq!(
+			attesters[0].get_stake_at_epoch(0, attesters[0].address()).await?,
+			U256::from(10)
+		);
+
+		// a commitment to a height of the next epoch rolls over to it
+		simulator.set_epoch_by_block_time(1).await;
+		attesters[1].post_block_commitment(commitment(1, 1)).await?;
+		assert_eq!(epochs.next().await.expect("stream has ended")?, 0);
+		assert_eq!(attesters[0].get_current_epoch().await?, 1);
+		assert_eq!(attesters[0].get_commitment_at_height(1).await?, None);
+
+		// the commitments are weighted by the stakes of the current epoch
+		attesters[0].post_block_commitment(commitment(1, 1)).await?;
+		assert_eq!(attesters[0].get_commitment_at_height(1).await?, Some(commitment(1, 1)));
+		assert_eq!(
+			attesters[0].compute_all_stake_at_epoch(1, attesters[0].address()).await?,
+			U256::from(30)
+		);
+		assert_eq!(
+			attesters[0].get_stake_at_epoch(1, attesters[1].address()).await?,
+			U256::from(6)
+		);
+
+		// the attester with the most stake in the epoch of the block settles alone
+		attesters[0].post_block_commitment(commitment(2, 2)).await?;
+		assert_eq!(attesters[0].get_commitment_at_height(2).await?, Some(commitment(2, 2)));
+		Ok(())
+	}
+}
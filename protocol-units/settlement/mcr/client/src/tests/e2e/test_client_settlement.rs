@@ -1,6 +1,6 @@
 use crate::eth_client::Client;
 use crate::eth_client::{MOVEToken, MovementStaking, MCR};
-use crate::McrSettlementClientOperations;
+use crate::{McrSettlementClientOperations, McrSettlementStakingOperations};
 use alloy::providers::ProviderBuilder;
 use alloy::signers::local::PrivateKeySigner;
 use alloy_network::EthereumWallet;
@@ -238,6 +238,13 @@ pub async fn test_client_settlement() -> Result<(), anyhow::Error> {
 	)
 	.await?;
 
+	// Anvil only mines blocks with transactions, so the acceptances are delivered without confirmations.
+	let events = mcr_settlement_config::common::events::Config {
+		confirmations: 0,
+		cursor_path: None,
+		..config.events.clone()
+	};
+
 	// Build client 1 and send the first commitment.
 	//let settlement_config =
 	let config1 = Config {
@@ -249,6 +256,7 @@ pub async fn test_client_settlement() -> Result<(), anyhow::Error> {
 				.to_string(),
 			..config.settle.clone()
 		},
+		events: events.clone(),
 		..config.clone()
 	};
 	let client1 = Client::build_with_config(&config1).await.unwrap();
@@ -276,6 +284,7 @@ pub async fn test_client_settlement() -> Result<(), anyhow::Error> {
 				.to_string(),
 			..config.settle.clone()
 		},
+		events: events.clone(),
 		..config.clone()
 	};
 	let client2 = Client::build_with_config(&config2).await.unwrap();
@@ -341,5 +350,53 @@ pub async fn test_client_settlement() -> Result<(), anyhow::Error> {
 	let commitment = client1.get_commitment_at_height(10).await?;
 	assert_eq!(commitment, None);
 
+	// Test the staking operations
+	let alice = client1.signer_address;
+	let attesters = client1.get_attesters().await?;
+	assert!(attesters.contains(&alice));
+	assert!(attesters.contains(&client2.signer_address));
+	let epoch = client1.get_current_epoch().await?;
+	let stake = client1.get_stake_at_epoch(epoch, alice).await?;
+	assert!(stake > U256::ZERO);
+	// the genesis ceremony only staked MOVE
+	assert_eq!(client1.compute_all_stake_at_epoch(epoch, alice).await?, stake);
+
+	// the custodian of DeployMCRDev is the staked token, so configuring it changes nothing
+	let move_token_address = Address::from_str(&testing_config.move_token_contract_address)?;
+	let config3 = Config {
+		settle: mcr_settlement_config::common::settlement::Config {
+			staking_custodian_address: Some(move_token_address.to_string()),
+			..config1.settle.clone()
+		},
+		..config1.clone()
+	};
+	let client3 = Client::build_with_config(&config3).await?;
+	assert_eq!(client3.get_stake_at_epoch(epoch, alice).await?, stake);
+
+	// alice staked all her MOVE in the genesis ceremony
+	let amount = U256::from(100);
+	let governor: PrivateKeySigner =
+		testing_config.mcr_testing_admin_account_private_key.parse()?;
+	let governor_rpc_provider = ProviderBuilder::new()
+		.with_recommended_fillers()
+		.wallet(EthereumWallet::from(governor))
+		.on_builtin(&rpc_url)
+		.await?;
+	MOVEToken::new(move_token_address, &governor_rpc_provider)
+		.mint(alice, amount)
+		.send()
+		.await?
+		.watch()
+		.await
+		.context("Governor failed to mint for alice")?;
+
+	let move_token = MOVEToken::new(move_token_address, &governor_rpc_provider);
+	let MOVEToken::balanceOfReturn { _0: balance } = move_token.balanceOf(alice).call().await?;
+	client3.stake(amount).await?;
+	let MOVEToken::balanceOfReturn { _0: staked_balance } =
+		move_token.balanceOf(alice).call().await?;
+	assert_eq!(staked_balance, balance - amount);
+	client3.unstake(amount).await?;
+
 	Ok(())
 }
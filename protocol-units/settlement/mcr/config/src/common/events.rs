@@ -0,0 +1,61 @@
+use godfig::env_default;
+use serde::{Deserialize, Serialize};
+use std::env;
+
+const DEFAULT_EVENT_CURSOR_PATH: &str = "mcr-settlement-event-cursor.json";
+
+/// Configuration of the stream of the commitments accepted by the MCR contract.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+	/// The number of L1 blocks on top of the block of an acceptance before it is delivered.
+	/// Acceptances in blocks removed by a reorg within this depth are dropped before they are delivered.
+	/// An acceptance which has been delivered is never retracted, so this has to cover the deepest
+	/// reorg expected on L1.
+	#[serde(default = "default_event_confirmations")]
+	pub confirmations: u64,
+
+	/// The file the position of the stream in L1 is persisted to, so that the acceptances
+	/// which happened while the node was stopped are delivered after a restart.
+	/// A relative path is resolved under the `.movement` directory when the settlement is set up.
+	/// If not set, the stream starts from the latest L1 block.
+	#[serde(default = "default_event_cursor_path")]
+	pub cursor_path: Option<String>,
+
+	/// The maximum number of L1 blocks requested at once with `eth_getLogs`.
+	#[serde(default = "default_event_backfill_block_range")]
+	pub backfill_block_range: u64,
+
+	/// The delay before reconnecting after the websocket connection is lost, in milliseconds.
+	#[serde(default = "default_event_reconnect_interval_milliseconds")]
+	pub reconnect_interval_milliseconds: u64,
+}
+
+env_default!(default_event_confirmations, "MCR_EVENT_CONFIRMATIONS", u64, 12);
+
+pub fn default_event_cursor_path() -> Option<String> {
+	match env::var("MCR_EVENT_CURSOR_PATH") {
+		// an empty path disables the persistence of the cursor
+		Ok(path) => Some(path).filter(|path| !path.is_empty()),
+		Err(_) => Some(DEFAULT_EVENT_CURSOR_PATH.to_string()),
+	}
+}
+
+env_default!(default_event_backfill_block_range, "MCR_EVENT_BACKFILL_BLOCK_RANGE", u64, 1_000);
+
+env_default!(
+	default_event_reconnect_interval_milliseconds,
+	"MCR_EVENT_RECONNECT_INTERVAL_MILLISECONDS",
+	u64,
+	5_000
+);
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			confirmations: default_event_confirmations(),
+			cursor_path: default_event_cursor_path(),
+			backfill_block_range: default_event_backfill_block_range(),
+			reconnect_interval_milliseconds: default_event_reconnect_interval_milliseconds(),
+		}
+	}
+}
@@ -1,6 +1,8 @@
 pub mod deploy;
 pub mod eth_connection;
+pub mod events;
 pub mod settlement;
+pub mod simulator;
 pub mod staking;
 pub mod testing;
 pub mod transactions;
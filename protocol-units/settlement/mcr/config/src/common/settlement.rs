@@ -13,6 +13,10 @@ pub struct Config {
 	pub signer_private_key: String,
 	#[serde(default = "default_mcr_contract_address")]
 	pub mcr_contract_address: String,
+	/// The custodian of the stakes of the attesters on the staking contract.
+	/// If not set, it is the token staked on the staking contract, as in the deployments of `DeployMCRDev`.
+	#[serde(default = "default_staking_custodian_address")]
+	pub staking_custodian_address: Option<String>,
 }
 
 pub fn default_signer_private_key() -> String {
@@ -28,6 +32,10 @@ env_default!(
 	DEFAULT_MCR_CONTRACT_ADDRESS.to_string()
 );
 
+pub fn default_staking_custodian_address() -> Option<String> {
+	env::var("MCR_STAKING_CUSTODIAN_ADDRESS").ok()
+}
+
 pub fn default_should_settle() -> bool {
 	env::var("ETH_SIGNER_PRIVATE_KEY").is_ok()
 }
@@ -38,6 +46,7 @@ impl Default for Config {
 			should_settle: default_should_settle(),
 			signer_private_key: default_signer_private_key(),
 			mcr_contract_address: default_mcr_contract_address(),
+			staking_custodian_address: default_staking_custodian_address(),
 		}
 	}
 }
@@ -0,0 +1,33 @@
+use godfig::env_short_default;
+use serde::{Deserialize, Serialize};
+
+/// Configuration of the simulation of the MCR contract in process, in place of the contract on L1.
+///
+/// The node settles as the only attester of the simulated contract, so its commitments are accepted
+/// unless other attesters are added to the simulator, as the tests of disagreements do.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+	/// The number of blocks above the last accepted block which can be committed to.
+	#[serde(default = "default_simulator_leading_block_tolerance")]
+	pub leading_block_tolerance: u64,
+	/// The stake of the node in the simulated staking contract.
+	#[serde(default = "default_simulator_attester_stake")]
+	pub attester_stake: u64,
+}
+
+env_short_default!(default_simulator_leading_block_tolerance, u64, 16 as u64);
+
+env_short_default!(default_simulator_attester_stake, u64, 1 as u64);
+
+pub fn maybe_simulator() -> Option<Config> {
+	std::env::var("MAYBE_SIMULATE_MCR").ok().map(|_| Config::default())
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			leading_block_tolerance: default_simulator_leading_block_tolerance(),
+			attester_stake: default_simulator_attester_stake(),
+		}
+	}
+}
@@ -10,6 +10,19 @@ pub struct Config {
 	pub batch_timeout: u64,
 	#[serde(default = "default_transaction_send_retries")]
 	pub transaction_send_retries: u32,
+	/// The highest max fee per gas a transaction is sent with, in wei
+	#[serde(default = "default_max_fee_per_gas_cap")]
+	pub max_fee_per_gas_cap: u128,
+	/// The highest max priority fee per gas a transaction is sent with, in wei
+	#[serde(default = "default_max_priority_fee_per_gas_cap")]
+	pub max_priority_fee_per_gas_cap: u128,
+	/// The percentage the fees are raised by when a transaction is replaced,
+	/// at least the 10% required by the nodes to accept a replacement
+	#[serde(default = "default_fee_escalation_percent")]
+	pub fee_escalation_percent: u64,
+	/// Timeout for a sent transaction to be mined before it is replaced, in milliseconds
+	#[serde(default = "default_receipt_timeout")]
+	pub receipt_timeout: u64,
 }
 
 env_short_default!(default_gas_limit, u64, 10_000_000_000_000_000 as u64);
@@ -18,12 +31,24 @@ env_short_default!(default_batch_timeout, u64, 2000 as u64);
 
 env_short_default!(default_transaction_send_retries, u32, 10 as u32);
 
+env_short_default!(default_max_fee_per_gas_cap, u128, 500_000_000_000 as u128);
+
+env_short_default!(default_max_priority_fee_per_gas_cap, u128, 50_000_000_000 as u128);
+
+env_short_default!(default_fee_escalation_percent, u64, 25 as u64);
+
+env_short_default!(default_receipt_timeout, u64, 120_000 as u64);
+
 impl Default for Config {
 	fn default() -> Self {
 		Config {
 			gas_limit: default_gas_limit(),
 			batch_timeout: default_batch_timeout(),
 			transaction_send_retries: default_transaction_send_retries(),
+			max_fee_per_gas_cap: default_max_fee_per_gas_cap(),
+			max_priority_fee_per_gas_cap: default_max_priority_fee_per_gas_cap(),
+			fee_escalation_percent: default_fee_escalation_percent(),
+			receipt_timeout: default_receipt_timeout(),
 		}
 	}
 }
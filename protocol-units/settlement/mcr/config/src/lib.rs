@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 pub mod common;
 
 use common::deploy::maybe_deploy;
+use common::simulator::maybe_simulator;
 use common::testing::maybe_testing;
 use godfig::env_short_default;
 
@@ -20,6 +21,10 @@ pub struct Config {
 	#[serde(default)]
 	pub transactions: common::transactions::Config,
 
+	/// The stream of the commitments accepted by the MCR contract.
+	#[serde(default)]
+	pub events: common::events::Config,
+
 	/// Whether or not to attempt to run locally.
 	#[serde(default = "maybe_run_local")]
 	pub maybe_run_local: bool,
@@ -31,6 +36,10 @@ pub struct Config {
 	/// Optional testing config
 	#[serde(default = "maybe_testing")]
 	pub testing: Option<common::testing::Config>,
+
+	/// Optional simulation of the MCR contract, which the node settles with in place of L1
+	#[serde(default = "maybe_simulator")]
+	pub simulator: Option<common::simulator::Config>,
 }
 
 env_short_default!(maybe_run_local, bool, false);
@@ -59,9 +68,11 @@ impl Default for Config {
 			eth_connection: common::eth_connection::Config::default(),
 			settle: common::settlement::Config::default(),
 			transactions: common::transactions::Config::default(),
+			events: common::events::Config::default(),
 			maybe_run_local: maybe_run_local(),
 			deploy: maybe_deploy(),
 			testing: maybe_testing(),
+			simulator: maybe_simulator(),
 		}
 	}
 }
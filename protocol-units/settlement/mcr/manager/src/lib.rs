@@ -2,8 +2,10 @@ use movement_types::block::{BlockCommitment, BlockCommitmentEvent};
 use tokio_stream::Stream;
 
 mod manager;
+mod store;
 
 pub use manager::Manager as McrSettlementManager;
+pub use store::{ManagerStore, PendingCommitment};
 
 pub type CommitmentEventStream =
 	std::pin::Pin<Box<dyn Stream<Item = Result<BlockCommitmentEvent, anyhow::Error>> + Send>>;
@@ -15,4 +17,10 @@ pub trait McrSettlementManagerOperations {
 		&self,
 		block_commitment: BlockCommitment,
 	) -> Result<(), anyhow::Error>;
+
+	/// Drops the queued commitments above the given block height, which are not settled yet.
+	///
+	/// This is used when the blocks above the height are reverted, so that only the commitments
+	/// of the blocks executed again are settled.
+	async fn rollback_block_commitments(&self, block_height: u64) -> Result<(), anyhow::Error>;
 }
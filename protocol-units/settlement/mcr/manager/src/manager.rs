@@ -1,6 +1,7 @@
+use crate::store::ManagerStore;
 use crate::{BlockCommitmentEvent, CommitmentEventStream, McrSettlementManagerOperations};
 
-use mcr_settlement_client::McrSettlementClientOperations;
+use mcr_settlement_client::{McrSettlementClientOperations, PostedTransactionStatus};
 use mcr_settlement_config::Config;
 use movement_types::block::{BlockCommitment, BlockCommitmentRejectionReason};
 
@@ -11,13 +12,28 @@ use tokio::sync::mpsc;
 use tokio::time;
 use tokio_stream::StreamExt;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Public handle for the MCR settlement manager.
 pub struct Manager {
-	sender: mpsc::Sender<BlockCommitment>,
+	sender: mpsc::Sender<(u64, BlockCommitment)>,
+	rollback_sender: mpsc::Sender<Rollback>,
+	// the number of commitments posted, which indexes them in the order they are sent
+	post_count: AtomicU64,
+}
+
+/// A rollback of the commitments above a height.
+///
+/// Rollbacks are received separately from the posted commitments, so that they are processed
+/// while posting is paused by backpressure. They apply to the commitments posted before them,
+/// including those which are not received yet.
+struct Rollback {
+	height: u64,
+	post_count: u64,
 }
 
 impl Manager {
@@ -26,14 +42,39 @@ impl Manager {
 	/// Returns the handle with the public API and the stream to receive commitment events.
 	/// The stream needs to be polled to drive the MCR settlement client and
 	/// process the commitments.
-	pub fn new<C: McrSettlementClientOperations + Send + 'static>(
+	pub fn new<C: McrSettlementClientOperations + Send + Sync + 'static>(
 		client: C,
 		config: &Config,
+	) -> (Self, CommitmentEventStream) {
+		Self::build(client, config, None)
+	}
+
+	/// Creates a new MCR settlement manager which persists its state to the store.
+	///
+	/// The commitments which were not settled before a restart are reconciled with
+	/// the commitments accepted in the meantime, and the others are settled as if
+	/// they had just been posted. The position of the settlement client in the stream
+	/// of accepted commitments is persisted to the store as well.
+	pub fn with_store<C: McrSettlementClientOperations + Send + Sync + 'static>(
+		mut client: C,
+		config: &Config,
+		store: ManagerStore,
+	) -> (Self, CommitmentEventStream) {
+		client.set_event_cursor_store(Arc::new(store.clone()));
+		Self::build(client, config, Some(store))
+	}
+
+	fn build<C: McrSettlementClientOperations + Send + Sync + 'static>(
+		client: C,
+		config: &Config,
+		store: Option<ManagerStore>,
 	) -> (Self, CommitmentEventStream) {
 		let batch_timeout = Duration::from_millis(config.transactions.batch_timeout);
 		let (sender, receiver) = mpsc::channel(16);
-		let event_stream = process_commitments(receiver, client, batch_timeout);
-		(Self { sender }, event_stream)
+		let (rollback_sender, rollback_receiver) = mpsc::channel(16);
+		let event_stream =
+			process_commitments(receiver, rollback_receiver, client, batch_timeout, store);
+		(Self { sender, rollback_sender, post_count: AtomicU64::new(0) }, event_stream)
 	}
 }
 
@@ -43,15 +84,79 @@ impl McrSettlementManagerOperations for Manager {
 		&self,
 		block_commitment: BlockCommitment,
 	) -> Result<(), anyhow::Error> {
-		self.sender.send(block_commitment).await?;
+		let index = self.post_count.fetch_add(1, Ordering::SeqCst);
+		self.sender.send((index, block_commitment)).await?;
+		Ok(())
+	}
+
+	async fn rollback_block_commitments(&self, block_height: u64) -> Result<(), anyhow::Error> {
+		let post_count = self.post_count.load(Ordering::SeqCst);
+		self.rollback_sender.send(Rollback { height: block_height, post_count }).await?;
 		Ok(())
 	}
 }
 
-fn process_commitments<C: McrSettlementClientOperations + Send + 'static>(
-	mut receiver: mpsc::Receiver<BlockCommitment>,
+/// Settles the commitments persisted before a restart which were accepted in the meantime,
+/// returning their events and the commitments which remain to be settled, with whether they were posted.
+///
+/// A commitment counts as posted only if the transaction which posted it succeeded,
+/// so that the commitments whose transaction was dropped or reverted are posted again.
+async fn reconcile<C: McrSettlementClientOperations + Sync>(
+	client: &C,
+	store: &ManagerStore,
+) -> Result<(Vec<BlockCommitmentEvent>, Vec<(BlockCommitment, bool)>), anyhow::Error> {
+	let mut events = Vec::new();
+	let mut unsettled = Vec::new();
+	for pending in store.get_pending_commitments().await? {
+		let height = pending.commitment.height();
+		match client.get_commitment_at_height(height).await? {
+			Some(settled) => {
+				let event = if settled.commitment() == pending.commitment.commitment() {
+					BlockCommitmentEvent::Accepted(settled)
+				} else {
+					BlockCommitmentEvent::Rejected {
+						height,
+						reason: BlockCommitmentRejectionReason::InvalidCommitment,
+					}
+				};
+				store.remove_pending_commitment(height).await?;
+				events.push(event);
+			}
+			None => {
+				let posted = match pending.transaction_hash {
+					Some(transaction_hash) => {
+						client.get_posted_transaction_status(transaction_hash).await?
+							== PostedTransactionStatus::Succeeded
+					}
+					None => false,
+				};
+				unsettled.push((pending.commitment, posted));
+			}
+		}
+	}
+	Ok((events, unsettled))
+}
+
+/// Posts a batch of commitments, recording the transaction which posted them.
+async fn post_batch<C: McrSettlementClientOperations + Sync>(
+	client: &C,
+	store: Option<&ManagerStore>,
+	batch: Vec<BlockCommitment>,
+) -> Result<(), anyhow::Error> {
+	let heights = batch.iter().map(BlockCommitment::height).collect::<Vec<_>>();
+	let transaction_hash = client.post_block_commitment_batch(batch).await?;
+	if let Some(store) = store.filter(|_| !heights.is_empty()) {
+		store.set_posted_transaction(heights, transaction_hash).await?;
+	}
+	Ok(())
+}
+
+fn process_commitments<C: McrSettlementClientOperations + Send + Sync + 'static>(
+	mut receiver: mpsc::Receiver<(u64, BlockCommitment)>,
+	mut rollback_receiver: mpsc::Receiver<Rollback>,
 	client: C,
 	batch_timeout: Duration,
+	store: Option<ManagerStore>,
 ) -> CommitmentEventStream {
 	// Can't mix try_stream! and select!, see https://github.com/tokio-rs/async-stream/issues/63
 	Box::pin(stream! {
@@ -61,9 +166,70 @@ fn process_commitments<C: McrSettlementClientOperations + Send + 'static>(
 		let mut commitments_to_settle = BTreeMap::new();
 		let mut batch_acc = Vec::new();
 		let mut batch_ready = Either::Left(future::pending::<()>());
+		// the commitments settled while the node was stopped, which the client may deliver again
+		let mut reconciled_heights = BTreeSet::new();
+		// the rollbacks which apply to commitments posted before them and not received yet
+		let mut rollbacks: Vec<Rollback> = Vec::new();
+		let mut received_count = 0;
+		if let Some(store) = &store {
+			let (events, unsettled) = reconcile(&client, store).await?;
+			for event in events {
+				reconciled_heights.insert(match &event {
+					BlockCommitmentEvent::Accepted(commitment) => commitment.height(),
+					BlockCommitmentEvent::Rejected { height, .. } => *height,
+				});
+				yield Ok(event);
+			}
+			for (commitment, posted) in unsettled {
+				commitments_to_settle.insert(commitment.height(), commitment.commitment());
+				// the commitments which were not posted before the restart are posted with the next batch
+				if !posted {
+					batch_acc.push(commitment);
+				}
+			}
+			if !batch_acc.is_empty() {
+				batch_ready = Either::Right(Box::pin(time::sleep(batch_timeout)));
+			}
+		}
 		loop {
 			tokio::select! {
-				Some(block_commitment) = receiver.recv(), if !ahead_of_settlement => {
+				Some(rollback) = rollback_receiver.recv() => {
+					let height = rollback.height;
+					if let Some(store) = &store {
+						if let Err(e) = store.remove_pending_commitments_above(height).await {
+							yield Err(e);
+							break;
+						}
+					}
+					// the commitments above the height are posted again once their blocks are executed again
+					commitments_to_settle.retain(|&commitment_height, _| commitment_height <= height);
+					batch_acc.retain(|commitment: &BlockCommitment| commitment.height() <= height);
+					if batch_acc.is_empty() {
+						batch_ready = Either::Left(future::pending::<()>());
+					}
+					// the commitment which paused posting may have been rolled back
+					if batch_acc.iter().all(|commitment| commitment.height() <= max_height) {
+						ahead_of_settlement = false;
+					}
+					if rollback.post_count > received_count {
+						rollbacks.push(rollback);
+					}
+				}
+				Some((index, block_commitment)) = receiver.recv(), if !ahead_of_settlement => {
+					received_count = received_count.max(index + 1);
+					let rolled_back = rollbacks.iter().any(|rollback| {
+						index < rollback.post_count && block_commitment.height() > rollback.height
+					});
+					rollbacks.retain(|rollback| rollback.post_count > received_count);
+					if rolled_back {
+						continue;
+					}
+					if let Some(store) = &store {
+						if let Err(e) = store.add_pending_commitment(block_commitment.clone()).await {
+							yield Err(e);
+							break;
+						}
+					}
 					commitments_to_settle.insert(
 						block_commitment.height(),
 						block_commitment.commitment().clone(),
@@ -74,7 +240,7 @@ fn process_commitments<C: McrSettlementClientOperations + Send + 'static>(
 						// and pause reading from input.
 						ahead_of_settlement = true;
 						let batch = mem::replace(&mut batch_acc, Vec::new());
-						if let Err(e) = client.post_block_commitment_batch(batch).await {
+						if let Err(e) = post_batch(&client, store.as_ref(), batch).await {
 							yield Err(e);
 							break;
 						}
@@ -88,7 +254,7 @@ fn process_commitments<C: McrSettlementClientOperations + Send + 'static>(
 				_ = &mut batch_ready => {
 					// Batch timeout has expired, post the commitments we have now
 					let batch = mem::replace(&mut batch_acc, Vec::new());
-					if let Err(e) = client.post_block_commitment_batch(batch).await {
+					if let Err(e) = post_batch(&client, store.as_ref(), batch).await {
 						yield Err(e);
 						break;
 					}
@@ -106,6 +272,12 @@ fn process_commitments<C: McrSettlementClientOperations + Send + 'static>(
 
 					let height = settled_commitment.height();
 					if let Some(commitment) = commitments_to_settle.remove(&height) {
+						if let Some(store) = &store {
+							if let Err(e) = store.remove_pending_commitment(height).await {
+								yield Err(e);
+								break;
+							}
+						}
 						let event = if commitment == settled_commitment.commitment() {
 							BlockCommitmentEvent::Accepted(settled_commitment)
 						} else {
@@ -115,6 +287,8 @@ fn process_commitments<C: McrSettlementClientOperations + Send + 'static>(
 							}
 						};
 						yield Ok(event);
+					} else if reconciled_heights.remove(&height) {
+						// Already settled when reconciling the persisted commitments.
 					} else if let Some((&lh, _)) = commitments_to_settle.last_key_value() {
 						if lh < height {
 							// Settlement has left some commitments behind, but the client could
@@ -146,7 +320,10 @@ fn process_commitments<C: McrSettlementClientOperations + Send + 'static>(
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use alloy_primitives::{Address, U256};
 	use mcr_settlement_client::mock::McrSettlementClient;
+	use mcr_settlement_client::simulator::McrSimulator;
+	use mcr_settlement_client::{McrSettlementStakingOperations, TransactionHash};
 	use movement_types::block::{BlockCommitment, Commitment};
 
 	#[tokio::test]
@@ -196,6 +373,200 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_block_commitment_rejected_by_attesters() -> Result<(), anyhow::Error> {
+		let mut config = Config::default();
+		config.transactions.batch_timeout = 100;
+		let simulator = McrSimulator::new(4, U256::from(5));
+		let client = simulator.add_attester(Address::with_last_byte(1), U256::from(20)).await;
+		let attester2 = simulator.add_attester(Address::with_last_byte(2), U256::from(40)).await;
+		let attester3 = simulator.add_attester(Address::with_last_byte(3), U256::from(40)).await;
+		let (manager, mut event_stream) = Manager::new(client.clone(), &config);
+
+		let commitment = BlockCommitment::new(1, Default::default(), Commitment::new([1; 32]));
+		manager.post_block_commitment(commitment).await?;
+		let event = tokio::spawn(async move { event_stream.next().await });
+		time::timeout(Duration::from_secs(2), async {
+			while simulator.get_attester_commitment_at_height(1, client.address()).await.is_none() {
+				time::sleep(Duration::from_millis(10)).await;
+			}
+		})
+		.await
+		.expect("commitment not posted");
+
+		// the other attesters have the supermajority on another commitment
+		let settled = BlockCommitment::new(1, Default::default(), Commitment::new([3; 32]));
+		attester2.post_block_commitment(settled.clone()).await?;
+		attester3.post_block_commitment(settled.clone()).await?;
+
+		let event = event.await?.expect("stream has ended")?;
+		assert_eq!(
+			event,
+			BlockCommitmentEvent::Rejected {
+				height: 1,
+				reason: BlockCommitmentRejectionReason::InvalidCommitment,
+			}
+		);
+		assert_eq!(client.get_commitment_at_height(1).await?, Some(settled));
+		assert_eq!(client.get_stake_at_epoch(0, client.address()).await?, U256::from(15));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_rollback_block_commitments() -> Result<(), anyhow::Error> {
+		let config = Config::default();
+		let mut client = McrSettlementClient::new();
+		client.block_lead_tolerance = 1;
+		let (manager, mut event_stream) = Manager::new(client.clone(), &config);
+
+		let commitment1 = BlockCommitment::new(1, Default::default(), Commitment::new([1; 32]));
+		manager.post_block_commitment(commitment1.clone()).await?;
+		let commitment2 = BlockCommitment::new(2, Default::default(), Commitment::new([2; 32]));
+		manager.post_block_commitment(commitment2).await?;
+		// the commitment at height 2 is dropped and posted again after the block is re-executed
+		manager.rollback_block_commitments(1).await?;
+		let commitment2 = BlockCommitment::new(2, Default::default(), Commitment::new([4; 32]));
+		manager.post_block_commitment(commitment2.clone()).await?;
+		let commitment3 = BlockCommitment::new(3, Default::default(), Commitment::new([3; 32]));
+		manager.post_block_commitment(commitment3).await?;
+
+		let event = event_stream.next().await.expect("stream has ended")?;
+		assert_eq!(event, BlockCommitmentEvent::Accepted(commitment1));
+		let event = event_stream.next().await.expect("stream has ended")?;
+		assert_eq!(event, BlockCommitmentEvent::Accepted(commitment2.clone()));
+		assert_eq!(client.get_commitment_at_height(2).await?, Some(commitment2));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_rollback_processed_ahead_of_settlement() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let store = ManagerStore::open(temp_dir.path())?;
+		let mut config = Config::default();
+		// the batches are only posted when posting gets ahead of settlement
+		config.transactions.batch_timeout = 60_000;
+		// the attester of the manager alone can't get its commitments accepted
+		let simulator = McrSimulator::new(2, U256::ZERO);
+		let client = simulator.add_attester(Address::with_last_byte(1), U256::from(1)).await;
+		simulator.add_attester(Address::with_last_byte(2), U256::from(2)).await;
+		let (manager, mut event_stream) = Manager::with_store(client, &config, store.clone());
+		let events = tokio::spawn(async move {
+			while let Some(event) = event_stream.next().await {
+				event?;
+			}
+			Ok::<_, anyhow::Error>(())
+		});
+
+		for height in 1..=4 {
+			let commitment = Commitment::new([height as u8; 32]);
+			manager
+				.post_block_commitment(BlockCommitment::new(height, Default::default(), commitment))
+				.await?;
+		}
+		let pending_commitments = || async {
+			let pending = store.get_pending_commitments().await?;
+			Ok::<_, anyhow::Error>(
+				pending.into_iter().map(|pending| pending.commitment).collect::<Vec<_>>(),
+			)
+		};
+		time::timeout(Duration::from_secs(2), async {
+			while pending_commitments().await?.len() < 3 {
+				time::sleep(Duration::from_millis(10)).await;
+			}
+			Ok::<_, anyhow::Error>(())
+		})
+		.await
+		.expect("commitments not received")?;
+
+		// the commitment at height 3 is above the max tolerable height, so posting is paused,
+		// but the rollback is processed and drops the commitment at height 4 still waiting,
+		// then the commitment executed again is received
+		manager.rollback_block_commitments(2).await?;
+		let commitment3 = BlockCommitment::new(3, Default::default(), Commitment::new([4; 32]));
+		manager.post_block_commitment(commitment3.clone()).await?;
+		time::timeout(Duration::from_secs(2), async {
+			while pending_commitments().await?.last() != Some(&commitment3) {
+				time::sleep(Duration::from_millis(10)).await;
+			}
+			Ok::<_, anyhow::Error>(())
+		})
+		.await
+		.expect("rollback not processed")?;
+		assert_eq!(pending_commitments().await?.len(), 3);
+		assert!(!events.is_finished());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_pending_commitments_settled_after_restart() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let store = ManagerStore::open(temp_dir.path())?;
+		let mut config = Config::default();
+		config.transactions.batch_timeout = 100;
+		let mut client = McrSettlementClient::new();
+		client.block_lead_tolerance = 1;
+		let (manager, mut event_stream) =
+			Manager::with_store(client.clone(), &config, store.clone());
+
+		let commitment1 = BlockCommitment::new(1, Default::default(), Commitment::new([1; 32]));
+		manager.post_block_commitment(commitment1.clone()).await?;
+		let commitment2 = BlockCommitment::new(2, Default::default(), Commitment::new([2; 32]));
+		manager.post_block_commitment(commitment2.clone()).await?;
+		let event = event_stream.next().await.expect("stream has ended")?;
+		assert_eq!(event, BlockCommitmentEvent::Accepted(commitment1));
+		drop(manager);
+		drop(event_stream);
+
+		// the commitment at height 2 was not posted before the restart,
+		// the one at height 3 was posted and got accepted while the node was stopped
+		let commitment3 = BlockCommitment::new(3, Default::default(), Commitment::new([3; 32]));
+		store.add_pending_commitment(commitment3.clone()).await?;
+		store.set_posted_transaction(vec![3], Default::default()).await?;
+		let client = McrSettlementClient::new();
+		client.post_block_commitment(commitment3.clone()).await?;
+
+		let (_manager, mut event_stream) =
+			Manager::with_store(client.clone(), &config, store.clone());
+		let event = event_stream.next().await.expect("stream has ended")?;
+		assert_eq!(event, BlockCommitmentEvent::Accepted(commitment3));
+		let event = time::timeout(Duration::from_secs(2), event_stream.next())
+			.await
+			.expect("no timeout")
+			.expect("stream has ended")?;
+		assert_eq!(event, BlockCommitmentEvent::Accepted(commitment2));
+		assert!(store.get_pending_commitments().await?.is_empty());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_dropped_commitment_reposted_after_restart() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let store = ManagerStore::open(temp_dir.path())?;
+		let mut config = Config::default();
+		config.transactions.batch_timeout = 100;
+
+		// the transaction which posted the commitment before the restart was never mined
+		let commitment = BlockCommitment::new(1, Default::default(), Commitment::new([1; 32]));
+		store.add_pending_commitment(commitment.clone()).await?;
+		store.set_posted_transaction(vec![1], TransactionHash([7; 32])).await?;
+
+		let client = McrSettlementClient::new();
+		let (_manager, mut event_stream) =
+			Manager::with_store(client.clone(), &config, store.clone());
+		let event = time::timeout(Duration::from_secs(2), event_stream.next())
+			.await
+			.expect("no timeout")
+			.expect("stream has ended")?;
+		assert_eq!(event, BlockCommitmentEvent::Accepted(commitment.clone()));
+		assert_eq!(client.get_commitment_at_height(1).await?, Some(commitment));
+		assert!(store.get_pending_commitments().await?.is_empty());
+
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_back_pressure() -> Result<(), anyhow::Error> {
 		let config = Config::default();
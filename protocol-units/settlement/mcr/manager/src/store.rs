@@ -0,0 +1,249 @@
+use mcr_settlement_client::{EventCursor, EventCursorStore, TransactionHash};
+use movement_types::block::BlockCommitment;
+
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+
+use std::path::Path;
+use std::sync::Arc;
+
+mod column_families {
+	pub const PENDING_COMMITMENTS: &str = "pending_commitments";
+	pub const POSTED_TRANSACTIONS: &str = "posted_transactions";
+	pub const EVENT_CURSOR: &str = "event_cursor";
+}
+use column_families::*;
+
+/// A commitment posted to the manager which has not been settled yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingCommitment {
+	pub commitment: BlockCommitment,
+	/// The hash of the transaction which posted the commitment to the settlement contract, if it was posted.
+	pub transaction_hash: Option<TransactionHash>,
+}
+
+/// Persistent state of the settlement manager, so that the commitments which are not settled yet
+/// are settled after a restart.
+///
+/// An async access API is provided to avoid blocking async tasks.
+/// The methods must be executed in the context of a Tokio runtime.
+#[derive(Clone, Debug)]
+pub struct ManagerStore {
+	inner: Arc<DB>,
+}
+
+impl ManagerStore {
+	pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+		let mut options = Options::default();
+		options.create_if_missing(true);
+		options.create_missing_column_families(true);
+
+		let pending_commitments =
+			ColumnFamilyDescriptor::new(PENDING_COMMITMENTS, Options::default());
+		let posted_transactions =
+			ColumnFamilyDescriptor::new(POSTED_TRANSACTIONS, Options::default());
+		let event_cursor = ColumnFamilyDescriptor::new(EVENT_CURSOR, Options::default());
+
+		let db = DB::open_cf_descriptors(
+			&options,
+			path,
+			vec![pending_commitments, posted_transactions, event_cursor],
+		)
+		.map_err(|e| anyhow::anyhow!("Failed to open settlement manager DB: {:?}", e))?;
+
+		Ok(Self { inner: Arc::new(db) })
+	}
+
+	fn decode_height(key: &[u8]) -> Result<u64, anyhow::Error> {
+		Ok(u64::from_be_bytes(
+			key.try_into().map_err(|_| anyhow::anyhow!("Invalid commitment height"))?,
+		))
+	}
+
+	/// Records a commitment posted to the manager.
+	pub async fn add_pending_commitment(
+		&self,
+		commitment: BlockCommitment,
+	) -> Result<(), anyhow::Error> {
+		let db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let commitments_cf = db
+				.cf_handle(PENDING_COMMITMENTS)
+				.ok_or(anyhow::anyhow!("No pending_commitments column family"))?;
+			let transactions_cf = db
+				.cf_handle(POSTED_TRANSACTIONS)
+				.ok_or(anyhow::anyhow!("No posted_transactions column family"))?;
+			let key = commitment.height().to_be_bytes();
+			let mut batch = WriteBatch::default();
+			batch.put_cf(&commitments_cf, key, serde_json::to_vec(&commitment)?);
+			// a commitment posted again for a height replaces the one posted before
+			batch.delete_cf(&transactions_cf, key);
+			db.write(batch)
+				.map_err(|e| anyhow::anyhow!("Failed to add pending commitment: {:?}", e))
+		})
+		.await?
+	}
+
+	/// Records the transaction which posted the commitments at the given heights.
+	pub async fn set_posted_transaction(
+		&self,
+		heights: Vec<u64>,
+		transaction_hash: TransactionHash,
+	) -> Result<(), anyhow::Error> {
+		let db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = db
+				.cf_handle(POSTED_TRANSACTIONS)
+				.ok_or(anyhow::anyhow!("No posted_transactions column family"))?;
+			let transaction_hash = serde_json::to_vec(&transaction_hash)?;
+			let mut batch = WriteBatch::default();
+			for height in heights {
+				batch.put_cf(&cf, height.to_be_bytes(), &transaction_hash);
+			}
+			db.write(batch)
+				.map_err(|e| anyhow::anyhow!("Failed to set posted transaction: {:?}", e))
+		})
+		.await?
+	}
+
+	/// Forgets the commitment at the given height, once it is settled.
+	pub async fn remove_pending_commitment(&self, height: u64) -> Result<(), anyhow::Error> {
+		self.remove_pending_commitments(height..=height).await
+	}
+
+	/// Forgets the commitments above the given height, when their blocks are rolled back.
+	pub async fn remove_pending_commitments_above(&self, height: u64) -> Result<(), anyhow::Error> {
+		self.remove_pending_commitments(height.saturating_add(1)..=u64::MAX).await
+	}
+
+	async fn remove_pending_commitments(
+		&self,
+		heights: std::ops::RangeInclusive<u64>,
+	) -> Result<(), anyhow::Error> {
+		let db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let mut batch = WriteBatch::default();
+			for name in [PENDING_COMMITMENTS, POSTED_TRANSACTIONS] {
+				let cf = db.cf_handle(name).ok_or(anyhow::anyhow!("No {} column family", name))?;
+				let from = heights.start().to_be_bytes();
+				for item in db.iterator_cf(&cf, IteratorMode::From(&from, Direction::Forward)) {
+					let (key, _) = item?;
+					if Self::decode_height(&key)? > *heights.end() {
+						break;
+					}
+					batch.delete_cf(&cf, key);
+				}
+			}
+			db.write(batch)
+				.map_err(|e| anyhow::anyhow!("Failed to remove pending commitments: {:?}", e))
+		})
+		.await?
+	}
+
+	/// Gets the commitments which have not been settled yet, by height.
+	pub async fn get_pending_commitments(&self) -> Result<Vec<PendingCommitment>, anyhow::Error> {
+		let db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let commitments_cf = db
+				.cf_handle(PENDING_COMMITMENTS)
+				.ok_or(anyhow::anyhow!("No pending_commitments column family"))?;
+			let transactions_cf = db
+				.cf_handle(POSTED_TRANSACTIONS)
+				.ok_or(anyhow::anyhow!("No posted_transactions column family"))?;
+			let mut pending = Vec::new();
+			for item in db.iterator_cf(&commitments_cf, IteratorMode::Start) {
+				let (key, value) = item?;
+				let commitment = serde_json::from_slice(&value)?;
+				let transaction_hash = db
+					.get_cf(&transactions_cf, &key)?
+					.map(|hash| serde_json::from_slice(&hash))
+					.transpose()?;
+				pending.push(PendingCommitment { commitment, transaction_hash });
+			}
+			Ok(pending)
+		})
+		.await?
+	}
+}
+
+#[async_trait::async_trait]
+impl EventCursorStore for ManagerStore {
+	async fn load_event_cursor(&self) -> Result<Option<EventCursor>, anyhow::Error> {
+		let db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = db
+				.cf_handle(EVENT_CURSOR)
+				.ok_or(anyhow::anyhow!("No event_cursor column family"))?;
+			db.get_cf(&cf, "event_cursor")?
+				.map(|cursor| serde_json::from_slice(&cursor))
+				.transpose()
+				.map_err(|e| anyhow::anyhow!("Failed to deserialize event cursor: {:?}", e))
+		})
+		.await?
+	}
+
+	async fn store_event_cursor(&self, cursor: EventCursor) -> Result<(), anyhow::Error> {
+		let db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = db
+				.cf_handle(EVENT_CURSOR)
+				.ok_or(anyhow::anyhow!("No event_cursor column family"))?;
+			db.put_cf(&cf, "event_cursor", serde_json::to_vec(&cursor)?)
+				.map_err(|e| anyhow::anyhow!("Failed to store event cursor: {:?}", e))
+		})
+		.await?
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use movement_types::block::Commitment;
+
+	#[tokio::test]
+	async fn test_pending_commitments() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let store = ManagerStore::open(temp_dir.path())?;
+
+		for height in 1..=4 {
+			let commitment = BlockCommitment::new(
+				height,
+				Default::default(),
+				Commitment::new([height as u8; 32]),
+			);
+			store.add_pending_commitment(commitment).await?;
+		}
+		store.set_posted_transaction(vec![1, 2], TransactionHash([1; 32])).await?;
+		store.remove_pending_commitment(1).await?;
+		store.remove_pending_commitments_above(3).await?;
+
+		let pending = store.get_pending_commitments().await?;
+		assert_eq!(
+			pending,
+			vec![
+				PendingCommitment {
+					commitment: BlockCommitment::new(
+						2,
+						Default::default(),
+						Commitment::new([2; 32])
+					),
+					transaction_hash: Some(TransactionHash([1; 32])),
+				},
+				PendingCommitment {
+					commitment: BlockCommitment::new(
+						3,
+						Default::default(),
+						Commitment::new([3; 32])
+					),
+					transaction_hash: None,
+				},
+			]
+		);
+
+		assert_eq!(store.load_event_cursor().await?, None);
+		store.store_event_cursor(EventCursor { next_block: 7 }).await?;
+		assert_eq!(store.load_event_cursor().await?, Some(EventCursor { next_block: 7 }));
+
+		Ok(())
+	}
+}
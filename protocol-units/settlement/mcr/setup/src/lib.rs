@@ -20,6 +20,20 @@ impl Setup {
 		dot_movement: &DotMovement,
 		mut config: Config,
 	) -> Result<(Config, tokio::task::JoinHandle<Result<String, anyhow::Error>>), anyhow::Error> {
+		// the event cursor is kept with the other state of the node
+		if let Some(cursor_path) = &config.events.cursor_path {
+			let cursor_path = dot_movement.get_path().join(cursor_path);
+			config.events.cursor_path = Some(
+				cursor_path
+					.to_str()
+					.ok_or(anyhow::anyhow!(
+						"Failed to convert event cursor path to string: {:?}",
+						cursor_path
+					))?
+					.to_string(),
+			);
+		}
+
 		let join_handle = if config.should_run_local() {
 			tracing::info!("Setting up local run...");
 			let (new_config, handle) = self.local.setup(dot_movement, config).await?;
@@ -86,9 +86,13 @@ where
 
 impl<T> Splitable for Vec<T> {
 	fn split(self, factor: usize) -> Result<Vec<Self>, anyhow::Error> {
-		let chunk_size = (self.len() as f64 / factor as f64).ceil() as usize;
+		if factor == 0 {
+			anyhow::bail!("Cannot split by a factor of 0");
+		}
 
 		// Calculate the chunk size based on the factor
+		let chunk_size = ((self.len() as f64 / factor as f64).ceil() as usize).max(1);
+
 		let result = self
 			.into_iter()
 			.chunks(chunk_size)
@@ -105,25 +109,163 @@ mod block {
 	use std::collections::BTreeSet;
 
 	use super::*;
-	use movement_types::block::Block;
+	use movement_types::block::{Block, BlockMetadata};
 
 	impl Splitable for Block {
+		/// Splits the transactions of the block into a chain of smaller blocks.
+		///
+		/// The first block has the parent of the original block and each following block has the previous one as its parent.
+		/// Each block is offset in height and timestamp by the position of its first transaction in the original block,
+		/// so that heights and timestamps stay distinct and monotonic however often the blocks are split again.
+		/// The sequencer reserves a height and a timestamp for each transaction of a block to leave room for the offsets.
+		/// The blocks are unsigned, because the signature of the original block does not cover them.
+		/// A block with fewer than two transactions cannot be split and is returned as is.
 		fn split(self, factor: usize) -> Result<Vec<Self>, anyhow::Error> {
+			if self.transactions().len() < 2 {
+				return Ok(vec![self]);
+			}
+
 			// unpack the transactions
 			let (metadata, parent, transactions, _id) = self.into_parts();
 
 			// split the vector of transactions
 			let split_transactions = Vec::from_iter(transactions).split(factor)?;
 
-			// create a new block for each split transaction
-			let mut blocks = Vec::new();
+			// create a new block for each split transaction, each linked to the one before
+			let mut blocks: Vec<Block> = Vec::new();
+			let mut offset = 0;
 			for split in split_transactions {
-				let parent =
-					Block::new(metadata.clone(), parent.clone(), BTreeSet::from_iter(split));
-				blocks.push(parent);
+				let parent = blocks.last().map_or(parent, |block| block.id());
+				let metadata =
+					BlockMetadata::new(metadata.height() + offset, metadata.timestamp() + offset);
+				offset += split.len() as u64;
+				blocks.push(Block::new(metadata, parent, BTreeSet::from_iter(split)));
 			}
 
 			Ok(blocks)
 		}
 	}
 }
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use crate::grouping_heuristic::binpacking::BinpackingWeighted;
+	use movement_types::{
+		block::{self, Block, BlockMetadata},
+		transaction::Transaction,
+	};
+	use proptest::prelude::*;
+	use std::collections::BTreeSet;
+
+	fn block(transactions: Vec<Vec<u8>>) -> Block {
+		Block::new(
+			BlockMetadata::new(7, 1_000),
+			block::Id::new([1; 32]),
+			transactions
+				.into_iter()
+				.enumerate()
+				.map(|(i, data)| Transaction::new(data, i as u64))
+				.collect(),
+		)
+	}
+
+	proptest! {
+		#[test]
+		fn test_vec_split_preserves_elements(
+			elements in proptest::collection::vec(any::<u8>(), 0..64),
+			factor in 1usize..8,
+		) {
+			let chunks = elements.clone().split(factor).unwrap();
+			prop_assert!(chunks.len() <= factor.max(1));
+			prop_assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+			prop_assert_eq!(chunks.concat(), elements);
+		}
+
+		#[test]
+		fn test_block_split_strictly_reduces_weight(
+			transactions in proptest::collection::vec(
+				proptest::collection::vec(any::<u8>(), 0..256),
+				2..32,
+			),
+			factor in 2usize..8,
+		) {
+			let block = block(transactions);
+			let weight = block.weight();
+			let blocks = block.clone().split(factor).unwrap();
+
+			prop_assert!(blocks.len() > 1);
+			for split_block in &blocks {
+				prop_assert!(split_block.weight() < weight);
+			}
+
+			// no transaction is lost or duplicated
+			let split_transactions =
+				blocks.iter().flat_map(|block| block.transactions().cloned()).collect::<Vec<_>>();
+			let transactions = block.transactions().cloned().collect::<BTreeSet<_>>();
+			prop_assert_eq!(split_transactions.len(), transactions.len());
+			prop_assert_eq!(split_transactions.into_iter().collect::<BTreeSet<_>>(), transactions);
+		}
+
+		#[test]
+		fn test_block_split_links_distinct_blocks(
+			transactions in proptest::collection::vec(
+				proptest::collection::vec(any::<u8>(), 0..64),
+				2..32,
+			),
+			factor in 2usize..8,
+		) {
+			let block = block(transactions);
+			let blocks = block.clone().split(factor).unwrap();
+
+			prop_assert_eq!(blocks[0].parent(), block.parent());
+			for pair in blocks.windows(2) {
+				prop_assert_eq!(pair[1].parent(), pair[0].id());
+				prop_assert!(pair[1].metadata().height() > pair[0].metadata().height());
+				prop_assert!(pair[1].metadata().timestamp() > pair[0].metadata().timestamp());
+			}
+			let ids = blocks.iter().map(|block| block.id()).collect::<BTreeSet<_>>();
+			prop_assert_eq!(ids.len(), blocks.len());
+			prop_assert!(!ids.contains(&block.id()));
+		}
+	}
+
+	proptest! {
+		#[test]
+		fn test_repeated_block_split_stays_within_reserved_heights(
+			transactions in proptest::collection::vec(
+				proptest::collection::vec(any::<u8>(), 0..16),
+				2..32,
+			),
+		) {
+			let block = block(transactions);
+			let (height, timestamp) = (block.metadata().height(), block.metadata().timestamp());
+			let reserved = block.transactions().len() as u64;
+
+			// split until every block holds a single transaction
+			let mut blocks = vec![block];
+			while blocks.iter().any(|block| block.transactions().len() > 1) {
+				blocks = blocks
+					.into_iter()
+					.map(|block| block.split(2))
+					.collect::<Result<Vec<_>, _>>()
+					.unwrap()
+					.concat();
+			}
+
+			for (index, block) in blocks.iter().enumerate() {
+				prop_assert_eq!(block.metadata().height(), height + index as u64);
+				prop_assert_eq!(block.metadata().timestamp(), timestamp + index as u64);
+			}
+			prop_assert_eq!(blocks.len() as u64, reserved);
+		}
+	}
+
+	#[test]
+	fn test_block_with_one_transaction_is_not_split() -> Result<(), anyhow::Error> {
+		let block = block(vec![vec![1, 2, 3]]);
+		assert_eq!(block.clone().split(2)?, vec![block]);
+		Ok(())
+	}
+}
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+
+/// The maximum length of a decompressed body, which bounds the memory a malformed envelope can claim.
+pub const MAX_DECOMPRESSED_LENGTH: usize = 256 * 1024 * 1024;
+
+/// The compression applied to the body of an envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+	/// No compression.
+	None,
+	/// zstd at the given level, where 0 is zstd's default level.
+	Zstd { level: i32 },
+	/// LZ4, which is faster than zstd but compresses less.
+	Lz4,
+}
+
+impl Codec {
+	const NONE_ID: u8 = 0;
+	const ZSTD_ID: u8 = 1;
+	const LZ4_ID: u8 = 2;
+
+	/// The id of the codec in the envelope header.
+	///
+	/// The id does not include the compression level, which is not needed to decompress.
+	pub fn id(&self) -> u8 {
+		match self {
+			Codec::None => Self::NONE_ID,
+			Codec::Zstd { .. } => Self::ZSTD_ID,
+			Codec::Lz4 => Self::LZ4_ID,
+		}
+	}
+
+	pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+		match self {
+			Codec::None => Ok(data.to_vec()),
+			Codec::Zstd { level } => Ok(zstd::encode_all(data, *level)?),
+			Codec::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+		}
+	}
+
+	/// Decompresses data compressed by the codec with the given id.
+	pub fn decompress(id: u8, data: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+		match id {
+			Self::NONE_ID => Ok(data.to_vec()),
+			Self::ZSTD_ID => zstd_decompress(data),
+			Self::LZ4_ID => {
+				let length = data
+					.get(..4)
+					.ok_or(anyhow::anyhow!("LZ4 body is too short"))?
+					.try_into()
+					.map(u32::from_le_bytes)?;
+				if length as usize > MAX_DECOMPRESSED_LENGTH {
+					anyhow::bail!("LZ4 body decompresses to {} bytes, which is too long", length);
+				}
+				lz4_flex::decompress_size_prepended(data)
+					.map_err(|e| anyhow::anyhow!("Failed to decompress LZ4 body: {}", e))
+			}
+			_ => anyhow::bail!("Unknown codec id: {}", id),
+		}
+	}
+}
+
+/// Decompresses zstd data, failing if it decompresses to more than [MAX_DECOMPRESSED_LENGTH] bytes.
+pub(crate) fn zstd_decompress(data: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+	let mut decompressed = Vec::new();
+	zstd::stream::read::Decoder::new(data)?
+		.take(MAX_DECOMPRESSED_LENGTH as u64 + 1)
+		.read_to_end(&mut decompressed)?;
+	if decompressed.len() > MAX_DECOMPRESSED_LENGTH {
+		anyhow::bail!("zstd body decompresses to more than {} bytes", MAX_DECOMPRESSED_LENGTH);
+	}
+	Ok(decompressed)
+}
+
+impl Default for Codec {
+	fn default() -> Self {
+		Codec::Zstd { level: 0 }
+	}
+}
+
+impl fmt::Display for Codec {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Codec::None => write!(f, "none"),
+			Codec::Zstd { level } => write!(f, "zstd:{}", level),
+			Codec::Lz4 => write!(f, "lz4"),
+		}
+	}
+}
+
+impl FromStr for Codec {
+	type Err = anyhow::Error;
+
+	/// Parses `none`, `lz4`, `zstd` or `zstd:<level>`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_lowercase().as_str() {
+			"none" => Ok(Codec::None),
+			"lz4" => Ok(Codec::Lz4),
+			"zstd" => Ok(Codec::Zstd { level: 0 }),
+			codec => match codec.strip_prefix("zstd:") {
+				Some(level) => {
+					let level = level
+						.parse()
+						.map_err(|_| anyhow::anyhow!("Invalid zstd level: {}", level))?;
+					if !zstd::compression_level_range().contains(&level) {
+						anyhow::bail!("zstd level out of range: {}", level);
+					}
+					Ok(Codec::Zstd { level })
+				}
+				None => Err(anyhow::anyhow!("Unknown codec: {}", s)),
+			},
+		}
+	}
+}
+
+/// Codecs are serialized as strings, e.g., `zstd:3`, so that they can be set in configuration files.
+impl Serialize for Codec {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for Codec {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	#[test]
+	fn test_codec_from_str() -> Result<(), anyhow::Error> {
+		assert_eq!("none".parse::<Codec>()?, Codec::None);
+		assert_eq!("LZ4".parse::<Codec>()?, Codec::Lz4);
+		assert_eq!("zstd".parse::<Codec>()?, Codec::Zstd { level: 0 });
+		assert_eq!("zstd:19".parse::<Codec>()?, Codec::Zstd { level: 19 });
+		assert!("zstd:fast".parse::<Codec>().is_err());
+		assert!("zstd:1000".parse::<Codec>().is_err());
+		assert!("gzip".parse::<Codec>().is_err());
+
+		for codec in [Codec::None, Codec::Lz4, Codec::Zstd { level: 3 }] {
+			assert_eq!(codec.to_string().parse::<Codec>()?, codec);
+			assert_eq!(serde_json::from_str::<Codec>(&serde_json::to_string(&codec)?)?, codec);
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_codecs_round_trip() -> Result<(), anyhow::Error> {
+		let data = b"movement ".repeat(100);
+		for codec in [Codec::None, Codec::Lz4, Codec::Zstd { level: 1 }, Codec::Zstd { level: 19 }]
+		{
+			let compressed = codec.compress(&data)?;
+			assert_eq!(Codec::decompress(codec.id(), &compressed)?, data);
+		}
+		assert!(Codec::decompress(42, &data).is_err());
+
+		Ok(())
+	}
+}
@@ -0,0 +1,263 @@
+//! A versioned envelope for the data Movement writes to the DA, i.e., blocks and transactions.
+//!
+//! An envelope is a header followed by a body:
+//!
+//! | magic (4 bytes) | version (1 byte) | codec id (1 byte) | checksum (4 bytes) | body |
+//!
+//! In version 1, the body is the BCS encoding of the value, compressed with the [Codec].
+//! The checksum is the first 4 bytes of the BLAKE3 hash of the body.
+//!
+//! Data written before envelopes were introduced has no header and is decoded in its [LegacyFormat],
+//! so that readers can be upgraded before writers. Until every reader is upgraded, writers can keep
+//! writing the legacy format, see [WriteFormat].
+pub mod codec;
+
+pub use codec::Codec;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// The bytes every envelope starts with.
+pub const MAGIC: [u8; 4] = *b"MVEN";
+
+/// The format version written by this crate.
+pub const VERSION: u8 = 1;
+
+/// The length of the envelope header.
+pub const HEADER_LENGTH: usize = 10;
+
+/// How data without an envelope, written before envelopes were introduced, is decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyFormat {
+	/// JSON, as transactions were encoded.
+	Json,
+	/// BCS compressed with zstd, as blocks were encoded.
+	///
+	/// Only the encoding is legacy: blocks written before envelopes have a layout of their own,
+	/// so they are to be decoded as `movement_types::legacy::Block`.
+	ZstdBcs,
+}
+
+/// How data is written to the DA.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WriteFormat {
+	/// In an envelope.
+	#[default]
+	Envelope,
+	/// In the legacy format, so that the readers which predate envelopes can decode the data until they are upgraded.
+	Legacy,
+}
+
+impl WriteFormat {
+	/// Encodes a value in the write format, compressing it with the codec if it is written in an envelope.
+	pub fn to_bytes<T: Serialize>(
+		&self,
+		value: &T,
+		codec: Codec,
+		legacy_format: LegacyFormat,
+	) -> Result<Vec<u8>, anyhow::Error> {
+		match self {
+			WriteFormat::Envelope => to_bytes(value, codec),
+			WriteFormat::Legacy => to_legacy_bytes(value, legacy_format),
+		}
+	}
+}
+
+impl fmt::Display for WriteFormat {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			WriteFormat::Envelope => write!(f, "envelope"),
+			WriteFormat::Legacy => write!(f, "legacy"),
+		}
+	}
+}
+
+impl FromStr for WriteFormat {
+	type Err = anyhow::Error;
+
+	/// Parses `envelope` or `legacy`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_lowercase().as_str() {
+			"envelope" => Ok(WriteFormat::Envelope),
+			"legacy" => Ok(WriteFormat::Legacy),
+			_ => Err(anyhow::anyhow!("Unknown write format: {}", s)),
+		}
+	}
+}
+
+/// Write formats are serialized as strings, so that they can be set in configuration files.
+impl Serialize for WriteFormat {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for WriteFormat {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+fn checksum(body: &[u8]) -> [u8; 4] {
+	let mut checksum = [0; 4];
+	checksum.copy_from_slice(&blake3::hash(body).as_bytes()[..4]);
+	checksum
+}
+
+/// Encodes a value in an envelope, compressing it with the codec.
+pub fn to_bytes<T: Serialize>(value: &T, codec: Codec) -> Result<Vec<u8>, anyhow::Error> {
+	let body = codec.compress(&bcs::to_bytes(value)?)?;
+
+	let mut bytes = Vec::with_capacity(HEADER_LENGTH + body.len());
+	bytes.extend_from_slice(&MAGIC);
+	bytes.push(VERSION);
+	bytes.push(codec.id());
+	bytes.extend_from_slice(&checksum(&body));
+	bytes.extend_from_slice(&body);
+	Ok(bytes)
+}
+
+/// Encodes a value in its legacy format, without an envelope.
+pub fn to_legacy_bytes<T: Serialize>(
+	value: &T,
+	legacy_format: LegacyFormat,
+) -> Result<Vec<u8>, anyhow::Error> {
+	match legacy_format {
+		LegacyFormat::Json => Ok(serde_json::to_vec(value)?),
+		LegacyFormat::ZstdBcs => Ok(Codec::Zstd { level: 0 }.compress(&bcs::to_bytes(value)?)?),
+	}
+}
+
+/// Whether the bytes start with an envelope header.
+pub fn is_enveloped(bytes: &[u8]) -> bool {
+	bytes.starts_with(&MAGIC)
+}
+
+/// Decodes a value from an envelope.
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, anyhow::Error> {
+	if bytes.len() < HEADER_LENGTH || !is_enveloped(bytes) {
+		anyhow::bail!("Not an envelope");
+	}
+
+	let version = bytes[4];
+	if version != VERSION {
+		anyhow::bail!("Unsupported envelope version: {}", version);
+	}
+
+	let codec_id = bytes[5];
+	let body = &bytes[HEADER_LENGTH..];
+	if bytes[6..HEADER_LENGTH] != checksum(body) {
+		anyhow::bail!("Envelope checksum mismatch");
+	}
+
+	let body = Codec::decompress(codec_id, body)?;
+	Ok(bcs::from_bytes(&body)?)
+}
+
+/// Decodes a value from an envelope, or from its legacy format if the bytes are not in an envelope.
+pub fn from_bytes_or_legacy<T: DeserializeOwned>(
+	bytes: &[u8],
+	legacy_format: LegacyFormat,
+) -> Result<T, anyhow::Error> {
+	if is_enveloped(bytes) {
+		return from_bytes(bytes);
+	}
+
+	match legacy_format {
+		LegacyFormat::Json => Ok(serde_json::from_slice(bytes)?),
+		LegacyFormat::ZstdBcs => Ok(bcs::from_bytes(&codec::zstd_decompress(bytes)?)?),
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use movement_types::{block::Block, legacy, transaction::Transaction};
+
+	fn block() -> Block {
+		Block::new(
+			Default::default(),
+			Default::default(),
+			vec![Transaction::new(vec![1; 64], 0), Transaction::new(vec![2; 64], 1)]
+				.into_iter()
+				.collect(),
+		)
+	}
+
+	#[test]
+	fn test_envelope_round_trip() -> Result<(), anyhow::Error> {
+		let block = block();
+		for codec in [Codec::None, Codec::Lz4, Codec::Zstd { level: 3 }] {
+			let bytes = to_bytes(&block, codec)?;
+			assert_eq!(&bytes[..4], &MAGIC);
+			assert_eq!(bytes[4], VERSION);
+			assert_eq!(bytes[5], codec.id());
+			assert_eq!(from_bytes::<Block>(&bytes)?, block);
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_envelope_rejects_corruption() -> Result<(), anyhow::Error> {
+		let bytes = to_bytes(&block(), Codec::Lz4)?;
+
+		let mut corrupted = bytes.clone();
+		*corrupted.last_mut().unwrap() ^= 1;
+		assert!(from_bytes::<Block>(&corrupted).is_err());
+
+		let mut unknown_version = bytes.clone();
+		unknown_version[4] = VERSION + 1;
+		assert!(from_bytes::<Block>(&unknown_version).is_err());
+
+		assert!(from_bytes::<Block>(&bytes[..HEADER_LENGTH - 1]).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_legacy_formats() -> Result<(), anyhow::Error> {
+		let block = legacy::Block::from(block());
+		let legacy_block = zstd::encode_all(bcs::to_bytes(&block)?.as_slice(), 0)?;
+		assert_eq!(
+			from_bytes_or_legacy::<legacy::Block>(&legacy_block, LegacyFormat::ZstdBcs)?,
+			block
+		);
+
+		let transaction = Transaction::new(vec![1, 2, 3], 0);
+		let legacy_transaction = serde_json::to_vec(&transaction)?;
+		assert_eq!(
+			from_bytes_or_legacy::<Transaction>(&legacy_transaction, LegacyFormat::Json)?,
+			transaction
+		);
+
+		// enveloped data is decoded regardless of the legacy format
+		let bytes = to_bytes(&transaction, Codec::None)?;
+		assert_eq!(from_bytes_or_legacy::<Transaction>(&bytes, LegacyFormat::Json)?, transaction);
+
+		Ok(())
+	}
+	#[test]
+	fn test_write_formats() -> Result<(), anyhow::Error> {
+		let transaction = Transaction::new(vec![1, 2, 3], 0);
+		for (write_format, enveloped) in
+			[(WriteFormat::Envelope, true), (WriteFormat::Legacy, false)]
+		{
+			assert_eq!(write_format.to_string().parse::<WriteFormat>()?, write_format);
+			let bytes = write_format.to_bytes(&transaction, Codec::None, LegacyFormat::Json)?;
+			assert_eq!(is_enveloped(&bytes), enveloped);
+			assert_eq!(
+				from_bytes_or_legacy::<Transaction>(&bytes, LegacyFormat::Json)?,
+				transaction
+			);
+		}
+
+		let block = legacy::Block::from(block());
+		let bytes = WriteFormat::Legacy.to_bytes(&block, Codec::None, LegacyFormat::ZstdBcs)?;
+		assert_eq!(from_bytes_or_legacy::<legacy::Block>(&bytes, LegacyFormat::ZstdBcs)?, block);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,490 @@
+//! The status of the transactions submitted to a node, from their acceptance into the mempool until their finalization.
+
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// The default number of transactions whose status is kept by the index.
+pub const DEFAULT_CAPACITY: usize = 1_000_000;
+
+/// The number of status updates buffered for each subscriber.
+const UPDATE_CHANNEL_SIZE: usize = 4096;
+
+/// The stage of its lifecycle a transaction submitted to the node has reached.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TransactionStatus {
+	/// The transaction was accepted into the mempool of the node.
+	Mempool,
+	/// The DA light node admitted the transaction to its mempool.
+	///
+	/// The transactions the light node rejects are failed, but a transaction its mempool drops after admitting it,
+	/// e.g., because it expired, stays batched.
+	Batched,
+	/// The transaction was included in a sequenced block, which was read from the DA at the given height.
+	Sequenced { block_id: String, da_height: u64 },
+	/// The block including the transaction was executed at the given block height.
+	Executed { block_height: u64 },
+	/// The block including the transaction was finalized by settlement.
+	Finalized { block_height: u64 },
+	/// The transaction was dropped and will not be executed.
+	Failed { reason: String },
+}
+
+impl TransactionStatus {
+	/// Whether the transaction will not change status anymore.
+	pub fn is_terminal(&self) -> bool {
+		matches!(self, Self::Finalized { .. } | Self::Failed { .. })
+	}
+
+	/// The position of the status in the lifecycle of a transaction.
+	fn stage(&self) -> u8 {
+		match self {
+			Self::Mempool => 0,
+			Self::Batched => 1,
+			Self::Sequenced { .. } => 2,
+			Self::Executed { .. } => 3,
+			Self::Finalized { .. } => 4,
+			Self::Failed { .. } => 5,
+		}
+	}
+
+	/// Whether a transaction with this status can move to the given status.
+	///
+	/// A transaction only moves forward in its lifecycle and can only fail before it is executed.
+	/// A transaction which failed can be submitted again, which returns it to the mempool.
+	pub fn can_become(&self, next: &TransactionStatus) -> bool {
+		match (self, next) {
+			(Self::Failed { .. }, Self::Mempool) => true,
+			(Self::Finalized { .. } | Self::Failed { .. }, _) => false,
+			(_, Self::Failed { .. }) => self.stage() < Self::Executed { block_height: 0 }.stage(),
+			_ => self.stage() <= next.stage(),
+		}
+	}
+}
+
+/// A change in the status of a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionStatusUpdate {
+	pub hash: String,
+	#[serde(flatten)]
+	pub status: TransactionStatus,
+}
+
+/// Transaction hashes are indexed as lowercase hex without a `0x` prefix.
+fn normalize_hash(hash: &str) -> String {
+	hash.trim_start_matches("0x").to_ascii_lowercase()
+}
+
+/// A durable record of the status of transactions, consulted for the transactions the index does not hold,
+/// e.g., because they were evicted or the node restarted since.
+#[async_trait]
+pub trait TransactionStatusStore: Send + Sync {
+	/// Gets the status of a transaction by its normalized hash, if it is recorded.
+	async fn get_status(&self, hash: &str) -> Result<Option<TransactionStatus>, anyhow::Error>;
+}
+
+struct Inner {
+	statuses: HashMap<String, TransactionStatus>,
+	// The hashes in the order they were first indexed, to evict the oldest ones
+	insertion_order: VecDeque<String>,
+	// The transactions of executed blocks which are not finalized yet, by block height
+	unfinalized: BTreeMap<u64, Vec<String>>,
+	unfinalized_count: usize,
+	capacity: usize,
+	store: Option<Arc<dyn TransactionStatusStore>>,
+}
+
+/// An in-memory index of the status of the transactions submitted to the node,
+/// from their acceptance into the mempool until their finalization.
+///
+/// The index holds a bounded number of transactions, evicting the oldest ones.
+/// The transactions it does not hold are looked up in its [TransactionStatusStore], if it has one.
+/// A status is only set if the transaction [can become](TransactionStatus::can_become) it.
+#[derive(Clone)]
+pub struct TransactionStatusIndex {
+	inner: Arc<Mutex<Inner>>,
+	updates: broadcast::Sender<TransactionStatusUpdate>,
+}
+
+impl Default for TransactionStatusIndex {
+	fn default() -> Self {
+		Self::new(DEFAULT_CAPACITY)
+	}
+}
+
+impl std::fmt::Debug for TransactionStatusIndex {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("TransactionStatusIndex").finish_non_exhaustive()
+	}
+}
+
+impl TransactionStatusIndex {
+	pub fn new(capacity: usize) -> Self {
+		let (updates, _) = broadcast::channel(UPDATE_CHANNEL_SIZE);
+		let inner = Inner {
+			statuses: HashMap::new(),
+			insertion_order: VecDeque::new(),
+			unfinalized: BTreeMap::new(),
+			unfinalized_count: 0,
+			capacity,
+			store: None,
+		};
+		Self { inner: Arc::new(Mutex::new(inner)), updates }
+	}
+
+	/// Gets the current status of a transaction, if it is indexed.
+	pub fn get(&self, hash: &str) -> Option<TransactionStatus> {
+		let inner = self.inner.lock().expect("transaction status index lock poisoned");
+		inner.statuses.get(&normalize_hash(hash)).cloned()
+	}
+
+	/// Sets the store the transactions which are not indexed are looked up in.
+	pub fn set_store(&self, store: Arc<dyn TransactionStatusStore>) {
+		let mut inner = self.inner.lock().expect("transaction status index lock poisoned");
+		inner.store = Some(store);
+	}
+
+	/// Gets the current status of a transaction, looking it up in the store if it is not indexed.
+	pub async fn lookup(&self, hash: &str) -> Result<Option<TransactionStatus>, anyhow::Error> {
+		let hash = normalize_hash(hash);
+		let store = {
+			let inner = self.inner.lock().expect("transaction status index lock poisoned");
+			if let Some(status) = inner.statuses.get(&hash) {
+				return Ok(Some(status.clone()));
+			}
+			inner.store.clone()
+		};
+		match store {
+			Some(store) => store.get_status(&hash).await,
+			None => Ok(None),
+		}
+	}
+
+	/// Subscribes to the status updates of all transactions.
+	pub fn subscribe(&self) -> broadcast::Receiver<TransactionStatusUpdate> {
+		self.updates.subscribe()
+	}
+
+	/// Streams the status of a transaction, starting with its current status, until the status is terminal.
+	pub fn watch(&self, hash: &str) -> impl Stream<Item = TransactionStatus> + Send + 'static {
+		let hash = normalize_hash(hash);
+		// subscribe before reading the current status, so that no update is missed
+		let mut updates = self.subscribe();
+		let index = self.clone();
+		async_stream::stream! {
+			// a transaction which cannot be looked up is treated as not indexed yet
+			let mut last = index.lookup(&hash).await.unwrap_or_default();
+			if let Some(status) = last.clone() {
+				yield status;
+			}
+			while !last.as_ref().is_some_and(TransactionStatus::is_terminal) {
+				let status = match updates.recv().await {
+					Ok(update) if update.hash == hash => update.status,
+					Ok(_) => continue,
+					// the status of the transaction may have been among the skipped updates
+					Err(broadcast::error::RecvError::Lagged(_)) => match index.get(&hash) {
+						Some(status) => status,
+						None => continue,
+					},
+					Err(broadcast::error::RecvError::Closed) => break,
+				};
+				if last.as_ref() == Some(&status) {
+					continue;
+				}
+				last = Some(status.clone());
+				yield status;
+			}
+		}
+	}
+
+	/// Sets the status of a transaction, unless the transaction cannot become it.
+	/// Returns whether the status was set.
+	pub fn set(&self, hash: &str, status: TransactionStatus) -> bool {
+		let hash = normalize_hash(hash);
+		{
+			let mut inner = self.inner.lock().expect("transaction status index lock poisoned");
+			if !Self::insert(&mut inner, hash.clone(), status.clone()) {
+				return false;
+			}
+		}
+		// there may be no subscribers, which is not an error
+		let _ = self.updates.send(TransactionStatusUpdate { hash, status });
+		true
+	}
+
+	/// Sets the status of the transactions of a block executed at the given height.
+	pub fn set_executed(&self, hashes: Vec<String>, block_height: u64) {
+		let hashes = {
+			let mut inner = self.inner.lock().expect("transaction status index lock poisoned");
+			let hashes = hashes
+				.iter()
+				.map(|hash| normalize_hash(hash))
+				.filter(|hash| {
+					Self::insert(
+						&mut inner,
+						hash.clone(),
+						TransactionStatus::Executed { block_height },
+					)
+				})
+				.collect::<Vec<_>>();
+			inner
+				.unfinalized
+				.entry(block_height)
+				.or_default()
+				.extend(hashes.iter().cloned());
+			inner.unfinalized_count += hashes.len();
+			// without settlement the blocks are never finalized, so only the latest ones are kept
+			while inner.unfinalized_count > inner.capacity {
+				match inner.unfinalized.pop_first() {
+					Some((_, evicted)) => inner.unfinalized_count -= evicted.len(),
+					None => break,
+				}
+			}
+			hashes
+		};
+		for hash in hashes {
+			let _ = self.updates.send(TransactionStatusUpdate {
+				hash,
+				status: TransactionStatus::Executed { block_height },
+			});
+		}
+	}
+
+	/// Finalizes the transactions of the blocks executed up to and including the given height.
+	pub fn set_finalized_height(&self, height: u64) {
+		let finalized = {
+			let mut inner = self.inner.lock().expect("transaction status index lock poisoned");
+			let unfinalized = inner.unfinalized.split_off(&height.saturating_add(1));
+			let finalized = std::mem::replace(&mut inner.unfinalized, unfinalized);
+			inner.unfinalized_count = inner.unfinalized.values().map(Vec::len).sum();
+			let mut updates = Vec::new();
+			for (block_height, hashes) in finalized {
+				for hash in hashes {
+					// the transaction may have been evicted or re-executed since
+					if inner.statuses.get(&hash)
+						!= Some(&TransactionStatus::Executed { block_height })
+					{
+						continue;
+					}
+					let status = TransactionStatus::Finalized { block_height };
+					inner.statuses.insert(hash.clone(), status.clone());
+					updates.push(TransactionStatusUpdate { hash, status });
+				}
+			}
+			updates
+		};
+		for update in finalized {
+			let _ = self.updates.send(update);
+		}
+	}
+
+	/// Reverts the transactions of the blocks executed above the given height.
+	///
+	/// The blocks are executed again from the DA, so their transactions return to being batched.
+	/// This is the only way a transaction moves back in its lifecycle.
+	pub fn revert_to_height(&self, height: u64) {
+		let reverted = {
+			let mut inner = self.inner.lock().expect("transaction status index lock poisoned");
+			let reverted = inner.unfinalized.split_off(&height.saturating_add(1));
+			inner.unfinalized_count = inner.unfinalized.values().map(Vec::len).sum();
+			let mut updates = Vec::new();
+			for (block_height, hashes) in reverted {
+				for hash in hashes {
+					// the transaction may have been evicted or re-executed since
+					if inner.statuses.get(&hash)
+						!= Some(&TransactionStatus::Executed { block_height })
+					{
+						continue;
+					}
+					inner.statuses.insert(hash.clone(), TransactionStatus::Batched);
+					updates
+						.push(TransactionStatusUpdate { hash, status: TransactionStatus::Batched });
+				}
+			}
+			updates
+		};
+		for update in reverted {
+			let _ = self.updates.send(update);
+		}
+	}
+
+	/// Sets the status of a transaction if it can become it, returning whether it was set.
+	fn insert(inner: &mut Inner, hash: String, status: TransactionStatus) -> bool {
+		match inner.statuses.get_mut(&hash) {
+			Some(current) => {
+				if !current.can_become(&status) {
+					return false;
+				}
+				*current = status;
+			}
+			None => {
+				inner.statuses.insert(hash.clone(), status);
+				inner.insertion_order.push_back(hash);
+				while inner.insertion_order.len() > inner.capacity {
+					if let Some(evicted) = inner.insertion_order.pop_front() {
+						inner.statuses.remove(&evicted);
+					}
+				}
+			}
+		}
+		true
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use futures::StreamExt;
+
+	#[tokio::test]
+	async fn test_transaction_lifecycle() -> Result<(), anyhow::Error> {
+		let index = TransactionStatusIndex::default();
+		let mut updates = index.subscribe();
+
+		index.set("0xAB", TransactionStatus::Mempool);
+		assert_eq!(index.get("ab"), Some(TransactionStatus::Mempool));
+
+		index.set("ab", TransactionStatus::Batched);
+		index.set(
+			"ab",
+			TransactionStatus::Sequenced { block_id: "block".to_string(), da_height: 3 },
+		);
+		index.set_executed(vec!["ab".to_string(), "cd".to_string()], 7);
+		assert_eq!(index.get("0xab"), Some(TransactionStatus::Executed { block_height: 7 }));
+
+		// a lower finalized height does not finalize the block
+		index.set_finalized_height(6);
+		assert_eq!(index.get("cd"), Some(TransactionStatus::Executed { block_height: 7 }));
+		index.set_finalized_height(7);
+		assert_eq!(index.get("ab"), Some(TransactionStatus::Finalized { block_height: 7 }));
+		assert!(index.get("ab").unwrap().is_terminal());
+
+		let first = updates.recv().await?;
+		assert_eq!(
+			first,
+			TransactionStatusUpdate { hash: "ab".to_string(), status: TransactionStatus::Mempool }
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_watch_until_terminal() -> Result<(), anyhow::Error> {
+		let index = TransactionStatusIndex::default();
+		index.set("ab", TransactionStatus::Mempool);
+		let statuses = index.watch("0xab");
+
+		index.set("cd", TransactionStatus::Mempool);
+		index.set("ab", TransactionStatus::Batched);
+		index.set("ab", TransactionStatus::Failed { reason: "unavailable".to_string() });
+		index.set("ab", TransactionStatus::Mempool);
+
+		let statuses = statuses.collect::<Vec<_>>().await;
+		assert_eq!(
+			statuses,
+			vec![
+				TransactionStatus::Mempool,
+				TransactionStatus::Batched,
+				TransactionStatus::Failed { reason: "unavailable".to_string() },
+			]
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_statuses_only_move_forward() {
+		let index = TransactionStatusIndex::default();
+		index.set_executed(vec!["ab".to_string()], 3);
+
+		// a late acknowledgement of the write does not regress the status
+		assert!(!index.set("ab", TransactionStatus::Batched));
+		assert!(!index.set("ab", TransactionStatus::Failed { reason: "unavailable".to_string() }));
+		assert_eq!(index.get("ab"), Some(TransactionStatus::Executed { block_height: 3 }));
+
+		index.set_finalized_height(3);
+		index.set_executed(vec!["ab".to_string()], 4);
+		assert_eq!(index.get("ab"), Some(TransactionStatus::Finalized { block_height: 3 }));
+
+		// a failed transaction can only be submitted again
+		index.set("cd", TransactionStatus::Failed { reason: "mempool full".to_string() });
+		assert!(!index.set("cd", TransactionStatus::Batched));
+		assert!(index.set("cd", TransactionStatus::Mempool));
+		assert!(index.set("cd", TransactionStatus::Batched));
+	}
+
+	struct FailedWrites;
+
+	#[async_trait]
+	impl TransactionStatusStore for FailedWrites {
+		async fn get_status(&self, hash: &str) -> Result<Option<TransactionStatus>, anyhow::Error> {
+			Ok((hash == "ab")
+				.then(|| TransactionStatus::Failed { reason: "unavailable".to_string() }))
+		}
+	}
+
+	#[tokio::test]
+	async fn test_lookup_falls_back_to_store() -> Result<(), anyhow::Error> {
+		let index = TransactionStatusIndex::default();
+		assert_eq!(index.lookup("0xAB").await?, None);
+
+		index.set_store(Arc::new(FailedWrites));
+		assert_eq!(
+			index.lookup("0xAB").await?,
+			Some(TransactionStatus::Failed { reason: "unavailable".to_string() })
+		);
+		assert_eq!(index.lookup("cd").await?, None);
+
+		// the index takes precedence over the store
+		index.set("ab", TransactionStatus::Mempool);
+		assert_eq!(index.lookup("ab").await?, Some(TransactionStatus::Mempool));
+
+		// a watched transaction which is in neither is streamed once it is indexed
+		let mut statuses = Box::pin(index.watch("ef"));
+		index.set_executed(vec!["ef".to_string()], 1);
+		assert_eq!(statuses.next().await, Some(TransactionStatus::Executed { block_height: 1 }));
+		index.set_finalized_height(1);
+		assert_eq!(
+			statuses.collect::<Vec<_>>().await,
+			vec![TransactionStatus::Finalized { block_height: 1 }]
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_revert_to_height() {
+		let index = TransactionStatusIndex::default();
+		index.set_executed(vec!["ab".to_string()], 3);
+		index.set_executed(vec!["cd".to_string()], 4);
+
+		index.revert_to_height(3);
+		assert_eq!(index.get("ab"), Some(TransactionStatus::Executed { block_height: 3 }));
+		assert_eq!(index.get("cd"), Some(TransactionStatus::Batched));
+
+		// the reverted transactions are no longer finalized with their former block
+		index.set_finalized_height(4);
+		assert_eq!(index.get("ab"), Some(TransactionStatus::Finalized { block_height: 3 }));
+		assert_eq!(index.get("cd"), Some(TransactionStatus::Batched));
+	}
+
+	#[test]
+	fn test_oldest_transactions_are_evicted() {
+		let index = TransactionStatusIndex::new(2);
+		index.set("01", TransactionStatus::Mempool);
+		index.set("02", TransactionStatus::Mempool);
+		// updating a transaction does not count against the capacity
+		index.set("01", TransactionStatus::Batched);
+		index.set("03", TransactionStatus::Mempool);
+
+		assert_eq!(index.get("01"), None);
+		assert_eq!(index.get("02"), Some(TransactionStatus::Mempool));
+		assert_eq!(index.get("03"), Some(TransactionStatus::Mempool));
+	}
+}
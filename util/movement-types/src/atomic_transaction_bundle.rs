@@ -1,3 +1,4 @@
+use crate::block::Block;
 use crate::transaction::Transaction;
 use core::fmt;
 use serde::{Deserialize, Serialize};
@@ -40,18 +41,90 @@ impl fmt::Display for Id {
 	}
 }
 
+/// A transaction addressed to a single consumer, e.g., a rollup, of a shared sequencer.
 #[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct TransactionEntry {
 	consumer_id: Id,
 	data: Transaction,
 }
 
+impl TransactionEntry {
+	pub fn new(consumer_id: Id, data: Transaction) -> Self {
+		Self { consumer_id, data }
+	}
+
+	pub fn consumer_id(&self) -> Id {
+		self.consumer_id
+	}
+
+	pub fn data(&self) -> &Transaction {
+		&self.data
+	}
+}
+
+/// Transactions for one or more consumers which are sequenced together, i.e., all in the same block or not at all.
 #[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct AtomicTransactionBundle {
 	sequencer_id: Id,
 	transactions: Vec<TransactionEntry>,
 }
 
+impl AtomicTransactionBundle {
+	pub fn new(sequencer_id: Id, transactions: Vec<TransactionEntry>) -> Self {
+		Self { sequencer_id, transactions }
+	}
+
+	pub fn sequencer_id(&self) -> Id {
+		self.sequencer_id
+	}
+
+	pub fn transactions(&self) -> &[TransactionEntry] {
+		&self.transactions
+	}
+
+	/// Gets the transactions addressed to the given consumer, in bundle order.
+	pub fn transactions_for(&self, consumer_id: Id) -> impl Iterator<Item = &Transaction> {
+		self.transactions
+			.iter()
+			.filter(move |entry| entry.consumer_id == consumer_id)
+			.map(|entry| &entry.data)
+	}
+
+	/// Encodes the bundle as a single transaction, so that it is sequenced as a unit.
+	///
+	/// The transaction has the highest application priority of the bundled transactions.
+	pub fn try_to_transaction(&self) -> Result<Transaction, anyhow::Error> {
+		let application_priority = self
+			.transactions
+			.iter()
+			.map(|entry| entry.data.application_priority())
+			.max()
+			.unwrap_or_default();
+		Ok(Transaction::new(bcs::to_bytes(self)?, 0)
+			.with_application_priority(application_priority))
+	}
+
+	/// Decodes a bundle from a transaction created by [AtomicTransactionBundle::try_to_transaction].
+	pub fn try_from_transaction(transaction: &Transaction) -> Result<Self, anyhow::Error> {
+		bcs::from_bytes(transaction.data())
+			.map_err(|e| anyhow::anyhow!("Failed to decode atomic transaction bundle: {}", e))
+	}
+}
+
+/// Gets the transactions addressed to the given consumer from a block built by a shared sequencer.
+///
+/// Each bundle contributes its transactions for the consumer in bundle order.
+/// The transactions which are not bundles are not addressed to any consumer, so they are skipped.
+pub fn consumer_transactions(block: &Block, consumer_id: Id) -> Vec<Transaction> {
+	let mut transactions = Vec::new();
+	for transaction in block.transactions() {
+		if let Ok(bundle) = AtomicTransactionBundle::try_from_transaction(transaction) {
+			transactions.extend(bundle.transactions_for(consumer_id).cloned());
+		}
+	}
+	transactions
+}
+
 impl TryFrom<AtomicTransactionBundle> for Transaction {
 	type Error = anyhow::Error;
 
@@ -72,3 +145,40 @@ impl From<Transaction> for AtomicTransactionBundle {
 		}
 	}
 }
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use std::collections::BTreeSet;
+
+	#[test]
+	fn test_consumer_transactions() -> Result<(), anyhow::Error> {
+		let (rollup_a, rollup_b) = (Id::new([1; 32]), Id::new([2; 32]));
+		let bundle = AtomicTransactionBundle::new(
+			Id::default(),
+			vec![
+				TransactionEntry::new(rollup_a, Transaction::new(vec![1], 0)),
+				TransactionEntry::new(rollup_b, Transaction::new(vec![2], 0)),
+				TransactionEntry::new(rollup_a, Transaction::new(vec![3], 0)),
+			],
+		);
+		let transaction = bundle.try_to_transaction()?;
+		assert_eq!(AtomicTransactionBundle::try_from_transaction(&transaction)?, bundle);
+
+		// a transaction which is not a bundle does not make the block unreadable
+		let block = Block::new(
+			Default::default(),
+			Default::default(),
+			BTreeSet::from([transaction, Transaction::new(vec![4], 0)]),
+		);
+		assert_eq!(
+			consumer_transactions(&block, rollup_a),
+			vec![Transaction::new(vec![1], 0), Transaction::new(vec![3], 0)]
+		);
+		assert_eq!(consumer_transactions(&block, rollup_b), vec![Transaction::new(vec![2], 0)]);
+		assert!(consumer_transactions(&block, Id::new([3; 32])).is_empty());
+
+		Ok(())
+	}
+}
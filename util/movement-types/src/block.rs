@@ -1,6 +1,10 @@
 use crate::transaction::Transaction;
 use aptos_types::state_proof::StateProof;
 use core::fmt;
+use k256::ecdsa::{
+	signature::{Signer, Verifier},
+	Signature, SigningKey, VerifyingKey,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::btree_set;
 use std::collections::BTreeSet;
@@ -49,10 +53,38 @@ impl fmt::Display for Id {
 	}
 }
 
+/// Identifies and authenticates the sequencer which produced a block.
 #[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub enum BlockMetadata {
-	#[default]
-	BlockMetadata,
+pub struct BlockMetadata {
+	/// The compressed SEC1-encoded public key of the sequencer, empty if the block is unsigned.
+	sequencer: Vec<u8>,
+	height: u64,
+	timestamp: u64,
+	/// The sequencer's signature over the block id, empty if the block is unsigned.
+	signature: Vec<u8>,
+}
+
+impl BlockMetadata {
+	/// Creates the metadata of an unsigned block.
+	pub fn new(height: u64, timestamp: u64) -> Self {
+		Self { sequencer: Vec::new(), height, timestamp, signature: Vec::new() }
+	}
+
+	pub fn sequencer(&self) -> &[u8] {
+		&self.sequencer
+	}
+
+	pub fn height(&self) -> u64 {
+		self.height
+	}
+
+	pub fn timestamp(&self) -> u64 {
+		self.timestamp
+	}
+
+	pub fn signature(&self) -> &[u8] {
+		&self.signature
+	}
 }
 
 #[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -65,14 +97,76 @@ pub struct Block {
 
 impl Block {
 	pub fn new(metadata: BlockMetadata, parent: Id, transactions: BTreeSet<Transaction>) -> Self {
+		let id = Self::compute_id(&metadata, &parent, &transactions);
+		Self { metadata, parent, transactions, id }
+	}
+
+	/// Creates a block with an id which was computed elsewhere, e.g., in a legacy layout.
+	pub(crate) fn with_id(
+		metadata: BlockMetadata,
+		parent: Id,
+		transactions: BTreeSet<Transaction>,
+		id: Id,
+	) -> Self {
+		Self { metadata, parent, transactions, id }
+	}
+
+	/// Computes the id of a block from its contents.
+	///
+	/// The sequencer and its signature are not part of the id, so that the id can be signed.
+	/// The metadata of the transactions is, so that the signature covers the way they are ordered.
+	fn compute_id(
+		metadata: &BlockMetadata,
+		parent: &Id,
+		transactions: &BTreeSet<Transaction>,
+	) -> Id {
 		let mut hasher = blake3::Hasher::new();
 		hasher.update(parent.as_bytes());
-		for transaction in &transactions {
-			hasher.update(&transaction.id().as_ref());
+		hasher.update(&metadata.height.to_be_bytes());
+		hasher.update(&metadata.timestamp.to_be_bytes());
+		for transaction in transactions {
+			hasher.update(transaction.id().as_ref());
+			hasher.update(&transaction.application_priority().to_be_bytes());
+			hasher.update(&(transaction.sender().len() as u64).to_be_bytes());
+			hasher.update(transaction.sender());
+			hasher.update(&transaction.gas_estimate().to_be_bytes());
 		}
-		let id = Id(hasher.finalize().into());
+		Id(hasher.finalize().into())
+	}
 
-		Self { metadata, parent, transactions, id }
+	/// Signs the block id with the given sequencer key.
+	pub fn sign(&mut self, signing_key: &SigningKey) {
+		let signature: Signature = signing_key.sign(self.id.as_bytes());
+		self.metadata.sequencer =
+			signing_key.verifying_key().to_encoded_point(true).as_bytes().to_vec();
+		self.metadata.signature = signature.to_vec();
+	}
+
+	/// Verifies that the block id matches the block contents and is signed by the block's sequencer.
+	pub fn verify_signature(&self) -> Result<(), anyhow::Error> {
+		if self.id != Self::compute_id(&self.metadata, &self.parent, &self.transactions) {
+			anyhow::bail!("Block id {} does not match the block contents", self.id);
+		}
+		let sequencer = VerifyingKey::from_sec1_bytes(&self.metadata.sequencer).map_err(|e| {
+			anyhow::anyhow!("Invalid sequencer public key in block {}: {}", self.id, e)
+		})?;
+		let signature = Signature::from_slice(&self.metadata.signature)
+			.map_err(|e| anyhow::anyhow!("Invalid signature in block {}: {}", self.id, e))?;
+		sequencer
+			.verify(self.id.as_bytes(), &signature)
+			.map_err(|e| anyhow::anyhow!("Invalid signature for block {}: {}", self.id, e))
+	}
+
+	/// Verifies the block signature and that the block's sequencer is one of the given sequencers.
+	///
+	/// An empty list of sequencers allows none.
+	pub fn verify_sequencer(&self, sequencers: &[VerifyingKey]) -> Result<(), anyhow::Error> {
+		self.verify_signature()?;
+		let sequencer = VerifyingKey::from_sec1_bytes(&self.metadata.sequencer)?;
+		if !sequencers.contains(&sequencer) {
+			anyhow::bail!("Block {} was produced by an unknown sequencer", self.id);
+		}
+		Ok(())
 	}
 
 	pub fn into_parts(self) -> (BlockMetadata, Id, BTreeSet<Transaction>, Id) {
@@ -97,7 +191,7 @@ impl Block {
 
 	pub fn test() -> Self {
 		Self::new(
-			BlockMetadata::BlockMetadata,
+			BlockMetadata::default(),
 			Id::test(),
 			BTreeSet::from_iter(vec![Transaction::test()]),
 		)
@@ -197,3 +291,79 @@ pub enum BlockCommitmentEvent {
 	Accepted(BlockCommitment),
 	Rejected { height: u64, reason: BlockCommitmentRejectionReason },
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use k256::elliptic_curve::rand_core::OsRng;
+
+	#[test]
+	fn test_signed_block_verifies() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::random(&mut OsRng);
+		let mut block = Block::new(
+			BlockMetadata::new(1, 2),
+			Id::test(),
+			BTreeSet::from_iter(vec![Transaction::test()]),
+		);
+		let id = block.id();
+		block.sign(&signing_key);
+
+		// signing does not change the id
+		assert_eq!(block.id(), id);
+		block.verify_signature()?;
+		assert!(block.verify_sequencer(&[]).is_err());
+		block.verify_sequencer(&[*signing_key.verifying_key()])?;
+		assert!(block
+			.verify_sequencer(&[*SigningKey::random(&mut OsRng).verifying_key()])
+			.is_err());
+
+		// the signature survives serialization
+		let block: Block = bcs::from_bytes(&bcs::to_bytes(&block)?)?;
+		block.verify_signature()?;
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_tampered_block_does_not_verify() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::random(&mut OsRng);
+
+		assert!(Block::test().verify_signature().is_err());
+
+		let mut block = Block::test();
+		block.sign(&signing_key);
+		block.add_transaction(Transaction::new(vec![1], 1));
+		assert!(block.verify_signature().is_err());
+
+		let mut block = Block::test();
+		block.sign(&signing_key);
+		let (mut metadata, parent, transactions, id) = block.into_parts();
+		metadata.height += 1;
+		let block = Block { metadata, parent, transactions, id };
+		assert!(block.verify_signature().is_err());
+
+		// the metadata of the transactions is signed too
+		let mut block = Block::test();
+		block.sign(&signing_key);
+		let (metadata, parent, transactions, id) = block.into_parts();
+		let transactions = transactions
+			.into_iter()
+			.map(|transaction| transaction.with_application_priority(1))
+			.collect();
+		let block = Block { metadata, parent, transactions, id };
+		assert!(block.verify_signature().is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_id_commits_to_height_and_timestamp() {
+		let transactions = BTreeSet::from_iter(vec![Transaction::test()]);
+		let block = Block::new(BlockMetadata::new(1, 1), Id::test(), transactions.clone());
+		assert_ne!(
+			block.id(),
+			Block::new(BlockMetadata::new(2, 1), Id::test(), transactions.clone()).id()
+		);
+		assert_ne!(block.id(), Block::new(BlockMetadata::new(1, 2), Id::test(), transactions).id());
+	}
+}
@@ -0,0 +1,123 @@
+//! The layouts of blocks and transactions written before blocks were signed,
+//! so that the blocks already on the DA can still be decoded.
+
+use crate::{block, transaction};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct Transaction {
+	data: Vec<u8>,
+	sequence_number: u64,
+	id: transaction::Id,
+}
+
+impl Ord for Transaction {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.sequence_number
+			.cmp(&other.sequence_number)
+			.then_with(|| self.id.cmp(&other.id))
+	}
+}
+
+impl PartialOrd for Transaction {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl From<Transaction> for transaction::Transaction {
+	fn from(transaction: Transaction) -> Self {
+		// the ids are computed the same way in both layouts
+		Self::new(transaction.data, transaction.sequence_number)
+	}
+}
+
+impl From<transaction::Transaction> for Transaction {
+	fn from(transaction: transaction::Transaction) -> Self {
+		Self {
+			data: transaction.data().to_vec(),
+			sequence_number: transaction.sequence_number(),
+			id: transaction.id(),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BlockMetadata {
+	#[default]
+	BlockMetadata,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Block {
+	metadata: BlockMetadata,
+	parent: block::Id,
+	transactions: BTreeSet<Transaction>,
+	id: block::Id,
+}
+
+impl Block {
+	/// Computes the id of a block as it was computed before blocks were signed.
+	fn compute_id(parent: &block::Id, transactions: &BTreeSet<Transaction>) -> block::Id {
+		let mut hasher = blake3::Hasher::new();
+		hasher.update(parent.as_bytes());
+		for transaction in transactions {
+			hasher.update(transaction.id.as_ref());
+		}
+		block::Id::new(hasher.finalize().into())
+	}
+
+	pub fn id(&self) -> block::Id {
+		self.id
+	}
+}
+
+/// A block in the legacy layout becomes an unsigned block at height zero which keeps its id,
+/// so it is only executed if the sequencers are not verified.
+impl From<Block> for block::Block {
+	fn from(block: Block) -> Self {
+		let transactions = block.transactions.into_iter().map(Into::into).collect();
+		block::Block::with_id(block::BlockMetadata::default(), block.parent, transactions, block.id)
+	}
+}
+
+/// The metadata of a block does not exist in the legacy layout, so the id is computed again without it.
+impl From<block::Block> for Block {
+	fn from(block: block::Block) -> Self {
+		let (_, parent, transactions, _) = block.into_parts();
+		let transactions = transactions.into_iter().map(Into::into).collect();
+		let id = Self::compute_id(&parent, &transactions);
+		Self { metadata: BlockMetadata::BlockMetadata, parent, transactions, id }
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_legacy_block_round_trip() -> Result<(), anyhow::Error> {
+		let block = block::Block::new(
+			block::BlockMetadata::new(1, 2),
+			block::Id::test(),
+			BTreeSet::from_iter(vec![
+				transaction::Transaction::new(vec![1], 1),
+				transaction::Transaction::new(vec![2], 0),
+			]),
+		);
+
+		let legacy_block = Block::from(block.clone());
+		let decoded: Block = bcs::from_bytes(&bcs::to_bytes(&legacy_block)?)?;
+		assert_eq!(decoded, legacy_block);
+
+		let converted = block::Block::from(decoded);
+		assert_eq!(converted.id(), legacy_block.id());
+		assert_eq!(converted.parent(), block.parent());
+		assert!(converted.transactions().eq(block.transactions()));
+		assert!(converted.verify_signature().is_err());
+
+		Ok(())
+	}
+}
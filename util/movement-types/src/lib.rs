@@ -1,3 +1,4 @@
 pub mod atomic_transaction_bundle;
 pub mod block;
+pub mod legacy;
 pub mod transaction;
@@ -1,6 +1,7 @@
 use core::fmt;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 
 #[derive(
 	Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord,
@@ -40,10 +41,21 @@ impl fmt::Display for Id {
 	}
 }
 
-#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash)]
+/// Transactions are equal if their sequence numbers and ids are,
+/// in line with their ordering, so the metadata set after the id is not compared.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
 pub struct Transaction {
 	data: Vec<u8>,
 	sequence_number: u64,
+	/// The priority the application assigns the transaction, e.g., its gas unit price. Higher is more urgent.
+	#[serde(default)]
+	application_priority: u64,
+	/// The account which sent the transaction, empty if unknown.
+	#[serde(default)]
+	sender: Vec<u8>,
+	/// An estimate of the gas the transaction uses, e.g., its maximum gas amount. Zero if unknown.
+	#[serde(default)]
+	gas_estimate: u64,
 	id: Id,
 }
 
@@ -53,7 +65,32 @@ impl Transaction {
 		hasher.update(&data);
 		hasher.update(&sequence_number.to_le_bytes());
 		let id = Id(hasher.finalize().into());
-		Self { data, sequence_number, id }
+		Self {
+			data,
+			sequence_number,
+			application_priority: 0,
+			sender: Vec::new(),
+			gas_estimate: 0,
+			id,
+		}
+	}
+
+	/// Sets the application priority. This does not change the id.
+	pub fn with_application_priority(mut self, application_priority: u64) -> Self {
+		self.application_priority = application_priority;
+		self
+	}
+
+	/// Sets the sender. This does not change the id.
+	pub fn with_sender(mut self, sender: Vec<u8>) -> Self {
+		self.sender = sender;
+		self
+	}
+
+	/// Sets the gas estimate. This does not change the id.
+	pub fn with_gas_estimate(mut self, gas_estimate: u64) -> Self {
+		self.gas_estimate = gas_estimate;
+		self
 	}
 
 	pub fn id(&self) -> Id {
@@ -68,6 +105,18 @@ impl Transaction {
 		self.sequence_number
 	}
 
+	pub fn application_priority(&self) -> u64 {
+		self.application_priority
+	}
+
+	pub fn sender(&self) -> &[u8] {
+		&self.sender
+	}
+
+	pub fn gas_estimate(&self) -> u64 {
+		self.gas_estimate
+	}
+
 	pub fn test() -> Self {
 		Self::new(vec![0], 0)
 	}
@@ -86,6 +135,21 @@ impl Ord for Transaction {
 	}
 }
 
+impl PartialEq for Transaction {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Transaction {}
+
+impl Hash for Transaction {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.sequence_number.hash(state);
+		self.id.hash(state);
+	}
+}
+
 impl PartialOrd for Transaction {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
 		Some(self.cmp(other))
@@ -105,4 +169,34 @@ mod test {
 		assert!(transaction < transaction2);
 		assert!(transaction2 < transaction3);
 	}
+
+	#[test]
+	fn test_transaction_equality_ignores_metadata() {
+		let transaction = Transaction::new(vec![1], 1);
+		let with_metadata = transaction
+			.clone()
+			.with_application_priority(2)
+			.with_sender(vec![3])
+			.with_gas_estimate(4);
+
+		assert_eq!(transaction, with_metadata);
+		assert_eq!(transaction.cmp(&with_metadata), Ordering::Equal);
+	}
+
+	#[test]
+	fn test_transaction_without_metadata_deserializes() -> Result<(), serde_json::Error> {
+		let transaction = Transaction::new(vec![1], 1);
+		let json = serde_json::json!({
+			"data": transaction.data(),
+			"sequence_number": transaction.sequence_number(),
+			"id": transaction.id(),
+		});
+
+		let deserialized: Transaction = serde_json::from_value(json)?;
+		assert_eq!(deserialized, transaction);
+		assert_eq!(deserialized.application_priority(), 0);
+		assert!(deserialized.sender().is_empty());
+		assert_eq!(deserialized.gas_estimate(), 0);
+		Ok(())
+	}
 }